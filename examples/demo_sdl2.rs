@@ -0,0 +1,130 @@
+use std::rc::Rc;
+use std::os::raw::c_void;
+
+use sdl2::event::Event;
+use thyme::{Align, bench};
+
+mod demo;
+
+/// The glium backend for an SDL2 window with an OpenGL context.  glium
+/// uses this to load OpenGL functions and present frames.
+struct SdlBackend {
+    window: sdl2::video::Window,
+    gl_context: sdl2::video::GLContext,
+}
+
+unsafe impl glium::backend::Backend for SdlBackend {
+    fn swap_buffers(&self) -> Result<(), glium::SwapBuffersError> {
+        self.window.gl_swap_window();
+        Ok(())
+    }
+
+    unsafe fn get_proc_address(&self, symbol: &str) -> *const c_void {
+        self.window.subsystem().gl_get_proc_address(symbol) as *const c_void
+    }
+
+    fn get_framebuffer_dimensions(&self) -> (u32, u32) {
+        self.window.drawable_size()
+    }
+
+    fn is_current(&self) -> bool {
+        self.gl_context.is_current()
+    }
+
+    unsafe fn make_current(&self) {
+        self.window.gl_make_current(&self.gl_context).unwrap();
+    }
+}
+
+/// A basic RPG character sheet, using the glium backend with SDL2 for windowing and input.
+/// This file contains the application setup code and SDL2 specifics.
+/// the `demo.rs` file contains the Thyme UI code and logic.
+/// A simple party creator and character sheet for an RPG.
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    use glium::Surface;
+
+    // initialize our very basic logger so error messages go to stdout
+    thyme::log::init(log::Level::Warn).unwrap();
+
+    let window_size = [1280, 720];
+
+    // create the SDL2 window and OpenGL context
+    let sdl = sdl2::init()?;
+    let video = sdl.video()?;
+    let window = video.window("Thyme Demo", window_size[0], window_size[1])
+        .opengl()
+        .allow_highdpi()
+        .resizable()
+        .build()?;
+    let gl_context = window.gl_create_context()?;
+
+    // hide the default cursor
+    sdl.mouse().show_cursor(false);
+
+    // create thyme io before the window is moved into the glium backend
+    let mut io = thyme::Sdl2Io::new(&window);
+    let clipboard_get = video.clipboard();
+    let clipboard_set = video.clipboard();
+    io.set_clipboard(
+        move || clipboard_get.clipboard_text().ok(),
+        move |text| { let _ = clipboard_set.set_clipboard_text(&text); },
+    );
+
+    // create glium context
+    let backend = SdlBackend { window, gl_context };
+    let display = unsafe {
+        glium::backend::Context::new(backend, true, Default::default())?
+    };
+
+    // create thyme backend
+    let mut renderer = thyme::GliumRenderer::new(&display)?;
+    let mut context_builder = thyme::ContextBuilder::with_defaults();
+
+    demo::register_assets(&mut context_builder);
+
+    let mut context = context_builder.build(&mut renderer, &mut io)?;
+
+    let mut party = demo::Party::default();
+    let mut event_pump = sdl.event_pump()?;
+
+    // run main loop
+    'running: loop {
+        let frame_start = std::time::Instant::now();
+
+        for event in event_pump.poll_iter() {
+            match event {
+                Event::Quit { .. } => break 'running,
+                event => io.handle_event(&mut context, &event),
+            }
+        }
+
+        party.check_context_changes(&mut context, &mut renderer);
+
+        let dimensions = display.get_framebuffer_dimensions();
+        let mut target = glium::Frame::new(Rc::clone(&display), dimensions);
+        target.clear_color(0.21404, 0.21404, 0.21404, 1.0); // manual sRGB conversion for 0.5
+
+        bench::run("thyme", || {
+            let mut ui = context.create_frame();
+
+            bench::run("frame", || {
+                // show a custom cursor.  it automatically inherits mouse presses in its state
+                ui.set_mouse_cursor("gui/cursor", Align::TopLeft);
+                demo::build_ui(&mut ui, &mut party);
+            });
+
+            bench::run("draw", || {
+                renderer.draw_frame(&mut target, ui).unwrap();
+            });
+        });
+
+        target.finish().unwrap();
+
+        let frame_time = std::time::Duration::from_millis(16);
+        if let Some(remaining) = frame_time.checked_sub(frame_start.elapsed()) {
+            std::thread::sleep(remaining);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,149 @@
+use thyme::{Context, Frame, HeadlessIo, HeadlessRenderer, HeightRelative, InputLog, KeyCode, Point, WidthRelative};
+
+/// Runs a few scripted UI interactions without a window or GPU, checking the results.  This
+/// shows how to use the headless renderer and IO to write automated tests for your UI, how
+/// to record inputs and replay them later, and how to save and restore the UI layout.
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // initialize very basic logger so error messages go to stdout
+    thyme::log::init(log::Level::Warn).unwrap();
+
+    let mut test = Test::new()?;
+    test.context.start_recording();
+
+    // hover over the button for a frame, then click it
+    test.frame();
+    assert_eq!(test.renderer.quad("window").unwrap().rect.size, Point::new(300.0, 400.0));
+    let button = test.center_of("window/button");
+    test.io.move_mouse(&mut test.context, button);
+    test.frame();
+    test.io.click(&mut test.context, button);
+    test.frame();
+    assert_eq!(test.clicks, 1);
+
+    // scroll the scrollpane down
+    let pane = test.center_of("pane");
+    test.io.move_mouse(&mut test.context, pane);
+    test.frame();
+    test.io.scroll(&mut test.context, Point::new(0.0, -2.0));
+    test.frame();
+
+    // the scroll is smoothed over time, which only advances when the IO says so
+    test.io.advance_time(&mut test.context, 500);
+    test.frame();
+    assert!(test.scroll != 0.0);
+
+    // give the input field keyboard focus, then type into it
+    let field = test.center_of("field");
+    test.io.move_mouse(&mut test.context, field);
+    test.frame();
+    test.io.click(&mut test.context, field);
+    test.frame();
+    test.io.type_text(&mut test.context, "Thyme");
+    test.frame();
+    test.io.press_key(&mut test.context, KeyCode::Backspace);
+    test.io.release_key(&mut test.context, KeyCode::Backspace);
+    test.frame();
+    assert_eq!(test.text, "Thym");
+
+    // widgets see exactly the time set by the IO
+    test.io.set_time(&mut test.context, 2000);
+    test.frame();
+    assert_eq!(test.time, 2000);
+
+    // save the recorded inputs, then replay them against a new context, getting the same results
+    let log = test.context.finish_recording().unwrap();
+    let log: InputLog = serde_yaml::from_str(&serde_yaml::to_string(&log)?)?;
+
+    let mut replay = Test::new()?;
+    let Test { context, renderer, clicks, scroll, text, time, .. } = &mut replay;
+    log.replay(context, |mut ui| {
+        build_ui(&mut ui, clicks, scroll, text, time);
+        renderer.draw_frame(ui);
+    });
+
+    assert_eq!((replay.clicks, replay.scroll, &replay.text, replay.time), (test.clicks, test.scroll, &test.text, test.time));
+    assert_eq!(replay.renderer.quads(), test.renderer.quads());
+
+    // save the scroll and other layout state, then load it into a new context
+    let saved = serde_yaml::to_string(&test.context.save_persistent_state())?;
+    let mut restored = Test::new()?;
+    restored.context.load_persistent_state(serde_yaml::Deserializer::from_str(&saved))?;
+    restored.frame();
+    assert_eq!(restored.scroll, test.scroll);
+
+    println!("All headless UI checks passed.");
+    Ok(())
+}
+
+struct Test {
+    context: Context,
+    renderer: HeadlessRenderer,
+    io: HeadlessIo,
+    clicks: u32,
+    scroll: f32,
+    text: String,
+    time: u32,
+}
+
+impl Test {
+    fn new() -> Result<Test, Box<dyn std::error::Error>> {
+        // load assets
+        let font_src = include_bytes!("data/fonts/Roboto-Medium.ttf");
+        let image_src = include_bytes!("data/images/gui-pixel.png");
+        let image = image::load_from_memory(image_src).unwrap().to_rgba();
+        let theme_src = include_str!("data/theme-base.yml");
+        let theme: serde_yaml::Value = serde_yaml::from_str(theme_src)?;
+
+        // create the headless thyme backend
+        let mut io = HeadlessIo::new(Point::new(1280.0, 720.0));
+        let mut renderer = HeadlessRenderer::new();
+        let mut context_builder = thyme::ContextBuilder::new(thyme::BuildOptions { enable_live_reload: false });
+
+        // register resources in thyme and create the context
+        let image_dims = image.dimensions();
+        context_builder.register_theme(theme)?;
+        context_builder.register_texture("pixel", image.into_raw(), image_dims);
+        context_builder.register_font("roboto", font_src.to_vec());
+        let context = context_builder.build(&mut renderer, &mut io)?;
+
+        Ok(Test { context, renderer, io, clicks: 0, scroll: 0.0, text: String::new(), time: 0 })
+    }
+
+    fn frame(&mut self) {
+        let mut ui = self.context.create_frame();
+        build_ui(&mut ui, &mut self.clicks, &mut self.scroll, &mut self.text, &mut self.time);
+        self.renderer.draw_frame(ui);
+    }
+
+    fn center_of(&self, id: &str) -> Point {
+        let rect = self.renderer.quad(id).unwrap_or_else(|| panic!("Widget '{}' was not drawn", id)).rect;
+        rect.pos + rect.size * 0.5
+    }
+}
+
+fn build_ui(ui: &mut Frame, clicks: &mut u32, scroll: &mut f32, text: &mut String, time: &mut u32) {
+    *time = ui.cur_time_millis();
+
+    ui.window("window", |ui| {
+        if ui.button("button", "Click Me").clicked {
+            *clicks += 1;
+        }
+
+        if let Some(new_text) = ui.input_field("input_field", "field", None) {
+            *text = new_text;
+        }
+
+        ui.start("scrollpane")
+        .id("pane")
+        .size_from(WidthRelative::Normal, HeightRelative::Normal)
+        .size(200.0, 100.0)
+        .scrollpane("pane_content")
+        .children(|ui| {
+            for i in 0..20 {
+                ui.label("label", format!("Row #{}", i));
+            }
+        });
+
+        *scroll = ui.scroll("pane_content").y;
+    });
+}
@@ -0,0 +1,146 @@
+use crate::{Color, Context, Point, Rect, image::ImageHandle};
+
+// primitives are broken up into strips of this size, in logical pixels, when they
+// can't be drawn as a single rectangle
+const STRIP_SIZE: f32 = 1.0;
+
+#[derive(Clone)]
+pub(crate) enum CanvasCommand {
+    Solid {
+        pos: Point,
+        size: Point,
+        color: Color,
+    },
+    Image {
+        image: ImageHandle,
+        pos: Point,
+        size: Point,
+    }
+}
+
+/**
+A surface for drawing simple graphics inside a widget, such as a minimap or lines connecting nodes in a graph.
+
+Create this using [`Frame.canvas`](struct.Frame.html#method.canvas) or [`WidgetBuilder.canvas`](struct.WidgetBuilder.html#method.canvas).
+All coordinates are in logical pixels, relative to the top left corner of the widget's inner area, and everything drawn is
+clipped to that area.  Drawing happens in the widget's render group, in place of its foreground, so the canvas is layered
+and clipped exactly as any other widget would be.
+
+Lines, rectangles, and circles are drawn using the canvas widget's foreground image as a brush, tinted to the requested
+color.  The brush should be a small, solid white simple image.  Images are drawn at the specified rectangle using their
+normal appearance.
+**/
+pub struct Canvas<'a> {
+    context: &'a Context,
+    size: Point,
+    color: Color,
+    commands: Vec<CanvasCommand>,
+}
+
+impl<'a> Canvas<'a> {
+    pub(crate) fn new(context: &'a Context, size: Point, color: Color) -> Canvas<'a> {
+        Canvas {
+            context,
+            size,
+            color,
+            commands: Vec::new(),
+        }
+    }
+
+    pub(crate) fn into_commands(self) -> Vec<CanvasCommand> {
+        self.commands
+    }
+
+    /// Returns the size of the canvas' drawable area, in logical pixels.
+    pub fn size(&self) -> Point { self.size }
+
+    /// Returns the canvas widget's text color, allowing drawing colors to be specified in the theme.
+    pub fn color(&self) -> Color { self.color }
+
+    /// Draws a line from `p1` to `p2`, `width` logical pixels thick.
+    pub fn line(&mut self, p1: Point, p2: Point, width: f32, color: Color) {
+        let delta = p2 - p1;
+        let len = (delta.x * delta.x + delta.y * delta.y).sqrt();
+        if len == 0.0 || width <= 0.0 { return; }
+
+        // axis aligned lines are a single rectangle
+        if delta.y == 0.0 {
+            self.push_solid(Point::new(p1.x.min(p2.x), p1.y - width / 2.0), Point::new(len, width), color);
+            return;
+        } else if delta.x == 0.0 {
+            self.push_solid(Point::new(p1.x - width / 2.0, p1.y.min(p2.y)), Point::new(width, len), color);
+            return;
+        }
+
+        // otherwise, draw strips along the major axis, each thick enough to keep the
+        // line's width perpendicular to its direction
+        let horizontal = delta.x.abs() >= delta.y.abs();
+        let major = if horizontal { delta.x.abs() } else { delta.y.abs() };
+        let steps = (major / STRIP_SIZE).ceil().max(1.0);
+        let step = delta / steps;
+        let thickness = width * len / major;
+
+        for i in 0..(steps as u32) {
+            let start = p1 + step * i as f32;
+            let center = start + step / 2.0;
+            let (pos, size) = if horizontal {
+                (Point::new(start.x.min(start.x + step.x), center.y - thickness / 2.0), Point::new(step.x.abs(), thickness))
+            } else {
+                (Point::new(center.x - thickness / 2.0, start.y.min(start.y + step.y)), Point::new(thickness, step.y.abs()))
+            };
+            self.push_solid(pos, size, color);
+        }
+    }
+
+    /// Draws the outline of `rect`, one logical pixel thick.
+    pub fn rect(&mut self, rect: Rect, color: Color) {
+        let (pos, size) = (rect.pos, rect.size);
+        if size.x <= 0.0 || size.y <= 0.0 { return; }
+
+        let width = STRIP_SIZE.min(size.x);
+        let height = STRIP_SIZE.min(size.y);
+        self.push_solid(pos, Point::new(size.x, height), color);
+        self.push_solid(Point::new(pos.x, pos.y + size.y - height), Point::new(size.x, height), color);
+        self.push_solid(Point::new(pos.x, pos.y + height), Point::new(width, size.y - 2.0 * height), color);
+        self.push_solid(Point::new(pos.x + size.x - width, pos.y + height), Point::new(width, size.y - 2.0 * height), color);
+    }
+
+    /// Draws `rect`, filled with `color`.
+    pub fn filled_rect(&mut self, rect: Rect, color: Color) {
+        if rect.size.x <= 0.0 || rect.size.y <= 0.0 { return; }
+
+        self.push_solid(rect.pos, rect.size, color);
+    }
+
+    /// Draws a filled circle at `center` with the specified `radius`.
+    pub fn circle(&mut self, center: Point, radius: f32, color: Color) {
+        if radius <= 0.0 { return; }
+
+        let mut y = -radius;
+        while y < radius {
+            let height = STRIP_SIZE.min(radius - y);
+            let mid = y + height / 2.0;
+            let half_width = (radius * radius - mid * mid).max(0.0).sqrt();
+            self.push_solid(
+                Point::new(center.x - half_width, center.y + y),
+                Point::new(2.0 * half_width, height),
+                color,
+            );
+            y += height;
+        }
+    }
+
+    /// Draws the image `image_id` to fill `rect`.  The ID consists of "{image_set_id}/{image_id}".
+    /// If the image is not found, an error is logged and nothing is drawn.
+    pub fn image(&mut self, image_id: &str, rect: Rect) {
+        if let Some(image) = self.context.find_image(image_id) {
+            self.commands.push(CanvasCommand::Image { image, pos: rect.pos, size: rect.size });
+        }
+    }
+
+    fn push_solid(&mut self, pos: Point, size: Point, color: Color) {
+        if size.x <= 0.0 || size.y <= 0.0 { return; }
+
+        self.commands.push(CanvasCommand::Solid { pos, size, color });
+    }
+}
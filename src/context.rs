@@ -1,13 +1,31 @@
-use std::collections::{HashMap, HashSet};
+use std::any::{Any, TypeId};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::cell::RefCell;
 use std::rc::Rc;
 use std::path::{Path, PathBuf};
-use std::time::Instant;
+use std::time::{Duration, Instant};
+
+use serde::{Serialize, Deserialize};
 
 use crate::{Error, Point, Frame, Rect, frame::{RendGroup, RendGroupDef}};
-use crate::{font::FontSummary, widget::Widget, image::ImageHandle, theme::ThemeSet, resource::ResourceSet};
-use crate::theme_definition::{AnimState, AnimStateKey};
-use crate::render::Renderer;
+use crate::{font::{FontAtlasStats, FontSummary}, widget::Widget, image::ImageHandle, theme::ThemeSet, resource::{ResourceSet, ThemeSets}};
+use crate::theme_definition::{AnimState, AnimStateKey, ThemeDefinition};
+use crate::render::{FontHandle, Renderer, TextureHandle};
+use crate::scrollpane::{ScrollRequest, SmoothScroll};
+use crate::transition::{Transition, TransitionDef, TransitionValues};
+use crate::recording::{InputEvent, InputLog};
+
+// the most times a held key is repeated on a single frame, so a long frame doesn't flood input
+const MAX_KEY_REPEATS_PER_FRAME: u32 = 2;
+
+// a notification shown by `Frame::render_toasts`
+#[derive(Clone)]
+pub(crate) struct Toast {
+    pub id: u32,
+    pub text: String,
+    pub expire_millis: u32,
+    pub duration_millis: u32,
+}
 
 #[derive(Copy, Clone)]
 pub(crate) struct PersistentStateData {
@@ -18,6 +36,34 @@ pub(crate) struct PersistentStateData {
     pub scroll: Point,
 }
 
+/**
+The layout related parts of the [`PersistentState`](struct.PersistentState.html) of all widgets, saved with
+[`Context::save_persistent_state`](struct.Context.html#method.save_persistent_state) so that window positions,
+sizes, collapsed states, and scroll positions survive restarts.  This may be serialized with any `serde` format,
+and then loaded back with [`Context::load_persistent_state`](struct.Context.html#method.load_persistent_state).
+*/
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct SavedState {
+    widgets: BTreeMap<String, SavedWidgetState>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+struct SavedWidgetState {
+    is_open: bool,
+    expanded: bool,
+    resize: Point,
+    moved: Point,
+    scroll: Point,
+    split: Option<f32>,
+}
+
+impl Default for SavedWidgetState {
+    fn default() -> Self {
+        PersistentState::default().saved()
+    }
+}
+
 /**
 The internal state stored by Thyme for a given Widget that
 persists between frames.
@@ -64,11 +110,158 @@ pub struct PersistentState {
     /// empty.  Widgets should typically drain this list as they work with input.
     pub characters: Vec<char>,
 
+    /// Any non-character [`key events`](struct.KeyEvent.html) that have been sent to this
+    /// widget from the keyboard.  Defaults to empty.  Like `characters`, widgets should
+    /// typically drain this list as they work with input.
+    pub keys: Vec<KeyEvent>,
+
     /// The text for this widget, overriding default text.  Defaults to `None`.
     pub text: Option<String>,
+
+    /// The position of the text editing caret for this widget, as a character (not byte) index
+    /// into `text`.  Used by [`input fields`](struct.Frame.html#method.input_field).  Defaults to zero.
+    pub caret: usize,
+
+    /// The anchor of the text selection for this widget, as a character index into `text`.  The
+    /// selected text is between this anchor and the `caret`.  Used by
+    /// [`input fields`](struct.Frame.html#method.input_field).  Defaults to `None`, meaning no selection.
+    pub selection: Option<usize>,
+
+    /// Whether this widget had keyboard focus when it was last built.  Used by
+    /// [`input fields`](struct.Frame.html#method.input_field_with) to detect gaining
+    /// or losing focus.  Defaults to `false`.
+    pub focused: bool,
+
+    /// The extra size, in logical pixels, along this widget's layout axis given to each of its
+    /// children that [`expand`](struct.WidgetBuilder.html#method.expand).  This is recomputed
+    /// from the space left over in the layout each time the widget is built.  Defaults to zero.
+    pub expand_share: f32,
+
+    /// The bounds of this widget's direct children, relative to the widget's position, as of the
+    /// last time the widget was built.  This is only recorded for some widgets, such as
+    /// [`scrollpane`](struct.ScrollpaneBuilder.html) content, where it is used to decide whether
+    /// scrollbars are needed before the content is built.  Defaults to an empty `Rect`.
+    pub children_bounds: Rect,
+
+    /// The fraction currently shown by an animated [`progress bar`](struct.Frame.html#method.progress_bar_with),
+    /// which moves toward the bar's actual fraction over time.  Defaults to `None`, meaning the bar has not been
+    /// shown yet.
+    pub progress: Option<f32>,
+
+    /// The time, in millis, that `progress` was last updated.  Defaults to zero.
+    pub progress_millis: u32,
+
+    /// The fraction of the space given to the first pane of a [`split`](struct.Frame.html#method.split_horizontal),
+    /// from 0.0 to 1.0.  Defaults to `None`, meaning the split's initial fraction is used.
+    pub split: Option<f32>,
+
+    /// The [`AnimStateKey`](enum.AnimStateKey.html) added to this widget by [`Frame.animate`](struct.Frame.html#method.animate),
+    /// along with the time, in millis, when it is removed again.  Defaults to `None`.
+    pub animation: Option<(AnimStateKey, u32)>,
+
+    /// Arbitrary application or custom widget data stored for this widget.  Defaults to empty.  See
+    /// [`Frame::modify_user_state`](struct.Frame.html#method.modify_user_state).
+    pub user_state: UserState,
+}
+
+// a value stored in a `UserState`, which must be cloneable so that state can be copied while measuring
+trait UserData: Any {
+    fn clone_box(&self) -> Box<dyn UserData>;
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    fn into_any(self: Box<Self>) -> Box<dyn Any>;
+    fn type_name(&self) -> &'static str;
+}
+
+impl<T: Any + Clone> UserData for T {
+    fn clone_box(&self) -> Box<dyn UserData> { Box::new(self.clone()) }
+    fn as_any(&self) -> &dyn Any { self }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+    fn into_any(self: Box<Self>) -> Box<dyn Any> { self }
+    fn type_name(&self) -> &'static str { std::any::type_name::<T>() }
+}
+
+/**
+Typed data stored per widget in its [`PersistentState`](struct.PersistentState.html), for use by the application and
+custom widgets, such as a selected row index or sort column.  One value of each type may be stored, so values of
+different types never conflict, and a value can never be read back as the wrong type.  User state is removed along
+with the rest of the widget's state, such as by [`Frame::clear`](struct.Frame.html#method.clear), and is not included
+in a [`SavedState`](struct.SavedState.html).
+*/
+#[derive(Default)]
+pub struct UserState {
+    data: HashMap<TypeId, Box<dyn UserData>>,
+}
+
+impl UserState {
+    /// Returns the stored value of type `T`, if there is one.
+    pub fn get<T: Any + Clone>(&self) -> Option<&T> {
+        self.data.get(&TypeId::of::<T>()).and_then(|data| data.as_ref().as_any().downcast_ref())
+    }
+
+    /// Returns a mutable reference to the stored value of type `T`, first storing the default value if there is none.
+    pub fn get_mut<T: Any + Clone + Default>(&mut self) -> &mut T {
+        self.data.entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(T::default()))
+            .as_mut()
+            .as_any_mut()
+            .downcast_mut()
+            .expect("User state is stored by its type")
+    }
+
+    /// Stores `value`, replacing any previously stored value of type `T`.
+    pub fn set<T: Any + Clone>(&mut self, value: T) {
+        self.data.insert(TypeId::of::<T>(), Box::new(value));
+    }
+
+    /// Removes and returns the stored value of type `T`, if there is one.
+    pub fn remove<T: Any + Clone>(&mut self) -> Option<T> {
+        let data = self.data.remove(&TypeId::of::<T>())?;
+        data.into_any().downcast().ok().map(|value: Box<T>| *value)
+    }
+
+    /// Removes all stored values.
+    pub fn clear(&mut self) {
+        self.data.clear();
+    }
+}
+
+impl Clone for UserState {
+    fn clone(&self) -> Self {
+        UserState {
+            data: self.data.iter().map(|(id, data)| (*id, data.as_ref().clone_box())).collect(),
+        }
+    }
+}
+
+impl std::fmt::Debug for UserState {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_set().entries(self.data.values().map(|data| data.as_ref().type_name())).finish()
+    }
 }
 
 impl PersistentState {
+    fn saved(&self) -> SavedWidgetState {
+        SavedWidgetState {
+            is_open: self.is_open,
+            expanded: self.expanded,
+            resize: self.resize,
+            moved: self.moved,
+            scroll: self.scroll,
+            split: self.split,
+        }
+    }
+
+    // applies `saved` to this state, keeping the position and size within a display of `display_size`
+    fn load(&mut self, saved: &SavedWidgetState, display_size: Point) {
+        self.is_open = saved.is_open;
+        self.expanded = saved.expanded;
+        self.resize = saved.resize.min(display_size);
+        self.moved = saved.moved.max(display_size * -1.0).min(display_size);
+        self.scroll = saved.scroll;
+        self.split = saved.split.map(|split| split.clamp(0.0, 1.0));
+    }
+
     pub(crate) fn copy_data(&self) -> PersistentStateData {
         PersistentStateData {
             is_open: self.is_open,
@@ -78,6 +271,31 @@ impl PersistentState {
             scroll: self.scroll,
         }
     }
+
+    // a copy used as the scratch state when measuring, so the original is never modified
+    fn measure_copy(&self) -> PersistentState {
+        PersistentState {
+            is_open: self.is_open,
+            expanded: self.expanded,
+            resize: self.resize,
+            moved: self.moved,
+            scroll: self.scroll,
+            base_time_millis: self.base_time_millis,
+            characters: self.characters.clone(),
+            keys: self.keys.clone(),
+            text: self.text.clone(),
+            caret: self.caret,
+            selection: self.selection,
+            focused: self.focused,
+            expand_share: self.expand_share,
+            children_bounds: self.children_bounds,
+            progress: self.progress,
+            progress_millis: self.progress_millis,
+            split: self.split,
+            animation: self.animation,
+            user_state: self.user_state.clone(),
+        }
+    }
 }
 
 impl Default for PersistentState {
@@ -90,14 +308,25 @@ impl Default for PersistentState {
             scroll: Point::default(),
             base_time_millis: 0,
             characters: Vec::default(),
+            keys: Vec::default(),
             text: None,
+            caret: 0,
+            selection: None,
+            focused: false,
+            expand_share: 0.0,
+            children_bounds: Rect::default(),
+            progress: None,
+            progress_millis: 0,
+            split: None,
+            animation: None,
+            user_state: UserState::default(),
         }
     }
 }
 
 /// The current state of the various keyboard modifier keys - Shift, Control, and Alt
 /// You can get this using [`Frame.input_modiifers`](struct.Frame.html#method.input_modifiers)
-#[derive(Default, Copy, Clone)]
+#[derive(Serialize, Deserialize, Default, Copy, Clone, Debug, PartialEq, Eq)]
 pub struct InputModifiers {
     /// whether the Shift key is pressed
     pub shift: bool,
@@ -109,37 +338,220 @@ pub struct InputModifiers {
     pub alt: bool,
 }
 
+/// A keyboard key that does not produce a character, or produces only a control character.
+/// These keys are sent to the widget with keyboard focus as [`KeyEvents`](struct.KeyEvent.html),
+/// rather than as characters.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum KeyCode {
+    /// The left arrow key
+    Left,
+
+    /// The right arrow key
+    Right,
+
+    /// The up arrow key
+    Up,
+
+    /// The down arrow key
+    Down,
+
+    /// The Home key
+    Home,
+
+    /// The End key
+    End,
+
+    /// The Page Up key
+    PageUp,
+
+    /// The Page Down key
+    PageDown,
+
+    /// The Insert key
+    Insert,
+
+    /// The Delete key
+    Delete,
+
+    /// The Backspace key
+    Backspace,
+
+    /// The Enter or Return key, including the numpad Enter
+    Return,
+
+    /// The Tab key
+    Tab,
+
+    /// The Escape key
+    Escape,
+
+    /// The Space bar.  This is sent as a key in addition to the space character
+    Space,
+
+    /// Select all text, typically Ctrl+A
+    SelectAll,
+
+    /// Copy the selected text to the clipboard, typically Ctrl+C
+    Copy,
+
+    /// Cut the selected text to the clipboard, typically Ctrl+X
+    Cut,
+}
+
+/// A key press sent to the widget with keyboard focus, consisting of the [`KeyCode`](enum.KeyCode.html)
+/// and the state of the [`InputModifiers`](struct.InputModifiers.html) at the time of the press.
+/// See [`Frame.keys_pressed`](struct.Frame.html#method.keys_pressed).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct KeyEvent {
+    /// The key that was pressed
+    pub key: KeyCode,
+
+    /// The modifier keys held down when the key was pressed
+    pub modifiers: InputModifiers,
+}
+
+/// A mouse button that may be used to interact with widgets.  See
+/// [`WidgetState.clicked_with`](struct.WidgetState.html#method.clicked_with).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MouseButton {
+    /// The primary, usually left, mouse button
+    Left,
+
+    /// The secondary, usually right, mouse button
+    Right,
+
+    /// The middle mouse button, often the scroll wheel
+    Middle,
+}
+
+impl MouseButton {
+    pub(crate) const ALL: [MouseButton; 3] = [MouseButton::Left, MouseButton::Right, MouseButton::Middle];
+
+    pub(crate) fn index(self) -> usize {
+        match self {
+            MouseButton::Left => 0,
+            MouseButton::Right => 1,
+            MouseButton::Middle => 2,
+        }
+    }
+}
+
 pub struct ContextInternal {
     resources: ResourceSet,
-    themes: ThemeSet,
+    themes: ThemeSets,
+    active_theme: usize,
     frame_active: bool,
 
+    // rebuilt themes, theme switches, and UI scale changes waiting to be applied at the start
+    // of the next frame
+    pending_themes: Option<ThemeSets>,
+    pending_active_theme: Option<String>,
+    pending_ui_scale: Option<f32>,
+
     mouse_taken_last_frame: Option<(String, RendGroup)>,
     mouse_in_rend_group_last_frame: Option<RendGroup>,
-    top_rend_group: RendGroup,
+
+    // the IDs of the render groups built last frame, from the top down, not including the root group
+    rend_group_order: Vec<String>,
     check_set_top_rend_group: Option<String>,
 
-    modal: Option<Modal>,
+    // open modals, from the bottom up.  only the last (top) modal receives input
+    modals: Vec<Modal>,
+
+    // modals dismissed by the user, by clicking outside or pressing Escape, as of the end of the
+    // previous frame, and those dismissed so far in the current frame
+    dismissed_modals: Vec<String>,
+    pending_dismissed_modals: Vec<String>,
 
     mouse_pressed_outside: [bool; 3],
 
     keyboard_focus_widget: Option<String>,
+
+    // whether the mouse was released since the last frame, which clears the keyboard focus unless the
+    // mouse was over the focused widget
+    focus_clear_pending: bool,
+
+    // focusable widgets from the last frame, and pending moves through them from Tab presses
+    focus_order: Vec<String>,
+    focus_handles_tab: bool,
+    focus_move: i32,
+
     persistent_state: HashMap<String, PersistentState>,
     empty_persistent_state: PersistentState,
 
+    // while measuring, state writes go to this scratch map and are discarded afterwards
+    measuring: bool,
+    measure_state: HashMap<String, PersistentState>,
+
+    // scrollpane scroll requests, keyed by content id, applied when the scrollpane is next built
+    scroll_requests: HashMap<String, ScrollRequest>,
+
+    // the animation state of smooth scrolling scrollpanes, keyed by content id
+    smooth_scrolls: HashMap<String, SmoothScroll>,
+
+    // state transitions in progress, by widget ID
+    transitions: HashMap<String, Transition>,
+
     input_modifiers: InputModifiers,
     last_mouse_pos: Point,
     mouse_pos: Point,
     mouse_pressed: [bool; 3],
+    mouse_pressed_last_frame: [bool; 3],
     mouse_clicked: [bool; 3],
     mouse_wheel: Point,
+    mouse_wheel_lines: Point,
+
+    // text copied by a widget this frame, waiting to be sent to the IO clipboard
+    clipboard_text: Option<String>,
+
+    // the key currently held down and the time, in millis, to next repeat it
+    held_key: Option<(KeyCode, u32)>,
+    key_repeat_delay: u32,
+    key_repeat_interval: u32,
+
+    // the payload of the drag and drop in progress, if any, and the IDs of its drag source and
+    // the source's ancestors
+    drag_payload: Option<String>,
+    drag_owners: Vec<String>,
+
+    // the payload of a drag and drop released this frame, to be taken by a drop target
+    dropped_payload: Option<String>,
+
+    // the widget held down with the mouse for repeated presses, the time, in millis, to next repeat it,
+    // and the time it was first pressed
+    held_press: Option<(String, u32, u32)>,
+
+    // the widget that took the current mouse press, and the mouse position when it was pressed.  When
+    // the mouse is released, this moves to `mouse_released` for one frame
+    mouse_press: Option<(String, Point)>,
+    mouse_released: Option<(String, Point)>,
+
+    // the queued notifications, oldest first, and the ID to give the next one
+    toasts: Vec<Toast>,
+    next_toast_id: u32,
+
+    // the widget under the mouse last frame, and the time it was first hovered
+    hovered_widget: Option<String>,
+    hover_start_millis: u32,
+
+    tooltip_theme: String,
+    tooltip_delay: u32,
 
     display_size: Point,
     scale_factor: f32,
 
+    // the user selected UI scale, applied on top of the display scale factor
+    ui_scale: f32,
+
     start_instant: Instant,
     time_millis: u32,
 
+    // the time set by the application, used in place of the time since `start_instant`
+    fixed_time_millis: Option<u32>,
+
+    // all inputs received since `Context::start_recording`
+    recording: Option<InputLog>,
+
     errors: HashSet<String>,
 }
 
@@ -151,41 +563,166 @@ impl ContextInternal {
         self.errors.insert(error);
     }
 
+    pub(crate) fn is_measuring(&self) -> bool { self.measuring }
+
+    pub(crate) fn request_scroll(&mut self, content_id: String, request: ScrollRequest) {
+        self.scroll_requests.insert(content_id, request);
+    }
+
+    // requests are left in place while measuring, so they apply to the scrollpane actually drawn
+    pub(crate) fn take_scroll_request(&mut self, content_id: &str) -> Option<ScrollRequest> {
+        if self.measuring { return None; }
+
+        self.scroll_requests.remove(content_id)
+    }
+
+    pub(crate) fn take_smooth_scroll(&mut self, content_id: &str) -> SmoothScroll {
+        self.smooth_scrolls.remove(content_id).unwrap_or_default()
+    }
+
+    pub(crate) fn set_smooth_scroll(&mut self, content_id: String, smooth: SmoothScroll) {
+        self.smooth_scrolls.insert(content_id, smooth);
+    }
+
+    // computes the current transition values for the widget `id` in `state`.  While measuring,
+    // the values for `state` are returned without touching any transition in progress
+    pub(crate) fn update_transition(&mut self, id: &str, def: &TransitionDef, state: AnimState) -> TransitionValues {
+        if self.measuring { return def.target(state); }
+
+        let now = self.time_millis;
+        match self.transitions.get_mut(id) {
+            Some(transition) => transition.update(def, state, now),
+            None => {
+                let transition = Transition::new(def, state, now);
+                self.transitions.insert(id.to_string(), transition);
+                def.target(state)
+            }
+        }
+    }
+
+    pub(crate) fn start_measure(&mut self) {
+        self.measuring = true;
+    }
+
+    pub(crate) fn end_measure(&mut self) {
+        self.measuring = false;
+        self.measure_state.clear();
+    }
+
     pub(crate) fn mut_modal<F: FnOnce(&mut Modal)>(&mut self, f: F) {
-        if let Some(modal) = self.modal.as_mut() {
+        if self.measuring { return; }
+
+        if let Some(modal) = self.modals.last_mut() {
             (f)(modal);
         }
     }
 
+    /// The ID of the top modal, which is the only one receiving input
     pub(crate) fn modal_id(&self) -> Option<&str> {
-        self.modal.as_ref().map(|modal| modal.id.as_ref())
+        self.modals.last().map(|modal| modal.id.as_ref())
     }
 
     pub(crate) fn has_modal(&self) -> bool {
-        self.modal.is_some()
+        !self.modals.is_empty()
     }
 
+    /// If `id` is an open modal, removes it from the stack along with all modals opened
+    /// on top of it.  Those modals are closed, but the state of `id` itself is left as is.
     pub(crate) fn clear_modal_if_match(&mut self, id: &str) {
-        if self.modal_id() == Some(id) {
-            self.modal.take();
+        if self.measuring { return; }
+
+        if let Some(index) = self.modals.iter().position(|modal| modal.id == id) {
+            self.close_modals_above(index);
+            self.modals.pop();
+        }
+    }
+
+    /// Pushes `id` onto the modal stack.  If it is already open as a modal, the modals above
+    /// it are closed and it becomes the top modal again.
+    pub(crate) fn set_modal(&mut self, id: String, ancestors: Vec<String>) {
+        if self.measuring { return; }
+
+        if let Some(index) = self.modals.iter().position(|modal| modal.id == id) {
+            self.close_modals_above(index);
+            self.modals.pop();
         }
+
+        self.modals.push(Modal::new(id, ancestors));
     }
 
-    pub(crate) fn set_modal(&mut self, id: String) {
-        self.modal = Some(Modal::new(id));
+    /// Closes the modal `id` and any modals above it, as in `clear_modal_if_match`, recording
+    /// it as dismissed by the user for the next frame.
+    pub(crate) fn dismiss_modal(&mut self, id: &str) {
+        if self.measuring { return; }
+
+        if self.modals.iter().any(|modal| modal.id == id) {
+            self.pending_dismissed_modals.push(id.to_string());
+        }
+
+        self.clear_modal_if_match(id);
+        self.state_mut(id.to_string()).is_open = false;
+    }
+
+    pub(crate) fn modal_dismissed(&self, id: &str) -> bool {
+        self.dismissed_modals.iter().any(|dismissed| dismissed == id)
+    }
+
+    // closes and removes all modals above the specified stack index
+    fn close_modals_above(&mut self, index: usize) {
+        let closed: Vec<Modal> = self.modals.drain((index + 1)..).collect();
+        for modal in closed {
+            self.state_mut(modal.id).is_open = false;
+        }
+    }
+
+    /// Returns true if any of the specified `owners` were either closed or not built at all this frame.
+    /// Modals, drags, and overlays such as tooltips and popups are hidden once orphaned this way.
+    pub(crate) fn is_orphaned<F: Fn(&str) -> bool>(&self, owners: &[String], was_built: F) -> bool {
+        owners.iter().any(|id| !was_built(id) || !self.state(id).is_open)
+    }
+
+    /// Closes each modal where any of the widgets it was opened from were
+    /// either closed or not built at all this frame, along with all modals above it.
+    pub(crate) fn close_orphaned_modals<F: Fn(&str) -> bool>(&mut self, was_built: F) {
+        let orphaned = self.modals.iter().position(|modal| self.is_orphaned(&modal.ancestors, &was_built));
+
+        if let Some(index) = orphaned {
+            self.close_modals_above(index);
+            let modal = self.modals.pop().unwrap();
+            self.state_mut(modal.id).is_open = false;
+        }
     }
 
     pub(crate) fn mouse_in_rend_group_last_frame(&self) -> Option<RendGroup> {
         self.mouse_in_rend_group_last_frame
     }
 
-    pub(crate) fn set_top_rend_group(&mut self, group: RendGroup) {
-        self.top_rend_group = group;
+    // moves the render group with the specified `id` to the top of the order
+    pub(crate) fn bring_rend_group_to_front(&mut self, id: &str) {
+        if self.measuring { return; }
+
+        self.rend_group_order.retain(|group| group != id);
+        self.rend_group_order.insert(0, id.to_string());
     }
 
-    pub(crate) fn top_rend_group(&self) -> RendGroup { self.top_rend_group }
+    pub(crate) fn rend_group_order(&self) -> &[String] { &self.rend_group_order }
+
+    // keeps only the `groups` built this frame in the render group order.  Groups that were not
+    // already in the order are placed on top, with the last one created topmost
+    pub(crate) fn update_rend_group_order(&mut self, groups: &[RendGroupDef]) {
+        let built: HashSet<&str> = groups.iter().skip(1).map(|group| group.id()).collect();
+        self.rend_group_order.retain(|id| built.contains(id.as_str()));
+
+        for group in groups.iter().skip(1) {
+            if !self.rend_group_order.iter().any(|id| id == group.id()) {
+                self.rend_group_order.insert(0, group.id().to_string());
+            }
+        }
+    }
 
     pub(crate) fn set_top_rend_group_id(&mut self, id: &str) {
+        if self.measuring { return; }
+
         self.check_set_top_rend_group = Some(id.to_string());
     }
 
@@ -195,26 +732,268 @@ impl ContextInternal {
             Some(id) => id,
         };
 
-        for group in groups {
-            if group.id() == id {
-                self.top_rend_group = group.group();
-                self.check_set_top_rend_group = None;
-                break;
-            }
+        if groups.iter().any(|group| group.id() == id) {
+            let id = id.to_string();
+            self.check_set_top_rend_group = None;
+            self.bring_rend_group_to_front(&id);
         }
     }
 
     pub(crate) fn base_time_millis_for(&self, id: &str) -> u32 {
-        self.persistent_state.get(id).map_or(0, |state| state.base_time_millis)
+        self.state(id).base_time_millis
     }
 
     pub(crate) fn time_millis(&self) -> u32 { self.time_millis }
+
+    // the key added by a running `Frame::animate` on the widget `id`, clearing the animation once it has finished
+    pub(crate) fn animation_key(&mut self, id: &str) -> Option<AnimStateKey> {
+        let (key, end_millis) = self.state(id).animation?;
+        if self.time_millis < end_millis { return Some(key); }
+
+        self.state_mut(id).animation = None;
+        None
+    }
+
+    // how long the widget with the specified id has been continuously under the mouse,
+    // as of the end of the last frame
+    pub(crate) fn hover_millis(&self, id: &str) -> u32 {
+        match &self.hovered_widget {
+            Some(hovered) if hovered == id => self.time_millis.saturating_sub(self.hover_start_millis),
+            _ => 0,
+        }
+    }
+
+    pub(crate) fn tooltip_theme(&self) -> &str { &self.tooltip_theme }
+    pub(crate) fn tooltip_delay(&self) -> u32 { self.tooltip_delay }
     pub(crate) fn mouse_pos(&self) -> Point { self.mouse_pos }
     pub(crate) fn last_mouse_pos(&self) -> Point { self.last_mouse_pos }
     pub(crate) fn mouse_pressed(&self, index: usize) -> bool { self.mouse_pressed[index] }
     pub(crate) fn mouse_clicked(&self, index: usize) -> bool { self.mouse_clicked[index] }
+    pub(crate) fn mouse_just_pressed(&self, index: usize) -> bool {
+        self.mouse_pressed[index] && !self.mouse_pressed_last_frame[index]
+    }
+
+    // records that the widget `id` holds the mouse press, returning whether it was just pressed and
+    // the mouse position when the press started
+    pub(crate) fn take_mouse_press(&mut self, id: &str) -> (bool, Point) {
+        match &self.mouse_press {
+            Some((press_id, start)) if press_id == id => (false, *start),
+            _ => {
+                self.mouse_press = Some((id.to_string(), self.mouse_pos));
+                (true, self.mouse_pos)
+            }
+        }
+    }
+
+    pub(crate) fn mouse_press_id(&self) -> Option<&str> {
+        self.mouse_press.as_ref().map(|(id, _)| id.as_ref())
+    }
+
+    // if the mouse press held by the widget `id` was released this frame, how far the mouse moved
+    // in total while it was pressed
+    pub(crate) fn mouse_released_drag(&self, id: &str) -> Option<Point> {
+        if self.measuring { return None; }
+
+        match &self.mouse_released {
+            Some((released_id, start)) if released_id == id => Some(self.mouse_pos - *start),
+            _ => None,
+        }
+    }
+
+    // sends a key event with the current modifiers to the widget with keyboard focus
+    fn push_key(&mut self, key: KeyCode) {
+        let id = match &self.keyboard_focus_widget {
+            Some(id) => id.to_string(),
+            None => return,
+        };
+
+        // Tab moves the focus on the next frame, unless the focused widget handles it
+        if key == KeyCode::Tab && !self.focus_handles_tab {
+            self.focus_move += if self.input_modifiers.shift { -1 } else { 1 };
+            return;
+        }
+
+        let modifiers = self.input_modifiers;
+        let state = self.state_mut(id);
+        state.keys.push(KeyEvent { key, modifiers });
+    }
+
+    pub(crate) fn set_focus_order(&mut self, order: Vec<String>, focus_handles_tab: bool) {
+        self.focus_order = order;
+        self.focus_handles_tab = focus_handles_tab;
+    }
+
+    // moves the keyboard focus through the focusable widgets, wrapping around at the ends
+    fn apply_focus_move(&mut self) {
+        let moves = std::mem::replace(&mut self.focus_move, 0);
+        if moves == 0 || self.focus_order.is_empty() { return; }
+
+        let len = self.focus_order.len() as i32;
+        let current = self.keyboard_focus_widget.as_ref()
+            .and_then(|id| self.focus_order.iter().position(|focus_id| focus_id == id));
+
+        let next = match current {
+            Some(index) => index as i32 + moves,
+            // if the focused widget is not focusable, start from the first or last widget
+            None if moves > 0 => moves - 1,
+            None => len + moves,
+        };
+
+        let id = self.focus_order[next.rem_euclid(len) as usize].clone();
+        self.keyboard_focus_widget = Some(id);
+    }
+
+    fn record(&mut self, event: InputEvent) {
+        if let Some(log) = self.recording.as_mut() {
+            log.push(event);
+        }
+    }
+
+    pub(crate) fn take_recording(&mut self) -> Option<InputLog> {
+        self.recording.take()
+    }
+
+    // the time set with `set_time_millis`, if any, or otherwise the time of the current frame.  Inputs
+    // are timed with this rather than the wall clock, so they follow any time set by the application
+    fn ui_time_millis(&self) -> u32 {
+        self.fixed_time_millis.unwrap_or(self.time_millis)
+    }
+
+    // the time in milliseconds at `now`, unless the time has been fixed with `set_time_millis`
+    fn current_time_millis(&self, now: Instant) -> u32 {
+        match self.fixed_time_millis {
+            Some(time) => time,
+            None => (now - self.start_instant).as_millis() as u32,
+        }
+    }
+
+    // re-sends the held key once for each repeat interval that has elapsed, up to
+    // MAX_KEY_REPEATS_PER_FRAME.  After a long frame, the repeat timer restarts from now
+    // rather than catching up on the missed repeats
+    fn repeat_held_key(&mut self) {
+        let (key, mut next_time) = match self.held_key {
+            None => return,
+            Some(held) => held,
+        };
+
+        let interval = self.key_repeat_interval.max(1);
+        let mut repeats = 0;
+        while next_time <= self.time_millis {
+            if repeats == MAX_KEY_REPEATS_PER_FRAME {
+                next_time = self.time_millis + interval;
+                break;
+            }
+
+            self.push_key(key);
+            repeats += 1;
+            next_time += interval;
+        }
+
+        self.held_key = Some((key, next_time));
+    }
+
+    // returns true when the widget with `id` is first pressed and then once each key repeat
+    // interval while it stays pressed, after the initial key repeat delay
+    pub(crate) fn repeat_press(&mut self, id: &str, pressed: bool) -> bool {
+        if self.measuring { return false; }
+
+        if !pressed {
+            if matches!(&self.held_press, Some((held, ..)) if held == id) {
+                self.held_press = None;
+            }
+            return false;
+        }
+
+        match self.held_press.as_mut() {
+            Some((held, next_time, _)) if held == id => {
+                if *next_time > self.time_millis { return false; }
+
+                *next_time = self.time_millis + self.key_repeat_interval.max(1);
+                true
+            },
+            _ => {
+                self.held_press = Some((id.to_string(), self.time_millis + self.key_repeat_delay, self.time_millis));
+                true
+            }
+        }
+    }
+
+    pub(crate) fn drag_payload(&self) -> Option<&str> { self.drag_payload.as_deref() }
+
+    pub(crate) fn dropped_payload(&self) -> Option<&str> { self.dropped_payload.as_deref() }
+
+    pub(crate) fn drag_owners(&self) -> &[String] { &self.drag_owners }
+
+    pub(crate) fn start_drag(&mut self, payload: String, owners: Vec<String>) {
+        if self.measuring || self.drag_payload.is_some() { return; }
+
+        self.drag_payload = Some(payload);
+        self.drag_owners = owners;
+    }
+
+    /// Cancels the drag and drop in progress if its drag source or any of the source's
+    /// ancestors were either closed or not built at all this frame.
+    pub(crate) fn cancel_orphaned_drag<F: Fn(&str) -> bool>(&mut self, was_built: F) {
+        if self.drag_payload.is_some() && self.is_orphaned(&self.drag_owners, &was_built) {
+            self.cancel_drag();
+        }
+    }
+
+    pub(crate) fn cancel_drag(&mut self) {
+        if self.measuring { return; }
+
+        self.drag_payload = None;
+        self.dropped_payload = None;
+    }
+
+    // the time, in millis, that the widget with `id` has been held down, as tracked by `repeat_press`
+    pub(crate) fn held_millis(&self, id: &str) -> u32 {
+        match &self.held_press {
+            Some((held, _, start)) if held == id => self.time_millis.saturating_sub(*start),
+            _ => 0,
+        }
+    }
+
+    pub(crate) fn set_clipboard_text(&mut self, text: String) {
+        if self.measuring { return; }
+
+        self.clipboard_text = Some(text);
+    }
+
+    pub(crate) fn push_toast(&mut self, text: String, duration_millis: u32) {
+        if self.measuring { return; }
+
+        self.toasts.push(Toast {
+            id: self.next_toast_id,
+            text,
+            expire_millis: self.time_millis + duration_millis,
+            duration_millis,
+        });
+        self.next_toast_id = self.next_toast_id.wrapping_add(1);
+    }
+
+    // removes expired toasts, and the oldest toasts beyond `max`, returning those remaining
+    pub(crate) fn update_toasts(&mut self, max: usize) -> Vec<Toast> {
+        if !self.measuring {
+            let time_millis = self.time_millis;
+            self.toasts.retain(|toast| toast.expire_millis > time_millis);
+
+            let excess = self.toasts.len().saturating_sub(max);
+            self.toasts.drain(..excess);
+        }
+
+        self.toasts.clone()
+    }
+
+    pub(crate) fn dismiss_toast(&mut self, id: u32) {
+        if self.measuring { return; }
+
+        self.toasts.retain(|toast| toast.id != id);
+    }
 
     pub (crate) fn set_focus_keyboard(&mut self, id: String) {
+        if self.measuring { return; }
+
         self.keyboard_focus_widget = Some(id);
     }
 
@@ -222,9 +1001,31 @@ impl ContextInternal {
         self.keyboard_focus_widget.as_deref() == Some(id)
     }
 
-    pub(crate) fn take_mouse_wheel(&mut self) -> Point {
-        let result = self.mouse_wheel;
+    pub(crate) fn clear_focus_keyboard(&mut self) {
+        if self.measuring { return; }
+
+        self.keyboard_focus_widget = None;
+    }
+
+    pub(crate) fn focus_keyboard_id(&self) -> Option<&str> {
+        self.keyboard_focus_widget.as_deref()
+    }
+
+    // gives back unused wheel movement so an enclosing widget can take it
+    pub(crate) fn release_mouse_wheel(&mut self, amount: Point) {
+        if self.measuring { return; }
+
+        self.mouse_wheel = self.mouse_wheel + amount;
+    }
+
+    /// Takes all mouse wheel movement that has occurred this frame, converting any line
+    /// based movement to logical pixels using `line_amount`.
+    pub(crate) fn take_mouse_wheel(&mut self, line_amount: f32) -> Point {
+        if self.measuring { return Point::default(); }
+
+        let result = self.mouse_wheel + self.mouse_wheel_lines * line_amount;
         self.mouse_wheel = Point::default();
+        self.mouse_wheel_lines = Point::default();
         result
     }
 
@@ -232,13 +1033,59 @@ impl ContextInternal {
         self.mouse_taken_last_frame.as_ref().map(|(id, _)| id.as_ref())
     }
 
-    pub(crate) fn scale_factor(&self) -> f32 { self.scale_factor }
+    // the number of physical pixels per logical pixel, including the UI scale
+    pub(crate) fn scale_factor(&self) -> f32 { self.scale_factor * self.ui_scale }
+    pub(crate) fn ui_scale(&self) -> f32 { self.ui_scale }
     pub(crate) fn display_size(&self) -> Point { self.display_size }
+    pub(crate) fn logical_display_size(&self) -> Point { self.display_size / self.scale_factor() }
+
+    pub(crate) fn themes(&self) -> &ThemeSet { &self.themes[self.active_theme].1 }
 
-    pub(crate) fn themes(&self) -> &ThemeSet { &self.themes }
+    // swaps in rebuilt themes, waiting for the next frame if one is currently being built
+    fn set_themes(&mut self, themes: ThemeSets) {
+        if self.frame_active {
+            self.pending_themes = Some(themes);
+        } else {
+            self.replace_themes(themes);
+        }
+    }
+
+    // changes the UI scale, waiting for the next frame if one is currently being built
+    pub(crate) fn set_ui_scale(&mut self, scale: f32) {
+        if !(scale > 0.0 && scale.is_finite()) { return; }
+
+        if self.frame_active {
+            self.pending_ui_scale = Some(scale);
+        } else {
+            self.apply_ui_scale(scale);
+        }
+    }
+
+    // the mouse position is in logical pixels, so it moves with the scale change
+    fn apply_ui_scale(&mut self, scale: f32) {
+        let ratio = self.ui_scale / scale;
+        self.mouse_pos = self.mouse_pos * ratio;
+        self.last_mouse_pos = self.last_mouse_pos * ratio;
+        self.ui_scale = scale;
+    }
+
+    // keeps the same theme set active if it is still present in the rebuilt themes
+    fn replace_themes(&mut self, themes: ThemeSets) {
+        let active_name = &self.themes[self.active_theme].0;
+        self.active_theme = themes.iter().position(|(name, _)| name == active_name).unwrap_or(0);
+        self.themes = themes;
+    }
 
     pub(crate) fn init_state<T: Into<String>>(&mut self, id: T, open: bool, expanded: bool) {
-        self.persistent_state.entry(id.into()).or_insert(
+        let id = id.into();
+        if self.measuring {
+            if !self.persistent_state.contains_key(&id) && !self.measure_state.contains_key(&id) {
+                self.measure_state.insert(id, PersistentState { is_open: open, expanded, ..Default::default() });
+            }
+            return;
+        }
+
+        self.persistent_state.entry(id).or_insert(
             PersistentState {
                 is_open: open,
                 expanded,
@@ -248,10 +1095,40 @@ impl ContextInternal {
     }
 
     pub(crate) fn clear_state(&mut self, id: &str) {
-        self.persistent_state.remove(id);
+        if self.measuring {
+            self.measure_state.insert(id.to_string(), PersistentState::default());
+            return;
+        }
+
+        self.clear_states(|state_id| state_id == id);
+    }
+
+    // removes the state of all widgets whose id `matches`, closing any of them that are modals
+    // along with the modals above them, and dropping keyboard focus if one of them has it
+    pub(crate) fn clear_states<F: Fn(&str) -> bool>(&mut self, matches: F) {
+        self.persistent_state.retain(|id, _| !matches(id));
+
+        if let Some(index) = self.modals.iter().position(|modal| matches(&modal.id)) {
+            let cleared: Vec<Modal> = self.modals.drain(index..).collect();
+            for modal in cleared {
+                if !matches(&modal.id) {
+                    self.state_mut(modal.id).is_open = false;
+                }
+            }
+        }
+
+        if matches!(self.keyboard_focus_widget.as_deref(), Some(id) if matches(id)) {
+            self.keyboard_focus_widget = None;
+        }
     }
 
     pub(crate) fn state(&self, id: &str) -> &PersistentState {
+        if self.measuring {
+            if let Some(state) = self.measure_state.get(id) {
+                return state;
+            }
+        }
+
         match self.persistent_state.get(id) {
             None => &self.empty_persistent_state,
             Some(state) => state,
@@ -259,6 +1136,14 @@ impl ContextInternal {
     }
 
     pub(crate) fn state_mut<T: Into<String>>(&mut self, id: T) -> &mut PersistentState {
+        if self.measuring {
+            let id = id.into();
+            let persistent_state = &self.persistent_state;
+            return self.measure_state.entry(id).or_insert_with_key(|id| {
+                persistent_state.get(id).map(PersistentState::measure_copy).unwrap_or_default()
+            });
+        }
+
         self.persistent_state.entry(id.into()).or_default()
     }
 
@@ -274,22 +1159,39 @@ impl ContextInternal {
     }
 
     pub(crate) fn next_frame(&mut self, mouse_taken: Option<(String, RendGroup)>, mouse_in_rend_group: Option<RendGroup>) {
+        // only the top modal may be closed by clicking outside of it
         let mut clear_modal = false;
-        if let Some(modal) = self.modal.as_mut() {
-            if modal.prevent_close {
-                modal.prevent_close = false;
-            } else if modal.close_on_click_outside && self.mouse_clicked[0] && !modal.bounds.is_inside(self.mouse_pos) {
+        if let Some(modal) = self.modals.last() {
+            if !modal.prevent_close && modal.close_on_click_outside && self.mouse_clicked[0] &&
+                !modal.bounds.is_inside(self.mouse_pos) {
                 clear_modal = true;
             }
         }
 
+        for modal in self.modals.iter_mut() {
+            modal.prevent_close = false;
+        }
+
+        self.dismissed_modals = std::mem::take(&mut self.pending_dismissed_modals);
         if clear_modal {
-            let modal = self.modal.take().unwrap();
-            self.state_mut(modal.id).is_open = false;
+            let modal = self.modals.pop().unwrap();
+            self.state_mut(modal.id.clone()).is_open = false;
+            self.dismissed_modals.push(modal.id);
         }
 
+        self.transitions.retain(|_, transition| transition.take_seen());
+
         self.mouse_wheel = Point::default();
+        self.mouse_wheel_lines = Point::default();
         self.mouse_clicked = [false; 3];
+        self.mouse_pressed_last_frame = self.mouse_pressed;
+
+        let hovered = mouse_taken.as_ref().map(|(id, _)| id);
+        if hovered != self.hovered_widget.as_ref() {
+            self.hovered_widget = hovered.cloned();
+            self.hover_start_millis = self.time_millis;
+        }
+
         self.mouse_taken_last_frame = mouse_taken;
         self.last_mouse_pos = self.mouse_pos;
         self.mouse_in_rend_group_last_frame = mouse_in_rend_group;
@@ -311,7 +1213,7 @@ pub struct Context {
 impl Context {
     pub(crate) fn new(
         resources: ResourceSet,
-        themes: ThemeSet,
+        themes: ThemeSets,
         display_size: Point,
         scale_factor: f32
     ) -> Context {
@@ -319,26 +1221,62 @@ impl Context {
             resources,
             display_size,
             scale_factor,
+            ui_scale: 1.0,
             themes,
+            active_theme: 0,
             persistent_state: HashMap::new(),
             empty_persistent_state: PersistentState::default(),
+            measuring: false,
+            measure_state: HashMap::new(),
+            scroll_requests: HashMap::new(),
+            smooth_scrolls: HashMap::new(),
+            transitions: HashMap::new(),
             mouse_pos: Point::default(),
             last_mouse_pos: Point::default(),
             input_modifiers: InputModifiers::default(),
             mouse_pressed: [false; 3],
+            mouse_pressed_last_frame: [false; 3],
             mouse_clicked: [false; 3],
             mouse_wheel: Point::default(),
+            mouse_wheel_lines: Point::default(),
+            clipboard_text: None,
+            held_key: None,
+            held_press: None,
+            mouse_press: None,
+            mouse_released: None,
+            toasts: Vec::new(),
+            next_toast_id: 0,
+            drag_payload: None,
+            drag_owners: Vec::new(),
+            dropped_payload: None,
+            key_repeat_delay: 500,
+            key_repeat_interval: 35,
+            hovered_widget: None,
+            hover_start_millis: 0,
+            tooltip_theme: "tooltip".to_string(),
+            tooltip_delay: 500,
             mouse_taken_last_frame: None,
             mouse_in_rend_group_last_frame: None,
-            top_rend_group: RendGroup::default(),
+            rend_group_order: Vec::new(),
             check_set_top_rend_group: None,
             mouse_pressed_outside: [false; 3],
-            modal: None,
+            modals: Vec::new(),
+            dismissed_modals: Vec::new(),
+            pending_dismissed_modals: Vec::new(),
             time_millis: 0,
             start_instant: Instant::now(),
+            fixed_time_millis: None,
+            recording: None,
             keyboard_focus_widget: None,
+            focus_clear_pending: false,
+            focus_order: Vec::new(),
+            focus_handles_tab: false,
+            focus_move: 0,
             errors: HashSet::new(),
             frame_active: false,
+            pending_themes: None,
+            pending_active_theme: None,
+            pending_ui_scale: None,
         };
 
         Context {
@@ -374,36 +1312,273 @@ impl Context {
     /// or game logic should handle input.
     pub fn wants_mouse(&self) -> bool {
         let internal = self.internal.borrow();
-        internal.mouse_taken_last_frame.is_some() || internal.modal.is_some()
+        internal.mouse_taken_last_frame.is_some() || !internal.modals.is_empty()
+    }
+
+    /// Returns true if a widget, such as an [`input field`](struct.Frame.html#method.input_field), has keyboard
+    /// focus, in which case key presses should not be used for other purposes, such as game hotkeys.  See
+    /// [`Frame::focus_keyboard`](struct.Frame.html#method.focus_keyboard).
+    pub fn wants_keyboard(&self) -> bool {
+        let internal = self.internal.borrow();
+        internal.keyboard_focus_widget.is_some()
+    }
+
+    /// Returns the current size, glyph count, and occupancy of the glyph atlas for each font in
+    /// the active theme, sorted by font ID.  Atlases start small and grow as new characters are drawn,
+    /// evicting glyphs that have not been recently used once they reach their maximum size.
+    pub fn font_atlas_stats(&self) -> Vec<(String, FontAtlasStats)> {
+        let internal = self.internal.borrow();
+        internal.themes().font_atlas_stats()
+    }
+
+    /// Returns the ID of the image registered with the [`ContextBuilder`](struct.ContextBuilder.html) that
+    /// the texture with `handle` was created from, or `None` if there is no such image, such as for the
+    /// internal texture used to draw solid colors.  This is useful for custom renderers working with
+    /// [`DrawData`](struct.DrawData.html).
+    pub fn texture_id(&self, handle: TextureHandle) -> Option<String> {
+        let internal = self.internal.borrow();
+        internal.resources.texture_id(handle).map(|id| id.to_string())
+    }
+
+    /// Returns the ID of the font in the theme that was registered with `handle`, or `None` if there
+    /// is no such font.  See [`texture_id`](#method.texture_id).
+    pub fn font_id(&self, handle: FontHandle) -> Option<String> {
+        let internal = self.internal.borrow();
+        internal.themes.iter()
+            .find_map(|(_, themes)| themes.font_id(handle))
+            .map(|id| id.to_string())
     }
 
     pub(crate) fn internal(&self) -> &Rc<RefCell<ContextInternal>> {
         &self.internal
     }
 
-    pub(crate) fn set_scale_factor(&mut self, scale: f32) {
+    /// Sets the display scale factor, the number of physical pixels per logical pixel.  This is
+    /// normally done by the [`IO`](trait.IO.html) backend from the scale reported by the OS, such
+    /// as when the window is moved to a monitor with a different DPI.  All layout, including
+    /// widget positions, sizes, borders, and [`PersistentState`](struct.PersistentState.html), is in
+    /// logical pixels and so is unaffected; the renderer scales the output, and fonts are
+    /// re-rasterized at the new size starting with the next [`Frame`](struct.Frame.html).  Mouse
+    /// positions passed to the context must be scaled by this factor, but not by the
+    /// [`UI scale`](#method.set_ui_scale).  Values that are not positive are ignored.
+    pub fn set_scale_factor(&mut self, scale: f32) {
+        if !(scale > 0.0 && scale.is_finite()) {
+            return;
+        }
+
         let mut internal = self.internal.borrow_mut();
+        internal.record(InputEvent::ScaleFactor(scale));
         internal.scale_factor = scale;
     }
 
+    /// Sets the UI scale, which multiplies the display [`scale factor`](#method.set_scale_factor) to
+    /// make the whole UI larger or smaller, such as from a settings menu.  The default is 1.0.  Logical
+    /// pixels, and so [`PersistentState`](struct.PersistentState.html) window positions, sizes, and scroll
+    /// amounts, are scaled along with everything else, so windows keep their relative positions.  The
+    /// logical display size shrinks or grows to match.  If called while a [`Frame`](struct.Frame.html)
+    /// is being built, the new scale takes effect starting with the next frame.  Values that are not
+    /// positive are ignored.
+    pub fn set_ui_scale(&mut self, scale: f32) {
+        self.internal.borrow_mut().set_ui_scale(scale);
+    }
+
+    /// Returns the current UI scale.  See [`set_ui_scale`](#method.set_ui_scale).
+    pub fn ui_scale(&self) -> f32 {
+        self.internal.borrow().ui_scale()
+    }
+
+    /**
+    Sets the UI time, in milliseconds, used starting with the next [`Frame`](struct.Frame.html), instead of
+    measuring it from the wall clock.  The time then stays fixed until this is called again, so the application
+    drives the time explicitly, such as to step it forward precisely in tests or to follow a game clock.  All
+    time based behavior, including animations, transitions, tooltip delays, key repeat, and double clicks, uses
+    this time.  Hover and click detection does not depend on the time, so the UI remains fully usable while
+    the time is not advancing.  Call [`use_wall_clock_time`](#method.use_wall_clock_time) to go back to the
+    default behavior.
+
+    # Example
+    ```
+    fn pause(context: &mut Context) {
+        // freeze all animations at the current time
+        let time = context.time_millis();
+        context.set_time_millis(time);
+    }
+
+    fn unpause(context: &mut Context) {
+        // continue from the frozen time
+        context.use_wall_clock_time();
+    }
+    ```
+    */
+    pub fn set_time_millis(&mut self, time_millis: u32) {
+        let mut internal = self.internal.borrow_mut();
+        internal.fixed_time_millis = Some(time_millis);
+    }
+
+    /// Measures the UI time from the wall clock, which is the default.  If the time was previously set with
+    /// [`set_time_millis`](#method.set_time_millis), the wall clock time continues on from the set time,
+    /// without any jump.
+    pub fn use_wall_clock_time(&mut self) {
+        let mut internal = self.internal.borrow_mut();
+        let time_millis = match internal.fixed_time_millis.take() {
+            None => return,
+            Some(time) => time,
+        };
+
+        let now = Instant::now();
+        if let Some(start) = now.checked_sub(Duration::from_millis(time_millis as u64)) {
+            internal.start_instant = start;
+        }
+    }
+
+    /// Returns the [`SavedState`](struct.SavedState.html) of all widgets, including whether each is open and
+    /// expanded, and how far it has been resized, moved, and scrolled.  Transient state, such as typed characters
+    /// and text, is not included.  Only widgets whose state differs from the default are saved.
+    pub fn save_persistent_state(&self) -> SavedState {
+        let internal = self.internal.borrow();
+        let default = SavedWidgetState::default();
+        let widgets = internal.persistent_state.iter()
+            .map(|(id, state)| (id.to_string(), state.saved()))
+            .filter(|(_, saved)| *saved != default)
+            .collect();
+        SavedState { widgets }
+    }
+
+    /**
+    Loads the [`SavedState`](struct.SavedState.html) previously returned by
+    [`save_persistent_state`](#method.save_persistent_state) from the specified `deserializer`.  Widgets in the
+    saved state that no longer exist are harmless, and widgets not in the saved state keep their current state.
+    Resize and move amounts are limited to the current display size, so that a state saved on a larger display
+    does not leave windows off screen; moveable [`windows`](struct.WindowBuilder.html#method.moveable) are
+    additionally kept fully within the display when next built.  If an error occurs, no changes are made.
+
+    # Example
+    ```
+    fn load_layout(context: &mut Context, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let file = std::fs::File::open(path)?;
+        context.load_persistent_state(serde_yaml::Deserializer::from_reader(file))?;
+        Ok(())
+    }
+    ```
+    */
+    pub fn load_persistent_state<'a, D: serde::Deserializer<'a>>(&mut self, deserializer: D) -> Result<(), Error> {
+        let saved: SavedState = match serde::Deserialize::deserialize(deserializer) {
+            Ok(saved) => saved,
+            Err(e) => return Err(Error::Serde(e.to_string())),
+        };
+
+        let mut internal = self.internal.borrow_mut();
+        let display_size = internal.logical_display_size();
+        for (id, saved) in saved.widgets.iter() {
+            internal.state_mut(id.to_string()).load(saved, display_size);
+        }
+        Ok(())
+    }
+
+    /// Clears the [`PersistentState`](struct.PersistentState.html) of all widgets, resetting them to their
+    /// defaults, such as for a "Reset UI Layout" button.  Any modal is closed and keyboard focus is dropped.
+    pub fn clear_all_state(&mut self) {
+        let mut internal = self.internal.borrow_mut();
+        internal.clear_states(|_| true);
+    }
+
+    /// Clears the [`PersistentState`](struct.PersistentState.html) of all widgets with an ID starting with
+    /// `prefix`, as in [`clear_all_state`](#method.clear_all_state).  Child widget IDs are prefixed with their
+    /// parent's ID and a `/`, so a `prefix` of `"inventory/"` clears everything inside the `inventory` window,
+    /// but not the window itself.  If a modal or the widget with keyboard focus is cleared, the modal and any
+    /// modals above it are closed or focus is dropped.
+    pub fn clear_state_matching(&mut self, prefix: &str) {
+        let mut internal = self.internal.borrow_mut();
+        internal.clear_states(|id| id.starts_with(prefix));
+    }
+
+    /// Starts recording all inputs sent to this context, along with the time of each
+    /// [`Frame`](struct.Frame.html), into an [`InputLog`](struct.InputLog.html).  The log begins with a
+    /// [`Snapshot`](enum.InputEvent.html#variant.Snapshot) of the current input state, so it can be replayed
+    /// from the same starting point.  Any recording already in progress is discarded.  See
+    /// [`finish_recording`](#method.finish_recording) and [`Recorder`](struct.Recorder.html).
+    pub fn start_recording(&mut self) {
+        let mut internal = self.internal.borrow_mut();
+        let mut log = InputLog::default();
+        log.push(InputEvent::Snapshot {
+            display_size: internal.display_size,
+            scale_factor: internal.scale_factor,
+            mouse_pos: internal.mouse_pos * internal.ui_scale,
+            mouse_pressed: internal.mouse_pressed,
+            modifiers: internal.input_modifiers,
+        });
+        internal.recording = Some(log);
+    }
+
+    // sets the input state directly from a recorded snapshot, without generating clicks
+    // for any changed mouse buttons
+    pub(crate) fn restore_snapshot(
+        &mut self,
+        display_size: Point,
+        scale_factor: f32,
+        mouse_pos: Point,
+        mouse_pressed: [bool; 3],
+        modifiers: InputModifiers,
+    ) {
+        self.set_display_size(display_size);
+        self.set_scale_factor(scale_factor);
+        self.set_mouse_pos(mouse_pos);
+        self.set_input_modifiers(modifiers);
+
+        let mut internal = self.internal.borrow_mut();
+        internal.mouse_pressed = mouse_pressed;
+        internal.mouse_pressed_outside = [false; 3];
+        internal.mouse_clicked = [false; 3];
+    }
+
+    /// Stops recording inputs and returns the [`InputLog`](struct.InputLog.html) recorded since
+    /// [`start_recording`](#method.start_recording), or `None` if no recording was in progress.
+    pub fn finish_recording(&mut self) -> Option<InputLog> {
+        let mut internal = self.internal.borrow_mut();
+        internal.take_recording()
+    }
+
+    /// Returns whether inputs are currently being recorded.  See [`start_recording`](#method.start_recording).
+    pub fn is_recording(&self) -> bool {
+        self.internal.borrow().recording.is_some()
+    }
+
+    /// Returns the current UI time, in milliseconds.  This is the time set with
+    /// [`set_time_millis`](#method.set_time_millis), if any, or otherwise the time at the start of the
+    /// most recent [`Frame`](struct.Frame.html).
+    pub fn time_millis(&self) -> u32 {
+        self.internal.borrow().ui_time_millis()
+    }
+
     pub(crate) fn set_display_size(&mut self, size: Point) {
         let mut internal = self.internal.borrow_mut();
+        internal.record(InputEvent::DisplaySize(size));
         internal.display_size = size;
     }
 
     pub(crate) fn add_mouse_wheel(&mut self, delta: Point) {
         let mut internal = self.internal.borrow_mut();
+        internal.record(InputEvent::MouseWheel(delta));
 
         internal.mouse_wheel = internal.mouse_wheel + delta;
     }
 
+    pub(crate) fn add_mouse_wheel_lines(&mut self, delta: Point) {
+        let mut internal = self.internal.borrow_mut();
+        internal.record(InputEvent::MouseWheelLines(delta));
+
+        internal.mouse_wheel_lines = internal.mouse_wheel_lines + delta;
+    }
+
     pub(crate) fn set_input_modifiers(&mut self, input_modifiers: InputModifiers) {
         let mut internal = self.internal.borrow_mut();
+        internal.record(InputEvent::Modifiers(input_modifiers));
         internal.input_modifiers = input_modifiers;
     }
 
     pub(crate) fn set_mouse_pressed(&mut self, pressed: bool, index: usize) {
         let mut internal = self.internal.borrow_mut();
+        internal.record(InputEvent::MousePressed { index, pressed });
 
         if index >= internal.mouse_pressed.len() {
             return;
@@ -420,7 +1595,7 @@ impl Context {
 
         if internal.mouse_pressed[index] && !pressed {
             internal.mouse_clicked[index] = true;
-            internal.keyboard_focus_widget = None;
+            internal.focus_clear_pending = true;
         }
 
         internal.mouse_pressed[index] = pressed;
@@ -428,6 +1603,7 @@ impl Context {
 
     pub(crate) fn push_character(&mut self, c: char) {
         let mut internal = self.internal.borrow_mut();
+        internal.record(InputEvent::Character(c));
 
         let id = match &internal.keyboard_focus_widget {
             Some(id) => id.to_string(),
@@ -438,9 +1614,69 @@ impl Context {
         state.characters.push(c);
     }
 
+    // sends a single key press, which is not repeated while held
+    pub(crate) fn push_key(&mut self, key: KeyCode) {
+        let mut internal = self.internal.borrow_mut();
+        internal.record(InputEvent::Key(key));
+        internal.push_key(key);
+    }
+
+    // handles a key being pressed or released.  The key is sent when first pressed and
+    // then repeated while it is held down
+    pub(crate) fn set_key_pressed(&mut self, key: KeyCode, pressed: bool) {
+        let mut internal = self.internal.borrow_mut();
+        let now = internal.ui_time_millis();
+        internal.record(InputEvent::KeyPressed { key, pressed, time_millis: now });
+
+        if !pressed {
+            if matches!(internal.held_key, Some((held, _)) if held == key) {
+                internal.held_key = None;
+            }
+            return;
+        }
+
+        // ignore presses repeated by the OS while the key is held
+        if matches!(internal.held_key, Some((held, _)) if held == key) {
+            return;
+        }
+
+        internal.push_key(key);
+        internal.held_key = Some((key, now + internal.key_repeat_delay));
+    }
+
+    /// Sets the key repeat behavior for keys such as Backspace and the arrow keys.  When a key is
+    /// held down, it is repeated after `delay_millis`, and then once every `interval_millis` until
+    /// it is released.  The defaults are 500 and 35 milliseconds, respectively.  The same timing
+    /// is used to repeat paging while a scrollbar track is held down with the mouse.
+    pub fn set_key_repeat(&mut self, delay_millis: u32, interval_millis: u32) {
+        let mut internal = self.internal.borrow_mut();
+        internal.key_repeat_delay = delay_millis;
+        internal.key_repeat_interval = interval_millis;
+    }
+
+    /// Sets the theme used for tooltips added with [`WidgetBuilder::tooltip`](struct.WidgetBuilder.html#method.tooltip)
+    /// and [`tooltip_with`](struct.WidgetBuilder.html#method.tooltip_with).  Defaults to `tooltip`.
+    pub fn set_tooltip_theme<T: Into<String>>(&mut self, theme: T) {
+        let mut internal = self.internal.borrow_mut();
+        internal.tooltip_theme = theme.into();
+    }
+
+    /// Sets how long, in milliseconds, the mouse must stay over a widget before its tooltip
+    /// is shown.  Defaults to 500 milliseconds.
+    pub fn set_tooltip_delay(&mut self, delay_millis: u32) {
+        let mut internal = self.internal.borrow_mut();
+        internal.tooltip_delay = delay_millis;
+    }
+
+    pub(crate) fn take_clipboard_text(&mut self) -> Option<String> {
+        let mut internal = self.internal.borrow_mut();
+        internal.clipboard_text.take()
+    }
+
     pub(crate) fn set_mouse_pos(&mut self, pos: Point) {
         let mut internal = self.internal.borrow_mut();
-        internal.mouse_pos = pos;
+        internal.record(InputEvent::MousePos(pos));
+        internal.mouse_pos = pos / internal.ui_scale;
     }
 
     /// Adds the specified path as a source file for the resources being used
@@ -465,6 +1701,70 @@ impl Context {
         internal.resources.remove_theme_file(path);
     }
 
+    /// Registers the font data located in the file at the specified `path` via the specified `id`.
+    /// See [`register_font`](#method.register_font).
+    pub fn register_font_from_file<T: Into<String>>(&mut self, id: T, path: &Path) {
+        let id = id.into();
+        log::debug!("Reading font source '{}' from file: '{:?}'", id, path);
+        let mut internal = self.internal.borrow_mut();
+        internal.resources.replace_font_from_file(id, path);
+    }
+
+    /// Registers the font data for use with Thyme via the specified `id`, in the same way as
+    /// [`ContextBuilder::register_font`](struct.ContextBuilder.html#method.register_font).  If a font source
+    /// is already registered with this `id`, it is replaced.  This only stores the font data; it is not sent to
+    /// the renderer and may not be used in the theme until you rebuild the theme with
+    /// [`rebuild_all`](#method.rebuild_all) or [`rebuild_themes`](#method.rebuild_themes).  Until then, the
+    /// current theme is unaffected.
+    pub fn register_font<T: Into<String>>(&mut self, id: T, data: Vec<u8>) {
+        let id = id.into();
+        log::debug!("Registering font source '{}'", id);
+        let mut internal = self.internal.borrow_mut();
+        internal.resources.replace_font_from_data(id, data);
+    }
+
+    /// Reads a texture from the specified image file.  See [`register_texture`](#method.register_texture).
+    /// Requires the `image` feature.
+    #[cfg(feature="image")]
+    pub fn register_texture_from_file<T: Into<String>>(&mut self, id: T, path: &Path) {
+        let id = id.into();
+        log::debug!("Reading texture '{}' from file: '{:?}'", id, path);
+        let mut internal = self.internal.borrow_mut();
+        internal.resources.replace_image_from_file(id, path);
+    }
+
+    /// Registers the image data for use with Thyme via the specified `id`, in the same way as
+    /// [`ContextBuilder::register_texture`](struct.ContextBuilder.html#method.register_texture).  If a texture
+    /// is already registered with this `id`, it is replaced.  This only stores the image data; it is not sent to
+    /// the renderer and may not be used as an image set `source` in the theme until you rebuild the theme with
+    /// [`rebuild_all`](#method.rebuild_all) or [`rebuild_themes`](#method.rebuild_themes).  Until then, the
+    /// current theme is unaffected.  Replacing a texture keeps the renderer handles of all other textures the same.
+    pub fn register_texture<T: Into<String>>(&mut self, id: T, data: Vec<u8>, dimensions: (u32, u32)) {
+        let id = id.into();
+        log::debug!("Registering texture '{}'", id);
+        let mut internal = self.internal.borrow_mut();
+        internal.resources.replace_image_from_data(id, data, dimensions.0, dimensions.1);
+    }
+
+    /// Replaces the contents of part or all of the texture registered with `id`, such as to show a minimap
+    /// drawn on the CPU.  `data` is in the same format as for [`register_texture`](#method.register_texture),
+    /// and covers the region of size `dimensions` with its corner at the texel `pos`.  The texture keeps the same
+    /// renderer handle, so all images using it show the new contents starting with the next frame that is drawn.
+    /// Returns `Err` if there is no texture with `id` in the current theme, the region does not fit within the
+    /// texture, or `data` is not the size of the region.  The new contents are kept when the theme is rebuilt,
+    /// unless the texture is read from a file, in which case [`rebuild_all`](#method.rebuild_all) reads it again.
+    pub fn update_texture<R: Renderer>(
+        &mut self,
+        renderer: &mut R,
+        id: &str,
+        data: &[u8],
+        pos: (u32, u32),
+        dimensions: (u32, u32),
+    ) -> Result<(), Error> {
+        let mut internal = self.internal.borrow_mut();
+        internal.resources.update_texture(renderer, id, data, pos, dimensions)
+    }
+
     /// Rebuilds this context, reloading all asset data.  Notably, files on disk
     /// that were used in [`building`](struct.ContextBuilder.html) the context
     /// are re-read.  If any errors are encountered in reading or parsing files, this
@@ -474,12 +1774,70 @@ impl Context {
         internal.resources.clear_data_cache();
         internal.resources.cache_data()?;
 
-        let scale_factor = internal.scale_factor;
+        let scale_factor = internal.scale_factor();
         let themes = internal.resources.build_assets(renderer, scale_factor)?;
-        internal.themes = themes;
+        internal.set_themes(themes);
+        Ok(())
+    }
+
+    /// Re-reads all asset data in the same way as [`rebuild_all`](#method.rebuild_all), and checks it for
+    /// errors without rebuilding.  The current theme is unchanged.  See
+    /// [`ContextBuilder::validate_theme`](struct.ContextBuilder.html#method.validate_theme).
+    pub fn validate_theme(&mut self) -> Result<(), Error> {
+        let mut internal = self.internal.borrow_mut();
+        internal.resources.clear_data_cache();
+        internal.resources.cache_data()?;
+        internal.resources.validate_assets()
+    }
+
+    /// Replaces the theme for this context with one deserialized from `theme`, and rebuilds all
+    /// assets.  `theme` is deserialized in the same way as in
+    /// [`ContextBuilder::register_theme`](struct.ContextBuilder.html#method.register_theme).  If
+    /// called while a [`Frame`](struct.Frame.html) is being built, the new theme takes effect starting
+    /// with the next frame.  The [`PersistentState`](struct.PersistentState.html) of all widgets is kept,
+    /// so windows and other widgets stay where they are.  If any errors are encountered in
+    /// deserializing the theme or rebuilding assets, this will return `Err` and the current theme
+    /// remains in use.
+    pub fn rebuild_themes<'a, T: serde::Deserializer<'a>, R: Renderer>(
+        &mut self,
+        theme: T,
+        renderer: &mut R
+    ) -> Result<(), Error> {
+        let theme_def: ThemeDefinition = match serde::Deserialize::deserialize(theme) {
+            Ok(theme_def) => theme_def,
+            Err(e) => return Err(Error::Serde(e.to_string())),
+        };
+
+        let mut internal = self.internal.borrow_mut();
+        let scale_factor = internal.scale_factor();
+        let themes = internal.resources.rebuild_with_theme(theme_def, renderer, scale_factor)?;
+        internal.set_themes(themes);
+        Ok(())
+    }
+
+    /// Switches the active theme set to the one registered under `name` with
+    /// [`ContextBuilder::register_named_theme`](struct.ContextBuilder.html#method.register_named_theme),
+    /// or `default` for the theme from [`register_theme`](struct.ContextBuilder.html#method.register_theme).
+    /// The switch takes effect starting with the next call to [`create_frame`](#method.create_frame).
+    /// The [`PersistentState`](struct.PersistentState.html) of all widgets is unaffected.  Returns `Err` if
+    /// there is no theme set with the specified `name`.
+    pub fn set_active_theme(&mut self, name: &str) -> Result<(), Error> {
+        let mut internal = self.internal.borrow_mut();
+        if !internal.themes.iter().any(|(theme, _)| theme == name) {
+            return Err(Error::Theme(format!("Unable to locate theme set '{}'", name)));
+        }
+
+        internal.pending_active_theme = Some(name.to_string());
         Ok(())
     }
 
+    /// Returns the name of the currently active theme set.  See
+    /// [`set_active_theme`](#method.set_active_theme).
+    pub fn active_theme(&self) -> String {
+        let internal = self.internal.borrow();
+        internal.themes[internal.active_theme].0.clone()
+    }
+
     /// Checks the internal live reload thread to see if any file notifications have occurred
     /// since the last check.  If so, will fully rebuild the theme.  If any errors are encountered
     /// in the process of rebuilding the theme, will return the `Err` and no changes are made to
@@ -487,12 +1845,12 @@ impl Context {
     /// (see [`BuildOptions`](struct.BuildOptions.html)), this function will do nothing.
     pub fn check_live_reload<R: Renderer>(&mut self, renderer: &mut R) -> Result<(), Error> {
         let mut internal = self.internal.borrow_mut();
-        let scale_factor = internal.scale_factor;
+        let scale_factor = internal.scale_factor();
 
         let themes = internal.resources.check_live_reload(renderer, scale_factor)?;
 
         if let Some(themes) = themes {
-            internal.themes = themes;
+            internal.set_themes(themes);
         }
 
         Ok(())
@@ -514,8 +1872,53 @@ impl Context {
 
             context.frame_active = true;
 
-            let elapsed = (now - context.start_instant).as_millis() as u32;
-            context.time_millis = elapsed;
+            if let Some(themes) = context.pending_themes.take() {
+                context.replace_themes(themes);
+            }
+
+            if let Some(name) = context.pending_active_theme.take() {
+                if let Some(index) = context.themes.iter().position(|(theme, _)| *theme == name) {
+                    context.active_theme = index;
+                }
+            }
+
+            if let Some(scale) = context.pending_ui_scale.take() {
+                context.apply_ui_scale(scale);
+            }
+
+            // fonts are re-rasterized for the current scale factor, rather than stretched
+            let scale_factor = context.scale_factor();
+            for (_, themes) in context.themes.iter_mut() {
+                themes.set_font_scale(scale_factor);
+            }
+
+            context.time_millis = context.current_time_millis(now);
+            let time_millis = context.time_millis;
+            context.record(InputEvent::Frame { time_millis });
+            // a click clears the keyboard focus, unless it was on the focused widget itself
+            if std::mem::take(&mut context.focus_clear_pending) &&
+                context.mouse_taken_last_frame_id() != context.keyboard_focus_widget.as_deref() {
+                context.keyboard_focus_widget = None;
+            }
+
+            context.apply_focus_move();
+            context.repeat_held_key();
+
+            if !context.mouse_pressed[0] {
+                context.held_press = None;
+            }
+
+            context.mouse_released = None;
+            if !context.mouse_pressed.iter().any(|pressed| *pressed) {
+                context.mouse_released = context.mouse_press.take();
+            }
+
+            // a drag ends when the mouse is released, leaving its payload for one frame.  If no drop
+            // target takes it, the drag is simply cancelled
+            context.dropped_payload = None;
+            if !context.mouse_pressed[0] {
+                context.dropped_payload = context.drag_payload.take();
+            }
 
             if context.mouse_pressed[0] {
                 anim_state = AnimState::new(AnimStateKey::Pressed);
@@ -523,7 +1926,7 @@ impl Context {
                 anim_state = AnimState::normal();
             }
 
-            context.display_size() / context.scale_factor()
+            context.logical_display_size()
         };
 
         let context = Context { internal: Rc::clone(&self.internal) };
@@ -535,15 +1938,17 @@ impl Context {
 
 pub(crate) struct Modal {
     pub(crate) id: String,
+    pub(crate) ancestors: Vec<String>,
     pub(crate) close_on_click_outside: bool,
     pub(crate) bounds: Rect,
     pub(crate) prevent_close: bool,
 }
 
 impl Modal {
-    fn new(id: String) -> Modal {
+    fn new(id: String, ancestors: Vec<String>) -> Modal {
         Modal {
             id,
+            ancestors,
             close_on_click_outside: false,
             bounds: Rect::default(),
             prevent_close: true,
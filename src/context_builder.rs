@@ -58,7 +58,7 @@ impl ContextBuilder {
     /// `theme`.  For example, `theme` could be a [`serde_json Value`](https://docs.serde.rs/serde_json/value/enum.Value.html) or
     /// [`serde_yaml Value`](https://docs.serde.rs/serde_yaml/enum.Value.html).  See [`the crate root`](index.html) for a
     /// discussion of the theme format.  If this method is called multiple times, only the last
-    /// theme is used
+    /// theme is used.  To combine several themes, see [`add_theme`](#method.add_theme).
     pub fn register_theme<'a, T: serde::Deserializer<'a>>(&mut self, theme: T) -> Result<(), T::Error> {
         log::debug!("Registering theme");
         
@@ -67,6 +67,54 @@ impl ContextBuilder {
         Ok(())
     }
 
+    /**
+    Adds a theme which is merged on top of the theme set with [`register_theme`](#method.register_theme) or
+    [`register_theme_from_files`](#method.register_theme_from_files), deserialized in the same way.  This may be
+    called repeatedly, with each theme merged in the order added, allowing a large theme to be split into several
+    sources or mods to add their own skins.  Later themes may add new fonts, image sets, images, and widget themes.
+    Entries with the same ID as an earlier entry replace it, with a warning logged.  Image sets with the same
+    ID and `source` have their images merged, while a set with a different `source` replaces the earlier set entirely.
+    Added themes are kept when the base theme is live reloaded.  If no base theme is registered, the first added
+    theme is used as the base.
+
+    Textures registered more than once under the same ID must be identical, or [`build`](#method.build) will fail.
+
+    # Example
+    ```no_run
+        let mut context_builder = thyme::ContextBuilder::with_defaults();
+        context_builder.register_theme(base_theme)?;
+        context_builder.add_theme(mod_theme)?;
+        ...
+    ```
+    **/
+    pub fn add_theme<'a, T: serde::Deserializer<'a>>(&mut self, theme: T) -> Result<(), T::Error> {
+        log::debug!("Adding theme");
+
+        let theme_def: ThemeDefinition = serde::Deserialize::deserialize(theme)?;
+        self.resources.add_theme(theme_def);
+        Ok(())
+    }
+
+    /// Registers an additional theme under the specified `name`, deserialized in the same way as
+    /// [`register_theme`](#method.register_theme).  Each registered theme is built into a separate
+    /// theme set, with all sets sharing the registered textures and fonts.  You may switch between sets
+    /// at runtime with [`Context::set_active_theme`](struct.Context.html#method.set_active_theme).  The
+    /// theme from `register_theme`, if any, is named `default` and is initially active; otherwise the first
+    /// named theme is.  Every theme must define the same set of font IDs, or building the context will fail.
+    /// Registering a theme with a name that is already in use replaces that theme.
+    pub fn register_named_theme<'a, N: Into<String>, T: serde::Deserializer<'a>>(
+        &mut self,
+        name: N,
+        theme: T,
+    ) -> Result<(), T::Error> {
+        let name = name.into();
+        log::debug!("Registering theme '{}'", name);
+
+        let theme_def: ThemeDefinition = serde::Deserialize::deserialize(theme)?;
+        self.resources.register_named_theme(name, theme_def);
+        Ok(())
+    }
+
     /// Sets the theme for this context by reading from the file at the specified `path`.  The files are first
     /// read to a string and then passed to the function `f`, which returns a serde Deserializable object.  That
     /// object is then deserialized as the theme.  See [`register_theme`](#method.register_theme)
@@ -160,6 +208,16 @@ impl ContextBuilder {
         self.resources.register_image_from_data(id, data, dimensions.0, dimensions.1);
     }
 
+    /// Checks the registered themes for errors without building a [`Context`](struct.Context.html).  Theme, image,
+    /// and font files are read, but nothing is registered with a renderer, so this may be used in tests or tools.
+    /// Every problem found is reported, not just the first, as [`Error::Multiple`](enum.Error.html#variant.Multiple)
+    /// if there is more than one.  Each error names the theme entry and field it was found in, such as
+    /// `In widget 'window/titlebar/label', field 'font': Unable to locate font 'heading'`.
+    pub fn validate_theme(&mut self) -> Result<(), Error> {
+        self.resources.cache_data()?;
+        self.resources.validate_assets()
+    }
+
     /// Consumes this builder and releases the borrows on the [`Renderer`](trait.Renderer.html) and [`IO`](trait.IO.html),
     /// so they can be used further.  Builds a [`Context`](struct.Context.html).
     pub fn build<R: Renderer, I: IO>(mut self, renderer: &mut R, io: &mut I) -> Result<Context, Error> {
@@ -169,6 +227,10 @@ impl ContextBuilder {
 
         self.resources.cache_data()?;
         let themes = self.resources.build_assets(renderer, scale_factor)?;
-        Ok(Context::new(self.resources, themes, display_size, scale_factor))
+        let mut context = Context::new(self.resources, themes, display_size, scale_factor);
+        if let Some(time_millis) = io.time_millis() {
+            context.set_time_millis(time_millis);
+        }
+        Ok(context)
     }
 }
\ No newline at end of file
@@ -0,0 +1,412 @@
+use std::ops::Range;
+use std::rc::Rc;
+
+use crate::context::Context;
+use crate::frame::RendGroupDef;
+use crate::image::{ImageDrawParams, ImageHandle};
+use crate::canvas::CanvasCommand;
+use crate::render::{DrawCallback, DrawCallbackParams, DrawList, DrawTarget, DrawMode, FontHandle, TexCoord, TintedDrawList};
+use crate::widget::Widget;
+use crate::{Align, AnimState, Color, Point, Rect, TextOverflow};
+
+/**
+The complete draw data for a [`Frame`](struct.Frame.html), for use with a custom rendering backend.  See
+[`Frame::finish_to_draw_data`](struct.Frame.html#method.finish_to_draw_data).
+
+Everything is drawn as textured, colored triangles, in the order of [`commands`](#structfield.commands).  Each
+[`DrawGroup`](struct.DrawGroup.html) draws a range of [`indices`](#structfield.indices) into
+[`vertices`](#structfield.vertices), using one texture and one clip rect.  All positions are in physical pixels,
+with the origin at the top left of the display and y increasing downward.  Colors should be alpha blended, with
+`src_alpha, one_minus_src_alpha`.
+*/
+pub struct DrawData {
+    /// The size of the display, in physical pixels
+    pub display_size: Point,
+
+    /// The display scale factor, the number of physical pixels per logical pixel
+    pub scale: f32,
+
+    /// The vertices for all draw groups
+    pub vertices: Vec<DrawVertex>,
+
+    /// The indices into [`vertices`](#structfield.vertices), three for each triangle
+    pub indices: Vec<u32>,
+
+    /// The commands to carry out to draw the frame, in order
+    pub commands: Vec<DrawCommand>,
+
+    /// Font atlas textures that have been created or changed since the last frame, and must be uploaded
+    /// before drawing.  An atlas replaces any previous texture for the same font, and may change size.
+    pub font_atlases: Vec<FontAtlasData>,
+}
+
+/**
+A single vertex in [`DrawData`](struct.DrawData.html).  The layout is `#[repr(C)]` and stable: 8 bytes of
+position, followed by 8 bytes of texture coordinates, followed by 16 bytes of color, for 32 bytes in total,
+with no padding.
+*/
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DrawVertex {
+    /// The position, in physical pixels
+    pub position: [f32; 2],
+
+    /// The texture coordinates, from 0 to 1 across the group's texture
+    pub uv: [f32; 2],
+
+    /// The color, as red, green, blue, alpha, each from 0 to 1.  This multiplies the texture color.
+    pub color: [f32; 4],
+}
+
+/// A single command in [`DrawData`](struct.DrawData.html).
+pub enum DrawCommand {
+    /// Draw a group of triangles
+    Group(DrawGroup),
+
+    /// Invoke a widget's [`draw callback`](struct.WidgetBuilder.html#method.draw_callback), after all
+    /// previous groups have been drawn
+    Callback(DrawCallbackCommand),
+}
+
+/// A group of triangles drawn with the same texture and clip rect.  See [`DrawData`](struct.DrawData.html).
+#[derive(Clone, Debug, PartialEq)]
+pub struct DrawGroup {
+    /// The texture to draw with.  For [`DrawMode::Image`](enum.DrawMode.html#variant.Image), this is
+    /// a texture registered with [`Renderer::register_texture`](trait.Renderer.html#tymethod.register_texture),
+    /// and its RGBA color multiplies the vertex color.  For [`DrawMode::Font`](enum.DrawMode.html#variant.Font),
+    /// this is the font's atlas from [`font_atlases`](struct.DrawData.html#structfield.font_atlases), which has
+    /// one byte per texel giving the coverage, to be multiplied into the vertex alpha.
+    pub mode: DrawMode,
+
+    /// The area outside of which nothing in this group should be drawn, in physical pixels
+    pub clip: Rect,
+
+    /// The range of [`indices`](struct.DrawData.html#structfield.indices) to draw
+    pub indices: Range<usize>,
+}
+
+/// A widget draw callback, to be invoked as part of the [`DrawData`](struct.DrawData.html) commands.
+pub struct DrawCallbackCommand {
+    callback: DrawCallback,
+    params: DrawCallbackParams,
+}
+
+impl DrawCallbackCommand {
+    /// The parameters that will be passed to the callback
+    pub fn params(&self) -> &DrawCallbackParams { &self.params }
+
+    /// Invokes the callback with [`DrawTarget::None`](enum.DrawTarget.html#variant.None)
+    pub fn invoke(&self) {
+        self.invoke_with(&mut DrawTarget::None);
+    }
+
+    /// Invokes the callback, passing it the specified `target` to draw into
+    pub fn invoke_with(&self, target: &mut DrawTarget) {
+        (self.callback)(&self.params, target);
+    }
+}
+
+/// The texture data of a font atlas.  See [`DrawData`](struct.DrawData.html).
+pub struct FontAtlasData {
+    /// The font this atlas belongs to
+    pub font: FontHandle,
+
+    /// The texture data, with one byte per texel, row by row from the top left
+    pub data: Vec<u8>,
+
+    /// The width of the texture, in texels
+    pub width: u32,
+
+    /// The height of the texture, in texels
+    pub height: u32,
+}
+
+impl DrawData {
+    pub(crate) fn build(
+        context: Context,
+        widgets: Vec<Widget>,
+        render_groups: Vec<RendGroupDef>,
+        mouse_cursor: Option<(ImageHandle, Align, AnimState)>,
+    ) -> DrawData {
+        let context = context.internal().borrow();
+
+        let time_millis = context.time_millis();
+        let scale = context.scale_factor();
+
+        let mut font_atlases = Vec::new();
+        context.themes().prepare_fonts(&widgets, |font, data, width, height| {
+            font_atlases.push(FontAtlasData { font, data: data.to_vec(), width, height });
+        });
+
+        let mut list = DrawDataList::default();
+
+        for render_group in render_groups.into_iter().rev() {
+            // render backgrounds
+            for widget in render_group.iter(&widgets) {
+                if !widget.visible() || widget.opacity() <= 0.0 { continue; }
+                list.alpha = widget.opacity();
+                let image_handle = match widget.background() {
+                    None => continue,
+                    Some(handle) => handle,
+                };
+                let time_millis = time_millis.saturating_sub(context.base_time_millis_for(widget.id()));
+                let image = context.themes().image(image_handle);
+
+                list.mode = DrawMode::Image(image.texture());
+                image.draw(
+                    &mut TintedDrawList::new(&mut list, widget.tint()),
+                    ImageDrawParams {
+                        pos: widget.pos().into(),
+                        size: widget.size().into(),
+                        anim_state: widget.anim_state(),
+                        clip: widget.clip(),
+                        time_millis,
+                        scale,
+                    }
+                );
+            }
+
+            // render foregrounds & text
+            for widget in render_group.iter(&widgets) {
+                if !widget.visible() || widget.opacity() <= 0.0 { continue; }
+                list.alpha = widget.opacity();
+
+                let border = widget.border();
+                let fg_pos = widget.pos() + border.tl();
+                let fg_size = widget.inner_size();
+
+                if !widget.canvas().is_empty() {
+                    let time_millis = time_millis.saturating_sub(context.base_time_millis_for(widget.id()));
+                    let clip = widget.clip().min(Rect::new(fg_pos, fg_size));
+                    let brush = widget.foreground().map(|handle| context.themes().image(handle));
+
+                    for command in widget.canvas() {
+                        match command {
+                            CanvasCommand::Solid { pos, size, color } => {
+                                let brush = match brush {
+                                    None => continue,
+                                    Some(brush) => brush,
+                                };
+                                list.mode = DrawMode::Image(brush.texture());
+                                brush.draw_solid(&mut TintedDrawList::new(&mut list, widget.tint()), fg_pos + *pos, *size, *color, clip, scale);
+                            },
+                            CanvasCommand::Image { image, pos, size } => {
+                                let image = context.themes().image(*image);
+                                list.mode = DrawMode::Image(image.texture());
+                                image.draw(
+                                    &mut TintedDrawList::new(&mut list, widget.tint()),
+                                    ImageDrawParams {
+                                        pos: (fg_pos + *pos).into(),
+                                        size: (*size).into(),
+                                        anim_state: widget.anim_state(),
+                                        clip,
+                                        time_millis,
+                                        scale,
+                                    }
+                                );
+                            }
+                        }
+                    }
+                } else if let Some(image_handle) = widget.foreground() {
+                    let time_millis = time_millis.saturating_sub(context.base_time_millis_for(widget.id()));
+                    let image = context.themes().image(image_handle);
+                    list.mode = DrawMode::Image(image.texture());
+
+                    image.draw_region(
+                        &mut TintedDrawList::new(&mut list, widget.tint()),
+                        ImageDrawParams {
+                            pos: fg_pos.into(),
+                            size: fg_size.into(),
+                            anim_state: widget.anim_state(),
+                            clip: widget.clip(),
+                            time_millis,
+                            scale,
+                        },
+                        widget.image_region(),
+                        widget.image_scaling(),
+                    );
+                }
+
+                if let Some(text) = widget.text() {
+                    if let Some(font_sum) = widget.font() {
+                        list.mode = DrawMode::Font(font_sum.handle);
+                        let font = context.themes().font(font_sum.handle);
+                        let clip = match widget.overflow() {
+                            TextOverflow::Visible => widget.clip(),
+                            TextOverflow::Clip | TextOverflow::Ellipsis => widget.clip().min(Rect::new(fg_pos, fg_size)),
+                        };
+
+                        font.draw(
+                            &mut TintedDrawList::new(&mut list, widget.tint()),
+                            fg_size * scale,
+                            (fg_pos * scale).into(),
+                            text,
+                            widget.text_align(),
+                            widget.wrap(),
+                            font_sum.spacing(scale),
+                            widget.text_color(),
+                            clip * scale,
+                        )
+                    }
+                }
+
+                if let Some(callback) = widget.draw_callback() {
+                    list.commands.push(DrawCommand::Callback(DrawCallbackCommand {
+                        callback: Rc::clone(callback),
+                        params: DrawCallbackParams { rect: Rect::new(widget.pos(), widget.size()), clip: widget.clip(), scale },
+                    }));
+                }
+            }
+        }
+
+        if let Some((mouse_cursor, align, anim_state)) = mouse_cursor {
+            list.alpha = 1.0;
+            let image = context.themes().image(mouse_cursor);
+            let mouse_pos = context.mouse_pos();
+            let size = image.base_size();
+            let pos = mouse_pos - align.adjust_for(size);
+            let clip = Rect::new(pos, size);
+
+            let params = ImageDrawParams {
+                pos: pos.into(),
+                size: size.into(),
+                anim_state,
+                clip,
+                time_millis,
+                scale
+            };
+
+            list.mode = DrawMode::Image(image.texture());
+            image.draw(&mut list, params);
+        }
+
+        DrawData {
+            display_size: context.display_size(),
+            scale,
+            vertices: list.vertices,
+            indices: list.indices,
+            commands: list.commands,
+            font_atlases,
+        }
+    }
+}
+
+// a DrawList that starts a new draw group whenever the mode or clip rect changes
+struct DrawDataList {
+    vertices: Vec<DrawVertex>,
+    indices: Vec<u32>,
+    commands: Vec<DrawCommand>,
+
+    // the texture for the next rects drawn, and the opacity of the widget currently being drawn
+    mode: DrawMode,
+    alpha: f32,
+}
+
+impl Default for DrawDataList {
+    fn default() -> Self {
+        DrawDataList {
+            vertices: Vec::new(),
+            indices: Vec::new(),
+            commands: Vec::new(),
+            mode: DrawMode::Image(Default::default()),
+            alpha: 1.0,
+        }
+    }
+}
+
+impl DrawList for DrawDataList {
+    fn len(&self) -> usize { self.vertices.len() }
+
+    fn back_adjust_positions(&mut self, since_index: usize, amount: Point) {
+        for vert in self.vertices.iter_mut().skip(since_index) {
+            vert.position[0] += amount.x;
+            vert.position[1] += amount.y;
+        }
+    }
+
+    fn push_rect(
+        &mut self,
+        pos: [f32; 2],
+        size: [f32; 2],
+        tex: [TexCoord; 2],
+        color: Color,
+        clip: Rect,
+    ) {
+        let [r, g, b]: [f32; 3] = color.into();
+        let color = [r, g, b, self.alpha];
+
+        let start = self.indices.len();
+        match self.commands.last_mut() {
+            Some(DrawCommand::Group(group)) if group.mode == self.mode && group.clip == clip => (),
+            _ => self.commands.push(DrawCommand::Group(DrawGroup { mode: self.mode, clip, indices: start..start })),
+        }
+
+        let (ul, lr) = (tex[0], tex[1]);
+        let idx = self.vertices.len() as u32;
+        self.vertices.extend_from_slice(&[
+            DrawVertex { position: [pos[0], pos[1]], uv: [ul.x(), ul.y()], color },
+            DrawVertex { position: [pos[0], pos[1] + size[1]], uv: [ul.x(), lr.y()], color },
+            DrawVertex { position: [pos[0] + size[0], pos[1] + size[1]], uv: [lr.x(), lr.y()], color },
+            DrawVertex { position: [pos[0] + size[0], pos[1]], uv: [lr.x(), ul.y()], color },
+        ]);
+        self.indices.extend_from_slice(&[idx, idx + 1, idx + 2, idx, idx + 2, idx + 3]);
+
+        if let Some(DrawCommand::Group(group)) = self.commands.last_mut() {
+            group.indices.end = self.indices.len();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use crate::{BuildOptions, ContextBuilder, DrawCommand, DrawTarget, HeadlessIo, HeadlessRenderer, Point};
+
+    #[test]
+    fn callbacks_interleave_with_groups() {
+        let font_src = include_bytes!("../examples/data/fonts/Roboto-Medium.ttf");
+        let image_src = include_bytes!("../examples/data/images/gui-pixel.png");
+        let image = image::load_from_memory(image_src).unwrap().to_rgba8();
+        let theme: serde_yaml::Value = serde_yaml::from_str(include_str!("../examples/data/theme-base.yml")).unwrap();
+
+        let mut io = HeadlessIo::new(Point::new(640.0, 480.0));
+        let mut renderer = HeadlessRenderer::new();
+        let mut builder = ContextBuilder::new(BuildOptions { enable_live_reload: false });
+        let dims = image.dimensions();
+        builder.register_theme(theme).unwrap();
+        builder.register_texture("pixel", image.into_raw(), dims);
+        builder.register_font("roboto", font_src.to_vec());
+        let mut context = builder.build(&mut renderer, &mut io).unwrap();
+
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut ui = context.create_frame();
+        ui.label("label", "a");
+        let first = Rc::clone(&log);
+        ui.start("label").id("first").draw_callback(move |_, _| first.borrow_mut().push("first")).finish();
+        ui.label("label", "b");
+        let second = Rc::clone(&log);
+        ui.start("label").id("second").draw_callback(move |_, _| second.borrow_mut().push("second")).finish();
+        ui.label("label", "c");
+        let data = ui.finish_to_draw_data();
+
+        // a fake renderer that records each group it draws and runs callbacks as they come up
+        let mut next_index = 0;
+        for command in &data.commands {
+            match command {
+                DrawCommand::Group(group) => {
+                    assert_eq!(group.indices.start, next_index);
+                    next_index = group.indices.end;
+                    log.borrow_mut().push("group");
+                },
+                DrawCommand::Callback(callback) => callback.invoke_with(&mut DrawTarget::None),
+            }
+        }
+        assert_eq!(next_index, data.indices.len());
+
+        let mut log = log.borrow().clone();
+        log.dedup();
+        assert_eq!(log, vec!["group", "first", "group", "second", "group"]);
+    }
+}
@@ -1,10 +1,16 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
 use crate::render::{TexCoord, DrawList, FontHandle, DummyDrawList};
-use crate::{Point, Rect, Align, Color};
+use crate::{Point, Rect, Align, Color, LineHeight};
 
+/// The font data for a font registered with the [`ContextBuilder`](struct.ContextBuilder.html).  This
+/// is passed to [`Renderer::register_font`](trait.Renderer.html#tymethod.register_font).
 pub struct FontSource {
     pub(crate) font: rusttype::Font<'static>,
 }
 
+#[derive(Copy, Clone)]
 pub struct FontChar {
     pub size: Point,
     pub(crate) tex_coords: [TexCoord; 2],
@@ -27,46 +33,196 @@ impl Default for FontChar {
 pub struct FontSummary {
     pub handle: FontHandle,
     pub line_height: f32,
+    pub letter_spacing: f32,
+
+    // the font's line height before any override
+    natural_line_height: f32,
+}
+
+impl FontSummary {
+    pub(crate) fn new(handle: FontHandle, line_height: f32) -> FontSummary {
+        FontSummary {
+            handle,
+            line_height,
+            letter_spacing: 0.0,
+            natural_line_height: line_height,
+        }
+    }
+
+    /// Returns this font with the specified letter spacing and line height, where specified.
+    pub(crate) fn with_spacing(mut self, letter_spacing: Option<f32>, line_height: Option<LineHeight>) -> FontSummary {
+        if let Some(letter_spacing) = letter_spacing {
+            self.letter_spacing = letter_spacing;
+        }
+        if let Some(line_height) = line_height {
+            self.line_height = line_height.apply(self.natural_line_height);
+        }
+        self
+    }
+
+    /// The spacing to lay out text with, in physical pixels for the specified `scale`.
+    pub(crate) fn spacing(&self, scale: f32) -> Spacing {
+        Spacing {
+            letter: self.letter_spacing * scale,
+            line_height: self.line_height * scale,
+        }
+    }
+}
+
+/// The spacing between characters and lines of text, in physical pixels.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct Spacing {
+    pub letter: f32,
+    pub line_height: f32,
+}
+
+impl Spacing {
+    // the distance to move past `font_char`.  Letter spacing is not added to zero width
+    // characters such as newlines and combining marks
+    fn advance(&self, font_char: &FontChar) -> f32 {
+        if font_char.x_advance > 0.0 {
+            font_char.x_advance + self.letter
+        } else {
+            0.0
+        }
+    }
 }
 
+/// The current usage of a font's glyph atlas texture, for debugging.  See
+/// [`Context::font_atlas_stats`](struct.Context.html#method.font_atlas_stats).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FontAtlasStats {
+    /// The width of the atlas texture, in texels
+    pub width: u32,
+
+    /// The height of the atlas texture, in texels
+    pub height: u32,
+
+    /// The number of glyphs currently in the atlas
+    pub glyphs: usize,
+
+    /// The number of texels used by the glyphs currently in the atlas
+    pub used_texels: u32,
+
+    /// The total number of glyphs evicted from the atlas to make room for new glyphs
+    pub evicted: u32,
+}
+
+impl FontAtlasStats {
+    /// The fraction of the atlas texture used by glyphs, from 0 to 1.
+    pub fn occupancy(&self) -> f32 {
+        self.used_texels as f32 / (self.width * self.height) as f32
+    }
+}
+
+/// A font at a specific size, with its glyph atlas.  A [`Renderer`](trait.Renderer.html) creates
+/// these with [`Font::new`](#method.new) when fonts are registered.
 pub struct Font {
     handle: FontHandle,
-    characters: Vec<FontChar>,
+    size: f32,
     line_height: f32,
     ascent: f32,
+    cache: RefCell<GlyphCache>,
 }
 
 impl Font {
-    pub(crate) fn new(handle: FontHandle, characters: Vec<FontChar>, line_height: f32, ascent: f32) -> Font {
+    /// Creates the font with the specified `size`, in logical pixels, and display `scale`.  The printable
+    /// ASCII and Latin-1 characters are rasterized up front, and others the first time they are used.
+    /// The atlas is passed to the renderer for upload each time it changes; see
+    /// [`DrawData::font_atlases`](struct.DrawData.html#structfield.font_atlases).
+    pub fn new(handle: FontHandle, source: &FontSource, size: f32, scale: f32) -> Font {
+        Font::build(handle, source.font.clone(), size, scale)
+    }
+
+    /// Re-rasterizes the font for a new display `scale`, keeping the same logical size.  The atlas is
+    /// rebuilt from scratch, and so will be uploaded again on the next draw.
+    pub(crate) fn set_scale(&mut self, scale: f32) {
+        let source = self.cache.get_mut().source.clone();
+        *self = Font::build(self.handle, source, self.size, scale);
+    }
+
+    fn build(handle: FontHandle, source: rusttype::Font<'static>, size: f32, scale: f32) -> Font {
+        let font_scale = rusttype::Scale { x: size * scale, y: size * scale };
+        let v_metrics = source.v_metrics(font_scale);
+
+        let mut cache = GlyphCache::new(source, font_scale);
+        for c in (32u8..=126).chain(161..=255) {
+            cache.get(c as char);
+        }
+
         Font {
             handle,
-            characters,
-            line_height,
-            ascent,
+            size,
+            line_height: v_metrics.ascent - v_metrics.descent + v_metrics.line_gap,
+            ascent: v_metrics.ascent,
+            cache: RefCell::new(cache),
         }
     }
 
-    fn char(&self, c: char) -> Option<&FontChar> {
-        self.characters.get(c as usize) // TODO smarter lookup
+    fn char(&self, c: char) -> Option<FontChar> {
+        Some(self.cache.borrow_mut().get(c))
     }
 
+    /// Starts a new frame for the purposes of evicting glyphs from the atlas.  Glyphs not used since
+    /// the previous call are the first to be evicted once the atlas is full.
+    pub(crate) fn next_generation(&self) {
+        self.cache.borrow_mut().generation += 1;
+    }
+
+    /// Rasterizes any glyphs in `text` not yet in the atlas, so they can be drawn.
+    pub(crate) fn cache_text(&self, text: &str) {
+        let mut cache = self.cache.borrow_mut();
+        for c in text.chars() {
+            cache.get(c);
+        }
+    }
+
+    /// Calls `upload` with the atlas texture data and its width and height, if the atlas has changed since
+    /// the last call.  The data has one byte per texel.
+    pub(crate) fn upload_atlas<F: FnOnce(&[u8], u32, u32)>(&self, upload: F) {
+        let mut cache = self.cache.borrow_mut();
+        if !cache.changed { return; }
+
+        cache.changed = false;
+        let atlas = &cache.atlas;
+        upload(&atlas.data, atlas.width, atlas.height);
+    }
+
+    pub(crate) fn atlas_stats(&self) -> FontAtlasStats {
+        let cache = self.cache.borrow();
+        FontAtlasStats {
+            width: cache.atlas.width,
+            height: cache.atlas.height,
+            glyphs: cache.glyphs.len(),
+            used_texels: cache.atlas.used_texels,
+            evicted: cache.evicted,
+        }
+    }
+
+    /// The natural line height of the font, in physical pixels
     pub fn line_height(&self) -> f32 { self.line_height }
 
+    /// The distance from the top of a line to the baseline, in physical pixels
     pub fn ascent(&self) -> f32 { self.ascent }
 
+    /// The handle this font was registered with
     pub fn handle(&self) -> FontHandle { self.handle }
 
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn layout(
         &self,
         area_size: Point,
         pos: Point,
         text: &str,
         align: Align,
+        wrap: bool,
+        spacing: Spacing,
         cursor: &mut Point,
     ) {
         let mut draw_list = DummyDrawList::new();
         let mut renderer = FontRenderer::new(
             &self,
+            spacing,
             &mut draw_list,
             area_size,
             pos,
@@ -74,18 +230,147 @@ impl Font {
             Color::white(),
             Rect::default(),
         );
+        renderer.wrap = wrap;
         renderer.render(text);
 
         if text.is_empty() {
             // compute the cursor position for empty text
             renderer.adjust_line_x();
-            renderer.size.y += 2.0 * renderer.font.line_height;
+            renderer.size.y += 2.0 * renderer.spacing.line_height;
             renderer.adjust_all_y();
         }
 
         *cursor = renderer.pos;
     }
 
+    /// Returns the width of the specified text laid out on a single line.
+    pub(crate) fn text_width(&self, text: &str, spacing: Spacing) -> f32 {
+        let area = Point::new(f32::MAX, f32::MAX);
+        let positions = self.layout_positions(area, Point::default(), text, Align::TopLeft, false, spacing);
+        positions.iter().map(|pos| pos.x).fold(0.0, f32::max)
+    }
+
+    /// Returns `text` with each line wider than `width` cut short and ended with an ellipsis,
+    /// measured using the advance of each character.  Returns `None` if every line already fits.
+    pub(crate) fn truncate(&self, text: &str, width: f32, spacing: Spacing) -> Option<String> {
+        const ELLIPSIS: &str = "...";
+
+        let advance = |c: char| self.char(c).map_or(0.0, |font_char| spacing.advance(&font_char));
+        let ellipsis_width: f32 = ELLIPSIS.chars().map(advance).sum();
+
+        let mut truncated = false;
+        let mut lines = Vec::new();
+        for line in text.split('\n') {
+            if line.chars().map(advance).sum::<f32>() <= width {
+                lines.push(line.to_string());
+                continue;
+            }
+
+            truncated = true;
+            let mut line_width = 0.0;
+            let mut end = 0;
+            for (index, c) in line.char_indices() {
+                line_width += advance(c);
+                if line_width + ellipsis_width > width { break; }
+                end = index + c.len_utf8();
+            }
+
+            // if even the ellipsis doesn't fit, show as much of it as will
+            let mut ellipsis = ELLIPSIS;
+            while !ellipsis.is_empty() && ellipsis.chars().map(advance).sum::<f32>() > width {
+                ellipsis = &ellipsis[1..];
+            }
+
+            lines.push(format!("{}{}", line[..end].trim_end(), ellipsis));
+        }
+
+        if truncated {
+            Some(lines.join("\n"))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the height of the specified text laid out in an area `width` wide, wrapping onto
+    /// more lines as needed if `wrap` is set.
+    pub(crate) fn text_height(&self, width: f32, text: &str, wrap: bool, spacing: Spacing) -> f32 {
+        let mut draw_list = DummyDrawList::new();
+        let mut renderer = FontRenderer::new(
+            self,
+            spacing,
+            &mut draw_list,
+            Point::new(width, 0.0),
+            Point::default(),
+            Align::TopLeft,
+            Color::white(),
+            Rect::default(),
+        );
+        renderer.wrap = wrap;
+        renderer.render(text);
+        renderer.size.y
+    }
+
+    /// Returns the size of the specified text laid out in an area `width` wide, wrapping onto more
+    /// lines as needed if `wrap` is set, along with the number of lines.  The width of the size is
+    /// the width of the widest line.
+    pub(crate) fn measure(&self, width: f32, text: &str, wrap: bool, spacing: Spacing) -> (Point, usize) {
+        let mut draw_list = DummyDrawList::new();
+        let mut renderer = FontRenderer::new(
+            self,
+            spacing,
+            &mut draw_list,
+            Point::new(width, 0.0),
+            Point::default(),
+            Align::TopLeft,
+            Color::white(),
+            Rect::default(),
+        );
+        renderer.wrap = wrap;
+        renderer.render(text);
+
+        let lines = (renderer.size.y / spacing.line_height).round() as usize;
+        (Point::new(renderer.max_line_width, renderer.size.y), lines)
+    }
+
+    /// Lays out the specified text, returning the position of each character boundary.  The
+    /// returned vec has one more entry than there are characters in `text`; the final entry is
+    /// the position after the last character.  Positions are at the top of the line.
+    pub(crate) fn layout_positions(
+        &self,
+        area_size: Point,
+        pos: Point,
+        text: &str,
+        align: Align,
+        wrap: bool,
+        spacing: Spacing,
+    ) -> Vec<Point> {
+        let mut draw_list = DummyDrawList::new();
+        let mut renderer = FontRenderer::new(
+            self,
+            spacing,
+            &mut draw_list,
+            area_size,
+            pos,
+            align,
+            Color::white(),
+            Rect::default(),
+        );
+        renderer.wrap = wrap;
+        renderer.char_positions = Some(Vec::new());
+        renderer.render(text);
+
+        if text.is_empty() {
+            renderer.adjust_line_x();
+            renderer.size.y += 2.0 * renderer.spacing.line_height;
+            renderer.adjust_all_y();
+        }
+
+        let end = renderer.pos;
+        let mut positions = renderer.char_positions.take().unwrap_or_default();
+        positions.push(end);
+        positions
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn draw<D: DrawList>(
         &self,
@@ -94,11 +379,14 @@ impl Font {
         pos: [f32; 2],
         text: &str,
         align: Align,
+        wrap: bool,
+        spacing: Spacing,
         color: Color,
         clip: Rect,
     ) {
         let mut renderer = FontRenderer::new(
             &self,
+            spacing,
             draw_list,
             area_size,
             pos.into(),
@@ -106,6 +394,7 @@ impl Font {
             color,
             clip
         );
+        renderer.wrap = wrap;
         renderer.render(text);
     }
 }
@@ -119,6 +408,14 @@ struct FontRenderer<'a, D> {
     align: Align,
     color: Color,
 
+    // whether lines are broken to fit within the area's width, rather than only at newlines
+    wrap: bool,
+
+    spacing: Spacing,
+
+    // the distance from the top of a line to the baseline, including half of any extra line height
+    ascent: f32,
+
     area_size: Point,
     initial_pos: Point,
 
@@ -126,13 +423,23 @@ struct FontRenderer<'a, D> {
     size: Point,
     cur_line_index: usize,
 
-    cur_word: Vec<&'a FontChar>,
+    // the width of the widest line laid out so far
+    max_line_width: f32,
+
+    // each character in the current word, and whether its position should be recorded
+    cur_word: Vec<(Option<FontChar>, bool)>,
     cur_word_width: f32,
+
+    // the position of each character, only recorded if requested
+    char_positions: Option<Vec<Point>>,
+    line_start_char: usize,
 }
 
 impl<'a, D: DrawList> FontRenderer<'a, D> {
+    #[allow(clippy::too_many_arguments)]
     fn new(
         font: &'a Font,
+        spacing: Spacing,
         draw_list: &'a mut D,
         area_size: Point,
         pos: Point,
@@ -149,46 +456,72 @@ impl<'a, D: DrawList> FontRenderer<'a, D> {
             align,
             color,
             clip,
+            wrap: true,
+            spacing,
+            ascent: font.ascent + (spacing.line_height - font.line_height) / 2.0,
             area_size,
             initial_pos: pos,
             pos,
             size: Point::default(),
             cur_line_index: initial_index,
+            max_line_width: 0.0,
             cur_word: Vec::new(),
             cur_word_width: 0.0,
+            char_positions: None,
+            line_start_char: 0,
+        }
+    }
+
+    fn record_char_position(&mut self) {
+        if let Some(positions) = self.char_positions.as_mut() {
+            positions.push(self.pos);
         }
     }
 
     fn render(&mut self, text: &str) {
         for c in text.chars() {
             let font_char = match self.font.char(c) {
-                None => continue, // TODO draw a special character here?
+                None => {
+                    // TODO draw a special character here?
+                    self.cur_word.push((None, true));
+                    continue;
+                },
                 Some(char) => char,
             };
 
+            let mut record = true;
             if c == '\n' {
                 self.draw_cur_word();
+                self.record_char_position();
+                record = false;
                 self.next_line();
             } else if c.is_whitespace() {
                 self.draw_cur_word();
+                self.record_char_position();
 
                 // don't draw whitespace at the start of a line
                 if self.cur_line_index != self.draw_list.len() {
-                    self.pos.x += font_char.x_advance;
-                    self.size.x += font_char.x_advance;
+                    self.pos.x += self.spacing.advance(&font_char);
+                    self.size.x += self.spacing.advance(&font_char);
                 }
 
                 continue;
             }
 
-            self.cur_word_width += font_char.x_advance;
-            self.cur_word.push(font_char);
+            self.cur_word_width += self.spacing.advance(&font_char);
+            self.cur_word.push((Some(font_char), record));
 
-            if self.size.x + self.cur_word_width > self.area_size.x {
-                // if the word was so long that we drew nothing at all
+            if self.wrap && self.size.x + self.cur_word_width > self.area_size.x {
+                // if the word is wider than the whole line, break it before the character that doesn't fit
                 if self.cur_line_index == self.draw_list.len() {
+                    let overflow = if self.cur_word.len() > 1 { self.cur_word.pop() } else { None };
                     self.draw_cur_word();
                     self.next_line();
+
+                    if let Some(overflow) = overflow {
+                        self.cur_word.push(overflow);
+                        self.cur_word_width = self.spacing.advance(&font_char);
+                    }
                 } else {
                     self.next_line();
                     self.draw_cur_word();
@@ -197,38 +530,55 @@ impl<'a, D: DrawList> FontRenderer<'a, D> {
         }
 
         self.draw_cur_word();
+        self.max_line_width = self.max_line_width.max(self.size.x);
 
         if self.cur_line_index < self.draw_list.len() {    
             // adjust characters on the last line
             self.adjust_line_x();
-            self.size.y += self.font.line_height;
+            self.size.y += self.spacing.line_height;
         }
 
         self.adjust_all_y();
     }
 
     fn draw_cur_word(&mut self) {
-        for font_char in self.cur_word.drain(..) {
+        // take the word out temporarily to avoid borrowing self
+        let mut cur_word = std::mem::take(&mut self.cur_word);
+        for (font_char, record) in cur_word.drain(..) {
+            if record {
+                self.record_char_position();
+            }
+
+            let font_char = match font_char {
+                None => continue,
+                Some(font_char) => font_char,
+            };
+
             self.draw_list.push_rect(
-                [self.pos.x, self.pos.y + font_char.y_offset + self.font.ascent],
+                [self.pos.x, self.pos.y + font_char.y_offset + self.ascent],
                 [font_char.size.x, font_char.size.y],
                 font_char.tex_coords,
                 self.color,
                 self.clip,
             );
-            self.pos.x += font_char.x_advance;
-            self.size.x += font_char.x_advance;
+            self.pos.x += self.spacing.advance(&font_char);
+            self.size.x += self.spacing.advance(&font_char);
         }
+        self.cur_word = cur_word;
         self.cur_word_width = 0.0;
     }
 
     fn next_line(&mut self) {
-        self.pos.y += self.font.line_height;
-        self.size.y += self.font.line_height;
+        self.pos.y += self.spacing.line_height;
+        self.size.y += self.spacing.line_height;
 
         self.adjust_line_x();
+        self.max_line_width = self.max_line_width.max(self.size.x);
         self.pos.x = self.initial_pos.x;
         self.cur_line_index = self.draw_list.len();
+        if let Some(positions) = self.char_positions.as_ref() {
+            self.line_start_char = positions.len();
+        }
         self.size.x = 0.0;
     }
 
@@ -251,6 +601,10 @@ impl<'a, D: DrawList> FontRenderer<'a, D> {
             self.initial_index,
             Point { x: 0.0, y: y_offset }
         );
+
+        if let Some(positions) = self.char_positions.as_mut() {
+            positions.iter_mut().for_each(|pos| pos.y += y_offset);
+        }
     }
 
     fn adjust_line_x(&mut self) {
@@ -272,146 +626,244 @@ impl<'a, D: DrawList> FontRenderer<'a, D> {
             self.cur_line_index,
             Point { x: x_offset, y: 0.0 }
         );
+
+        if let Some(positions) = self.char_positions.as_mut() {
+            positions[self.line_start_char..].iter_mut().for_each(|pos| pos.x += x_offset);
+        }
     }
 }
 
-// TODO this should be sized appropriately based on how much space is needed
-const FONT_TEX_SIZE: u32 = 512;
+// the initial and largest sizes of a font's glyph atlas, in each dimension.  The atlas doubles in
+// size whenever a glyph does not fit, and evicts glyphs once it can no longer grow
+const INITIAL_ATLAS_SIZE: u32 = 256;
+const MAX_ATLAS_SIZE: u32 = 4096;
 
-pub(crate) struct FontTextureOut {
-    pub font: Font,
-    pub data: Vec<u8>,
-    pub tex_width: u32,
-    pub tex_height: u32,
-}
+struct Glyph {
+    font_char: FontChar,
 
-pub(crate) struct FontTextureWriter<'a> {
-    // current state
-    tex_x: u32,
-    tex_y: u32,
-    max_row_height: u32,
-
-    //input
-    tex_width: u32,
-    tex_height: u32,
-    font: &'a rusttype::Font<'a>,
-    font_scale: rusttype::Scale,
-    
-    //output
-    data: Vec<u8>,
-    characters: Vec<FontChar>,
+    // the position of the glyph in the atlas, in texels
+    tex_pos: (u32, u32),
+
+    // the generation in which this glyph was last used
+    last_used: u64,
 }
 
-impl<'a> FontTextureWriter<'a> {
-    pub fn new(font: &'a rusttype::Font<'a>, size: f32, scale: f32) -> FontTextureWriter<'a> {
-        let tex_width = FONT_TEX_SIZE;
-        let tex_height = FONT_TEX_SIZE;
-        let data = vec![0u8; (tex_width * tex_height) as usize];
-        let font_scale = rusttype::Scale { x: size * scale, y: size * scale };
+// rasterizes glyphs on demand into a single channel atlas texture
+struct GlyphCache {
+    source: rusttype::Font<'static>,
+    scale: rusttype::Scale,
+    glyphs: HashMap<char, Glyph>,
+    atlas: Atlas,
+    generation: u64,
+    evicted: u32,
+
+    // whether the atlas has been modified since it was last uploaded
+    changed: bool,
+}
 
-        FontTextureWriter {
-            tex_x: 0,
-            tex_y: 0,
-            max_row_height: 0,
-            tex_width,
-            tex_height,
-            font,
-            font_scale,
-            data,
-            characters: Vec::new(),
+impl GlyphCache {
+    fn new(source: rusttype::Font<'static>, scale: rusttype::Scale) -> GlyphCache {
+        GlyphCache {
+            source,
+            scale,
+            glyphs: HashMap::new(),
+            atlas: Atlas::new(INITIAL_ATLAS_SIZE),
+            generation: 0,
+            evicted: 0,
+            changed: true,
         }
     }
 
-    pub fn write(mut self, handle: FontHandle) -> Result<FontTextureOut, crate::Error> {
-        // push empty characters to fill array indices
-        for _ in 0..32 {
-            self.characters.push(FontChar::default());
+    // looks up the glyph for `c`, rasterizing it if needed
+    fn get(&mut self, c: char) -> FontChar {
+        let generation = self.generation;
+        if let Some(glyph) = self.glyphs.get_mut(&c) {
+            glyph.last_used = generation;
+            return self.atlas.font_char(glyph);
         }
 
-        // write ASCII printable characters
-        for i in 32..=126 {
-            let font_char = self.add_char(i);
-            self.characters.push(font_char);
+        // control characters such as newlines are never drawn
+        if c.is_control() {
+            return FontChar::default();
         }
 
-        // push empty characters to fill array indices
-        for _ in 127..161 {
-            self.characters.push(FontChar::default());
-        }
+        let glyph = match self.rasterize(c) {
+            Some(glyph) => glyph,
+            None => {
+                // make room by evicting the least recently used glyphs and try again.  If the glyphs
+                // used this generation fill the atlas, draw the character as blank space
+                self.evict();
+                match self.rasterize(c) {
+                    Some(glyph) => glyph,
+                    None => {
+                        log::warn!("Font atlas is full, unable to draw '{}'", c);
+                        let mut glyph = self.metrics(c);
+                        glyph.font_char.size = Point::default();
+                        glyph
+                    }
+                }
+            }
+        };
+
+        let font_char = self.atlas.font_char(&glyph);
+        self.glyphs.insert(c, glyph);
+        font_char
+    }
 
-        for i in 161..=255 {
-            let font_char = self.add_char(i);
-            self.characters.push(font_char);
+    // the glyph for `c`, without space in the atlas
+    fn metrics(&self, c: char) -> Glyph {
+        let glyph = self.source.glyph(c).scaled(self.scale);
+        Glyph {
+            font_char: FontChar {
+                x_advance: glyph.h_metrics().advance_width,
+                ..FontChar::default()
+            },
+            tex_pos: (0, 0),
+            last_used: self.generation,
         }
+    }
 
-        let v_metrics = self.font.v_metrics(self.font_scale);
+    // draws `c` into the atlas.  returns `None` if there is no room for it
+    fn rasterize(&mut self, c: char) -> Option<Glyph> {
+        let glyph = self.source.glyph(c)
+            .scaled(self.scale)
+            .positioned(rusttype::Point { x: 0.0, y: 0.0 });
 
-        let font_out = Font::new(
-            handle,
-            self.characters,
-            v_metrics.ascent - v_metrics.descent + v_metrics.line_gap,
-            v_metrics.ascent,
-        );
+        let mut result = self.metrics(c);
+        let bounding_box = match glyph.pixel_bounding_box() {
+            // glyphs with nothing to draw, such as spaces, take no room
+            None => return Some(result),
+            Some(bb) => bb,
+        };
+
+        let size = (bounding_box.width() as u32, bounding_box.height() as u32);
+        let tex_pos = self.atlas.allocate(size)?;
+
+        let atlas = &mut self.atlas;
+        glyph.draw(|x, y, val| {
+            let index = (tex_pos.0 + x) + (tex_pos.1 + y) * atlas.width;
+            atlas.data[index as usize] = (val * 255.0).round() as u8;
+        });
+        self.changed = true;
 
-        Ok(FontTextureOut {
-            font: font_out,
-            data: self.data,
-            tex_width: self.tex_width,
-            tex_height: self.tex_height,
-        })
+        result.tex_pos = tex_pos;
+        result.font_char.size = Point::new(size.0 as f32, size.1 as f32);
+        result.font_char.y_offset = bounding_box.min.y as f32;
+        Some(result)
     }
-    
-    fn add_char(
-        &mut self,
-        i: usize,
-    ) -> FontChar {
-        let c: char = i as u8 as char;
-
-        let glyph = self.font.glyph(c)
-            .scaled(self.font_scale)
-            .positioned(rusttype::Point { x: 0.0, y: 0.0 });
 
-        // compute the glyph size.  use a minimum size of (1,1) for spaces
-        let y_offset = glyph.pixel_bounding_box().map_or(0.0, |bb| bb.min.y as f32);
-        let bounding_box = glyph.pixel_bounding_box()
-            .map_or((1, 1), |bb| (bb.width() as u32, bb.height() as u32));
-        
-        if self.tex_x + bounding_box.0 >= FONT_TEX_SIZE {
-            // move to next row
-            self.tex_x = 0;
-            self.tex_y = self.tex_y + self.max_row_height + 1;
-            self.max_row_height = 0;
+    // removes all glyphs not used in the current generation, and repacks the rest into the atlas
+    fn evict(&mut self) {
+        let generation = self.generation;
+        let keep: Vec<char> = self.glyphs.iter()
+            .filter(|(_, glyph)| glyph.last_used == generation)
+            .map(|(c, _)| *c)
+            .collect();
+
+        self.evicted += (self.glyphs.len() - keep.len()) as u32;
+        self.glyphs.clear();
+        self.atlas.clear();
+        self.changed = true;
+
+        for c in keep {
+            if let Some(glyph) = self.rasterize(c) {
+                self.glyphs.insert(c, glyph);
+            }
         }
+    }
+}
 
-        assert!(bounding_box.0 + self.tex_x < self.tex_width);
-        assert!(bounding_box.1 + self.tex_y < self.tex_height);
+// a single channel texture packed with rows of glyphs
+struct Atlas {
+    width: u32,
+    height: u32,
+    data: Vec<u8>,
+    used_texels: u32,
 
-        self.max_row_height = self.max_row_height.max(bounding_box.1);
+    // the position for the next glyph, and the height of the tallest glyph in the current row
+    x: u32,
+    y: u32,
+    row_height: u32,
+}
 
-        glyph.draw(|x, y, val| {
-            let index = (self.tex_x + x) + (self.tex_y + y) * FONT_TEX_SIZE;
-            let value = (val * 255.0).round() as u8;
-            self.data[index as usize] = value;
-        });
+impl Atlas {
+    fn new(size: u32) -> Atlas {
+        Atlas {
+            width: size,
+            height: size,
+            data: vec![0u8; (size * size) as usize],
+            used_texels: 0,
+            x: 0,
+            y: 0,
+            row_height: 0,
+        }
+    }
 
-        let tex_coords = [
-            TexCoord::new(
-                self.tex_x as f32 / FONT_TEX_SIZE as f32,
-                self.tex_y as f32 / FONT_TEX_SIZE as f32
-            ),
-            TexCoord::new(
-                (self.tex_x + bounding_box.0) as f32 / FONT_TEX_SIZE as f32,
-                (self.tex_y + bounding_box.1) as f32 / FONT_TEX_SIZE as f32
-            ),
-        ];
+    fn clear(&mut self) {
+        self.data.iter_mut().for_each(|texel| *texel = 0);
+        self.used_texels = 0;
+        self.x = 0;
+        self.y = 0;
+        self.row_height = 0;
+    }
 
-        self.tex_x += bounding_box.0 + 1;
+    // the glyph's metrics, with texture coordinates for the current atlas size
+    fn font_char(&self, glyph: &Glyph) -> FontChar {
+        let (x, y) = glyph.tex_pos;
+        let size = glyph.font_char.size;
+        let (width, height) = (self.width as f32, self.height as f32);
 
         FontChar {
-            size: (bounding_box.0 as f32, bounding_box.1 as f32).into(),
-            tex_coords,
-            x_advance: glyph.unpositioned().h_metrics().advance_width,
-            y_offset,
+            tex_coords: [
+                TexCoord::new(x as f32 / width, y as f32 / height),
+                TexCoord::new((x as f32 + size.x) / width, (y as f32 + size.y) / height),
+            ],
+            ..glyph.font_char
+        }
+    }
+
+    // finds room for a glyph of the specified size, growing the atlas if needed.  Returns
+    // `None` if the atlas is full and cannot grow
+    fn allocate(&mut self, size: (u32, u32)) -> Option<(u32, u32)> {
+        loop {
+            if self.x + size.0 >= self.width {
+                // move to the next row
+                self.x = 0;
+                self.y += self.row_height + 1;
+                self.row_height = 0;
+            }
+
+            if self.x + size.0 < self.width && self.y + size.1 < self.height {
+                break;
+            }
+
+            if !self.grow() {
+                return None;
+            }
         }
+
+        let pos = (self.x, self.y);
+        self.x += size.0 + 1;
+        self.row_height = self.row_height.max(size.1);
+        self.used_texels += size.0 * size.1;
+        Some(pos)
     }
-}
\ No newline at end of file
+
+    // doubles the size of the atlas, keeping existing glyphs at the same texel positions
+    fn grow(&mut self) -> bool {
+        if self.width >= MAX_ATLAS_SIZE { return false; }
+
+        let width = self.width * 2;
+        let height = self.height * 2;
+        let mut data = vec![0u8; (width * height) as usize];
+        for (row, old_row) in self.data.chunks(self.width as usize).enumerate() {
+            let start = row * width as usize;
+            data[start..start + old_row.len()].copy_from_slice(old_row);
+        }
+
+        self.width = width;
+        self.height = height;
+        self.data = data;
+        true
+    }
+}
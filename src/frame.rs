@@ -1,16 +1,25 @@
-use std::collections::HashMap;
+use std::any::Any;
+use std::collections::{HashMap, HashSet};
 use std::cell::RefCell;
 use std::rc::Rc;
 
-use crate::context::{Context, ContextInternal, InputModifiers};
+use crate::context::{Context, ContextInternal, InputModifiers, KeyCode, KeyEvent, MouseButton};
 use crate::{
     AnimState, AnimStateKey, Rect, Point, WidgetBuilder, PersistentState, Align,
 };
 use crate::image::ImageHandle;
+use crate::draw_data::DrawData;
 use crate::widget::Widget;
-
-const MOUSE_NOT_TAKEN: MouseState =
-    MouseState { clicked: false, anim: AnimState::normal(), dragged: Point { x: 0.0, y: 0.0 } };
+use crate::scrollpane::ScrollRequest;
+
+const MOUSE_NOT_TAKEN: MouseState = MouseState {
+    clicked: [false; 3],
+    anim: AnimState::normal(),
+    dragged: Point { x: 0.0, y: 0.0 },
+    pressed_button: None,
+    just_pressed: false,
+    drag_total: Point { x: 0.0, y: 0.0 },
+};
 
 /// A Frame, holding the widget tree to be drawn on a given frame, and a reference to the
 /// Thyme [`Context`](struct.Context.html)
@@ -41,12 +50,23 @@ pub struct Frame {
 
     mouse_cursor: Option<(ImageHandle, Align)>,
     mouse_anim_state: AnimState,
+    cursor_icon: CursorIcon,
+
+    // the focusable widgets in the order they were created, for Tab traversal
+    focus_order: Vec<String>,
+    focus_handles_tab: bool,
+
+    // whether a drop target has already taken the drag and drop this frame
+    drop_taken: bool,
 }
 
 pub(crate) struct MouseState {
-    pub clicked: bool,
+    pub clicked: [bool; 3],
     pub anim: AnimState,
     pub dragged: Point,
+    pub pressed_button: Option<MouseButton>,
+    pub just_pressed: bool,
+    pub drag_total: Point,
 }
 
 impl Frame {
@@ -63,6 +83,9 @@ impl Frame {
                 group: cur_rend_group,
                 start: 0,
                 num: 0,
+                overlay: false,
+                always_on_top: false,
+                owners: Vec::new(),
             }],
             parent_index: 0,
             in_modal_tree: false,
@@ -71,6 +94,10 @@ impl Frame {
             generated_ids: HashMap::default(),
             mouse_cursor: None,
             mouse_anim_state,
+            cursor_icon: CursorIcon::Default,
+            focus_order: Vec::new(),
+            focus_handles_tab: false,
+            drop_taken: false,
         }
     }
 
@@ -99,12 +126,12 @@ impl Frame {
         &self.context.internal()
     }
 
-    pub(crate) fn check_mouse_wheel(&mut self, index: usize) -> Option<Point> {
+    pub(crate) fn check_mouse_wheel(&mut self, index: usize, line_amount: f32) -> Option<Point> {
         let widget = &self.widgets[index];
 
         let mut context = self.context.internal().borrow_mut();
 
-        if context.has_modal() && !self.in_modal_tree {
+        if context.is_measuring() || (context.has_modal() && !self.in_modal_tree) {
             return None;
         }
 
@@ -119,7 +146,14 @@ impl Frame {
             return None;
         }
 
-        Some(context.take_mouse_wheel())
+        let mut wheel = context.take_mouse_wheel(line_amount);
+
+        // treat shift + vertical wheel as horizontal scrolling
+        if context.input_modifiers().shift && wheel.x == 0.0 {
+            wheel = Point::new(wheel.y, 0.0);
+        }
+
+        Some(wheel)
     }
 
     pub(crate) fn check_mouse_state(&mut self, index: usize) -> MouseState {
@@ -127,7 +161,7 @@ impl Frame {
 
         let mut context = self.context.internal().borrow_mut();
 
-        if context.has_modal() && !self.in_modal_tree {
+        if context.is_measuring() || (context.has_modal() && !self.in_modal_tree) {
             return MOUSE_NOT_TAKEN;
         }
 
@@ -143,20 +177,35 @@ impl Frame {
         }
 
         let was_taken_last = context.mouse_taken_last_frame_id() == Some(widget.id());
+        let clicked = [context.mouse_clicked(0), context.mouse_clicked(1), context.mouse_clicked(2)];
 
-        // check if we are dragging on this widget
-        if context.mouse_pressed(0) {
+        // check if we are dragging on this widget, with any button
+        let pressed_button = MouseButton::ALL.iter().copied().find(|button| context.mouse_pressed(button.index()));
+        if let Some(button) = pressed_button {
             if was_taken_last {
                 self.mouse_taken = Some((widget.id().to_string(), widget.rend_group()));
                 let dragged = context.mouse_pos() - context.last_mouse_pos();
+                let (just_pressed, press_pos) = context.take_mouse_press(widget.id());
 
-                if context.mouse_pressed(0) {
-                    context.set_top_rend_group(widget.rend_group());
+                let group_index = widget.rend_group().index as usize;
+                if group_index > 0 {
+                    context.bring_rend_group_to_front(&self.render_groups[group_index].id);
                 }
+
+                // only the primary button shows the pressed animation
+                let anim = if button == MouseButton::Left {
+                    AnimState::new(AnimStateKey::Pressed)
+                } else {
+                    AnimState::new(AnimStateKey::Hover)
+                };
+
                 return MouseState {
-                    clicked: context.mouse_clicked(0),
-                    anim: AnimState::new(AnimStateKey::Pressed),
-                    dragged
+                    clicked,
+                    anim,
+                    dragged,
+                    pressed_button,
+                    just_pressed,
+                    drag_total: context.mouse_pos() - press_pos,
                 };
             } else {
                 return MOUSE_NOT_TAKEN;
@@ -168,15 +217,14 @@ impl Frame {
             return MOUSE_NOT_TAKEN;
         }
 
-        if context.mouse_pressed(0) {
-            context.set_top_rend_group(widget.rend_group());
-        }
-
         self.mouse_taken = Some((widget.id().to_string(), widget.rend_group()));
         MouseState {
-            clicked: was_taken_last && context.mouse_clicked(0),
+            clicked: if was_taken_last { clicked } else { [false; 3] },
             anim: AnimState::new(AnimStateKey::Hover),
-            dragged: Point::default()
+            dragged: Point::default(),
+            pressed_button: None,
+            just_pressed: false,
+            drag_total: Point::default(),
         }
     }
 
@@ -236,7 +284,43 @@ impl Frame {
 
     /// Returns the current window display size, in logical pixels.
     pub fn display_size(&self) -> Point {
-        self.context_internal().borrow().display_size()
+        self.context_internal().borrow().logical_display_size()
+    }
+
+    /// Sets the UI scale, applied on top of the display scale factor, starting with the next frame.
+    /// See [`Context::set_ui_scale`](struct.Context.html#method.set_ui_scale).
+    pub fn set_ui_scale(&mut self, scale: f32) {
+        self.context_internal().borrow_mut().set_ui_scale(scale);
+    }
+
+    /// Returns the current UI scale.  See [`Context::ui_scale`](struct.Context.html#method.ui_scale).
+    pub fn ui_scale(&self) -> f32 {
+        self.context_internal().borrow().ui_scale()
+    }
+
+    /// Returns the ID of the widget that took the mouse on the last frame, if any.  This is the innermost
+    /// widget under the mouse that [`wants the mouse`](struct.WidgetBuilder.html#method.wants_mouse), or the
+    /// widget the mouse is being dragged on.  See also [`Context::wants_mouse`](struct.Context.html#method.wants_mouse).
+    pub fn mouse_taken(&self) -> Option<String> {
+        let context = self.context_internal().borrow();
+        context.mouse_taken_last_frame_id().map(|id| id.to_string())
+    }
+
+    /// Returns whether the widget with the specified `id` was hovered by the mouse on the last frame.  Like
+    /// [`WidgetState::hovered`](struct.WidgetState.html#structfield.hovered), this is also `true` while the widget
+    /// is pressed.  This may be used anywhere in the UI code, such as to react to a widget built elsewhere.
+    /// Returns `false` if no widget with the `id` was built.  See [`mouse_taken`](#method.mouse_taken).
+    pub fn is_hovered(&self, id: &str) -> bool {
+        let context = self.context_internal().borrow();
+        context.mouse_taken_last_frame_id() == Some(id)
+    }
+
+    /// Returns whether a mouse button is held down on the widget with the specified `id`, with any button, as of
+    /// the last time the widget was built.  This remains `true` while the mouse is dragged away from the widget,
+    /// until the button is released.  Returns `false` if no widget with the `id` was built.
+    pub fn is_pressed(&self, id: &str) -> bool {
+        let context = self.context_internal().borrow();
+        context.mouse_press_id() == Some(id)
     }
 
     /// Returns the current state of the keyboard modifier keys
@@ -245,6 +329,12 @@ impl Frame {
         context.input_modifiers()
     }
 
+    /// Sends the specified `text` to the clipboard.  The text is passed on to the
+    /// [`IO`](trait.IO.html) clipboard the next time the IO handles an event.
+    pub fn set_clipboard_text(&mut self, text: String) {
+        self.context_internal().borrow_mut().set_clipboard_text(text);
+    }
+
     /// Returns the current mouse position and size, in logical pixels
     pub fn mouse_rect(&self) -> Rect {
         let context = self.context_internal().borrow();
@@ -277,6 +367,34 @@ impl Frame {
         self.mouse_anim_state = state;
     }
 
+    /// Queues a notification with the specified `text`, to be shown for `duration_millis` milliseconds
+    /// by [`render_toasts`](#method.render_toasts).
+    pub fn push_toast<T: Into<String>>(&mut self, text: T, duration_millis: u32) {
+        self.context_internal().borrow_mut().push_toast(text.into(), duration_millis);
+    }
+
+    /// Sets the OS mouse cursor shape that the application should show for this frame.  Thyme does not
+    /// change the OS cursor itself; instead, the application should read it with [`cursor_icon`](#method.cursor_icon)
+    /// and pass it on to its windowing library before drawing the frame.  If it is called multiple times, the
+    /// last call will take effect.  [`Windows`](struct.WindowBuilder.html) set this while their edges are hovered
+    /// or dragged for resizing.
+    pub fn set_cursor_icon(&mut self, icon: CursorIcon) {
+        self.cursor_icon = icon;
+    }
+
+    /// Returns the OS mouse cursor shape requested for this frame.  See [`set_cursor_icon`](#method.set_cursor_icon).
+    ///
+    /// # Example
+    /// ```
+    /// fn draw(ui: Frame, window: &winit::window::Window) {
+    ///     window.set_cursor_icon(ui.cursor_icon().into());
+    ///     // draw the frame with the renderer here
+    /// }
+    /// ```
+    pub fn cursor_icon(&self) -> CursorIcon {
+        self.cursor_icon
+    }
+
     /// Adds a gap between the previous widget and the next to be specified, subject
     /// to the current parent's layout requirement.
     pub fn gap(&mut self, gap: f32) {
@@ -311,6 +429,26 @@ impl Frame {
         context.set_focus_keyboard(id.into());
     }
 
+    /**
+    Takes all [`KeyEvents`](struct.KeyEvent.html) that have been sent to the widget with the specified `id`
+    since they were last taken, in the order they occurred.  Only the widget with keyboard focus receives key
+    events; see [`focus_keyboard`](#method.focus_keyboard).  Keys that produce a printable character are sent
+    as characters instead, see [`PersistentState`](struct.PersistentState.html).  You may also access the key events
+    directly with [`modify`](#method.modify).
+
+    # Example
+    ```
+    fn check_submit(ui: &mut Frame, id: &str) -> bool {
+        ui.keys_pressed(id).any(|event| event.key == KeyCode::Return)
+    }
+    ```
+    */
+    pub fn keys_pressed(&mut self, id: &str) -> impl Iterator<Item=KeyEvent> {
+        let mut context = self.context.internal().borrow_mut();
+        let keys: Vec<_> = context.state_mut(id).keys.drain(..).collect();
+        keys.into_iter()
+    }
+
     /// Returns whether or not the widget with the specified `id` currently has keyboard focus.
     /// See [`focus_keyboard`](#method.focus_keyboard).
     pub fn is_focus_keyboard(&self, id: &str) -> bool {
@@ -318,6 +456,173 @@ impl Frame {
         context.is_focus_keyboard(id)
     }
 
+    /// Returns the ID of the widget that currently has keyboard focus, if any.  See
+    /// [`focus_keyboard`](#method.focus_keyboard).
+    pub fn focused_id(&self) -> Option<String> {
+        let context = self.context.internal().borrow();
+        context.focus_keyboard_id().map(|id| id.to_string())
+    }
+
+    /// Removes keyboard focus from whichever widget has it, so that no widget receives keyboard events.
+    /// Keyboard focus is also cleared when the mouse is clicked anywhere other than on the focused widget.
+    /// See [`focus_keyboard`](#method.focus_keyboard).
+    pub fn clear_focus(&mut self) {
+        let mut context = self.context.internal().borrow_mut();
+        context.clear_focus_keyboard();
+    }
+
+    /// Returns the payload of the drag and drop in progress, if any.  A drag starts when the mouse is
+    /// dragged on a widget set as a [`drag_source`](struct.WidgetBuilder.html#method.drag_source), and ends
+    /// when the mouse is released.
+    pub fn drag_payload(&self) -> Option<String> {
+        let context = self.context.internal().borrow();
+        context.drag_payload().map(|payload| payload.to_string())
+    }
+
+    /// Cancels the drag and drop in progress, if any, so that no drop target receives it.
+    pub fn cancel_drag(&mut self) {
+        self.context.internal().borrow_mut().cancel_drag();
+    }
+
+    /**
+    If a drag and drop is in progress, creates a widget with the specified `theme` at the mouse position,
+    and calls the `preview` closure with the drag payload to add its children, such as an image of the
+    item being dragged.  The preview is drawn on top of all other widgets and never takes the mouse.  This
+    should usually be called after all other widgets have been created for the frame.  If the drag source or
+    any of its parents, such as a window, is closed or not built, the drag is cancelled and the preview is hidden.
+
+    # Example
+    ```
+    fn inventory(ui: &mut Frame, slots: &mut [Option<String>]) {
+        for index in 0..slots.len() {
+            let mut builder = ui.start("slot").id(format!("slot{}", index)).drop_target();
+            if let Some(item) = &slots[index] {
+                builder = builder.drag_source(&index.to_string()).foreground(item);
+            }
+
+            if let Some(from) = builder.finish().dropped.and_then(|payload| payload.parse::<usize>().ok()) {
+                slots.swap(from, index);
+            }
+        }
+
+        ui.drag_preview("drag_preview", |ui, payload| {
+            if let Some(Some(item)) = payload.parse::<usize>().ok().and_then(|index| slots.get(index)) {
+                ui.start("item").foreground(item).finish();
+            }
+        });
+    }
+    ```
+    */
+    pub fn drag_preview<F: FnOnce(&mut Frame, &str)>(&mut self, theme: &str, preview: F) {
+        let payload = match self.drag_payload() {
+            None => return,
+            Some(payload) => payload,
+        };
+
+        let mouse = self.mouse_rect();
+        let group = self.render_groups.len();
+        self.overlay(|ui| {
+            ui.start(theme)
+            .unclip()
+            .unparent()
+            .screen_pos(mouse.pos.x, mouse.pos.y)
+            .new_render_group()
+            .children(|ui| (preview)(ui, &payload));
+        });
+
+        // the preview belongs to the drag source, wherever it is built
+        if let Some(group) = self.render_groups.get_mut(group) {
+            group.owners = self.context.internal().borrow().drag_owners().to_vec();
+        }
+    }
+
+    /**
+    Runs the specified closure in a measuring mode, returning the overall size of the widgets it creates,
+    in logical pixels.  Widgets are laid out as normal, but are then discarded - nothing created inside the
+    closure is drawn, no widget receives any mouse or keyboard input, and any changes made to the
+    [`PersistentState`](struct.PersistentState.html) are dropped once the closure completes.  The current
+    parent's layout, including its cursor and any partially filled row, and the frame's
+    [`cursor_icon`](#method.cursor_icon) are also left unchanged, so the content may then be built for real.
+
+    This effectively builds the content twice, so it should be used sparingly.
+
+    # Example
+    ```
+    fn centered_buttons(ui: &mut Frame) {
+        let size = ui.measure(|ui| {
+            ui.button("button", "Ok");
+            ui.button("button", "Cancel");
+        });
+
+        let x = (ui.display_size().x - size.x) / 2.0;
+        ui.set_cursor(x, ui.cursor().y);
+        ui.button("button", "Ok");
+        ui.button("button", "Cancel");
+    }
+    ```
+    */
+    pub fn measure<F: FnOnce(&mut Frame)>(&mut self, f: F) -> Point {
+        let was_measuring = {
+            let mut context = self.context.internal().borrow_mut();
+            let was_measuring = context.is_measuring();
+            context.start_measure();
+            was_measuring
+        };
+
+        let num_widgets = self.widgets.len();
+        let num_render_groups = self.render_groups.len();
+        let cur_rend_group = self.cur_rend_group;
+        let cur_rend_group_num = self.render_groups[cur_rend_group.index as usize].num;
+        let parent_index = self.parent_index;
+        let parent_layout = self.widgets[parent_index].child_layout();
+        let in_modal_tree = self.in_modal_tree;
+        let max_child_bounds = self.max_child_bounds;
+        let parent_max_child_bounds = self.parent_max_child_bounds;
+        let generated_ids = self.generated_ids.clone();
+        let mouse_cursor = self.mouse_cursor;
+        let mouse_anim_state = self.mouse_anim_state;
+        let cursor_icon = self.cursor_icon;
+
+        (f)(self);
+
+        let bounds = self.children_bounds_since(num_widgets);
+
+        self.widgets.truncate(num_widgets);
+        self.render_groups.truncate(num_render_groups);
+        self.cur_rend_group = cur_rend_group;
+        self.render_groups[cur_rend_group.index as usize].num = cur_rend_group_num;
+        self.parent_index = parent_index;
+        self.widgets[parent_index].set_child_layout(parent_layout);
+        self.in_modal_tree = in_modal_tree;
+        self.max_child_bounds = max_child_bounds;
+        self.parent_max_child_bounds = parent_max_child_bounds;
+        self.generated_ids = generated_ids;
+        self.mouse_cursor = mouse_cursor;
+        self.mouse_anim_state = mouse_anim_state;
+        self.cursor_icon = cursor_icon;
+
+        if !was_measuring {
+            self.context.internal().borrow_mut().end_measure();
+        }
+
+        bounds.map_or(Point::default(), |bounds| bounds.size)
+    }
+
+    // computes the bounds of all direct children of the current parent that were added
+    // after the widget with the specified index
+    pub(crate) fn children_bounds_since(&self, start: usize) -> Option<Rect> {
+        let parent_index = self.parent_index;
+        let mut bounds: Option<Rect> = None;
+        for widget in self.widgets[start..].iter().filter(|widget| widget.parent_index() == parent_index) {
+            let widget_bounds = Rect::new(widget.pos(), widget.size());
+            bounds = Some(match bounds {
+                None => widget_bounds,
+                Some(bounds) => bounds.max(widget_bounds),
+            });
+        }
+        bounds
+    }
+
     /// Returns a [`Rect`](struct.Rect.html) encompassing all children that have currently
     /// been added to the parent widget, recursively.  This includes each widget's actual
     /// final position and size.
@@ -348,7 +653,8 @@ impl Frame {
     /// specified `id` to the specified `time`.
     /// This time should probably be based on something obtained from [`cur_time_millis`](#method.cur_time_millis)
     /// or [`base_time_millis`](#method.base_time_millis).  The base time of a widget is used to specify the
-    /// zero time of an Timed images associated with that widget.
+    /// zero time of any Timed images associated with that widget, so setting it restarts their animation.  Until a base
+    /// time in the future is reached, Timed images show their first frame.
     pub fn set_base_time_millis<T: Into<String>>(&mut self, id: T, time: u32) {
         let mut context = self.context.internal().borrow_mut();
         let state = context.state_mut(id);
@@ -365,6 +671,35 @@ impl Frame {
         state.base_time_millis = cur_time;
     }
 
+    /**
+    Adds `key` to the [`AnimState`](struct.AnimState.html) of the widget with the specified `id` for the next
+    `duration_millis` milliseconds, allowing the widget to briefly pulse or flash in response to an event.  The widget's
+    theme can map states including `key` to a highlight image, or a tint with `state_tints`.  The widget's base time is also
+    set to the current time, so [`Timed`](index.html#timed-images) images used while the animation runs start from their
+    first frame.  Calling this again while the animation is running restarts it.
+
+    # Example
+    ```
+    fn add_gold(ui: &mut Frame, gold: &mut u32, amount: u32) {
+        *gold += amount;
+        ui.animate("gold_counter", AnimStateKey::Flash, 500);
+    }
+    ```
+    */
+    pub fn animate<T: Into<String>>(&mut self, id: T, key: AnimStateKey, duration_millis: u32) {
+        let mut context = self.context.internal().borrow_mut();
+        let cur_time = context.time_millis();
+        let state = context.state_mut(id);
+        state.animation = Some((key, cur_time + duration_millis));
+        state.base_time_millis = cur_time;
+    }
+
+    /// Flashes the widget with the specified `id`, adding [`AnimStateKey::Flash`](enum.AnimStateKey.html#variant.Flash) to
+    /// its state for `duration_millis` milliseconds.  See [`animate`](#method.animate).
+    pub fn flash<T: Into<String>>(&mut self, id: T, duration_millis: u32) {
+        self.animate(id, AnimStateKey::Flash, duration_millis);
+    }
+
     /// Returns the current base time in millis of the [`PersistentState`](struct.PersistentState.html) for the
     /// widget with the current `id`.
     pub fn base_time_millis(&self, id: &str) -> u32 {
@@ -387,6 +722,34 @@ impl Frame {
         state.scroll = state.scroll + Point { x, y }
     }
 
+    /// Gives back mouse wheel movement, in logical pixels, that was taken by a widget but not used,
+    /// typically because the widget was already scrolled as far as it can go.  The movement may then be
+    /// taken by an ancestor of the widget which [`wants scroll`](struct.WidgetBuilder.html#method.wants_scroll),
+    /// as those are finished after their children.  [`Scrollpanes`](struct.ScrollpaneBuilder.html) do this
+    /// automatically, so wheel movement passes from a nested pane to the pane containing it.  See
+    /// [`WidgetState::scrolled`](struct.WidgetState.html#structfield.scrolled).
+    pub fn release_mouse_wheel(&mut self, amount: Point) {
+        self.context.internal().borrow_mut().release_mouse_wheel(amount);
+    }
+
+    /// Scrolls the [`scrollpane`](struct.WidgetBuilder.html#method.scrollpane) with the specified
+    /// `content_id` vertically to the end of its content.  The scroll is applied the next time the scrollpane
+    /// is built, once its children are known, so the new position is shown starting with the next frame.
+    pub fn scroll_to_end<T: Into<String>>(&mut self, content_id: T) {
+        let mut context = self.context.internal().borrow_mut();
+        context.request_scroll(content_id.into(), ScrollRequest::End);
+    }
+
+    /// Scrolls the [`scrollpane`](struct.WidgetBuilder.html#method.scrollpane) with the specified
+    /// `content_id` by the smallest amount that makes the widget with `target_id` visible, if it is
+    /// not already.  The target must be a descendant of the scrollpane content.  The scroll is clamped
+    /// to the scrollpane's content and takes effect in the same way as [`scroll_to_end`](#method.scroll_to_end).
+    /// Both are animated in scrollpanes with [`smooth_scroll`](struct.ScrollpaneBuilder.html#method.smooth_scroll).
+    pub fn scroll_to<T: Into<String>, U: Into<String>>(&mut self, content_id: T, target_id: U) {
+        let mut context = self.context.internal().borrow_mut();
+        context.request_scroll(content_id.into(), ScrollRequest::Widget(target_id.into()));
+    }
+
     /// Returns the current `text` associated with the [`PersistentState`](struct.PersistentState.html) of
     /// the widget with the specified `id`.  Useful for [`input fields`](#method.input_field).
     pub fn text_for(&self, id: &str) -> Option<String> {
@@ -394,6 +757,27 @@ impl Frame {
         context.state(id).text.clone()
     }
 
+    /// Returns the current text of the [`input field`](#method.input_field) or [`text area`](#method.text_area)
+    /// with the specified `id`, or `None` if it has not been created or set.  This is equivalent to
+    /// [`text_for`](#method.text_for).
+    pub fn input_text(&self, id: &str) -> Option<String> {
+        self.text_for(id)
+    }
+
+    /// Sets the text of the [`input field`](#method.input_field) or [`text area`](#method.text_area) with the
+    /// specified `id`, such as to reset or pre-fill it.  The caret and selection are clamped to the new text.
+    /// The field will not report this as a change to its text.
+    pub fn set_input_text<T: Into<String>, U: Into<String>>(&mut self, id: T, text: U) {
+        let text = text.into();
+        let len = text.chars().count();
+
+        let mut context = self.context.internal().borrow_mut();
+        let state = context.state_mut(id);
+        state.caret = state.caret.min(len);
+        state.selection = state.selection.map(|anchor| anchor.min(len));
+        state.text = Some(text);
+    }
+
     /// Returns whether the widget with the specified `id` is expanded in its [`PersistentState`](struct.PersistentState.html).
     /// Trees and similar widgets will not show their entire content if not expanded
     pub fn is_expanded(&self, id: &str) -> bool {
@@ -408,26 +792,51 @@ impl Frame {
     }
 
     /// Returns whether the widget with the specified `id` is open in its [`PersistentState`](struct.PersistentState.html).
-    /// If not open, widgets are not visible.
+    /// If not open, widgets are not visible.  This works for any `id`, whether or not the widget has been built this
+    /// frame.  A widget that has never been built or opened or closed is considered open, even if it will be
+    /// [`initially closed`](struct.WidgetBuilder.html#method.initially_open) when built.
     pub fn is_open(&self, id: &str) -> bool {
         let context = self.context.internal().borrow();
         context.state(id).is_open
     }
 
     /// Opens the widget with the specified `id` as a modal.  This modifies the [`PersistentState`](struct.PersistentState.html)
-    /// associated with that widget, as well as setting the overall Thyme modal to the specified widget.
-    /// When a modal is open, only the modal and its children may receive input.  There may be only one modal open at a time.
-    /// If the specified `id` is closed, i.e. via [`close`](#method.close), the modal state ends.
+    /// associated with that widget, as well as pushing the specified widget onto Thyme's stack of modals.
+    /// When a modal is open, only the top modal and its children may receive input.  Modals lower in the stack, such
+    /// as a settings window that opened a confirmation dialog, are still drawn but receive no input until the modals
+    /// above them are closed.  Only the top modal is closed by clicking outside of it, if
+    /// [`close_modal_on_click_outside`](#method.close_modal_on_click_outside) was set.
+    /// If the specified `id` is closed, i.e. via [`close`](#method.close), it is removed from the stack along with any
+    /// modals opened above it, which are closed as well.  Opening an `id` that is already a modal closes any modals
+    /// above it, making it the top modal again.
+    ///
+    /// The modal is tied to the current parent widget and all of its ancestors.  If any of those widgets are
+    /// closed or are not built on a subsequent frame, the modal is automatically closed as well.  This
+    /// way, a popup opened from inside a window will not outlive that window.
     pub fn open_modal<T: Into<String>>(&mut self, id: T) {
         let id = id.into();
+        let ancestors = self.parent_ids();
 
         let mut context = self.context.internal().borrow_mut();
         context.set_top_rend_group_id(&id);
         context.state_mut(id.clone()).is_open = true;
-        context.set_modal(id);
+        context.set_modal(id, ancestors);
     }
 
-    /// Sets the currently open modal, if there is one, to close if the mouse is clicked outside of the modal's area.
+    // the IDs of the current parent widget and all of its ancestors, not including the root
+    fn parent_ids(&self) -> Vec<String> {
+        let mut ids = Vec::new();
+        let mut index = self.parent_index;
+        while index != 0 {
+            let widget = &self.widgets[index];
+            ids.push(widget.id().to_string());
+            index = widget.parent_index();
+        }
+        ids
+    }
+
+    /// Sets the top open modal, if there is one, to close if the mouse is clicked outside of the modal's area.
+    /// See [`modal_closed_this_frame`](#method.modal_closed_this_frame) to respond when this happens.
     pub fn close_modal_on_click_outside(&mut self) {
         let mut context = self.context.internal().borrow_mut();
         context.mut_modal(|modal| {
@@ -435,6 +844,29 @@ impl Frame {
         });
     }
 
+    /**
+    Returns true if the modal with the specified `id` was dismissed by the user at the end of the previous
+    frame, so that this is the first frame it is no longer shown.  A modal is dismissed when it is
+    [`closed by a click outside`](#method.close_modal_on_click_outside) of it, or when Escape closes a
+    modal popup such as a [`combo_box`](#method.combo_box) or [`confirm_dialog`](#method.confirm_dialog).
+    Closing a modal with [`close`](#method.close), or because its parent was closed, does not count as a dismissal.
+
+    # Example
+    ```
+    fn inventory_button(ui: &mut Frame, state: &mut InventoryState) {
+        if ui.modal_closed_this_frame("item_popup") {
+            state.highlight_item = None;
+        }
+
+        ui.button("button", "Inventory");
+    }
+    ```
+    */
+    pub fn modal_closed_this_frame(&self, id: &str) -> bool {
+        let context = self.context.internal().borrow();
+        context.modal_dismissed(id)
+    }
+
     /// Opens the widget with the specified `id`.  This modifies the [`PersistentState`](struct.PersistentState.html).
     /// See [`is_open`](#method.is_open)
     pub fn open<T: Into<String>>(&mut self, id: T) {
@@ -444,8 +876,19 @@ impl Frame {
         context.state_mut(id).is_open = true;
     }
 
+    /// Moves the [`window`](struct.WindowBuilder.html) or other widget with its own
+    /// [`render group`](struct.WidgetBuilder.html#method.new_render_group) with the specified `id` above all
+    /// other render groups, so it is drawn on top and receives the mouse first.  Windows are also brought to
+    /// the front when pressed with the mouse.  The new order takes effect starting with the next frame.
+    pub fn bring_to_front<T: Into<String>>(&mut self, id: T) {
+        let id = id.into();
+        let mut context = self.context.internal().borrow_mut();
+        context.set_top_rend_group_id(&id);
+    }
+
     /// Closes the widget with the specified `id`.  This modifies the [`PersistentState`](struct.PersistentState.html).
-    /// See [`is_open`](#method.is_open).  If the widget was the current modal, resets Thyme so there is no longer a modal.
+    /// See [`is_open`](#method.is_open).  If the widget is an open modal, it is removed from the modal stack, and any
+    /// modals opened above it are closed as well.
     pub fn close<T: Into<String>>(&mut self, id: T) {
         let id = id.into();
 
@@ -454,6 +897,30 @@ impl Frame {
         context.state_mut(id).is_open = false;
     }
 
+    /// Opens or closes the widget with the specified `id`, as in [`open`](#method.open) and [`close`](#method.close).
+    pub fn set_open<T: Into<String>>(&mut self, id: T, open: bool) {
+        if open {
+            self.open(id);
+        } else {
+            self.close(id);
+        }
+    }
+
+    /// Closes the widget with the specified `id` if it is open, or opens it if it is closed.  See
+    /// [`set_open`](#method.set_open).  This is useful for showing and hiding windows with a keyboard shortcut:
+    /// ```
+    /// fn handle_hotkeys(ui: &mut Frame, toggle_inventory: bool) {
+    ///     if toggle_inventory {
+    ///         ui.toggle_open("inventory");
+    ///     }
+    /// }
+    /// ```
+    pub fn toggle_open<T: Into<String>>(&mut self, id: T) {
+        let id = id.into();
+        let open = !self.is_open(&id);
+        self.set_open(id, open);
+    }
+
     /// Opens the current parent widget.  See [`open`](#method.open).
     pub fn open_parent(&mut self) {
         let mut context = self.context.internal().borrow_mut();
@@ -472,7 +939,8 @@ impl Frame {
 
     /// Completely clears all [`PersistentState`](struct.PersistentState.html) associated with the 
     /// specified `id`, resetting it to its default state.
-    /// This includies clearing the modal state if the `id` is the current modal.
+    /// This includies closing the modal and any modals above it if the `id` is an open modal, and dropping keyboard
+    /// focus if the widget has it.  See also [`Context::clear_all_state`](struct.Context.html#method.clear_all_state).
     pub fn clear(&mut self, id: &str) {
         let mut context = self.context.internal().borrow_mut();
         context.clear_modal_if_match(id);
@@ -489,6 +957,41 @@ impl Frame {
         (f)(context.state_mut(id))
     }
 
+    /**
+    Calls the passed in closure, `f`, with a mutable reference to the [`user state`](struct.UserState.html) of type
+    `U` stored for the widget with the specified `id`, first storing the default value if there is none.  This
+    allows custom widgets and application code to keep arbitrary typed state per widget, in the same way as the
+    rest of its [`PersistentState`](struct.PersistentState.html).  The return value of `f` is passed through.
+
+    # Example
+    ```
+    #[derive(Clone, Default)]
+    struct TableSort {
+        column: usize,
+        ascending: bool,
+    }
+
+    fn sort_by(ui: &mut Frame, column: usize) {
+        ui.modify_user_state("table", |sort: &mut TableSort| {
+            sort.ascending = sort.column != column || !sort.ascending;
+            sort.column = column;
+        });
+    }
+    ```
+    */
+    pub fn modify_user_state<U, T, Ret, F>(&mut self, id: T, f: F) -> Ret where
+        U: Any + Clone + Default, T: Into<String>, F: FnOnce(&mut U) -> Ret {
+        let mut context = self.context.internal().borrow_mut();
+        (f)(context.state_mut(id).user_state.get_mut())
+    }
+
+    /// Returns a copy of the [`user state`](struct.UserState.html) of type `U` stored for the widget with the
+    /// specified `id`, or the default value if there is none.  See [`modify_user_state`](#method.modify_user_state).
+    pub fn user_state<U: Any + Clone + Default>(&self, id: &str) -> U {
+        let context = self.context.internal().borrow();
+        context.state(id).user_state.get().cloned().unwrap_or_default()
+    }
+
     /// Queries the theme for the specified custom float, in the `custom_floats` field for the
     /// theme with the specified `key`.  Returns the `default_value` if the theme or key cannot
     /// be found.
@@ -497,10 +1000,40 @@ impl Frame {
 
         match context.themes().theme(theme_id) {
             None => default_value,
-            Some(theme) => *theme.custom_floats.get(key).unwrap_or(&0.0),
+            Some(theme) => *theme.custom_floats.get(key).unwrap_or(&default_value),
         }
     }
 
+    /// Returns the size, in logical pixels, of `text` drawn with the font with the specified
+    /// `font_id`, as defined in the theme's font definitions.  The text is not wrapped, but
+    /// explicit newlines start new lines.  The size is measured using the same font metrics used
+    /// to draw text, so it matches a widget's text exactly.  Returns `None` if the font cannot be found.
+    pub fn text_size(&self, font_id: &str, text: &str) -> Option<Point> {
+        self.measure_text(font_id, text, None).map(|(size, _)| size)
+    }
+
+    /// Returns the size, in logical pixels, of `text` drawn with the font with the specified
+    /// `font_id`, wrapping onto new lines as needed to fit within `max_width`, in the same way
+    /// as a widget's text with [`wrap`](struct.WidgetBuilder.html#method.wrap) enabled.  The number
+    /// of lines is also returned.  Returns `None` if the font cannot be found.
+    pub fn wrapped_text_size(&self, font_id: &str, text: &str, max_width: f32) -> Option<(Point, usize)> {
+        self.measure_text(font_id, text, Some(max_width))
+    }
+
+    fn measure_text(&self, font_id: &str, text: &str, max_width: Option<f32>) -> Option<(Point, usize)> {
+        let context = self.context_internal().borrow();
+        let font_sum = context.themes().find_font(Some(font_id))?;
+        let font = context.themes().font(font_sum.handle);
+        let scale = context.scale_factor();
+
+        let spacing = font_sum.spacing(scale);
+        let (size, lines) = match max_width {
+            None => font.measure(f32::MAX, text, false, spacing),
+            Some(width) => font.measure(width * scale, text, true, spacing),
+        };
+        Some((size / scale, lines))
+    }
+
     pub(crate) fn push_widget(&mut self, mut widget: Widget) {
         widget.set_rend_group(self.cur_rend_group);
         self.render_groups[self.cur_rend_group.index as usize].num += 1;
@@ -517,6 +1050,7 @@ impl Frame {
         let widgets_len = self.widgets.len();
         let index = self.render_groups.len() as u16;
         let cur_rend_group = RendGroup { index };
+        let owners = self.parent_ids();
 
         self.render_groups.push(RendGroupDef {
             rect,
@@ -524,6 +1058,9 @@ impl Frame {
             group: cur_rend_group,
             start: widgets_len,
             num: 0,
+            overlay: false,
+            always_on_top: false,
+            owners,
         });
         self.cur_rend_group = cur_rend_group;
     }
@@ -532,34 +1069,234 @@ impl Frame {
         self.render_groups[self.cur_rend_group.index as usize].rect = bounds;
     }
 
-    pub(crate) fn finish_frame(self) -> (Context, Vec<Widget>, Vec<RendGroupDef>) {
-        let (top_rend_group, mouse_pos) = {
+    // shows a tooltip next to the mouse cursor, flipping it above the cursor or moving it left
+    // as needed to keep it on the display.  The tooltip is drawn in an overlay render group
+    pub(crate) fn show_tooltip(&mut self, theme: &str, text: Option<&str>, content: Option<&dyn Fn(&mut Frame)>) {
+        let build = |ui: &mut Frame, pos: Point| {
+            let builder = ui.start(theme)
+            .unclip()
+            .unparent()
+            .screen_pos(pos.x, pos.y)
+            .new_render_group();
+
+            let builder = match text {
+                None => builder,
+                Some(text) => builder.text(text),
+            };
+
+            match content {
+                None => builder.finish(),
+                Some(content) => builder.children(content),
+            };
+        };
+
+        let size = self.measure(|ui| build(ui, Point::default()));
+        let mouse = self.mouse_rect();
+        let display = self.display_size();
+
+        let mut pos = Point::new(mouse.right(), mouse.bot());
+        if pos.x + size.x > display.x {
+            pos.x = (display.x - size.x).max(0.0);
+        }
+        if pos.y + size.y > display.y {
+            pos.y = (mouse.pos.y - size.y).max(0.0);
+        }
+
+        self.overlay(|ui| build(ui, pos));
+    }
+
+    pub(crate) fn num_render_groups(&self) -> usize { self.render_groups.len() }
+
+    // marks the render group with the specified index as always on top, if it exists
+    pub(crate) fn set_always_on_top(&mut self, group: usize) {
+        if let Some(group) = self.render_groups.get_mut(group) {
+            group.always_on_top = true;
+        }
+    }
+
+    // calls `build`, marking the first render group it creates as an overlay, which is drawn on top
+    // and never takes the mouse
+    pub(crate) fn overlay<F: FnOnce(&mut Frame)>(&mut self, build: F) {
+        let group = self.render_groups.len();
+        (build)(self);
+        if let Some(group) = self.render_groups.get_mut(group) {
+            group.overlay = true;
+        }
+    }
+
+    // starts a drag and drop from the widget with the specified `id`, a child of the current parent
+    pub(crate) fn start_drag(&mut self, payload: String, id: &str) {
+        let mut owners = vec![id.to_string()];
+        owners.extend(self.parent_ids());
+        self.context.internal().borrow_mut().start_drag(payload, owners);
+    }
+
+    // checks whether the widget with the specified index is the drop target under the mouse, returning
+    // whether a drag is in progress over it and the payload dropped on it this frame, if any.  Only the
+    // first target checked, which is the innermost one, takes the drop
+    pub(crate) fn check_drop(&mut self, index: usize) -> (bool, Option<String>) {
+        if self.drop_taken { return (false, None); }
+
+        let widget = &self.widgets[index];
+        let context = self.context.internal().borrow();
+        if context.is_measuring() || (context.has_modal() && !self.in_modal_tree) {
+            return (false, None);
+        }
+
+        if let Some(group) = context.mouse_in_rend_group_last_frame() {
+            if widget.rend_group() != group {
+                return (false, None);
+            }
+        }
+
+        let mouse_pos = context.mouse_pos();
+        if !widget.clip().is_inside(mouse_pos) || !Rect::new(widget.pos(), widget.size()).is_inside(mouse_pos) {
+            return (false, None);
+        }
+
+        let result = match (context.drag_payload(), context.dropped_payload()) {
+            (Some(_), _) => (true, None),
+            (None, Some(payload)) => (false, Some(payload.to_string())),
+            (None, None) => return (false, None),
+        };
+
+        drop(context);
+        self.drop_taken = true;
+        result
+    }
+
+    // see ContextInternal::repeat_press
+    pub(crate) fn repeat_press(&mut self, id: &str, pressed: bool) -> bool {
+        self.context.internal().borrow_mut().repeat_press(id, pressed)
+    }
+
+    // whether the mouse was released this frame after being pressed on the widget with `id`
+    pub(crate) fn mouse_released(&self, id: &str) -> bool {
+        let context = self.context.internal().borrow();
+        context.mouse_clicked(0) && context.mouse_taken_last_frame_id() == Some(id)
+    }
+
+    // see ContextInternal::held_millis
+    pub(crate) fn held_millis(&self, id: &str) -> u32 {
+        self.context.internal().borrow().held_millis(id)
+    }
+
+    // removes any Enter or Space key presses sent to the specified widget, returning
+    // true if there were any
+    pub(crate) fn take_activation_keys(&mut self, index: usize) -> bool {
+        let mut context = self.context.internal().borrow_mut();
+        if context.is_measuring() { return false; }
+
+        let keys = &mut context.state_mut(self.widgets[index].id()).keys;
+        let len = keys.len();
+        keys.retain(|event| event.key != KeyCode::Return && event.key != KeyCode::Space);
+        keys.len() != len
+    }
+
+    pub(crate) fn push_focusable(&mut self, id: &str, handles_tab: bool) {
+        let context = self.context.internal().borrow();
+        if context.is_measuring() { return; }
+
+        if context.is_focus_keyboard(id) {
+            self.focus_handles_tab = handles_tab;
+        }
+
+        self.focus_order.push(id.to_string());
+    }
+
+    /// Finishes this frame and returns its final [`DrawData`](struct.DrawData.html), for use with a custom
+    /// rendering backend.  This is an alternative to drawing the frame with a provided renderer such as
+    /// [`GliumRenderer::draw_frame`](struct.GliumRenderer.html#method.draw_frame).  Textures and fonts are
+    /// still registered through the [`Renderer`](trait.Renderer.html) passed to the
+    /// [`ContextBuilder`](struct.ContextBuilder.html), which records the handles used in the draw data.
+    /// See [`Context::texture_id`](struct.Context.html#method.texture_id) and
+    /// [`Context::font_id`](struct.Context.html#method.font_id) to map handles back to IDs.
+    pub fn finish_to_draw_data(self) -> DrawData {
+        let mouse_cursor = self.mouse_cursor();
+        let (context, widgets, render_groups) = self.finish_frame();
+        DrawData::build(context, widgets, render_groups, mouse_cursor)
+    }
+
+    pub(crate) fn finish_frame(mut self) -> (Context, Vec<Widget>, Vec<RendGroupDef>) {
+        let (order, mouse_pos) = {
             let mut context = self.context.internal().borrow_mut();
 
+            context.update_rend_group_order(&self.render_groups);
             context.check_set_rend_group_top(&self.render_groups);
 
-            (context.top_rend_group(), context.mouse_pos())
+            // popups, tooltips, and drags whose owning widgets were closed or not built are dropped
+            let built: HashSet<&str> = self.widgets.iter().map(|widget| widget.id()).collect();
+            let was_built = |id: &str| built.contains(id);
+            context.close_orphaned_modals(was_built);
+            context.cancel_orphaned_drag(was_built);
+            self.render_groups.retain(|group| !context.is_orphaned(&group.owners, was_built));
+
+            let order: HashMap<String, usize> = context.rend_group_order().iter()
+                .enumerate()
+                .map(|(index, id)| (id.to_string(), index))
+                .collect();
+
+            (order, context.mouse_pos())
         };
 
+        // groups are sorted from the top down, with the root group at the bottom
         let mut render_groups = self.render_groups;
         render_groups.sort_by_key(|group| {
-            if group.group == top_rend_group { 0 } else { 1 }
+            let pos = order.get(&group.id).copied().unwrap_or(0);
+            if group.overlay {
+                (0, 0)
+            } else if group.group == RendGroup::default() {
+                (3, 0)
+            } else if group.always_on_top {
+                (1, pos)
+            } else {
+                (2, pos)
+            }
         });
 
         let mut mouse_in_rend_group = None;
-        for rend_group in render_groups.iter() {
-            if rend_group.rect.is_inside(mouse_pos) {
+        for rend_group in render_groups.iter().filter(|group| !group.overlay) {
+            let transparent = matches!(rend_group.iter(&self.widgets).next(), Some(widget) if widget.opacity() <= 0.0);
+            if !transparent && rend_group.rect.is_inside(mouse_pos) {
                 mouse_in_rend_group = Some(rend_group.group);
                 break;
             }
         }
 
-        self.context.internal().borrow_mut().next_frame(self.mouse_taken, mouse_in_rend_group);
+        {
+            let mut context = self.context.internal().borrow_mut();
+            context.set_focus_order(self.focus_order, self.focus_handles_tab);
+            context.next_frame(self.mouse_taken, mouse_in_rend_group);
+        }
 
         (self.context, self.widgets, render_groups)
     }
 }
 
+/// A hint for the shape of the OS mouse cursor.  See [`Frame::set_cursor_icon`](struct.Frame.html#method.set_cursor_icon).
+/// This may be converted into a `winit` `CursorIcon`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CursorIcon {
+    /// The normal cursor.
+    Default,
+
+    /// A cursor for resizing horizontally, from the left or right edge.
+    EwResize,
+
+    /// A cursor for resizing vertically, from the top or bottom edge.
+    NsResize,
+
+    /// A cursor for resizing diagonally, from the top left or bottom right corner.
+    NwseResize,
+
+    /// A cursor for resizing diagonally, from the top right or bottom left corner.
+    NeswResize,
+}
+
+impl Default for CursorIcon {
+    fn default() -> Self { CursorIcon::Default }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
 pub(crate) struct RendGroup {
     index: u16,
@@ -572,6 +1309,16 @@ pub(crate) struct RendGroupDef {
     group: RendGroup,
     start: usize,
     num: usize,
+
+    // overlay groups are drawn on top of all others and never take the mouse
+    overlay: bool,
+
+    // always on top groups are drawn above all groups except overlays
+    always_on_top: bool,
+
+    // the IDs of the widgets this group was built from, or of the drag source for a drag preview.
+    // The group is not drawn if any of these are closed
+    owners: Vec<String>,
 }
 
 impl RendGroupDef {
@@ -581,5 +1328,4 @@ impl RendGroupDef {
     }
 
     pub(crate) fn id(&self) -> &str { &self.id }
-    pub(crate) fn group(&self) -> RendGroup { self.group }
 }
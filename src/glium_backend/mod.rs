@@ -9,10 +9,13 @@ use glium::uniforms::{MagnifySamplerFilter, MinifySamplerFilter, Sampler, Sample
 use glium::texture::{Texture2d, RawImage2d};
 use glium::index::PrimitiveType;
 
-use crate::{image::ImageDrawParams};
-use crate::render::{view_matrix, TexCoord, DrawList, DrawMode, Renderer, TextureHandle, TextureData, FontHandle};
-use crate::font::{Font, FontSource, FontTextureWriter};
-use crate::{Frame, Point, Color, Rect};
+use crate::{image::ImageDrawParams, canvas::CanvasCommand};
+use crate::render::{
+    view_matrix, run_callbacks, TexCoord, DrawList, DrawMode, DrawCallbackParams, DrawTarget,
+    PendingCallback,     TintedDrawList, Renderer, TextureHandle, TextureData, FontHandle,
+};
+use crate::font::{Font, FontSource};
+use crate::{Frame, Point, Color, Rect, TextOverflow};
 
 /// A Thyme [`Renderer`](trait.Renderer.html) for [`Glium`](https://github.com/glium/glium).
 ///
@@ -44,6 +47,7 @@ pub struct GliumRenderer {
     // per frame data
     draw_list: GliumDrawList,
     groups: Vec<DrawGroup>,
+    callbacks: Vec<PendingCallback>,
     matrix: [[f32; 4]; 4],
     params: DrawParameters<'static>,
 }
@@ -89,6 +93,7 @@ impl GliumRenderer {
             textures: Vec::new(),
             draw_list: GliumDrawList::new(),
             groups: Vec::new(),
+            callbacks: Vec::new(),
             matrix: view_matrix(Point::default(), Point { x: 100.0, y: 100.0 }),
             params: DrawParameters {
                 blend: glium::Blend::alpha_blending(),
@@ -106,8 +111,44 @@ impl GliumRenderer {
         &self.textures[texture.id()]
     }
 
-    /// Draws the specified [`Frame`](struct.Frame.html) to the Glium surface, usually the Glium Frame.
-    pub fn draw_frame<T: Surface>(&mut self, target: &mut T, frame: Frame) -> Result<(), GliumError> {
+    // creates or replaces the texture for the font atlas with `handle`
+    fn set_font_texture(&mut self, handle: FontHandle, data: &[u8], width: u32, height: u32) {
+        let font_tex = Texture2d::with_format(
+            &self.context,
+            RawImage2d {
+                data: Cow::Borrowed(data),
+                width,
+                height,
+                format: glium::texture::ClientFormat::U8,
+            },
+            glium::texture::UncompressedFloatFormat::U8,
+            glium::texture::MipmapsOption::NoMipmap,
+        ).unwrap();
+
+        let sampler = SamplerBehavior {
+            minify_filter: MinifySamplerFilter::Nearest,
+            magnify_filter: MagnifySamplerFilter::Nearest,
+            wrap_function: (
+                SamplerWrapFunction::BorderClamp,
+                SamplerWrapFunction::BorderClamp,
+                SamplerWrapFunction::BorderClamp,
+            ),
+            ..Default::default()
+        };
+
+        assert!(handle.id() <= self.fonts.len());
+        if handle.id() == self.fonts.len() {
+            self.fonts.push(GliumTexture { texture: font_tex, sampler });
+        } else {
+            self.fonts[handle.id()] = GliumTexture { texture: font_tex, sampler };
+        }
+    }
+
+    /// Draws the specified [`Frame`](struct.Frame.html) to the Glium Frame.
+    /// Widget [`draw callbacks`](struct.WidgetBuilder.html#method.draw_callback) are invoked in order,
+    /// after the UI below them has been drawn, and are passed `target` as
+    /// [`DrawTarget::Glium`](enum.DrawTarget.html#variant.Glium).
+    pub fn draw_frame(&mut self, target: &mut glium::Frame, frame: Frame) -> Result<(), GliumError> {
         let mouse_cursor = frame.mouse_cursor();
         let (context, widgets, render_groups) = frame.finish_frame();
         let context = context.internal().borrow();
@@ -118,26 +159,33 @@ impl GliumRenderer {
         let scale = context.scale_factor();
         self.matrix = view_matrix(display_pos, display_size);
 
+        // add any glyphs new to this frame to the font atlases before drawing
+        context.themes().prepare_fonts(&widgets, |handle, data, width, height| {
+            self.set_font_texture(handle, data, width, height);
+        });
+
         self.draw_list.clear();
         self.groups.clear();
+        self.callbacks.clear();
 
         for render_group in render_groups.into_iter().rev() {
             let mut draw_mode = None;
 
             // render backgrounds
             for widget in render_group.iter(&widgets) {
-                if !widget.visible() { continue; }
+                if !widget.visible() || widget.opacity() <= 0.0 { continue; }
+                self.draw_list.alpha = widget.opacity();
                 let image_handle = match widget.background() {
                     None => continue,
                     Some(handle) => handle,
                 };
-                let time_millis = time_millis - context.base_time_millis_for(widget.id());
+                let time_millis = time_millis.saturating_sub(context.base_time_millis_for(widget.id()));
                 let image = context.themes().image(image_handle);
     
                 self.write_group_if_changed(&mut draw_mode, DrawMode::Image(image.texture()));
                 
                 image.draw(
-                    &mut self.draw_list,
+                    &mut TintedDrawList::new(&mut self.draw_list, widget.tint()),
                     ImageDrawParams {
                         pos: widget.pos().into(),
                         size: widget.size().into(),
@@ -151,19 +199,52 @@ impl GliumRenderer {
 
             // render foregrounds & text
             for widget in render_group.iter(&widgets) {
-                if !widget.visible() { continue; }
+                if !widget.visible() || widget.opacity() <= 0.0 { continue; }
+                self.draw_list.alpha = widget.opacity();
 
                 let border = widget.border();
                 let fg_pos = widget.pos() + border.tl();
                 let fg_size = widget.inner_size();
     
-                if let Some(image_handle) = widget.foreground() {
-                    let time_millis = time_millis - context.base_time_millis_for(widget.id());
+                if !widget.canvas().is_empty() {
+                    let time_millis = time_millis.saturating_sub(context.base_time_millis_for(widget.id()));
+                    let clip = widget.clip().min(Rect::new(fg_pos, fg_size));
+                    let brush = widget.foreground().map(|handle| context.themes().image(handle));
+
+                    for command in widget.canvas() {
+                        match command {
+                            CanvasCommand::Solid { pos, size, color } => {
+                                let brush = match brush {
+                                    None => continue,
+                                    Some(brush) => brush,
+                                };
+                                self.write_group_if_changed(&mut draw_mode, DrawMode::Image(brush.texture()));
+                                brush.draw_solid(&mut TintedDrawList::new(&mut self.draw_list, widget.tint()), fg_pos + *pos, *size, *color, clip, scale);
+                            },
+                            CanvasCommand::Image { image, pos, size } => {
+                                let image = context.themes().image(*image);
+                                self.write_group_if_changed(&mut draw_mode, DrawMode::Image(image.texture()));
+                                image.draw(
+                                    &mut TintedDrawList::new(&mut self.draw_list, widget.tint()),
+                                    ImageDrawParams {
+                                        pos: (fg_pos + *pos).into(),
+                                        size: (*size).into(),
+                                        anim_state: widget.anim_state(),
+                                        clip,
+                                        time_millis,
+                                        scale,
+                                    }
+                                );
+                            }
+                        }
+                    }
+                } else if let Some(image_handle) = widget.foreground() {
+                    let time_millis = time_millis.saturating_sub(context.base_time_millis_for(widget.id()));
                     let image = context.themes().image(image_handle);
                     self.write_group_if_changed(&mut draw_mode, DrawMode::Image(image.texture()));
     
-                    image.draw(
-                        &mut self.draw_list,
+                    image.draw_region(
+                        &mut TintedDrawList::new(&mut self.draw_list, widget.tint()),
                         ImageDrawParams {
                             pos: fg_pos.into(),
                             size: fg_size.into(),
@@ -171,7 +252,9 @@ impl GliumRenderer {
                             clip: widget.clip(),
                             time_millis,
                             scale,
-                        }
+                        },
+                        widget.image_region(),
+                        widget.image_scaling(),
                     );
                 }
     
@@ -179,18 +262,37 @@ impl GliumRenderer {
                     if let Some(font_sum) = widget.font() {
                         self.write_group_if_changed(&mut draw_mode, DrawMode::Font(font_sum.handle));
                         let font = context.themes().font(font_sum.handle);
+                        let clip = match widget.overflow() {
+                            TextOverflow::Visible => widget.clip(),
+                            TextOverflow::Clip | TextOverflow::Ellipsis => widget.clip().min(Rect::new(fg_pos, fg_size)),
+                        };
     
                         font.draw(
-                            &mut self.draw_list,
+                            &mut TintedDrawList::new(&mut self.draw_list, widget.tint()),
                             fg_size * scale,
                             (fg_pos * scale).into(),
                             text,
                             widget.text_align(),
+                            widget.wrap(),
+                            font_sum.spacing(scale),
                             widget.text_color(),
-                            widget.clip() * scale,
+                            clip * scale,
                         )
                     }
                 }
+
+                // the callback is drawn on top of everything so far, so end the current group
+                if let Some(callback) = widget.draw_callback() {
+                    if let Some(mode) = draw_mode.take() {
+                        self.write_group(mode);
+                    }
+
+                    self.callbacks.push(PendingCallback {
+                        group: self.groups.len(),
+                        callback: Rc::clone(callback),
+                        params: DrawCallbackParams { rect: Rect::new(widget.pos(), widget.size()), clip: widget.clip(), scale },
+                    });
+                }
             }
 
             // render anything from the final draw calls
@@ -200,6 +302,7 @@ impl GliumRenderer {
         }
 
         if let Some((mouse_cursor, align, anim_state)) = mouse_cursor {
+            self.draw_list.alpha = 1.0;
             let image = context.themes().image(mouse_cursor);
             let mouse_pos = context.mouse_pos();
             let size = image.base_size();
@@ -224,7 +327,9 @@ impl GliumRenderer {
             &self.context, &self.draw_list.vertices
         )?;
         let indices = glium::index::NoIndices(PrimitiveType::Points);
-        for group in &self.groups {
+        for (index, group) in self.groups.iter().enumerate() {
+            run_callbacks(&self.callbacks, index, &mut DrawTarget::Glium(target));
+
             match group.mode {
                 DrawMode::Font(font_handle) => {
                     let font = self.font(font_handle);
@@ -255,6 +360,7 @@ impl GliumRenderer {
                 }
             };
         }
+        run_callbacks(&self.callbacks, self.groups.len(), &mut DrawTarget::Glium(target));
 
         Ok(())
     }
@@ -319,6 +425,25 @@ impl Renderer for GliumRenderer {
         Ok(TextureData::new(handle, dimensions.0, dimensions.1))
     }
 
+    fn update_texture(
+        &mut self,
+        handle: TextureHandle,
+        image_data: &[u8],
+        pos: (u32, u32),
+        dimensions: (u32, u32),
+    ) -> Result<(), crate::Error> {
+        let texture = match self.textures.get(handle.id()) {
+            None => return Err(crate::Error::Theme(format!("Invalid texture handle {}", handle.id()))),
+            Some(texture) => &texture.texture,
+        };
+
+        let image = RawImage2d::from_raw_rgba(image_data.to_vec(), dimensions);
+        let rect = glium::Rect { left: pos.0, bottom: pos.1, width: dimensions.0, height: dimensions.1 };
+        texture.write(rect, image);
+
+        Ok(())
+    }
+
     fn register_font(
         &mut self,
         handle: FontHandle,
@@ -326,44 +451,10 @@ impl Renderer for GliumRenderer {
         size: f32,
         scale: f32,
     ) -> Result<Font, crate::Error> {
-        let font = &source.font;
-
-        let writer = FontTextureWriter::new(font, size, scale);
-
-        let writer_out = writer.write(handle)?;
-
-        let font_tex = Texture2d::with_format(
-            &self.context,
-            RawImage2d {
-                data: Cow::Owned(writer_out.data),
-                width: writer_out.tex_width,
-                height: writer_out.tex_height,
-                format: glium::texture::ClientFormat::U8,
-            },
-            glium::texture::UncompressedFloatFormat::U8,
-            glium::texture::MipmapsOption::NoMipmap,
-        ).unwrap();
+        let font = Font::new(handle, source, size, scale);
+        font.upload_atlas(|data, width, height| self.set_font_texture(handle, data, width, height));
 
-        let sampler = SamplerBehavior {
-            minify_filter: MinifySamplerFilter::Nearest,
-            magnify_filter: MagnifySamplerFilter::Nearest,
-            wrap_function: (
-                SamplerWrapFunction::BorderClamp,
-                SamplerWrapFunction::BorderClamp,
-                SamplerWrapFunction::BorderClamp,
-            ),
-            ..Default::default()
-        };
-
-        assert!(handle.id() <= self.fonts.len());
-        if handle.id() == self.fonts.len() {
-            self.fonts.push(GliumTexture { texture: font_tex, sampler });
-        } else {
-            self.fonts[handle.id()] = GliumTexture { texture: font_tex, sampler };
-        }
-        
-
-        Ok(writer_out.font)
+        Ok(font)
     }
 }
 
@@ -452,14 +543,14 @@ const VERT_SHADER_SRC: &str = r#"
   in vec2 size;
   in vec2 tex0;
   in vec2 tex1;
-  in vec3 color;
+  in vec4 color;
   in vec2 clip_pos;
   in vec2 clip_size;
 
   out vec2 g_size;
   out vec2 g_tex0;
   out vec2 g_tex1;
-  out vec3 g_color;
+  out vec4 g_color;
   out vec2 g_clip_pos;
   out vec2 g_clip_size;
 
@@ -484,12 +575,12 @@ const GEOM_SHADER_SRC: &str = r#"
   in vec2 g_size[];
   in vec2 g_tex0[];
   in vec2 g_tex1[];
-  in vec3 g_color[];
+  in vec4 g_color[];
   in vec2 g_clip_pos[];
   in vec2 g_clip_size[];
 
   out vec2 v_tex_coords;
-  out vec3 v_color;
+  out vec4 v_color;
 
   uniform mat4 matrix;
 
@@ -553,14 +644,14 @@ const FRAGMENT_SHADER_SRC: &str = r#"
   #version 140
 
   in vec2 v_tex_coords;
-  in vec3 v_color;
+  in vec4 v_color;
 
   out vec4 color;
 
   uniform sampler2D tex;
 
   void main() {
-    color = vec4(v_color, 1.0) * texture(tex, v_tex_coords);
+    color = v_color * texture(tex, v_tex_coords);
   }
 "#;
 
@@ -568,25 +659,29 @@ const FONT_FRAGMENT_SHADER_SRC: &str = r#"
     #version 140
 
     in vec2 v_tex_coords;
-    in vec3 v_color;
+    in vec4 v_color;
 
     out vec4 color;
 
     uniform sampler2D tex;
     
     void main() {
-        color = vec4(v_color, texture(tex, v_tex_coords).r);
+        color = vec4(v_color.rgb, v_color.a * texture(tex, v_tex_coords).r);
     }
 "#;
 
 struct GliumDrawList {
     vertices: Vec<GliumVertex>,
+
+    // the opacity of the widget currently being drawn
+    alpha: f32,
 }
 
 impl GliumDrawList {
     fn new() -> Self {
         GliumDrawList {
             vertices: Vec::new(),
+            alpha: 1.0,
         }
     }
 
@@ -613,12 +708,13 @@ impl DrawList for GliumDrawList {
         color: Color,
         clip: Rect,
     ) {
+        let [r, g, b]: [f32; 3] = color.into();
         let vert = GliumVertex {
             position: pos,
             size,
             tex0: [tex[0].x(), tex[0].y()],
             tex1: [tex[1].x(), tex[1].y()],
-            color: color.into(),
+            color: [r, g, b, self.alpha],
             clip_pos: clip.pos.into(),
             clip_size: clip.size.into(),
         };
@@ -633,7 +729,7 @@ struct GliumVertex {
     pub size: [f32; 2],
     pub tex0: [f32; 2],
     pub tex1: [f32; 2],
-    pub color: [f32; 3],
+    pub color: [f32; 4],
     pub clip_pos: [f32; 2],
     pub clip_size: [f32; 2],
 }
@@ -0,0 +1,244 @@
+use crate::context::{Context, KeyCode, MouseButton};
+use crate::font::{Font, FontSource};
+use crate::render::{FontHandle, IO, Renderer, TextureData, TextureHandle};
+use crate::{Error, Frame, Point, Rect};
+
+/**
+A Thyme [`Renderer`](trait.Renderer.html) that draws nothing, for automated tests of your UI that run without a
+GPU or window.
+
+Textures are only recorded by their dimensions.  Instead of drawing, [`draw_frame`](#method.draw_frame) captures
+a [`HeadlessQuad`](struct.HeadlessQuad.html) for each visible widget, in the order it would have been drawn.  Use
+this with a [`HeadlessIo`](struct.HeadlessIo.html) to drive input.
+
+# Example
+```
+fn test_button(mut context: thyme::Context, mut renderer: thyme::HeadlessRenderer, mut io: thyme::HeadlessIo) {
+    let mut ui = context.create_frame();
+    ui.button("button", "Click Me");
+    renderer.draw_frame(ui);
+
+    let rect = renderer.quad("button").unwrap().rect;
+    io.click(&mut context, rect.pos + rect.size * 0.5);
+
+    let mut ui = context.create_frame();
+    assert!(ui.button("button", "Click Me").clicked);
+    renderer.draw_frame(ui);
+}
+```
+*/
+#[derive(Default)]
+pub struct HeadlessRenderer {
+    textures: Vec<(u32, u32)>,
+    quads: Vec<HeadlessQuad>,
+}
+
+/// A widget captured by a [`HeadlessRenderer`](struct.HeadlessRenderer.html).  All positions and sizes are
+/// in logical pixels.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HeadlessQuad {
+    /// The widget's unique ID
+    pub id: String,
+
+    /// The widget's final position and size
+    pub rect: Rect,
+
+    /// The area the widget is clipped to
+    pub clip: Rect,
+
+    /// The widget's text, if any
+    pub text: Option<String>,
+}
+
+impl HeadlessRenderer {
+    /// Creates a new headless renderer
+    pub fn new() -> HeadlessRenderer {
+        HeadlessRenderer::default()
+    }
+
+    /// "Draws" the specified [`Frame`](struct.Frame.html), replacing the previously captured quads with one for each
+    /// visible widget in the frame.  Widget [`draw callbacks`](struct.WidgetBuilder.html#method.draw_callback)
+    /// are invoked in order, just after their widget is captured, with [`DrawTarget::None`](enum.DrawTarget.html#variant.None).
+    pub fn draw_frame(&mut self, frame: Frame) {
+        let (context, widgets, render_groups) = frame.finish_frame();
+        let context = context.internal().borrow();
+        context.themes().prepare_fonts(&widgets, |_, _, _, _| ());
+
+        self.quads.clear();
+        for render_group in render_groups.into_iter().rev() {
+            for widget in render_group.iter(&widgets) {
+                if !widget.visible() || widget.opacity() <= 0.0 { continue; }
+
+                let quad = HeadlessQuad {
+                    id: widget.id().to_string(),
+                    rect: Rect::new(widget.pos(), widget.size()),
+                    clip: widget.clip(),
+                    text: widget.text().map(|text| text.to_string()),
+                };
+
+                if let Some(callback) = widget.draw_callback() {
+                    let scale = context.scale_factor();
+                    callback(&crate::DrawCallbackParams { rect: quad.rect, clip: quad.clip, scale }, &mut crate::DrawTarget::None);
+                }
+
+                self.quads.push(quad);
+            }
+        }
+    }
+
+    /// All quads captured in the last call to [`draw_frame`](#method.draw_frame), in draw order
+    pub fn quads(&self) -> &[HeadlessQuad] { &self.quads }
+
+    /// Returns the quad captured in the last call to [`draw_frame`](#method.draw_frame) for the widget
+    /// with the specified `id`, if it was drawn.
+    pub fn quad(&self, id: &str) -> Option<&HeadlessQuad> {
+        self.quads.iter().find(|quad| quad.id == id)
+    }
+
+    /// Returns the width and height of the texture registered with `handle`
+    pub fn texture_dimensions(&self, handle: TextureHandle) -> Option<(u32, u32)> {
+        self.textures.get(handle.id()).copied()
+    }
+}
+
+impl Renderer for HeadlessRenderer {
+    fn register_font(
+        &mut self,
+        handle: FontHandle,
+        source: &FontSource,
+        size: f32,
+        scale: f32,
+    ) -> Result<Font, Error> {
+        Ok(Font::new(handle, source, size, scale))
+    }
+
+    fn register_texture(
+        &mut self,
+        handle: TextureHandle,
+        _image_data: &[u8],
+        dimensions: (u32, u32),
+    ) -> Result<TextureData, Error> {
+        assert!(handle.id() <= self.textures.len());
+        if handle.id() == self.textures.len() {
+            self.textures.push(dimensions);
+        } else {
+            self.textures[handle.id()] = dimensions;
+        }
+
+        Ok(TextureData::new(handle, dimensions.0, dimensions.1))
+    }
+
+    fn update_texture(
+        &mut self,
+        handle: TextureHandle,
+        _image_data: &[u8],
+        _pos: (u32, u32),
+        _dimensions: (u32, u32),
+    ) -> Result<(), Error> {
+        match self.textures.get(handle.id()) {
+            None => Err(Error::Theme(format!("Invalid texture handle {}", handle.id()))),
+            Some(_) => Ok(()),
+        }
+    }
+}
+
+/**
+A Thyme Input/Output adapter for automated tests, with no window.  Input and time are injected directly
+into the [`Context`](struct.Context.html), so tests are fully deterministic.  Time starts at zero and only
+advances with [`advance_time`](#method.advance_time).  See [`HeadlessRenderer`](struct.HeadlessRenderer.html).
+
+Note that, as with real input, a mouse press is only taken by the UI if the mouse was over a widget in the
+previous frame, so move the mouse and build a frame before pressing.
+*/
+pub struct HeadlessIo {
+    scale_factor: f32,
+    display_size: Point,
+    clipboard: String,
+}
+
+impl IO for HeadlessIo {
+    fn scale_factor(&self) -> f32 { self.scale_factor }
+
+    fn display_size(&self) -> Point { self.display_size }
+
+    fn clipboard_text(&mut self) -> Option<String> { Some(self.clipboard.clone()) }
+
+    fn set_clipboard_text(&mut self, text: String) { self.clipboard = text; }
+
+    fn time_millis(&self) -> Option<u32> { Some(0) }
+}
+
+impl HeadlessIo {
+    /// Creates a new adapter with the specified display size, in logical pixels, and a scale factor of 1.
+    pub fn new(display_size: Point) -> HeadlessIo {
+        HeadlessIo {
+            scale_factor: 1.0,
+            display_size,
+            clipboard: String::new(),
+        }
+    }
+
+    /// Sets the time, in milliseconds since the context was built, for the following frames
+    pub fn set_time(&mut self, context: &mut Context, time_millis: u32) {
+        context.set_time_millis(time_millis);
+    }
+
+    /// Advances the time by `millis` for the following frames
+    pub fn advance_time(&mut self, context: &mut Context, millis: u32) {
+        let time = context.time_millis();
+        context.set_time_millis(time + millis);
+    }
+
+    /// Moves the mouse to `pos`, in logical pixels
+    pub fn move_mouse(&mut self, context: &mut Context, pos: Point) {
+        context.set_mouse_pos(pos);
+    }
+
+    /// Presses the mouse `button`
+    pub fn press_mouse(&mut self, context: &mut Context, button: MouseButton) {
+        context.set_mouse_pressed(true, button.index());
+    }
+
+    /// Releases the mouse `button`
+    pub fn release_mouse(&mut self, context: &mut Context, button: MouseButton) {
+        context.set_mouse_pressed(false, button.index());
+    }
+
+    /// Moves the mouse to `pos`, and then presses and releases the left mouse button.  The widget at
+    /// `pos` must have been under the mouse in the previous frame to take the click.
+    pub fn click(&mut self, context: &mut Context, pos: Point) {
+        self.move_mouse(context, pos);
+        self.press_mouse(context, MouseButton::Left);
+        self.release_mouse(context, MouseButton::Left);
+    }
+
+    /// Scrolls the mouse wheel by the specified number of `lines`
+    pub fn scroll(&mut self, context: &mut Context, lines: Point) {
+        context.add_mouse_wheel_lines(lines);
+    }
+
+    /// Types each character of `text` into the widget with keyboard focus
+    pub fn type_text(&mut self, context: &mut Context, text: &str) {
+        for c in text.chars() {
+            context.push_character(c);
+        }
+    }
+
+    /// Presses the `key`.  It is repeated, as the time advances, until released
+    pub fn press_key(&mut self, context: &mut Context, key: KeyCode) {
+        context.set_key_pressed(key, true);
+    }
+
+    /// Releases the `key`
+    pub fn release_key(&mut self, context: &mut Context, key: KeyCode) {
+        context.set_key_pressed(key, false);
+    }
+
+    /// Returns the text most recently copied to the clipboard by a widget, if any
+    pub fn clipboard(&mut self, context: &mut Context) -> &str {
+        if let Some(text) = context.take_clipboard_text() {
+            self.clipboard = text;
+        }
+        &self.clipboard
+    }
+}
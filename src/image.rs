@@ -2,8 +2,35 @@ use std::collections::HashMap;
 
 use crate::{Error};
 use crate::render::{TexCoord, DrawList, TextureHandle, TextureData};
-use crate::{Rect, Color, AnimState, Point};
+use crate::{Rect, Color, AnimState, Point, Border};
 use crate::theme_definition::{ImageFill, ImageDefinition, ImageDefinitionKind};
+use crate::theme::field_error;
+
+// the most strips a gradient is drawn with; smaller gradients use one strip per logical pixel
+const MAX_GRADIENT_STRIPS: f32 = 256.0;
+
+/// How an image is scaled to the inner area of its widget when drawn with an explicit
+/// region or scaling mode.  See [`WidgetBuilder::image_scaling`](struct.WidgetBuilder.html#method.image_scaling).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ImageScaling {
+    /// The image is stretched to exactly cover the widget, ignoring its aspect ratio.
+    Stretch,
+
+    /// The image is scaled as large as possible while fitting entirely within the widget,
+    /// preserving its aspect ratio.  The image is centered within the widget.
+    Fit,
+
+    /// The image is scaled to cover the entire widget, preserving its aspect ratio.  Parts of the
+    /// image falling outside the widget are cropped.  The image is centered within the widget.
+    Fill,
+
+    /// The image is repeated at its natural size to cover the widget.
+    Tile,
+}
+
+impl Default for ImageScaling {
+    fn default() -> Self { ImageScaling::Stretch }
+}
 
 #[derive(Copy, Clone)]
 pub struct ImageHandle {
@@ -38,6 +65,7 @@ enum ImageKind {
     Simple {
         tex_coords: [TexCoord; 2],
         base_size: [f32; 2],
+        texels: [f32; 2],
         fill: ImageFill,
     },
     Timed {
@@ -47,7 +75,22 @@ enum ImageKind {
     },
     Animated {
         states: Vec<(AnimState, Image)>
-    }
+    },
+    Solid {
+        tex_coords: [TexCoord; 2],
+        color: Color,
+    },
+    Gradient {
+        tex_coords: [TexCoord; 2],
+        colors: [Color; 2],
+        vertical: bool,
+    },
+    Outline {
+        tex_coords: [TexCoord; 2],
+        widths: Border,
+        colors: [Color; 4],
+        fill: Option<Color>,
+    },
 }
 
 pub(crate) struct ImageDrawParams {
@@ -159,7 +202,7 @@ impl Image {
                     params.clip * params.scale
                 )
             },
-            ImageKind::Simple { tex_coords, base_size, fill } => {
+            ImageKind::Simple { tex_coords, base_size, fill, .. } => {
                 let clip = params.clip * params.scale;
                 match fill {
                     ImageFill::None => {
@@ -212,7 +255,62 @@ impl Image {
             },
             ImageKind::Animated { states } => {
                 self.draw_animated(draw_list, states, params);
-            }
+            },
+            ImageKind::Solid { tex_coords, color } => {
+                let (pos, size, clip) = scaled_rect(&params);
+                draw_list.push_rect(pos.into(), size.into(), *tex_coords, *color * self.color, clip);
+            },
+            ImageKind::Gradient { tex_coords, colors, vertical } => {
+                let (pos, size, clip) = scaled_rect(&params);
+                let length = if *vertical { params.size[1] } else { params.size[0] };
+                let strips = length.ceil().clamp(1.0, MAX_GRADIENT_STRIPS);
+                let strip_size = if *vertical { size.y } else { size.x } / strips;
+
+                for i in 0..(strips as u32) {
+                    let color = mix(colors[0], colors[1], (i as f32 + 0.5) / strips) * self.color;
+                    let offset = i as f32 * strip_size;
+                    let (strip_pos, strip_size) = if *vertical {
+                        (Point::new(pos.x, pos.y + offset), Point::new(size.x, strip_size))
+                    } else {
+                        (Point::new(pos.x + offset, pos.y), Point::new(strip_size, size.y))
+                    };
+                    draw_list.push_rect(strip_pos.into(), strip_size.into(), *tex_coords, color, clip);
+                }
+            },
+            ImageKind::Outline { tex_coords, widths, colors, fill } => {
+                let (pos, size, clip) = scaled_rect(&params);
+                let widths = Border {
+                    top: widths.top * params.scale,
+                    bot: widths.bot * params.scale,
+                    left: widths.left * params.scale,
+                    right: widths.right * params.scale,
+                };
+                let inner_height = size.y - widths.vertical();
+
+                let rects = [
+                    (pos, Point::new(size.x, widths.top), colors[0]),
+                    (Point::new(pos.x, pos.y + size.y - widths.bot), Point::new(size.x, widths.bot), colors[1]),
+                    (Point::new(pos.x, pos.y + widths.top), Point::new(widths.left, inner_height), colors[2]),
+                    (
+                        Point::new(pos.x + size.x - widths.right, pos.y + widths.top),
+                        Point::new(widths.right, inner_height),
+                        colors[3]
+                    ),
+                ];
+
+                if let Some(fill) = fill {
+                    let fill_pos = pos + widths.tl();
+                    let fill_size = Point::new(size.x - widths.horizontal(), inner_height);
+                    if fill_size.x > 0.0 && fill_size.y > 0.0 {
+                        draw_list.push_rect(fill_pos.into(), fill_size.into(), *tex_coords, *fill * self.color, clip);
+                    }
+                }
+
+                for (pos, size, color) in rects.iter() {
+                    if size.x <= 0.0 || size.y <= 0.0 { continue; }
+                    draw_list.push_rect((*pos).into(), (*size).into(), *tex_coords, *color * self.color, clip);
+                }
+            },
         }
     }
 
@@ -275,13 +373,18 @@ impl Image {
                 let tex1 = texture.tex_coord(position[0], position[1]);
                 let tex2 = texture.tex_coord(position[0] + size[0], position[1] + size[1]);
                 base_size = Point::new(size[0] as f32 * scale, size[1] as f32 * scale);
-                ImageKind::Simple { tex_coords: [tex1, tex2], base_size: base_size.into(), fill: *fill }
+                ImageKind::Simple {
+                    tex_coords: [tex1, tex2],
+                    base_size: base_size.into(),
+                    texels: [size[0] as f32, size[1] as f32],
+                    fill: *fill,
+                }
             },
             ImageDefinitionKind::Collected { sub_images } => {
                 let mut size = Point::default();
                 let mut images_out = Vec::new();
                 for (id, sub_image_def) in sub_images {
-                    let image = find_image_in_set(image_id, others, &id)?;
+                    let image = find_image_in_set(image_id, "sub_images", others, &id)?;
                     size = size.max(image.base_size);
 
                     images_out.push(SubImage {
@@ -294,36 +397,79 @@ impl Image {
                 base_size = size;
                 ImageKind::Collected { sub_images: images_out }
             },
-            ImageDefinitionKind::Timed { frame_time_millis, frames, once } => {
+            ImageDefinitionKind::Timed { frame_time_millis, total_time_millis, frames, once } => {
                 let mut size = Point::default();
                 let mut frames_out = Vec::new();
                 for id in frames {
-                    let image = find_image_in_set(image_id, others, &id)?;
+                    let image = find_image_in_set(image_id, "frames", others, &id)?;
                     size = image.base_size;
                     frames_out.push(image);
                 }
                 
                 if frames_out.is_empty() {
                     return Err(
-                        Error::Theme(format!("No frames specified for image: {}", image_id))
+                        field_error("image", image_id, "frames", "No frames specified".to_string())
+                    );
+                }
+
+                let frame_time_millis = match (frame_time_millis, total_time_millis) {
+                    (Some(frame_time), None) => *frame_time,
+                    (None, Some(total_time)) => *total_time / frames_out.len() as u32,
+                    _ => return Err(
+                        Error::Theme(format!(
+                            "In image '{}': Exactly one of frame_time_millis or total_time_millis must be specified", image_id
+                        ))
+                    ),
+                };
+
+                if frame_time_millis == 0 {
+                    return Err(
+                        field_error("image", image_id, "frame_time_millis", "Frame time must be at least one millisecond".to_string())
                     );
                 }
 
                 base_size = size;
-                ImageKind::Timed { frame_time_millis: *frame_time_millis, frames: frames_out, once: *once }
+                ImageKind::Timed { frame_time_millis, frames: frames_out, once: *once }
             },
             ImageDefinitionKind::Animated { states } => {
                 let mut size = Point::default();
                 let mut states_out: Vec<(AnimState, Image)> = Vec::new();
                 for (state, id) in states {
-                    let image = find_image_in_set(image_id, others, &id)?;
+                    let image = find_image_in_set(image_id, "states", others, &id)?;
                     size = image.base_size;
                     states_out.push((*state, image));
                 }
 
                 base_size = size;
                 ImageKind::Animated { states: states_out }
-            }
+            },
+            ImageDefinitionKind::Solid { solid } => {
+                base_size = Point::default();
+                ImageKind::Solid { tex_coords: solid_tex_coords(texture), color: *solid }
+            },
+            ImageDefinitionKind::GradientVertical { gradient_vert } => {
+                base_size = Point::default();
+                ImageKind::Gradient { tex_coords: solid_tex_coords(texture), colors: *gradient_vert, vertical: true }
+            },
+            ImageDefinitionKind::GradientHorizontal { gradient_horiz } => {
+                base_size = Point::default();
+                ImageKind::Gradient { tex_coords: solid_tex_coords(texture), colors: *gradient_horiz, vertical: false }
+            },
+            ImageDefinitionKind::Outline { outline, outline_color, solid } => {
+                let widths = Border {
+                    top: outline.top * scale,
+                    bot: outline.bot * scale,
+                    left: outline.left * scale,
+                    right: outline.right * scale,
+                };
+                base_size = Point::new(widths.horizontal(), widths.vertical());
+                ImageKind::Outline {
+                    tex_coords: solid_tex_coords(texture),
+                    widths,
+                    colors: outline_color.sides(),
+                    fill: *solid,
+                }
+            },
         };
 
         Ok(Image {
@@ -334,17 +480,133 @@ impl Image {
         })
     }
 
+    /// Draws this image, showing only the specified `region` in texels relative to the image's
+    /// position in its texture, scaled to the draw area using `scaling`.  Only simple images support
+    /// regions and scaling; other images are drawn normally.
+    pub(crate) fn draw_region<D: DrawList>(
+        &self,
+        draw_list: &mut D,
+        params: ImageDrawParams,
+        region: Option<Rect>,
+        scaling: ImageScaling,
+    ) {
+        let (tex_coords, base_size, texels) = match &self.kind {
+            ImageKind::Simple { tex_coords, base_size, texels, .. } => (tex_coords, base_size, texels),
+            _ => {
+                self.draw(draw_list, params);
+                return;
+            }
+        };
+
+        if region.is_none() && scaling == ImageScaling::Stretch {
+            self.draw(draw_list, params);
+            return;
+        }
+
+        let region = region.unwrap_or(Rect::new(Point::default(), Point::new(texels[0], texels[1])));
+        if region.size.x <= 0.0 || region.size.y <= 0.0 || texels[0] <= 0.0 || texels[1] <= 0.0 { return; }
+
+        let tex_coord = |x: f32, y: f32| {
+            let (tl, br) = (tex_coords[0], tex_coords[1]);
+            TexCoord::new(
+                tl.x() + (br.x() - tl.x()) * x / texels[0],
+                tl.y() + (br.y() - tl.y()) * y / texels[1],
+            )
+        };
+        let tex = [
+            tex_coord(region.left(), region.top()),
+            tex_coord(region.right(), region.bot()),
+        ];
+
+        let pos = Point::from(params.pos);
+        let size = Point::from(params.size);
+        let clip = params.clip.min(Rect::new(pos, size)) * params.scale;
+
+        // the region's size at the theme's scale
+        let natural = Point::new(
+            region.size.x * base_size[0] / texels[0],
+            region.size.y * base_size[1] / texels[1],
+        );
+
+        match scaling {
+            ImageScaling::Stretch => {
+                self.draw_simple(draw_list, &tex, (pos * params.scale).into(), (size * params.scale).into(), clip);
+            },
+            ImageScaling::Fit | ImageScaling::Fill => {
+                let factor_x = size.x / natural.x;
+                let factor_y = size.y / natural.y;
+                let factor = if scaling == ImageScaling::Fit {
+                    factor_x.min(factor_y)
+                } else {
+                    factor_x.max(factor_y)
+                };
+
+                let draw_size = natural * factor;
+                let draw_pos = pos + (size - draw_size) / 2.0;
+                self.draw_simple(draw_list, &tex, (draw_pos * params.scale).into(), (draw_size * params.scale).into(), clip);
+            },
+            ImageScaling::Tile => {
+                if natural.x <= 0.0 || natural.y <= 0.0 { return; }
+
+                let mut y = pos.y;
+                while y < pos.y + size.y {
+                    let mut x = pos.x;
+                    while x < pos.x + size.x {
+                        self.draw_simple(
+                            draw_list,
+                            &tex,
+                            [x * params.scale, y * params.scale],
+                            (natural * params.scale).into(),
+                            clip,
+                        );
+                        x += natural.x;
+                    }
+                    y += natural.y;
+                }
+            }
+        }
+    }
+
+    /// Draws a rectangle at `pos` and `size` filled with `color`, using the center of this image
+    /// as a solid brush.  Only simple and solid images may be used as brushes; other images draw nothing.
+    pub(crate) fn draw_solid<D: DrawList>(
+        &self,
+        draw_list: &mut D,
+        pos: Point,
+        size: Point,
+        color: Color,
+        clip: Rect,
+        scale: f32,
+    ) {
+        let tex_coords = match &self.kind {
+            ImageKind::Simple { tex_coords, .. } => tex_coords,
+            ImageKind::Solid { tex_coords, color: solid } => {
+                draw_list.push_rect((pos * scale).into(), (size * scale).into(), *tex_coords, color * *solid, clip * scale);
+                return;
+            },
+            _ => return,
+        };
+
+        let center = TexCoord::new(
+            (tex_coords[0].x() + tex_coords[1].x()) / 2.0,
+            (tex_coords[0].y() + tex_coords[1].y()) / 2.0,
+        );
+
+        draw_list.push_rect((pos * scale).into(), (size * scale).into(), [center, center], color, clip * scale);
+    }
+
     fn draw_animated<D: DrawList>(
         &self,
         draw_list: &mut D,
         states: &[(AnimState, Image)],
         params: ImageDrawParams,
     ) {
-        for (state, image) in states {
-            if state == &params.anim_state {
-                image.draw(draw_list, params);
-                break;
-            }
+        // images without a focused, fading, or flash state are drawn as if without those keys
+        let image = params.anim_state.fallbacks()
+            .find_map(|anim_state| states.iter().find(|(state, _)| *state == anim_state));
+
+        if let Some((_, image)) = image {
+            image.draw(draw_list, params);
         }
     }
 
@@ -528,12 +790,29 @@ impl Image {
     }
 }
 
-fn find_image_in_set(parent_id: &str, set: &HashMap<String, Image>, id: &str) -> Result<Image, Error> {
+fn find_image_in_set(parent_id: &str, field: &str, set: &HashMap<String, Image>, id: &str) -> Result<Image, Error> {
     match set.get(id) {
         None => {
             Err(
-                Error::Theme(format!("Unable to find image '{}' referenced as sub image of '{}'", id, parent_id))
+                field_error("image", parent_id, field, format!("Unable to find image '{}' in the same image set", id))
             )
         }, Some(image) => Ok(image.clone())
     }
-}
\ No newline at end of file
+}
+// the texture coordinates covering the whole of the single white texel used for solid images
+fn solid_tex_coords(texture: &TextureData) -> [TexCoord; 2] {
+    [texture.tex_coord(0, 0), texture.tex_coord(1, 1)]
+}
+
+// the position, size, and clip of an image in physical pixels
+fn scaled_rect(params: &ImageDrawParams) -> (Point, Point, Rect) {
+    let pos = Point::from(params.pos) * params.scale;
+    let size = Point::from(params.size) * params.scale;
+    (pos, size, params.clip * params.scale)
+}
+
+// linearly interpolates from `a` to `b`
+fn mix(a: Color, b: Color, frac: f32) -> Color {
+    let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * frac).round() as u8;
+    Color { r: mix(a.r, b.r), g: mix(a.g, b.g), b: mix(a.b, b.b) }
+}
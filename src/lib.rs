@@ -7,7 +7,8 @@ however the advantage is full flexibility and control over the ultimate appearan
 
 To use Thyme, you need the core library, a renderer (there are currently two - one using [Glium](https://github.com/glium/glium) and
 the other using [wgpu](https://github.com/gfx-rs/wgpu-rs)), event handling support (one using
-[winit](https://github.com/rust-windowing/winit) is included), and a theme definition
+[winit](https://github.com/rust-windowing/winit) is included, and one using [SDL2](https://github.com/Rust-SDL2/rust-sdl2)
+is available with the `sdl2_backend` feature), and a theme definition
 with associated images and fonts.  Thyme logs errors using the [`log`](https://github.com/rust-lang/log) crate.  A very
 simple logger that sends messages to stdout is included to help you get started.
 
@@ -71,7 +72,24 @@ theme fairly small with just a base set of widgets, defining most things in code
 
 The theme can be defined from any [`serde`](https://serde.rs/)
 compatible source, with the examples in this project using [`YAML`](https://yaml.org/).
-The theme has several sections: `fonts`, `image_sets`, and `widgets`.
+The theme has several sections: `constants`, `fonts`, `image_sets`, and `widgets`.
+
+## Constants
+The optional `constants` section maps names to values which may be referenced anywhere in the other sections, by
+giving a string consisting of `$` followed by the constant name in place of the value.  Number constants may be used
+anywhere a number is accepted, such as sizes, borders, and spacing, while color constants may be used anywhere a color
+is accepted.  Constants are substituted when the theme is built, after all theme files have been merged, so a constant
+defined in one file may be used in another.  Referencing an undefined constant is an error naming the widget and field
+it was used in.  To specify a string value that begins with `$`, such as widget text, start it with `$$` instead.
+```yaml
+constants:
+  accent: "#FFAA00"
+  panel_width: 200
+widgets:
+  panel:
+    size: [$panel_width, 30]
+    text_color: $accent
+```
 
 ## Fonts
 Defining fonts is very simple.  The `fonts` section consists of a mapping, with `IDs` mapped
@@ -80,7 +98,9 @@ a [`font`](struct.WidgetBuilder.html#method.font).
 
 The data consists of a `source`, which is a string which must match one of the fonts registered
 with the [`ContextBuilder`](struct.ContextBuilder.html#method.register_font_source), and a `size`
-in logical pixels.
+in logical pixels.  Optionally, a `letter_spacing` in logical pixels may be added between each character,
+and the `line_height` may be overridden with a [`LineHeight`](enum.LineHeight.html).  Both may also be
+overridden by individual widget themes.
 ```yaml
 fonts:
   medium:
@@ -89,6 +109,12 @@ fonts:
   small:
     source: roboto
     size: 16
+  dialogue:
+    source: roboto
+    size: 16
+    letter_spacing: 1
+    line_height:
+      Multiplier: 1.4
 ```
 
 ## Image Sets
@@ -171,8 +197,13 @@ differentiate the different types.
 
 #### Timed Images
 Timed images display one out of several frames, on a timer.  Timed images can repeat continuously (the default), or only display once,
-based on the value of the optional `once` parameter.  `frame_time_millis` is how long each frame is shown for, in milliseconds.  Each
-`frame` is the `id` of an image within the current image set.  It can be any of the other types of images in the current set.
+based on the value of the optional `once` parameter.  `frame_time_millis` is how long each frame is shown for, in milliseconds.  Alternatively,
+`total_time_millis` specifies the length of one complete cycle, which is divided evenly between the frames.  Exactly one of these must be
+given.  Each `frame` is the `id` of an image within the current image set.  It can be any of the other types of images in the current set,
+except `Animated` images.
+
+The timer runs from the widget's base time, which is zero unless set with [`set_base_time_now`](struct.Frame.html#method.set_base_time_now)
+or [`set_base_time_millis`](struct.Frame.html#method.set_base_time_millis).  Setting the base time restarts the animation.
 
 In this example, each frame is displayed for 500 milliseconds in an endless cycle.
 ```yaml
@@ -184,9 +215,21 @@ In this example, each frame is displayed for 500 milliseconds in an endless cycl
       - button_bright
 ```
 
+In this example, a loading spinner cycles through four frames once per second.
+```yaml
+  spinner:
+    total_time_millis: 1000
+    frames:
+      - spinner_0
+      - spinner_1
+      - spinner_2
+      - spinner_3
+```
+
 #### Animated Images
 Animated images display one of several sub images based on the [`AnimState`](struct.AnimState.html). of the parent widget.
-The referenced images are specified by `id`, and can include Simple, Composed, or Collected images.
+The referenced images are specified by `id`, and can include Simple, Composed, Collected, or Timed images.  Referencing Timed
+images allows, for example, a different looping animation to be shown while a widget is hovered.
 ```yaml
   button:
     states:
@@ -198,8 +241,31 @@ The referenced images are specified by `id`, and can include Simple, Composed, o
       Active + Pressed: button_pressed_active
 ```
 
-Images which contain references to other images are parsed in a particular order - `Collected`, then `Animated`, then
-`Timed`.  This means an `Animated` image may reference a `Collected` image, but not the other way around.  All of these
+#### Solid, Gradient, and Outline Images
+These images are drawn using flat colors rather than a texture, which is useful for prototyping or a debug theme without any
+image assets.  They must be placed in an image set without a `source`, which may also contain Collected, Timed, and Animated
+images and aliases referencing them.  A `solid` image fills its area with a single color, while `gradient_vert` and `gradient_horiz`
+images blend between two colors from top to bottom or left to right, respectively.  An `outline` image draws a border with the
+specified width on each side, in the same format as a widget `border`, using either a single `outline_color` or one color per side.
+Its interior is filled with the optional `solid` color.
+
+```yaml
+image_sets:
+  flat:
+    scale: 1
+    images:
+      panel:
+        solid: "#333"
+      title:
+        gradient_vert: ["#555", "#222"]
+      frame:
+        outline: { all: 1 }
+        outline_color: { top: "#AAA", left: "#AAA", bot: "#111", right: "#111" }
+        solid: "#333"
+```
+
+Images which contain references to other images are parsed in a particular order - `Collected`, then `Timed`, then
+`Animated`.  This means an `Animated` image may reference a `Timed` image, but not the other way around.  All of these
 image types may contain references to the basic image types - `Simple`, `Composed`, `ComposedHorizontal`, and
 `ComposedVertical`.
 
@@ -230,7 +296,9 @@ Thyme will first look for a theme at the full ID, i.e.
         children:
           button
 ```
-If that is not found, it will look for `button` at the top level.
+If that is not found, it will look for `button` at the top level.  If neither theme exists, a warning is logged and the widget
+is created using a minimal fallback theme, with no images and a fixed size.  In debug builds, these widgets show the
+missing theme ID in magenta text.
 
 ### Widget `from` attribute
 Each widget entry in the `widgets` section may optionally have a `from` attribute, which instructs Thyme to copy the specified widget theme into this theme.
@@ -289,7 +357,13 @@ the widget will layout its children.
    complicated_button:
      text: Hello
      text_color: "#FFAA00"
+     tint: "#FF8080"
      text_align: Center
+     wrap: true
+     overflow: Clip
+     letter_spacing: 0.5
+     line_height:
+       Absolute: 24
      font: medium
      background: gui/button
      foreground: gui/button_icon
@@ -307,6 +381,42 @@ the widget will layout its children.
      layout_spacing: 5
 ```
 
+### Per state text color and font
+The `text_color` and `font` attributes may be given either as a single value, or as a mapping from
+[`AnimState`](struct.AnimState.html) to value, in the same way as animated images.  The value matching the widget's
+final state is used when drawing, with the `Normal` value used for states that don't match.  Note that the size of a widget
+sized from its font is computed using the `Normal` font.  Calling [`text_color`](struct.WidgetBuilder.html#method.text_color)
+or [`font`](struct.WidgetBuilder.html#method.font) on the widget builder overrides the values for all states.
+The `placeholder_color` attribute is used by widgets that show hint text, such as the placeholder of an
+[`input field`](struct.Frame.html#method.input_field_with), in place of their normal text color.
+
+```yaml
+  bright_button:
+    from: button
+    text_color:
+      Normal: "#CCC"
+      Hover: "#FFF"
+      Pressed: "#FF0"
+```
+
+### State transitions
+A widget theme may specify a `state_tints` and `state_offsets` mapping, keyed by [`AnimState`](struct.AnimState.html) in the same way
+as animated images.  The widget's tint is multiplied by the color for its current state, and the widget and its children are moved
+by the offset for its current state.  When the state changes, these values are interpolated over `transition_ms` milliseconds, following
+the [`easing`](enum.Easing.html) curve.  Widgets that appear for the first time start at the values for their state, without animating.
+
+```yaml
+  transition_button:
+    from: button
+    transition_ms: 150
+    easing: EaseOut
+    state_tints:
+      Normal: "#CCC"
+      Hover: "#FFF"
+    state_offsets:
+      Pressed: [0, 1]
+```
+
 ### Custom fields
 You may optionally specify custom values in the `custom_floats` mapping of the theme.  This allows more specialized widgets to
 obtain neccessary parameters from the theme itself, rather than relying on another external source.
@@ -324,18 +434,26 @@ obtain neccessary parameters from the theme itself, rather than relying on anoth
 pub mod bench;
 pub mod log;
 
+mod canvas;
 mod context;
 mod context_builder;
+mod draw_data;
 mod font;
 mod frame;
+mod headless;
 mod image;
 mod theme;
 mod recipes;
+mod recording;
 mod render;
 mod resource;
+mod rich_text;
 mod theme_definition;
+mod theme_value;
 mod point;
 mod scrollpane;
+mod text_edit;
+mod transition;
 mod widget;
 mod window;
 mod winit_io;
@@ -352,17 +470,32 @@ mod wgpu_backend;
 #[cfg(feature = "wgpu_backend")]
 pub use wgpu_backend::WgpuRenderer;
 
-pub use frame::Frame;
+#[cfg(feature = "sdl2_backend")]
+mod sdl2_io;
+
+#[cfg(feature = "sdl2_backend")]
+pub use sdl2_io::Sdl2Io;
+
+pub use canvas::Canvas;
+pub use draw_data::{DrawCallbackCommand, DrawCommand, DrawData, DrawGroup, DrawVertex, FontAtlasData};
+pub use frame::{CursorIcon, Frame};
+pub use headless::{HeadlessIo, HeadlessQuad, HeadlessRenderer};
+pub use image::ImageScaling;
+pub use font::{Font, FontAtlasStats, FontSource};
 pub use point::{Rect, Point, Border};
+pub use recording::{replay, InputEvent, InputLog, Recorder};
 pub use widget::{WidgetBuilder, WidgetState};
 pub use context_builder::{BuildOptions, ContextBuilder};
-pub use context::{Context, PersistentState, InputModifiers};
+pub use context::{Context, PersistentState, SavedState, UserState, InputModifiers, KeyCode, KeyEvent, MouseButton};
 pub use scrollpane::{ScrollpaneBuilder, ShowElement};
-pub use theme_definition::{AnimStateKey, AnimState, Align, Color, Layout, WidthRelative, HeightRelative};
+pub use theme_definition::{AnimStateKey, AnimState, Align, Color, Easing, Layout, LineHeight, TextOverflow, WidthRelative, HeightRelative};
 pub use window::WindowBuilder;
 pub use winit_io::WinitIo;
 
-pub use render::{IO, Renderer};
+pub use recipes::{
+    CheckState, Column, FillDirection, InputFieldOptions, InputFieldResult, LabeledSliderOptions, ProgressBarOptions, SliderResult,
+};
+pub use render::{DrawCallbackParams, DrawMode, DrawTarget, FontHandle, IO, Renderer, TextureData, TextureHandle};
 
 /// A generic error that can come from a variety of internal sources.
 #[derive(Debug)]
@@ -376,6 +509,10 @@ pub enum Error {
     /// An error originating from an invalid font source
     FontSource(String),
 
+    /// Several errors found while building or validating a theme, all reported at once.  Each is
+    /// a `Serde` or `Theme` error naming the theme entry and field it occurred in.
+    Multiple(Vec<Error>),
+
     /// An error that occurred attempting to use the filesystem
     IO(std::io::Error),
 
@@ -391,6 +528,13 @@ impl std::fmt::Display for Error {
           Serde(e) => write!(f, "Error deserializing theme: {}", e),
             Theme(msg) => write!(f, "Error creating theme from theme definition: {}", msg),
             FontSource(msg) => write!(f, "Error reading font source: {}", msg),
+            Multiple(errors) => {
+                write!(f, "{} errors in theme:", errors.len())?;
+                for error in errors {
+                    write!(f, "\n  {}", error)?;
+                }
+                Ok(())
+            },
             IO(error) => write!(f, "IO Error: {}", error),
 
             #[cfg(feature="image")]
@@ -399,6 +543,17 @@ impl std::fmt::Display for Error {
     }
 }
 
+impl Error {
+    // combines the errors found while building a theme into a single result
+    pub(crate) fn check_all(mut errors: Vec<Error>) -> Result<(), Error> {
+        match errors.len() {
+            0 => Ok(()),
+            1 => Err(errors.remove(0)),
+            _ => Err(Error::Multiple(errors)),
+        }
+    }
+}
+
 impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         use self::Error::*;
@@ -406,6 +561,7 @@ impl std::error::Error for Error {
             Serde(..) => None,
             Theme(..) => None,
             FontSource(..) => None,
+            Multiple(..) => None,
             IO(error) => Some(error),
 
             #[cfg(feature="image")]
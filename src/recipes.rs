@@ -1,6 +1,12 @@
 use std::fmt::Display;
+use std::str::FromStr;
 
-use crate::{Align, Frame, Point, Rect, WidgetState};
+use crate::{Align, Canvas, Color, Frame, HeightRelative, Layout, WidthRelative, KeyCode, KeyEvent, MouseButton, Point, Rect, ShowElement, WidgetState};
+use crate::text_edit;
+use crate::rich_text::{self, Item, Run};
+
+// the time, in milliseconds, after which text typed into a combo box popup is forgotten
+const TYPE_AHEAD_TIMEOUT: u32 = 1000;
 
 // Specific widget builders and convenience methods
 impl Frame {
@@ -37,6 +43,373 @@ impl Frame {
         self.start(theme).text(text).finish();
     }
 
+    /**
+    A label displaying `text` containing inline markup, allowing a single label to mix colors, fonts, and icons.
+    The supported tags are `<c=COLOR>...</c>` to draw text in a color, specified by name or hex code as in the
+    theme, `<f=FONT_ID>...</f>` to draw text in one of the theme's fonts, and `<i=IMAGE_ID>` to draw an inline
+    icon using an image from the theme.  Spans may be nested.  Tags that are unknown, refer to a font or image that does not exist, or are not
+    balanced are shown as literal text, and a warning is logged.
+
+    Each word is drawn as a `text` child and each icon as an `icon` child, wrapping onto new rows as needed in the
+    parent's `HorizontalWrap` layout.  Icons are sized to the line height of the font of the surrounding text, keeping
+    the image's aspect ratio, so they fill the same line as the text beside them.  Icons are drawn like any other
+    foreground image, so they are batched with the rest of the theme's images.  A word made up of more than one span is placed in a `word` child so it is
+    never broken across rows.  Newlines in `text` start a new row.  Plain [`label`](#method.label) does not parse
+    any markup.
+
+    An example theme definition:
+    ```yaml
+    rich_label:
+      size_from: [Parent, Children]
+      layout: HorizontalWrap
+      children:
+        text:
+          font: small
+          height_from: FontLine
+        icon: {}
+        word:
+          size_from: [Children, Children]
+          layout: Horizontal
+          children:
+            text:
+              from: rich_label/text
+            icon:
+              from: rich_label/icon
+    ```
+
+    # Example
+    ```
+    fn describe_attack(ui: &mut Frame, damage: u32) {
+        ui.rich_label("rich_label", &format!("Deal <c=red>{}</c> <f=medium>fire</f> damage <i=gui/fire>", damage));
+    }
+    ```
+    **/
+    pub fn rich_label(&mut self, theme: &str, text: &str) {
+        let items = {
+            let context = self.context_internal().borrow();
+            let themes = context.themes();
+            rich_text::parse(
+                text,
+                |id| themes.find_font(Some(id)).is_some(),
+                |id| themes.find_image(Some(id)).is_some(),
+            )
+        };
+
+        self.start(theme).children(|ui| {
+            let mut row_start = true;
+            for item in &items {
+                match item {
+                    Item::LineBreak => {
+                        // fill the rest of the row so the next word wraps onto a new row.  An
+                        // empty row takes the height of a line of text
+                        let height_from = if row_start { HeightRelative::FontLine } else { HeightRelative::Normal };
+                        ui.start("text").size(0.0, 0.0).size_from(WidthRelative::Parent, height_from).finish();
+                        row_start = true;
+                    },
+                    Item::Word(runs) => {
+                        row_start = false;
+                        if let [run] = runs.as_slice() {
+                            rich_run(ui, run);
+                        } else {
+                            ui.start("word").children(|ui| {
+                                for run in runs {
+                                    rich_run(ui, run);
+                                }
+                            });
+                        }
+                    },
+                }
+            }
+        });
+    }
+
+    /**
+    Advances the parent's layout cursor by `size` logical pixels along its layout direction, leaving empty space
+    before the next widget.  Nothing is drawn.  This is equivalent to [`gap`](struct.Frame.html#method.gap).
+
+    # Example
+    ```
+    fn toolbar(ui: &mut Frame) {
+        ui.button("button", "New");
+        ui.button("button", "Open");
+        ui.spacer(20.0);
+        ui.button("button", "Quit");
+    }
+    ```
+    **/
+    pub fn spacer(&mut self, size: f32) {
+        self.gap(size);
+    }
+
+    /**
+    A thin line across the parent, separating the widgets before and after it.  The line runs across the parent's
+    inner width in a `Vertical` layout, and across its inner height in a `Horizontal` layout, taking its place in the
+    layout like any other widget.  See [`separator_with`](#method.separator_with) to choose the orientation explicitly.
+    The `thickness` custom float sets the line's thickness, defaulting to 1.
+
+    An example theme definition:
+    ```yaml
+    separator:
+      background: gui/brush
+      custom_floats:
+        thickness: 1
+    ```
+    **/
+    pub fn separator(&mut self, theme: &str) {
+        let vertical = match self.widget(self.parent_index()).layout() {
+            Layout::Horizontal | Layout::HorizontalWrap => true,
+            Layout::Vertical | Layout::Grid { .. } | Layout::Free => false,
+        };
+
+        self.separator_with(theme, vertical);
+    }
+
+    /**
+    A separator, as in [`separator`](#method.separator), with its orientation specified rather than taken from the
+    parent's layout.  If `vertical` is true, the line runs across the parent's inner height, otherwise across its
+    inner width.
+    **/
+    pub fn separator_with(&mut self, theme: &str, vertical: bool) {
+        let thickness = self.custom_float(theme, "thickness", 1.0);
+
+        let builder = self.start(theme);
+        if vertical {
+            builder.size(thickness, 0.0).height_from(HeightRelative::Parent).finish();
+        } else {
+            builder.size(0.0, thickness).width_from(WidthRelative::Parent).finish();
+        }
+    }
+
+    /**
+    A widget displaying the image `image_id` as its foreground, with no user interactivity.  The ID
+    consists of "{image_set_id}/{image_id}".  If the image is not found, an error is logged and nothing
+    is drawn.
+
+    To show only part of an image, such as a region of a texture atlas computed at runtime, or to
+    control how the image is scaled, use [`WidgetBuilder::image_region`](struct.WidgetBuilder.html#method.image_region)
+    and [`WidgetBuilder::image_scaling`](struct.WidgetBuilder.html#method.image_scaling) directly.
+
+    An example theme definition:
+    ```yaml
+    image:
+      size: [64, 64]
+    ```
+
+    # Example
+    ```
+    fn portraits(ui: &mut Frame, portrait_index: u32) {
+        ui.image("image", "gui/logo");
+
+        // show one 32x32 portrait from an atlas, preserving its aspect ratio
+        let x = (portrait_index % 8) as f32 * 32.0;
+        let y = (portrait_index / 8) as f32 * 32.0;
+        ui.start("image")
+        .foreground("gui/portraits")
+        .image_region(Rect::new(Point::new(x, y), Point::new(32.0, 32.0)))
+        .image_scaling(ImageScaling::Fit)
+        .finish();
+    }
+    ```
+    **/
+    pub fn image(&mut self, theme: &str, image_id: &str) -> WidgetState {
+        self.start(theme).foreground(image_id).finish()
+    }
+
+    /**
+    A widget with custom graphics drawn by the `draw` closure, using the provided [`Canvas`](struct.Canvas.html).
+    Coordinates are relative to the widget's inner area, and everything drawn is clipped to it.  The canvas is part
+    of the current render group, so it is layered and clipped along with the rest of the UI.  The `id` must be unique.
+
+    The widget's foreground image is used as the brush for lines, rectangles, and circles, and should be a small
+    solid white image.
+
+    An example theme definition:
+    ```yaml
+    canvas:
+      foreground: gui/brush
+      background: gui/frame
+      border: { all: 5 }
+      size: [200, 200]
+    ```
+
+    # Example
+    ```
+    fn minimap(ui: &mut Frame, player: Point, enemies: &[Point]) {
+        ui.canvas("canvas", "minimap", |canvas| {
+            let center = canvas.size() / 2.0;
+            canvas.filled_rect(Rect::new(Point::default(), canvas.size()), Color::black());
+            for enemy in enemies {
+                canvas.line(center, center + (*enemy - player), 1.0, Color::red());
+            }
+            canvas.circle(center, 4.0, Color::green());
+        });
+    }
+    ```
+    **/
+    pub fn canvas<F: FnOnce(&mut Canvas)>(&mut self, theme: &str, id: &str, draw: F) -> WidgetState {
+        self.start(theme).id(id).canvas(draw).finish()
+    }
+
+    /**
+    A graph of `values` drawn as a line, scaled so that `min` is at the bottom of the widget's inner area and `max`
+    is at the top, with the values spread evenly across its width.  When there are more values than pixels across the
+    widget, each pixel column shows the lowest and highest values falling in it, so spikes are never lost.  The `id`
+    must be unique.
+
+    The plot is drawn on a [`canvas`](#method.canvas), using the widget's `text_color` for the line.  The `line_width`
+    custom float sets the line thickness, defaulting to 1.  If the `fill_shade` custom float is greater than zero,
+    the area under the line is filled with the line color darkened by that factor.  The `grid_lines` custom float
+    sets the number of evenly spaced `grid` children drawn across the plot, behind the line.  While the mouse is over
+    the plot, the `crosshair` child is shown at the nearest value, and a tooltip shows that value.
+
+    An example theme definition:
+    ```yaml
+    plot:
+      foreground: gui/brush
+      background: gui/frame
+      text_color: "#6F6"
+      wants_mouse: true
+      border: { all: 5 }
+      size: [200, 100]
+      custom_floats:
+        grid_lines: 3
+        fill_shade: 0.3
+      children:
+        grid:
+          background: gui/brush
+          align: TopLeft
+          width_from: Parent
+          size: [0, 1]
+        crosshair:
+          foreground: gui/brush
+          align: TopLeft
+          height_from: Parent
+          size: [1, 0]
+    ```
+
+    # Example
+    ```
+    fn frame_times(ui: &mut Frame, times: &[f32]) {
+        ui.plot_line("plot", "frame_times", times, 0.0, 33.0);
+    }
+    ```
+    **/
+    pub fn plot_line(&mut self, theme: &str, id: &str, values: &[f32], min: f32, max: f32) -> WidgetState {
+        self.plot(theme, id, values, min, max, false)
+    }
+
+    /**
+    A graph of `values` drawn as vertical bars, as for a histogram.  The scaling, theme, crosshair, and tooltip are
+    the same as for [`plot_line`](#method.plot_line), so the two may be overlaid.  Bars rise from zero, or from
+    whichever of `min` and `max` is nearest to zero.  The `bar_gap` custom float sets the space between bars, defaulting
+    to 1.  When there are more values than pixels across the widget, each pixel column shows the highest value falling
+    in it.
+
+    # Example
+    ```
+    fn damage_histogram(ui: &mut Frame, counts: &[f32]) {
+        ui.plot_bars("plot", "damage", counts, 0.0, 50.0);
+    }
+    ```
+    **/
+    pub fn plot_bars(&mut self, theme: &str, id: &str, values: &[f32], min: f32, max: f32) -> WidgetState {
+        self.plot(theme, id, values, min, max, true)
+    }
+
+    fn plot(&mut self, theme: &str, id: &str, values: &[f32], min: f32, max: f32, bars: bool) -> WidgetState {
+        let line_width = self.custom_float(theme, "line_width", 1.0);
+        let fill_shade = self.custom_float(theme, "fill_shade", 0.0);
+        let bar_gap = self.custom_float(theme, "bar_gap", 1.0);
+        let grid_lines = self.custom_float(theme, "grid_lines", 0.0).max(0.0) as u32;
+
+        let mouse_pos = self.context_internal().borrow().mouse_pos();
+
+        let mut inner = Rect::default();
+        let builder = self.start(theme).id(id).trigger_layout_inner(&mut inner);
+
+        let hovered = if inner.is_inside(mouse_pos) {
+            plot_index(values.len(), inner.size.x, mouse_pos.x - inner.pos.x, bars)
+        } else {
+            None
+        };
+
+        let mut builder = builder.canvas(|canvas| {
+            let size = canvas.size();
+            let color = canvas.color();
+            if values.is_empty() || max <= min || size.x <= 0.0 { return; }
+
+            let to_y = |value: f32| size.y - ((value - min) / (max - min)).clamp(0.0, 1.0) * size.y;
+
+            if bars {
+                let columns = plot_columns(values, size.x, true);
+                let width = size.x / columns.len() as f32;
+                let base = to_y(0.0f32.clamp(min, max));
+                for (i, (_, high)) in columns.into_iter().enumerate() {
+                    let top = to_y(high);
+                    canvas.filled_rect(Rect::new(
+                        Point::new(i as f32 * width, top.min(base)),
+                        Point::new((width - bar_gap).max(1.0), (top - base).abs())
+                    ), color);
+                }
+                return;
+            }
+
+            // each column contributes its low and high values, in the order they occur
+            let points: Vec<Point> = if values.len() == 1 {
+                vec![Point::new(0.0, to_y(values[0])), Point::new(size.x, to_y(values[0]))]
+            } else if values.len() as f32 <= size.x {
+                let step = size.x / (values.len() - 1) as f32;
+                values.iter().enumerate().map(|(i, value)| Point::new(i as f32 * step, to_y(*value))).collect()
+            } else {
+                plot_columns(values, size.x, false).into_iter().enumerate().flat_map(|(i, (first, second))| {
+                    let x = i as f32 + 0.5;
+                    vec![Point::new(x, to_y(first)), Point::new(x, to_y(second))]
+                }).collect()
+            };
+
+            if fill_shade > 0.0 {
+                let shade = |c: u8| (c as f32 * fill_shade.min(1.0)) as u8;
+                let fill = Color { r: shade(color.r), g: shade(color.g), b: shade(color.b) };
+                for pair in points.windows(2) {
+                    let (start, end) = (pair[0], pair[1]);
+                    let mut x = start.x;
+                    while x < end.x {
+                        let next = (x + 1.0).min(end.x);
+                        let y = start.y + (end.y - start.y) * ((x + next) / 2.0 - start.x) / (end.x - start.x);
+                        canvas.filled_rect(Rect::new(Point::new(x, y), Point::new(next - x, size.y - y)), fill);
+                        x = next;
+                    }
+                }
+            }
+
+            for pair in points.windows(2) {
+                canvas.line(pair[0], pair[1], line_width, color);
+            }
+        });
+
+        if let Some(index) = hovered {
+            builder = builder.tooltip(format!("{:.2}", values[index]));
+        }
+
+        builder.children(|ui| {
+            for i in 1..=grid_lines {
+                let y = inner.size.y * i as f32 / (grid_lines + 1) as f32;
+                ui.start("grid").pos(0.0, y.round()).finish();
+            }
+
+            if let Some(index) = hovered {
+                let x = if bars {
+                    (index as f32 + 0.5) * inner.size.x / values.len() as f32
+                } else if values.len() > 1 {
+                    index as f32 * inner.size.x / (values.len() - 1) as f32
+                } else {
+                    0.0
+                };
+                ui.start("crosshair").pos(x.round(), 0.0).finish();
+            }
+        })
+    }
+
     /**
     A simple button with a text `label`.
 
@@ -65,10 +438,12 @@ impl Frame {
     }
 
     /**
-    A simple vertical slider.  The slider button can be dragged by the user.  The position
-    of the button is based on the relative distance of `value` from `min` and `max`.
-    Returns the new value if the user moved the slider on this frame, None, otherwise.  Will
-    always return a value within [`min`, `max`] inclusive.  `max` must be greater than `min`.
+    A simple vertical slider.  The slider button can be dragged by the user, and pressing elsewhere on the
+    slider moves the button to the mouse.  The position of the button is based on the relative distance of
+    `value` from `min` and `max`.  Returns the new value if the user moved the slider on this frame, None,
+    otherwise.  Will always return a value within [`min`, `max`] inclusive.  `max` must be greater than `min`.
+    See [`vertical_slider_with`](#method.vertical_slider_with) to snap the value to a step or to find out
+    when the user lets go of the slider.
 
     An example theme definition:
     ```yaml
@@ -88,41 +463,24 @@ impl Frame {
     ```
     */
     pub fn vertical_slider(&mut self, theme: &str, min: f32, max: f32, value: f32) -> Option<f32> {
-        let mut inner = Rect::default();
-        let mut new_value = None;
-
-        self.start(theme)
-        .wants_mouse(true)
-        .trigger_layout_inner(&mut inner)
-        .children(|ui| {
-            ui.child("slider_bar");
-
-            let mut button_rect = Rect::default();
-            let builder = ui.start("slider_button").wants_mouse(true).align(Align::Left).trigger_layout(&mut button_rect);
-
-            let total_height = inner.size.y - button_rect.size.y;
-            let pos = total_height * (value - min) / (max - min);
-
-            let result = builder.pos(0.0, pos).finish();
-
-            if result.moved.y != 0.0 {
-                let delta_y = result.moved.y;
-
-                let next_pos = pos + delta_y;
-                let new_val = (max - min) * next_pos / total_height + min;
-
-                new_value = Some(new_val.min(max).max(min));
-            }
-        });
+        self.slider(theme, min, max, value, None, true, None).value
+    }
 
-        new_value
+    /// A vertical slider, as in [`vertical_slider`](#method.vertical_slider).  If `step` is specified, the
+    /// returned value is snapped to a multiple of the step, and the button follows the mouse position while
+    /// dragged.  The returned [`SliderResult`](struct.SliderResult.html) also reports when the user lets go of
+    /// the slider.
+    pub fn vertical_slider_with(&mut self, theme: &str, min: f32, max: f32, value: f32, step: Option<f32>) -> SliderResult {
+        self.slider(theme, min, max, value, step, true, None)
     }
 
     /**
-    A simple horizontal slider.  The slider button can be dragged by the user.  The position
-    of the button is based on the relative distance of `value` from `min` and `max`.
-    Returns the new value if the user moved the slider on this frame, None, otherwise.  Will
-    always return a value within [`min`, `max`] inclusive.  `max` must be greater than `min`.
+    A simple horizontal slider.  The slider button can be dragged by the user, and pressing elsewhere on the
+    slider moves the button to the mouse.  The position of the button is based on the relative distance of
+    `value` from `min` and `max`.  Returns the new value if the user moved the slider on this frame, None,
+    otherwise.  Will always return a value within [`min`, `max`] inclusive.  `max` must be greater than `min`.
+    See [`horizontal_slider_with`](#method.horizontal_slider_with) to snap the value to a step or to find out
+    when the user lets go of the slider.
 
     An example theme definition:
     ```yaml
@@ -151,34 +509,205 @@ impl Frame {
     ```
     */
     pub fn horizontal_slider(&mut self, theme: &str, min: f32, max: f32, value: f32) -> Option<f32> {
+        self.slider(theme, min, max, value, None, false, None).value
+    }
+
+    /**
+    A horizontal slider, as in [`horizontal_slider`](#method.horizontal_slider).  If `step` is specified, the
+    returned value is snapped to a multiple of the step, and the button follows the mouse position while
+    dragged.  The returned [`SliderResult`](struct.SliderResult.html) also reports when the user lets go of
+    the slider.
+
+    # Example
+    ```
+    fn volume_slider(ui: &mut Frame, volume: &mut f32, settings_volume: &mut f32) {
+        let result = ui.horizontal_slider_with("slider", 0.0, 1.0, *volume, Some(0.05));
+        if let Some(new_volume) = result.value {
+            *volume = new_volume;
+        }
+
+        if result.released {
+            *settings_volume = *volume;
+        }
+    }
+    ```
+    */
+    pub fn horizontal_slider_with(&mut self, theme: &str, min: f32, max: f32, value: f32, step: Option<f32>) -> SliderResult {
+        self.slider(theme, min, max, value, step, false, None)
+    }
+
+    /**
+    A horizontal slider, as in [`horizontal_slider_with`](#method.horizontal_slider_with), along with its current
+    value as text.  The text is created by calling `format` on the value.  By default, the text is shown in the
+    `value` label to the right of the `slider`, but it may be shown on the slider button instead; see
+    [`LabeledSliderOptions`](struct.LabeledSliderOptions.html).  The text is kept at least as wide as the text
+    for `max`, so the layout does not shift as the value changes.  The `min_label` and `max_label` captions, if
+    enabled, show the text for `min` and `max` at either end of the slider.
+
+    An example theme definition:
+    ```yaml
+    labeled_slider:
+      size: [0, 20]
+      width_from: Parent
+      layout: Horizontal
+      layout_spacing: [5, 0]
+      child_align: Left
+      children:
+        slider:
+          from: horizontal_slider
+          width_from: Normal
+          size: [0, 15]
+          expand: true
+        min_label:
+          from: label
+          width_from: Normal
+          size: [30, 0]
+        max_label:
+          from: label
+          width_from: Normal
+          size: [30, 0]
+        value:
+          from: label
+          width_from: Normal
+          size: [0, 0]
+    ```
+
+    # Example
+    ```
+    fn volume_slider(ui: &mut Frame, volume: &mut f32) {
+        let options = LabeledSliderOptions {
+            captions: true,
+            step: Some(0.05),
+            ..Default::default()
+        };
+
+        let format = |value: f32| format!("{:.0}%", value * 100.0);
+        if let Some(new_volume) = ui.labeled_slider("labeled_slider", 0.0, 1.0, *volume, format, options).value {
+            *volume = new_volume;
+        }
+    }
+    ```
+    */
+    pub fn labeled_slider<F: Fn(f32) -> String>(
+        &mut self,
+        theme: &str,
+        min: f32,
+        max: f32,
+        value: f32,
+        format: F,
+        options: LabeledSliderOptions,
+    ) -> SliderResult {
+        let widest = format(max);
+        let mut result = SliderResult { value: None, released: false };
+
+        self.start(theme).children(|ui| {
+            if options.captions {
+                ui.label("min_label", format(min));
+            }
+
+            let label = if options.value_in_button {
+                Some((format(value), widest.clone()))
+            } else {
+                None
+            };
+
+            result = ui.slider("slider", min, max, value, options.step, false, label);
+
+            if options.captions {
+                ui.label("max_label", format(max));
+            }
+
+            if !options.value_in_button {
+                // show the value being dragged to on this frame
+                let builder = ui.start("value");
+                let width = builder.text_width(&widest);
+                builder.text(format(result.value.unwrap_or(value))).min_width(width).finish();
+            }
+        });
+
+        result
+    }
+
+    // builds a slider.  If a `label` is specified, it is shown as the button's text, and the button is made
+    // wide enough for the widest text, which is the label's second field
+    #[allow(clippy::too_many_arguments)]
+    fn slider(
+        &mut self,
+        theme: &str,
+        min: f32,
+        max: f32,
+        value: f32,
+        step: Option<f32>,
+        vertical: bool,
+        label: Option<(String, String)>,
+    ) -> SliderResult {
+        let axis = |point: Point| if vertical { point.y } else { point.x };
+
         let mut inner = Rect::default();
-        let mut new_value = None;
+        let mut button_rect = Rect::default();
+        let mut new_pos = None;
+        let mut released = false;
+        let mut slider_id = String::new();
 
-        self.start(theme)
+        let slider = self.start(theme)
         .wants_mouse(true)
         .trigger_layout_inner(&mut inner)
         .children(|ui| {
+            slider_id = ui.widget(ui.parent_index()).id().to_string();
+            let button_id = format!("{}_button", slider_id);
+
             ui.child("slider_bar");
 
-            let mut button_rect = Rect::default();
-            let builder = ui.start("slider_button").wants_mouse(true).align(Align::Left).trigger_layout(&mut button_rect);
+            let mut builder = ui.start("slider_button")
+            .id(&button_id)
+            .wants_mouse(true)
+            .focusable(false)
+            .align(Align::Left);
+
+            if let Some((text, widest)) = label {
+                let width = builder.text_width(&widest);
+                builder = builder.text(text).min_width(width);
+            }
+
+            let builder = builder.trigger_layout(&mut button_rect);
 
-            let total_width = inner.size.x - button_rect.size.x;
-            let pos = total_width * (value - min) / (max - min);
+            let total = axis(inner.size) - axis(button_rect.size);
+            let pos = total * (value - min) / (max - min);
 
-            let result = builder.pos(pos, 0.0).finish();
+            let result = if vertical {
+                builder.pos(0.0, pos)
+            } else {
+                builder.pos(pos, 0.0)
+            }.finish();
 
-            if result.moved.x != 0.0 {
-                let delta_x = result.moved.x;
+            if result.pressed {
+                if step.is_some() {
+                    new_pos = Some(axis(ui.mouse_rect().pos - inner.pos - button_rect.size * 0.5));
+                } else if axis(result.moved) != 0.0 {
+                    new_pos = Some(pos + axis(result.moved));
+                }
+            }
+
+            released = ui.mouse_released(&button_id);
+        });
 
-                let next_pos = pos + delta_x;
-                let new_val = (max - min) * next_pos / total_width + min;
+        // pressing the slider outside the button moves the button to the mouse
+        if slider.pressed {
+            new_pos = Some(axis(self.mouse_rect().pos - inner.pos - button_rect.size * 0.5));
+        }
+        released |= self.mouse_released(&slider_id);
 
-                new_value = Some(new_val.min(max).max(min));
+        let total = axis(inner.size) - axis(button_rect.size);
+        let value = new_pos.map(|pos| {
+            let mut value = (max - min) * pos / total + min;
+            if let Some(step) = step {
+                value = (value / step).round() * step;
             }
+
+            value.min(max).max(min)
         });
 
-        new_value
+        SliderResult { value, released }
     }
 
     /**
@@ -238,40 +767,225 @@ impl Frame {
     }
 
     /**
-    A tree widget.  Depending on its internal `expanded` state (see [`Frame.is_expanded`](struct.Frame.html#method.is_expanded), this
-    widget will either show both its `title` and `children` widgets, or just its `title` widgets.  It is intended that
-    you use [`height_from`](struct.WidgetBuilder.html#method.height_from) with [`Children`](enum.HeightRelative.html).
+    A numeric stepper, used to select an integer `value` between `min` and `max`.  The stepper includes the value, a
+    `decrease` button to subtract `step` from the value, and an `increase` button to add `step` to the value.  Holding
+    down a button repeats it, as with held keys, and after the `accel_delay` custom float, in milliseconds, the
+    amount changed on each repeat grows steadily up to `max_accel` times `step`.  The buttons are disabled when the
+    value is at the end of the range in their direction.
+
+    If `editable` is true, the value is shown in a `value_field` [`input field`](#method.input_field) with the
+    id `{id}_value`, rather than in the `value` label.  The typed value is applied when Enter is pressed or the field
+    loses keyboard focus, and is clamped to the range.  Text that does not parse is discarded.
 
+    Returns the new value, clamped to [`min`, `max`], on the frame it changes, otherwise returning `None`.  The
+    specified `id` must be unique.
+
+    An example theme definition:
     ```yaml
-    tree:
-      size_from: [Parent, Children]
-      border: { all: 5 }
-      background: gui/window_bg
+    stepper:
+      size: [100, 20]
+      layout: Horizontal
+      layout_spacing: [5, 5]
+      child_align: Left
+      custom_floats:
+        accel_delay: 1500
+        max_accel: 10
       children:
-        expand:
-          from: button
-          align: TopLeft
-          pos: [0, 0]
-          text: "+"
-          text_align: Center
-          size: [24, 24]
-        collapse:
+        decrease:
           from: button
-          align: TopLeft
-          pos: [0, 0]
           text: "-"
-          text_align: Center
-          size: [24, 24]
+          background: gui/small_button
+          size: [20, 20]
+        value:
+          from: label
+          size: [40, 0]
+          font: medium
+          width_from: Normal
+        value_field:
+          from: input_field
+          size: [40, 20]
+        increase:
+          from: button
+          text: "+"
+          background: gui/small_button
+          size: [20, 20]
     ```
 
     # Example
     ```
-    fn create_tree(ui: &mut Frame, name: &str, description: &str) {
-        ui.tree("tree", "unique_id", |ui| {
-          ui.label("label", name);
-        }, |ui| {
-          ui.label("label", description);
-        });
+    fn quantity_stepper(ui: &mut Frame, quantity: &mut i64) {
+        if let Some(value) = ui.stepper("stepper", "quantity", *quantity, 1, 99, 1, true) {
+            *quantity = value;
+        }
+    }
+    ```
+    */
+    #[allow(clippy::too_many_arguments)]
+    pub fn stepper(
+        &mut self,
+        theme: &str,
+        id: &str,
+        value: i64,
+        min: i64,
+        max: i64,
+        step: i64,
+        editable: bool,
+    ) -> Option<i64> {
+        let options = InputFieldOptions {
+            numeric: true,
+            filter: Some(|c| c != '.'),
+            ..Default::default()
+        };
+
+        self.stepper_with(theme, id, (value, min, max), editable, options, |value, steps| {
+            value.saturating_add(step.saturating_mul(steps))
+        })
+    }
+
+    /// A numeric stepper for an `f32` value.  See [`stepper`](#method.stepper).
+    #[allow(clippy::too_many_arguments)]
+    pub fn stepper_f32(
+        &mut self,
+        theme: &str,
+        id: &str,
+        value: f32,
+        min: f32,
+        max: f32,
+        step: f32,
+        editable: bool,
+    ) -> Option<f32> {
+        let options = InputFieldOptions {
+            numeric: true,
+            ..Default::default()
+        };
+
+        self.stepper_with(theme, id, (value, min, max), editable, options, |value, steps| {
+            value + step * steps as f32
+        })
+    }
+
+    // builds a stepper for `range`, containing the value, min, and max.  `offset` adds the given number
+    // of steps to a value
+    fn stepper_with<T, F>(
+        &mut self,
+        theme: &str,
+        id: &str,
+        range: (T, T, T),
+        editable: bool,
+        options: InputFieldOptions,
+        offset: F,
+    ) -> Option<T> where T: Copy + PartialOrd + Display + FromStr, F: Fn(T, i64) -> T {
+        let (value, min, max) = range;
+        let clamp = |value: T| if value < min { min } else if value > max { max } else { value };
+
+        let decrease_id = format!("{}_decrease", id);
+        let increase_id = format!("{}_increase", id);
+        let value_id = format!("{}_value", id);
+
+        let mut steps = 0;
+        let mut edited = None;
+
+        self.start(theme).id(id).children(|ui| {
+            let theme_id = ui.widget(ui.parent_index()).theme_id().to_string();
+            let accel_delay = ui.custom_float(&theme_id, "accel_delay", 1500.0);
+            let max_accel = ui.custom_float(&theme_id, "max_accel", 10.0);
+
+            // the number of steps to move on one repeat of a button held down for `held` millis
+            let accel = |held: u32| {
+                let extra = (held as f32 - accel_delay).max(0.0) / 500.0;
+                (1.0 + extra).min(max_accel.max(1.0)) as i64
+            };
+
+            let state = ui.start("decrease").id(&decrease_id).enabled(value > min).focusable(false).finish();
+            if ui.repeat_press(&decrease_id, state.pressed) {
+                steps -= accel(ui.held_millis(&decrease_id));
+            }
+
+            if editable {
+                // show the current value unless the user is editing it
+                let was_focused = ui.modify(&value_id, |state| state.focused);
+                if !was_focused && !ui.is_focus_keyboard(&value_id) {
+                    ui.set_input_text(&value_id, value.to_string());
+                }
+
+                let result = ui.input_field_with("value_field", &value_id, None, options);
+                if result.submitted || result.lost_focus {
+                    let text = ui.input_text(&value_id).unwrap_or_default();
+                    edited = text.trim().parse::<T>().ok().map(clamp);
+                    ui.set_input_text(&value_id, edited.unwrap_or(value).to_string());
+                }
+            } else {
+                ui.label("value", value.to_string());
+            }
+
+            let state = ui.start("increase").id(&increase_id).enabled(value < max).focusable(false).finish();
+            if ui.repeat_press(&increase_id, state.pressed) {
+                steps += accel(ui.held_millis(&increase_id));
+            }
+        });
+
+        let new_value = match edited {
+            Some(edited) => edited,
+            None if steps != 0 => clamp(offset(value, steps)),
+            None => return None,
+        };
+
+        if new_value == value {
+            None
+        } else {
+            Some(new_value)
+        }
+    }
+
+    /**
+    A tree widget.  Depending on its internal `expanded` state (see [`Frame.is_expanded`](struct.Frame.html#method.is_expanded), this
+    widget will either show both its `title` and `children` widgets, or just its `title` widgets.  It is intended that
+    you use [`height_from`](struct.WidgetBuilder.html#method.height_from) with [`Children`](enum.HeightRelative.html).
+    The `expand` and `collapse` buttons toggle the expanded state, which is stored under the specified `id`.
+
+    The `indent` custom float, if present, offsets the `children` horizontally from the title, so nested trees
+    are indented one step further at each level.
+
+    The returned [`WidgetState`](struct.WidgetState.html) is that of the tree widget, except that `clicked` is only
+    set when the user clicks on the header area containing the `title`, not counting the expand or collapse button.
+    This is useful for implementing selection on top of the tree.
+
+    ```yaml
+    tree:
+      size_from: [Parent, Children]
+      border: { all: 5 }
+      background: gui/window_bg
+      custom_floats:
+        indent: 10
+      children:
+        expand:
+          from: button
+          align: TopLeft
+          pos: [0, 0]
+          text: "+"
+          text_align: Center
+          size: [24, 24]
+        collapse:
+          from: button
+          align: TopLeft
+          pos: [0, 0]
+          text: "-"
+          text_align: Center
+          size: [24, 24]
+    ```
+
+    # Example
+    ```
+    fn create_tree(ui: &mut Frame, name: &str, description: &str, selected: &mut bool) {
+        let result = ui.tree("tree", "unique_id", false, |ui| {
+          ui.label("label", name);
+        }, |ui| {
+          ui.label("label", description);
+        });
+
+        if result.clicked {
+            *selected = true;
+        }
     }
     ```
     */
@@ -282,108 +996,1728 @@ impl Frame {
         initially_expanded: bool,
         title: F,
         children: G
-    ) {
+    ) -> WidgetState {
         self.context_internal().borrow_mut().init_state(id, true, initially_expanded);
         let expanded = self.is_expanded(id);
 
-        self.start(theme).children(|ui| {
+        let mut header = Rect::default();
+        let mut result = self.start(theme).wants_mouse(true).children(|ui| {
+            let start = ui.num_widgets();
             (title)(ui);
 
             if expanded {
                 if ui.child("collapse").clicked {
                     ui.set_expanded(id, false);
                 }
-
-                (children)(ui);
             } else if ui.child("expand").clicked {
                 ui.set_expanded(id, true);
             }
+
+            // the header includes the title and expander, spanning the full width of the tree
+            let tree = ui.widget(ui.parent_index());
+            let tree_bounds = Rect::new(tree.pos(), tree.size());
+            header = match ui.children_bounds_since(start) {
+                None => Rect::new(tree_bounds.pos, Point::new(tree_bounds.size.x, 0.0)),
+                Some(bounds) => Rect::new(
+                    tree_bounds.pos,
+                    Point::new(tree_bounds.size.x, bounds.bot() - tree_bounds.pos.y),
+                ),
+            };
+
+            if expanded {
+                let indent = ui.custom_float(tree.theme_id(), "indent", 0.0);
+                if indent != 0.0 {
+                    let cursor = ui.cursor();
+                    ui.set_cursor(cursor.x + indent, cursor.y);
+                }
+
+                (children)(ui);
+            }
         });
-    }
 
-    // TODO menubar
+        if result.clicked {
+            let mouse_pos = self.context_internal().borrow().mouse_pos();
+            result.clicked = header.is_inside(mouse_pos);
+        }
+
+        result
+    }
 
     /**
-    A drop down box. It displays its currently active selection (`current`), and opens a modal popup to select a new
-    choice from the list of `values` when the user clicks on it.  The specified `id` must be unique.
-    The method will return a selected choice on the frame the user clicks on it, otherwise returning `None`.
+    A collapsible section.  The `header` button shows the `title`, and toggles whether the `content` is open
+    when clicked.  The open state is stored as the `is_open` field of the [`PersistentState`](struct.PersistentState.html)
+    for `{id}_content`, starting out as `default_open`, so it may also be changed with [`open`](#method.open) and
+    [`close`](#method.close).  The `children` closure is only called while the section is open.  The `header` and
+    its `arrow` child are shown with the `Active` [`AnimStateKey`](enum.AnimStateKey.html) while the section is open,
+    so the theme may swap the arrow image.  Sections may be nested.  Returns the
+    [`WidgetState`](struct.WidgetState.html) of the header.
 
-    An example theme definition;  See [`ScrollpaneBuilder`](struct.ScrollpaneBuilder.html) for the scrollpane example.
+    An example theme definition:
     ```yaml
-    combo_box:
-      from: button
+    collapsible:
+      size_from: [Parent, Children]
+      layout: Vertical
       children:
-        expand:
-          size: [12, 12]
-          align: Right
-          foreground: gui/arrow_down
-        combo_box_popup:
-          from: scrollpane
+        header:
+          from: button
           width_from: Parent
-          height_from: Normal
-          size: [10, 75]
-          background: gui/small_button_normal
+          text_align: Left
           children:
-            content:
-              size: [-18, -10]
-              children:
-                entry:
-                  from: button
-                  width_from: Parent
-                  size: [0, 25]
-            scrollbar_vertical:
-              size: [20, 20]
+            arrow:
+              size: [12, 12]
+              align: Right
+              foreground: gui/arrow_right_or_down
+        content:
+          size_from: [Parent, Children]
+          border: { left: 10 }
+          layout: Vertical
+    ```
+    where `arrow_right_or_down` is an image with an `Active` state.
+
+    # Example
+    ```
+    fn settings(ui: &mut Frame) {
+        ui.collapsible("collapsible", "video", "Video", true, |ui| {
+            ui.label("label", "Resolution");
+            ui.collapsible("collapsible", "video_advanced", "Advanced", false, |ui| {
+                ui.label("label", "Shadow Quality");
+            });
+        });
+    }
     ```
     */
-    pub fn combo_box<'a, T: Display>(&mut self, theme: &str, id: &str, current: &T, values: &'a [T]) -> Option<&'a T> {
-        let popup_id = format!("{}_popup", id);
+    pub fn collapsible<T: Into<String>, F: FnOnce(&mut Frame)>(
+        &mut self,
+        theme: &str,
+        id: &str,
+        title: T,
+        default_open: bool,
+        children: F,
+    ) -> WidgetState {
+        self.collapsible_section(theme, id, None, title.into(), default_open, children)
+    }
 
-        let mut result = None;
-        let open_result = self.start(theme)
-        .text(current.to_string())
+    /**
+    A collapsible section, as in [`collapsible`](#method.collapsible), that is part of an accordion.  Of the sections
+    sharing the same `group` id, only one may be open at a time, so opening one section closes the others.  The `id`
+    of the open section is stored as the `text` of the [`PersistentState`](struct.PersistentState.html) for the
+    `group`.  If more than one section in a group has `default_open` set, only the first one built is opened.
+
+    # Example
+    ```
+    fn help(ui: &mut Frame) {
+        ui.accordion_section("collapsible", "help_topics", "help_start", "Getting Started", true, |ui| {
+            ui.label("label", "Welcome!");
+        });
+        ui.accordion_section("collapsible", "help_topics", "help_keys", "Key Bindings", false, |ui| {
+            ui.label("label", "WASD to move");
+        });
+    }
+    ```
+    */
+    pub fn accordion_section<T: Into<String>, F: FnOnce(&mut Frame)>(
+        &mut self,
+        theme: &str,
+        group: &str,
+        id: &str,
+        title: T,
+        default_open: bool,
+        children: F,
+    ) -> WidgetState {
+        self.collapsible_section(theme, id, Some(group), title.into(), default_open, children)
+    }
+
+    fn collapsible_section<F: FnOnce(&mut Frame)>(
+        &mut self,
+        theme: &str,
+        id: &str,
+        group: Option<&str>,
+        title: String,
+        default_open: bool,
+        children: F,
+    ) -> WidgetState {
+        let content_id = format!("{}_content", id);
+        self.context_internal().borrow_mut().init_state(&content_id, default_open, true);
+
+        // only the section last opened in a group stays open
+        if let Some(group) = group {
+            let open = self.is_open(&content_id);
+            let group_open = self.text_for(group);
+            match group_open {
+                Some(open_id) if open && open_id != id => self.close(&content_id),
+                None if open => self.modify(group, |state| state.text = Some(id.to_string())),
+                _ => (),
+            }
+        }
+
+        let open = self.is_open(&content_id);
+        let mut header = WidgetState::hidden();
+        self.start(theme).id(id).children(|ui| {
+            header = ui.start("header")
+            .id(format!("{}_header", id))
+            .text(title)
+            .active(open)
+            .wants_mouse(true)
+            .children(|ui| {
+                ui.start("arrow").active(open).finish();
+            });
+
+            ui.start("content")
+            .id(&content_id)
+            .children(children);
+        });
+
+        if header.clicked {
+            self.modify(&content_id, |state| state.is_open = !open);
+
+            if let Some(group) = group {
+                let open_id = if open { None } else { Some(id.to_string()) };
+                self.modify(group, |state| state.text = open_id);
+            }
+        }
+
+        header
+    }
+
+    /**
+    A horizontal menu bar.  The specified closure should add [`menus`](#method.menu) to the bar.
+    See [`menu`](#method.menu) for more details and a full example.
+
+    An example theme definition:
+    ```yaml
+    menubar:
+      width_from: Parent
+      size: [0, 30]
+      border: { all: 2 }
+      layout: Horizontal
+      layout_spacing: [5, 5]
+      child_align: Left
+      background: gui/frame
+      children:
+        menu:
+          from: button
+          size: [80, 0]
+          height_from: Parent
+    ```
+    */
+    pub fn menubar<F: FnOnce(&mut Frame)>(&mut self, theme: &str, children: F) {
+        self.start(theme).children(children);
+    }
+
+    /**
+    A single drop down menu, normally added to a [`menubar`](#method.menubar).  The menu shows a button with the specified `title`.
+    When the title is clicked, a `menu_popup` child is opened as a modal below the title, and the `children` closure
+    is called to fill it, usually with [`menu_item`](#method.menu_item) and [`submenu`](#method.submenu) entries.
+    While one menu of a menubar is open, hovering the title of another menu in the same menubar switches to that menu.
+    Clicking outside the menu closes it.
+
+    An example theme definition:
+    ```yaml
+    menu_popup:
+      size_from: [Children, Children]
+      border: { all: 5 }
+      layout: Vertical
+      background: gui/small_button_normal
+    menu_item:
+      from: button
+      size: [150, 25]
+    submenu:
+      from: button
+      size: [150, 25]
+      text_align: Left
+    ```
+
+    # Example
+    ```
+    fn main_menu(ui: &mut Frame) {
+        ui.menubar("menubar", |ui| {
+            ui.menu("menu", "File", |ui| {
+                if ui.menu_item("menu_item", "Open").clicked {
+                    println!("Open");
+                }
+
+                ui.submenu("submenu", "Recent", |ui| {
+                    if ui.menu_item("menu_item", "notes.txt").clicked {
+                        println!("Open notes.txt");
+                    }
+                });
+            });
+
+            ui.menu("menu", "Help", |ui| {
+                ui.menu_item("menu_item", "About");
+            });
+        });
+    }
+    ```
+    */
+    pub fn menu<T: Into<String>, F: FnOnce(&mut Frame)>(&mut self, theme: &str, title: T, children: F) {
+        let bar_prefix = format!("{}/", self.widget(self.parent_index()).id());
+
+        let mut title_rect = Rect::default();
+        let builder = self.start(theme)
+        .text(title)
         .wants_mouse(true)
-        .children(|ui| {
-            ui.child("expand");
+        .trigger_layout(&mut title_rect);
+
+        let popup_id = format!("{}_popup", builder.widget.id());
+        builder.frame.context_internal().borrow_mut().init_state(&popup_id, false, true);
+        let is_open = builder.frame.is_open(&popup_id);
 
-            ui.start("combo_box_popup")
+        let result = builder
+        .active(is_open)
+        .children(|ui| {
+            ui.start("menu_popup")
             .id(&popup_id)
-            .initially_open(false)
             .unclip()
             .unparent()
             .new_render_group()
-            .scrollpane(&format!("{}_content", popup_id))
-            .children(|ui| {
-                for value in values {
-                    if ui.button("entry", value.to_string()).clicked {
-                        result = Some(value);
-                        ui.close(&popup_id);
-                    }
+            .screen_pos(title_rect.pos.x, title_rect.pos.y + title_rect.size.y)
+            .children(children);
+        });
+
+        if result.clicked {
+            self.open_modal(&popup_id);
+            self.close_modal_on_click_outside();
+            return;
+        }
+
+        // switch to this menu if another menu from the same bar is open and this title is hovered
+        let (other_open, mouse_pos) = {
+            let context = self.context_internal().borrow();
+            let other_open = match context.modal_id() {
+                None => None,
+                Some(id) => if id != popup_id && id.starts_with(&bar_prefix) && id.ends_with("_popup") {
+                    Some(id.to_string())
+                } else {
+                    None
                 }
-            });
+            };
+            (other_open, context.mouse_pos())
+        };
+
+        if let Some(other_id) = other_open {
+            if title_rect.is_inside(mouse_pos) {
+                self.close(other_id);
+                self.open_modal(&popup_id);
+                self.close_modal_on_click_outside();
+            }
+        }
+    }
+
+    /**
+    A nested menu, for use within a [`menu`](#method.menu) or another submenu.  The submenu shows an entry with the specified
+    `title`.  While that entry is hovered, a `menu_popup` child is shown to the right of it, and the `children` closure is
+    called to fill it.  The popup stays open while the mouse is over either the entry or the popup.
+
+    See [`menu`](#method.menu) for an example.
+    */
+    pub fn submenu<T: Into<String>, F: FnOnce(&mut Frame)>(&mut self, theme: &str, title: T, children: F) {
+        let mut title_rect = Rect::default();
+        let builder = self.start(theme)
+        .text(title)
+        .wants_mouse(true)
+        .trigger_layout(&mut title_rect);
+
+        let popup_id = format!("{}_popup", builder.widget.id());
+        builder.frame.context_internal().borrow_mut().init_state(&popup_id, false, true);
+        let is_open = builder.frame.is_open(&popup_id);
+
+        let mut popup_rect = Rect::default();
+        let result = builder
+        .active(is_open)
+        .children(|ui| {
+            let start = ui.num_widgets();
+
+            ui.start("menu_popup")
+            .id(&popup_id)
+            .unclip()
+            .unparent()
+            .new_render_group()
+            .screen_pos(title_rect.pos.x + title_rect.size.x, title_rect.pos.y)
+            .children(children);
+
+            if let Some(bounds) = ui.children_bounds_since(start) {
+                popup_rect = bounds;
+            }
+        });
+
+        let (menu_open, mouse_pos) = {
+            let context = self.context_internal().borrow();
+            (context.has_modal(), context.mouse_pos())
+        };
+
+        if !menu_open {
+            // a menu item was clicked and the whole menu closed
+            self.close(&popup_id);
+        } else if result.hovered {
+            self.open(&popup_id);
+        } else if is_open && !popup_rect.is_inside(mouse_pos) && !title_rect.is_inside(mouse_pos) {
+            self.close(&popup_id);
+        }
+    }
+
+    /**
+    An entry in a [`menu`](#method.menu) or [`submenu`](#method.submenu).  When the entry is clicked, the
+    entire menu, including any parent menus, is closed.  Check the `clicked` field of the returned
+    [`WidgetState`](struct.WidgetState.html) to respond to the user's selection.
+
+    See [`menu`](#method.menu) for an example.
+    */
+    pub fn menu_item<T: Into<String>>(&mut self, theme: &str, label: T) -> WidgetState {
+        let result = self.button(theme, label);
+
+        if result.clicked {
+            let modal_id = self.context_internal().borrow().modal_id().map(|id| id.to_string());
+            if let Some(modal_id) = modal_id {
+                self.close(modal_id);
+            }
+        }
+
+        result
+    }
+
+    /**
+    A popup menu, opened at the mouse position when the `trigger` widget is clicked with the right mouse button.
+    The specified `id` must be unique.  The popup is shown as a modal, and closes when the user clicks outside of it.
+    While open, the `entries` closure is called each frame to add entries, usually [`menu_items`](#method.menu_item),
+    which close the menu when clicked.  The value returned by `entries`, if any, is passed through to the caller.
+
+    The popup opens to the right of and below the mouse, but flips to the left and / or above the mouse if it would
+    otherwise extend past the edge of the display.  To do this, the `entries` closure is called an additional time
+    each frame in [`measure`](struct.Frame.html#method.measure) mode.
+
+    An example theme definition:
+    ```yaml
+    context_menu:
+      size_from: [Children, Children]
+      border: { all: 5 }
+      layout: Vertical
+      background: gui/small_button_normal
+    ```
+
+    # Example
+    ```
+    enum Action {
+        Use,
+        Drop,
+    }
+
+    fn inventory_slot(ui: &mut Frame, index: usize) -> Option<Action> {
+        let result = ui.button("slot", "Sword");
+
+        ui.context_menu("context_menu", &format!("slot_menu_{}", index), &result, |ui| {
+            if ui.menu_item("menu_item", "Use").clicked {
+                return Some(Action::Use);
+            }
+
+            if ui.menu_item("menu_item", "Drop").clicked {
+                return Some(Action::Drop);
+            }
+
+            None
+        })
+    }
+    ```
+    */
+    pub fn context_menu<R, F: FnMut(&mut Frame) -> Option<R>>(
+        &mut self,
+        theme: &str,
+        id: &str,
+        trigger: &WidgetState,
+        mut entries: F,
+    ) -> Option<R> {
+        self.context_internal().borrow_mut().init_state(id, false, true);
+
+        if trigger.clicked_with(MouseButton::Right) {
+            // the popup is placed manually, so its moved state is used to hold the opening position
+            let mouse_pos = self.context_internal().borrow().mouse_pos();
+            self.modify(id, |state| state.moved = mouse_pos);
+            self.open_modal(id);
+            self.close_modal_on_click_outside();
+        }
+
+        if !self.is_open(id) {
+            return None;
+        }
+
+        let size = self.measure(|ui| {
+            ui.start(theme).id(id).unparent().children(|ui| { (entries)(ui); });
+        });
+
+        let anchor = self.modify(id, |state| state.moved);
+        let display = self.display_size();
+        let mut pos = anchor;
+        if pos.x + size.x > display.x {
+            pos.x = (anchor.x - size.x).max(0.0);
+        }
+        if pos.y + size.y > display.y {
+            pos.y = (anchor.y - size.y).max(0.0);
+        }
+
+        let mut result = None;
+        self.start(theme)
+        .id(id)
+        .unclip()
+        .unparent()
+        .new_render_group()
+        .screen_pos(pos.x, pos.y)
+        .children(|ui| {
+            result = (entries)(ui);
+        });
+
+        result
+    }
+
+    /**
+    A drop down box. It displays its currently active selection (`current`), and opens a modal popup to select a new
+    choice from the list of `values` when the user clicks on it.  The specified `id` must be unique.
+    The method will return a selected choice on the frame the user clicks on it, otherwise returning `None`.
+    If the popup would extend past the bottom of the display, it opens upward instead, and it is moved left
+    as needed to stay within the right edge of the display.
+    With keyboard focus, Enter or Space opens the popup, Up and Down move between the entries, and Enter
+    selects the focused entry.  The focused entry is highlighted with the `Hover`
+    [`AnimStateKey`](enum.AnimStateKey.html).  Escape closes the popup without changing the selection.  While the popup
+    is open, typing moves the focus to the first entry starting with the typed text, ignoring case.  The typed text
+    is forgotten after a second without typing.
+
+    An example theme definition;  See [`ScrollpaneBuilder`](struct.ScrollpaneBuilder.html) for the scrollpane example.
+    ```yaml
+    combo_box:
+      from: button
+      children:
+        expand:
+          size: [12, 12]
+          align: Right
+          foreground: gui/arrow_down
+        combo_box_popup:
+          from: scrollpane
+          width_from: Parent
+          height_from: Normal
+          size: [10, 75]
+          background: gui/small_button_normal
+          children:
+            content:
+              size: [-18, -10]
+              children:
+                entry:
+                  from: button
+                  width_from: Parent
+                  size: [0, 25]
+            scrollbar_vertical:
+              size: [20, 20]
+    ```
+    */
+    pub fn combo_box<'a, T: Display>(&mut self, theme: &str, id: &str, current: &T, values: &'a [T]) -> Option<&'a T> {
+        self.combo_box_select(theme, id, current.to_string(), values, |ui, index, entry_id, highlight| {
+            text_entry(ui, &values[index], entry_id, highlight)
+        }).map(|index| &values[index])
+    }
+
+    /**
+    A drop down box, as in [`combo_box`](#method.combo_box), where each entry in the popup is built by the `entry`
+    closure.  The closure is passed the value for the entry and whether it is the `current` value, and returns the
+    state of the widget it builds, such as a button.  The entry is selected and the popup closed when that state
+    reports a click.  The current value is the one in `values` that `current` refers to, or otherwise the first one
+    with the same `Display` output.
+
+    The closure's widgets are added as children of an `entry` widget, which holds the keyboard focus for the entry
+    and may itself be clicked.  The text of the combo box is still the `Display` output of `current`.
+
+    # Example
+    ```
+    struct Item {
+        name: String,
+        rarity: &'static str,
+    }
+
+    impl std::fmt::Display for Item {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{}", self.name)
+        }
+    }
+
+    fn select_item<'a>(ui: &mut Frame, current: &Item, items: &'a [Item]) -> Option<&'a Item> {
+        ui.combo_box_custom("combo_box_custom", "item_selector", current, items, |ui, item, is_current| {
+            ui.start("icon").foreground(item.rarity).finish();
+            ui.start("name").text(&item.name).active(is_current).wants_mouse(true).finish()
+        })
+    }
+    ```
+
+    An example theme definition;  See [`combo_box`](#method.combo_box) for the remainder of the theme.
+    ```yaml
+    combo_box_custom:
+      from: combo_box
+      children:
+        combo_box_popup:
+          children:
+            content:
+              children:
+                entry:
+                  layout: Horizontal
+                  children:
+                    icon:
+                      size: [20, 20]
+                    name:
+                      from: label
+                      width_from: Normal
+                      size: [100, 0]
+    ```
+    */
+    pub fn combo_box_custom<'a, T, F>(
+        &mut self,
+        theme: &str,
+        id: &str,
+        current: &T,
+        values: &'a [T],
+        mut entry: F,
+    ) -> Option<&'a T> where T: Display, F: FnMut(&mut Frame, &T, bool) -> WidgetState {
+        let current_index = values.iter().position(|value| std::ptr::eq(value, current)).or_else(|| {
+            let current = current.to_string();
+            values.iter().position(|value| value.to_string() == current)
+        });
+
+        self.combo_box_select(theme, id, current.to_string(), values, |ui, index, entry_id, highlight| {
+            let mut clicked = false;
+            let state = ui.start("entry").id(entry_id).wants_mouse(true).highlight(highlight).children(|ui| {
+                clicked = (entry)(ui, &values[index], current_index == Some(index)).clicked;
+            });
+            clicked || state.clicked
+        }).map(|index| &values[index])
+    }
+
+    /**
+    A drop down box, as in [`combo_box`](#method.combo_box), with the current selection specified by its index in
+    `values`.  The method will return the index of the selected choice on the frame the user clicks on it, otherwise
+    returning `None`.  If `current_index` is past the end of `values`, the last value is displayed as the current
+    selection, and nothing is displayed if `values` is empty.
+
+    # Example
+    ```
+    struct Mod {
+        name: String,
+    }
+
+    impl std::fmt::Display for Mod {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{}", self.name)
+        }
+    }
+
+    fn select_mod(ui: &mut Frame, mods: &[Mod], selected: &mut usize) {
+        if let Some(index) = ui.combo_box_index("combo_box", "mod_selector", *selected, mods) {
+            *selected = index;
+        }
+    }
+    ```
+    */
+    pub fn combo_box_index<T: Display>(&mut self, theme: &str, id: &str, current_index: usize, values: &[T]) -> Option<usize> {
+        let current = match values.len() {
+            0 => String::new(),
+            len => values[current_index.min(len - 1)].to_string(),
+        };
+
+        self.combo_box_select(theme, id, current, values, |ui, index, entry_id, highlight| {
+            text_entry(ui, &values[index], entry_id, highlight)
+        })
+    }
+
+    // builds a combo box, using `entry` to build each entry in the popup, given the value index, the entry
+    // widget id, and whether the entry is highlighted.  `entry` returns whether the entry was clicked
+    fn combo_box_select<T, F>(
+        &mut self,
+        theme: &str,
+        id: &str,
+        current: String,
+        values: &[T],
+        mut entry: F,
+    ) -> Option<usize> where T: Display, F: FnMut(&mut Frame, usize, String, bool) -> bool {
+        let popup_id = format!("{}_popup", id);
+        let content_id = format!("{}_content", popup_id);
+        let entry_id = |index: usize| format!("{}_entry{}", popup_id, index);
+
+        let mut result = None;
+        let mut combo_rect = Rect::default();
+        let open_result = self.start(theme)
+        .id(id)
+        .text(current)
+        .wants_mouse(true)
+        .trigger_layout(&mut combo_rect)
+        .children(|ui| {
+            ui.child("expand");
+
+            let mut popup_rect = Rect::default();
+            let popup = ui.start("combo_box_popup")
+            .id(&popup_id)
+            .initially_open(false)
+            .unclip()
+            .unparent()
+            .new_render_group()
+            .trigger_layout(&mut popup_rect);
+
+            let pos = popup_pos(combo_rect, popup_rect, popup.frame.display_size());
+            popup
+            .screen_pos(pos.x, pos.y)
+            .scrollpane(&content_id)
+            .children(|ui| {
+                for index in 0..values.len() {
+                    let entry_id = entry_id(index);
+                    let highlight = ui.is_focus_keyboard(&entry_id);
+                    if (entry)(ui, index, entry_id, highlight) {
+                        result = Some(index);
+                        ui.close(&popup_id);
+                    }
+                }
+            });
+
+        });
+        if open_result.clicked {
+            self.open_modal(&popup_id);
+            self.close_modal_on_click_outside();
+        }
+
+        if result.is_some() {
+            // return the keyboard focus from the entry to the combo box
+            if !self.is_focus_keyboard(id) && (0..values.len()).any(|index| self.is_focus_keyboard(&entry_id(index))) {
+                self.focus_keyboard(id);
+            }
+            return result;
+        }
+
+        // the Up and Down keys move the keyboard focus between the combo box and its entries
+        let popup_open = self.is_open(&popup_id);
+        let focused_entry = (0..values.len()).find(|index| popup_open && self.is_focus_keyboard(&entry_id(*index)));
+        let focused_id = match focused_entry {
+            Some(index) => entry_id(index),
+            None if self.is_focus_keyboard(id) => id.to_string(),
+            None => return result,
+        };
+
+        let keys: Vec<KeyEvent> = self.keys_pressed(&focused_id).collect();
+        for event in keys {
+            if event.key == KeyCode::Escape && popup_open {
+                self.context_internal().borrow_mut().dismiss_modal(&popup_id);
+                self.focus_keyboard(id);
+                return result;
+            }
+
+            if values.is_empty() { break; }
+
+            let next = match (event.key, focused_entry) {
+                (KeyCode::Down, Some(index)) => (index + 1).min(values.len() - 1),
+                (KeyCode::Up, Some(index)) => index.saturating_sub(1),
+                (KeyCode::Down, None) | (KeyCode::Up, None) => 0,
+                _ => continue,
+            };
+
+            if !popup_open {
+                self.open_modal(&popup_id);
+                self.close_modal_on_click_outside();
+            }
+
+            self.focus_keyboard(entry_id(next));
+            self.scroll_to(&content_id, entry_id(next));
+        }
+
+        // type-ahead jumps to the first entry starting with the recently typed text
+        let typed: Vec<char> = self.modify(&focused_id, |state| state.characters.drain(..).collect());
+        if popup_open && !typed.is_empty() {
+            let search_id = format!("{}_search", popup_id);
+            let mut search = if self.cur_time_millis().saturating_sub(self.base_time_millis(&search_id)) > TYPE_AHEAD_TIMEOUT {
+                String::new()
+            } else {
+                self.text_for(&search_id).unwrap_or_default()
+            };
+            search.extend(typed.into_iter().filter(|c| !c.is_control()).flat_map(char::to_lowercase));
+
+            self.modify(&search_id, |state| state.text = Some(search.clone()));
+            self.set_base_time_now(&search_id);
+
+            if let Some(index) = values.iter().position(|value| value.to_string().to_lowercase().starts_with(&search)) {
+                self.focus_keyboard(entry_id(index));
+                self.scroll_to(&content_id, entry_id(index));
+            }
+        }
+
+        result
+    }
+
+    /**
+    A drop down box for long lists, as in [`combo_box`](#method.combo_box), with a filter field at the top of its popup.
+    Only the entries whose `Display` output contains the filter text, ignoring case, are shown.  The filter text is
+    stored as the text of the `{id}_popup_filter` widget and is cleared whenever the popup closes.  When no entries
+    match, the `no_results` child is shown in place of the list.  The method will return a selected choice on the frame
+    the user clicks on it, otherwise returning `None`.
+
+    Opening the popup gives the keyboard focus to the filter field.  Enter in the filter field selects the first
+    matching entry, Up and Down move between the filter field and the matching entries, and Escape closes the popup
+    without changing the selection.  Typing while an entry has keyboard focus adds to the filter.
+
+    An example theme definition;  See [`ScrollpaneBuilder`](struct.ScrollpaneBuilder.html) for the scrollpane example
+    and [`input_field`](#method.input_field) for the input field example.
+    ```yaml
+    combo_box_filtered:
+      from: button
+      children:
+        expand:
+          size: [12, 12]
+          align: Right
+          foreground: gui/arrow_down
+        combo_box_popup:
+          width_from: Parent
+          height_from: Normal
+          size: [10, 150]
+          layout: Vertical
+          background: gui/small_button_normal
+          children:
+            filter:
+              from: input_field
+              width_from: Parent
+            no_results:
+              from: label
+              text: No results
+            list:
+              from: scrollpane
+              height_from: Parent
+              size: [0, -30]
+              children:
+                content:
+                  children:
+                    entry:
+                      from: button
+                      width_from: Parent
+                      size: [0, 25]
+    ```
+    */
+    pub fn combo_box_filtered<'a, T: Display>(&mut self, theme: &str, id: &str, current: &T, values: &'a [T]) -> Option<&'a T> {
+        let popup_id = format!("{}_popup", id);
+        let filter_id = format!("{}_filter", popup_id);
+        let content_id = format!("{}_content", popup_id);
+        let entry_id = |index: usize| format!("{}_entry{}", popup_id, index);
+
+        if self.is_open(&popup_id) {
+            self.combo_box_filter_keys(id, &popup_id, values);
+        } else if self.text_for(&filter_id).is_some() {
+            self.modify(&filter_id, |state| {
+                state.text = None;
+                state.caret = 0;
+                state.selection = None;
+            });
+        }
+
+        let mut result = None;
+        let mut combo_rect = Rect::default();
+        let open_result = self.start(theme)
+        .id(id)
+        .text(current.to_string())
+        .wants_mouse(true)
+        .trigger_layout(&mut combo_rect)
+        .children(|ui| {
+            ui.child("expand");
+
+            let mut popup_rect = Rect::default();
+            let popup = ui.start("combo_box_popup")
+            .id(&popup_id)
+            .initially_open(false)
+            .unclip()
+            .unparent()
+            .new_render_group()
+            .trigger_layout(&mut popup_rect);
+
+            let pos = popup_pos(combo_rect, popup_rect, popup.frame.display_size());
+            popup
+            .screen_pos(pos.x, pos.y)
+            .children(|ui| {
+                let submitted = ui.input_field_with("filter", &filter_id, None, InputFieldOptions::default()).submitted;
+                let matching = filter_matches(values, &ui.text_for(&filter_id).unwrap_or_default());
+
+                if submitted {
+                    if let Some(index) = matching.first() {
+                        result = Some(&values[*index]);
+                        ui.close(&popup_id);
+                    }
+                }
+
+                if matching.is_empty() {
+                    ui.child("no_results");
+                    return;
+                }
+
+                ui.scrollpane("list", &content_id, |ui| {
+                    for index in matching {
+                        let entry_id = entry_id(index);
+                        let highlight = ui.is_focus_keyboard(&entry_id);
+                        if ui.start("entry").id(entry_id).text(values[index].to_string()).wants_mouse(true).highlight(highlight).finish().clicked {
+                            result = Some(&values[index]);
+                            ui.close(&popup_id);
+                        }
+                    }
+                });
+            });
+        });
+
+        let keys: Vec<KeyEvent> = self.keys_pressed(id).collect();
+        let open_key = keys.iter().any(|event| event.key == KeyCode::Down || event.key == KeyCode::Up);
+        if open_result.clicked || (open_key && !self.is_open(&popup_id)) {
+            self.open_modal(&popup_id);
+            self.close_modal_on_click_outside();
+            self.focus_keyboard(&filter_id);
+        }
+
+        if result.is_some() {
+            self.focus_keyboard(id);
+        }
+
+        result
+    }
+
+    // handles the keys for the open popup of a filtered combo box.  This happens before the popup is
+    // built so the filter field does not consume the navigation keys
+    fn combo_box_filter_keys<T: Display>(&mut self, id: &str, popup_id: &str, values: &[T]) {
+        let filter_id = format!("{}_filter", popup_id);
+        let entry_id = |index: usize| format!("{}_entry{}", popup_id, index);
+
+        let matching = filter_matches(values, &self.text_for(&filter_id).unwrap_or_default());
+        let focused_entry = matching.iter().position(|index| self.is_focus_keyboard(&entry_id(*index)));
+        let focused_id = match focused_entry {
+            Some(pos) => entry_id(matching[pos]),
+            None if self.is_focus_keyboard(&filter_id) => filter_id.clone(),
+            None => return,
+        };
+
+        let mut focus = focused_entry;
+        let mut unhandled = Vec::new();
+        let keys: Vec<KeyEvent> = self.keys_pressed(&focused_id).collect();
+        for event in keys {
+            match event.key {
+                KeyCode::Escape => {
+                    self.context_internal().borrow_mut().dismiss_modal(popup_id);
+                    self.focus_keyboard(id);
+                    return;
+                },
+                KeyCode::Down if !matching.is_empty() => {
+                    focus = Some(focus.map_or(0, |pos| (pos + 1).min(matching.len() - 1)));
+                },
+                KeyCode::Up => focus = focus.and_then(|pos| pos.checked_sub(1)),
+                _ => unhandled.push(event),
+            }
+        }
+
+        // characters typed on an entry go to the filter field instead
+        if focused_entry.is_some() {
+            let typed: Vec<char> = self.modify(&focused_id, |state| state.characters.drain(..).collect());
+            if !typed.is_empty() {
+                self.modify(&filter_id, |state| state.characters.extend(typed));
+                focus = None;
+            }
+        }
+
+        if focus == focused_entry {
+            self.modify(&focused_id, |state| state.keys.extend(unhandled));
+            return;
+        }
+
+        match focus {
+            None => self.focus_keyboard(&filter_id),
+            Some(pos) => {
+                self.focus_keyboard(entry_id(matching[pos]));
+                self.scroll_to(format!("{}_content", popup_id), entry_id(matching[pos]));
+            }
+        }
+    }
+
+    /**
+    A tabbed pane.  A row of `tab` buttons, one for each of the `titles`, is shown in the `tab_bar` above the
+    `tab_content` panel.  The tab at index `active` is shown with the `Active` [`AnimStateKey`](enum.AnimStateKey.html),
+    and the `content` closure is called with that index to fill the panel.  Returns the index of a different tab on
+    the frame the user clicks it, otherwise returning `None`.  The specified `id` must be unique.
+
+    When the tabs are too wide for the `tab_bar`, the `scroll_left` and `scroll_right` buttons are shown at either
+    end of the `tab_row`, and scroll it by the `scroll_amount` custom float, in logical pixels.  The `tab_row`
+    should [`expand`](struct.WidgetBuilder.html#method.expand) to fill the space between the buttons.
+
+    Only the active tab's content is built, so any of its widgets using [`PersistentState`](struct.PersistentState.html),
+    such as scrollpanes, should have ids including the tab index to keep their state separate for each tab.
+
+    An example theme definition:
+    ```yaml
+    tabs:
+      width_from: Parent
+      height_from: Parent
+      layout: Vertical
+      children:
+        tab_bar:
+          width_from: Parent
+          size: [0, 24]
+          layout: Horizontal
+          child_align: Left
+          custom_floats:
+            scroll_amount: 50
+          children:
+            scroll_left:
+              from: button
+              size: [20, 24]
+              foreground: gui/arrow_left
+            tab_row:
+              width_from: Normal
+              height_from: Parent
+              expand: true
+              layout: Horizontal
+              child_align: Left
+              children:
+                tab:
+                  from: button
+                  size: [100, 24]
+            scroll_right:
+              from: button
+              size: [20, 24]
+              foreground: gui/arrow_right
+        tab_content:
+          width_from: Parent
+          height_from: Parent
+          size: [0, -24]
+          border: { all: 5 }
+          background: gui/window_bg
+    ```
+
+    # Example
+    ```
+    fn settings_tabs(ui: &mut Frame, active: &mut usize) {
+        let titles = ["Video", "Audio", "Controls"];
+        if let Some(index) = ui.tabs("tabs", "settings", &titles, *active, |ui, index| {
+            ui.scrollpane("scrollpane", &format!("settings_scroll{}", index), |ui| {
+                ui.label("label", format!("{} settings", titles[index]));
+            });
+        }) {
+            *active = index;
+        }
+    }
+    ```
+    */
+    pub fn tabs<F: FnOnce(&mut Frame, usize)>(
+        &mut self,
+        theme: &str,
+        id: &str,
+        titles: &[&str],
+        active: usize,
+        content: F,
+    ) -> Option<usize> {
+        let row_id = format!("{}_tabs", id);
+        let active = active.min(titles.len().saturating_sub(1));
+        let mut result = None;
+
+        self.start(theme).id(id).children(|ui| {
+            let mut bar_rect = Rect::default();
+            ui.start("tab_bar")
+            .trigger_layout_inner(&mut bar_rect)
+            .children(|ui| {
+                let theme_id = ui.widget(ui.parent_index()).theme_id().to_string();
+                let scroll_amount = ui.custom_float(&theme_id, "scroll_amount", 50.0);
+
+                // the width of the tabs isn't known until they are built, so use the width from the last frame
+                let (tabs_width, scroll) = ui.modify(&row_id, |state| (state.children_bounds.size.x, state.scroll.x));
+                let overflow = tabs_width > bar_rect.size.x;
+                let mut delta = 0.0;
+
+                if overflow && ui.start("scroll_left").enabled(scroll < 0.0).focusable(false).finish().clicked {
+                    delta += scroll_amount;
+                }
+
+                let mut row_rect = Rect::default();
+                ui.start("tab_row")
+                .id(&row_id)
+                .trigger_layout_inner(&mut row_rect)
+                .children(|ui| {
+                    let start = ui.num_widgets();
+                    for (index, title) in titles.iter().enumerate() {
+                        let clicked = ui.start("tab")
+                        .id(format!("{}_tab{}", id, index))
+                        .text(*title)
+                        .active(index == active)
+                        .wants_mouse(true)
+                        .finish().clicked;
+
+                        if clicked && index != active {
+                            result = Some(index);
+                        }
+                    }
+
+                    let bounds = ui.children_bounds_since(start).unwrap_or_default();
+                    ui.modify(&row_id, |state| state.children_bounds = bounds);
+                });
+
+                let min_scroll = (row_rect.size.x - tabs_width).min(0.0);
+                if overflow && ui.start("scroll_right").enabled(scroll > min_scroll).focusable(false).finish().clicked {
+                    delta -= scroll_amount;
+                }
+
+                ui.modify(&row_id, |state| state.scroll.x = (state.scroll.x + delta).max(min_scroll).min(0.0));
+            });
+
+            ui.start("tab_content")
+            .id(format!("{}_content", id))
+            .children(|ui| {
+                if !titles.is_empty() {
+                    (content)(ui, active);
+                }
+            });
+        });
+
+        result
+    }
+
+    /**
+    A table with a fixed row of column headers above a scrolling body.  Each of the `columns` has a `header`
+    button showing its title, which is clicked to report the column, such as to sort the table.  Dragging the
+    `resize` child at the right edge of a header changes the column's width, down to the `min_column_width`
+    custom float.  The change in width is stored as the `resize` of the
+    [`PersistentState`](struct.PersistentState.html) for the `{id}_header{column}` widget.
+
+    The body is a [`scrollpane`](struct.ScrollpaneBuilder.html) with the content id `{id}_body`, and contains
+    `row_count` `row` widgets, each with one `cell` per column.  The `cell` closure is called with the row and
+    column indices to build the content of each cell, which is clipped to the column width.  The header row
+    scrolls horizontally along with the body.  The cells may report clicks or other interactions with their own
+    [`WidgetState`](struct.WidgetState.html)s.
+
+    Returns the index of the column whose header was clicked on this frame, or `None`.  The specified `id` must be
+    unique.
+
+    An example theme definition:
+    ```yaml
+    table:
+      width_from: Parent
+      height_from: Parent
+      layout: Vertical
+      custom_floats:
+        min_column_width: 20
+      children:
+        header_row:
+          width_from: Parent
+          size: [0, 24]
+          layout: Horizontal
+          child_align: Left
+          children:
+            header:
+              from: button
+              height_from: Parent
+              text_align: Left
+              children:
+                resize:
+                  wants_mouse: true
+                  align: Right
+                  size: [6, 0]
+                  height_from: Parent
+        body:
+          from: scrollpane
+          size: [0, -24]
+          children:
+            content:
+              children:
+                row:
+                  size_from: [Children, Normal]
+                  size: [0, 24]
+                  layout: Horizontal
+                  child_align: Left
+                  children:
+                    cell:
+                      height_from: Parent
+                      border: { width: 5 }
+    ```
+
+    # Example
+    ```
+    struct Item {
+        name: String,
+        price: u32,
+    }
+
+    fn item_table(ui: &mut Frame, items: &mut Vec<Item>) {
+        let columns = [Column::new("Name", 150.0), Column::new("Price", 80.0)];
+        let clicked = ui.table("table", "items", &columns, items.len(), |ui, row, column| {
+            match column {
+                0 => ui.label("label", items[row].name.clone()),
+                _ => ui.label("label", items[row].price.to_string()),
+            }
+        });
+
+        match clicked {
+            Some(0) => items.sort_by(|a, b| a.name.cmp(&b.name)),
+            Some(_) => items.sort_by_key(|item| item.price),
+            None => (),
+        }
+    }
+    ```
+    */
+    pub fn table<F: FnMut(&mut Frame, usize, usize)>(
+        &mut self,
+        theme: &str,
+        id: &str,
+        columns: &[Column],
+        row_count: usize,
+        mut cell: F,
+    ) -> Option<usize> {
+        let header_row_id = format!("{}_header_row", id);
+        let body_id = format!("{}_body", id);
+        let header_id = |column: usize| format!("{}_header{}", id, column);
+
+        let widths: Vec<f32> = columns.iter().enumerate().map(|(index, column)| {
+            column.width + self.modify(header_id(index), |state| state.resize.x)
+        }).collect();
+
+        // keep the headers lined up with the scrolled body
+        let body_scroll = self.scroll(&body_id);
+        self.modify(&header_row_id, |state| state.scroll.x = body_scroll.x);
+
+        let mut clicked = None;
+        self.start(theme).id(id).children(|ui| {
+            let theme_id = ui.widget(ui.parent_index()).theme_id().to_string();
+            let min_width = ui.custom_float(&theme_id, "min_column_width", 20.0);
+
+            let mut header_rect = Rect::default();
+            ui.start("header_row")
+            .id(&header_row_id)
+            .trigger_layout(&mut header_rect)
+            .clip(header_rect)
+            .children(|ui| {
+                for (index, column) in columns.iter().enumerate() {
+                    let mut drag = 0.0;
+                    let result = ui.start("header")
+                    .id(header_id(index))
+                    .text(&column.title)
+                    .size(column.width, 0.0)
+                    .children(|ui| {
+                        let handle = ui.start("resize").focusable(false).finish();
+                        if handle.pressed {
+                            drag = handle.moved.x;
+                        }
+                    });
+
+                    if result.clicked {
+                        clicked = Some(index);
+                    }
+
+                    if drag != 0.0 {
+                        let min_resize = min_width - column.width;
+                        ui.modify(header_id(index), |state| state.resize.x = (state.resize.x + drag).max(min_resize));
+                    }
+                }
+            });
+
+            ui.start("body")
+            .scrollpane(&body_id)
+            .children(|ui| {
+                for row in 0..row_count {
+                    ui.start("row").children(|ui| {
+                        for (column, width) in widths.iter().enumerate() {
+                            let mut rect = Rect::default();
+                            ui.start("cell")
+                            .size(*width, 0.0)
+                            .trigger_layout(&mut rect)
+                            .clip(rect)
+                            .children(|ui| (cell)(ui, row, column));
+                        }
+                    });
+                }
+            });
+        });
+
+        clicked
+    }
+
+    /**
+    Creates a list of `len` rows which the user may reorder by dragging.  The `id` must be unique.  The
+    `row` closure is called with the index of each row to add the row's children.  Pressing and dragging
+    anywhere on a row that is not taken by one of its children starts a drag, during which the dragged
+    row follows the mouse in an overlay and is drawn with the `active` state in the list.  The `gap` child
+    marks where the row will be placed.  The list is a [`scrollpane`](struct.WidgetBuilder.html#method.scrollpane)
+    with the content ID `{id}_content`, and scrolls while the mouse is dragged within the `auto_scroll_margin`
+    custom float of its top or bottom edge, defaulting to 20 logical pixels, by the `auto_scroll_amount`
+    custom float each frame, defaulting to 5.
+
+    Returns `(from, to)` on the frame a row is dropped at a new position.  The list does not hold its
+    items, so the caller should then move its item with `from` to index `to`, as in
+    `let item = items.remove(from); items.insert(to, item);`.  Releasing the mouse outside the list
+    cancels the drag.
+
+    An example YAML theme definition:
+    ```yaml
+    reorderable_list:
+      from: scrollpane
+      custom_floats:
+        auto_scroll_margin: 20
+        auto_scroll_amount: 5
+      children:
+        content:
+          layout: Vertical
+          children:
+            row:
+              from: button
+              width_from: Parent
+              text_align: Left
+              layout: Horizontal
+            gap:
+              width_from: Parent
+              size: [0, 2]
+              background: gui/caret
+            dragged_row:
+              from: button
+              text_align: Left
+              layout: Horizontal
+    ```
+
+    # Example
+    ```
+    fn playlist(ui: &mut Frame, songs: &mut Vec<String>) {
+        let reordered = ui.reorderable_list("reorderable_list", "playlist", songs.len(), |ui, index| {
+            ui.label("label", songs[index].clone());
+        });
+
+        if let Some((from, to)) = reordered {
+            let song = songs.remove(from);
+            songs.insert(to, song);
+        }
+    }
+    ```
+    */
+    pub fn reorderable_list<F: FnMut(&mut Frame, usize)>(
+        &mut self,
+        theme: &str,
+        id: &str,
+        len: usize,
+        mut row: F,
+    ) -> Option<(usize, usize)> {
+        let content_id = format!("{}_content", id);
+        let prefix = format!("{}:", id);
+        let dragging = self.drag_payload()
+            .and_then(|payload| payload.strip_prefix(&prefix).and_then(|index| index.parse::<usize>().ok()))
+            .filter(|index| *index < len);
+        let mouse_pos = self.context_internal().borrow().mouse_pos();
+
+        let mut list_rect = Rect::default();
+        let mut rows: Vec<Rect> = Vec::with_capacity(len);
+        let mut scroll = 0.0;
+
+        let result = self.start(theme)
+        .id(id)
+        .drop_target()
+        .trigger_layout(&mut list_rect)
+        .scrollpane(&content_id)
+        .children(|ui| {
+            for index in 0..len {
+                let start = ui.num_widgets();
+                ui.start("row")
+                .id(format!("{}_row{}", id, index))
+                .drag_source(&format!("{}{}", prefix, index))
+                .active(dragging == Some(index))
+                .children(|ui| (row)(ui, index));
+
+                let widget = ui.widget(start);
+                rows.push(Rect::new(widget.pos(), widget.size()));
+            }
+
+            let from = match dragging {
+                Some(from) if list_rect.is_inside(mouse_pos) => from,
+                _ => return,
+            };
+
+            let gap = drop_gap(&rows, mouse_pos.y);
+            let gap_y = match (rows.get(gap), rows.last()) {
+                (Some(next), _) => next.pos.y,
+                (None, Some(last)) => last.bot(),
+                (None, None) => return,
+            };
+
+            let mut gap_rect = Rect::default();
+            let builder = ui.start("gap").trigger_layout(&mut gap_rect);
+            builder.screen_pos(rows[0].pos.x, gap_y - gap_rect.size.y / 2.0).finish();
+
+            let dragged = rows[from];
+            ui.overlay(|ui| {
+                ui.start("dragged_row")
+                .unclip()
+                .unparent()
+                .size(dragged.size.x, dragged.size.y)
+                .screen_pos(dragged.pos.x, mouse_pos.y - dragged.size.y / 2.0)
+                .new_render_group()
+                .children(|ui| (row)(ui, from));
+            });
+
+            let theme_id = ui.widget(ui.parent_index()).theme_id().to_string();
+            let margin = ui.custom_float(&theme_id, "auto_scroll_margin", 20.0);
+            let amount = ui.custom_float(&theme_id, "auto_scroll_amount", 5.0);
+            if mouse_pos.y < list_rect.pos.y + margin {
+                scroll = amount;
+            } else if mouse_pos.y > list_rect.bot() - margin {
+                scroll = -amount;
+            }
+        });
+
+        // the scroll is bounded to the content the next time the list is built
+        if scroll != 0.0 {
+            self.modify(&content_id, |state| state.scroll.y += scroll);
+        }
+
+        let from = result.dropped?.strip_prefix(&prefix)?.parse::<usize>().ok().filter(|from| *from < len)?;
+        let gap = drop_gap(&rows, mouse_pos.y);
+        let to = if gap > from { gap - 1 } else { gap };
+
+        if to == from { None } else { Some((from, to)) }
+    }
+
+    /**
+    Creates two panes side by side, separated by a `divider` which may be dragged to resize them.  The `id`
+    must be unique.  The `left` and `right` closures are called to add the children of the `first` and `second`
+    panes.  The fraction of the space given to the left pane starts at `initial_frac` and is then kept in the
+    [`PersistentState`](struct.PersistentState.html) `split` for the `id`.  The recipe sets the width of each pane,
+    so their themes should usually have a `height_from` of `Parent`.
+
+    Both panes are kept at least as wide as the `min_pane_size` custom float, defaulting to 20 logical pixels.
+    If the `collapse_distance` custom float is set, dragging the divider within that distance of either edge
+    collapses the pane on that side, and its closure is not called while it is collapsed.  Splits may be nested
+    in either pane to create more complex layouts, together with [`split_vertical`](#method.split_vertical).
+
+    An example YAML theme definition:
+    ```yaml
+    split_horizontal:
+      width_from: Parent
+      height_from: Parent
+      custom_floats:
+        min_pane_size: 50
+        collapse_distance: 10
+      children:
+        first:
+          height_from: Parent
+          layout: Vertical
+        second:
+          height_from: Parent
+          layout: Vertical
+        divider:
+          wants_mouse: true
+          size: [6, 0]
+          height_from: Parent
+          background: gui/small_button
+    ```
+
+    # Example
+    ```
+    fn editor(ui: &mut Frame) {
+        ui.split_horizontal("split_horizontal", "editor_split", 0.25, |ui| {
+            ui.label("label", "Files");
+        }, |ui| {
+            ui.split_vertical("split_vertical", "editor_console_split", 0.75, |ui| {
+                ui.label("label", "Source");
+            }, |ui| {
+                ui.label("label", "Console");
+            });
+        });
+    }
+    ```
+    */
+    pub fn split_horizontal<L: FnOnce(&mut Frame), R: FnOnce(&mut Frame)>(
+        &mut self,
+        theme: &str,
+        id: &str,
+        initial_frac: f32,
+        left: L,
+        right: R,
+    ) {
+        self.split(theme, id, initial_frac, false, left, right);
+    }
+
+    /**
+    Creates two panes, one above the other, separated by a `divider` which may be dragged to resize them.  The
+    `top` and `bottom` closures are called to add the children of the `first` and `second` panes.  The recipe
+    sets the height of each pane, so their themes should usually have a `width_from` of `Parent`.  Otherwise,
+    this is the same as [`split_horizontal`](#method.split_horizontal).
+
+    An example YAML theme definition:
+    ```yaml
+    split_vertical:
+      width_from: Parent
+      height_from: Parent
+      custom_floats:
+        min_pane_size: 50
+        collapse_distance: 10
+      children:
+        first:
+          width_from: Parent
+          layout: Vertical
+        second:
+          width_from: Parent
+          layout: Vertical
+        divider:
+          wants_mouse: true
+          size: [0, 6]
+          width_from: Parent
+          background: gui/small_button
+    ```
+    */
+    pub fn split_vertical<T: FnOnce(&mut Frame), B: FnOnce(&mut Frame)>(
+        &mut self,
+        theme: &str,
+        id: &str,
+        initial_frac: f32,
+        top: T,
+        bottom: B,
+    ) {
+        self.split(theme, id, initial_frac, true, top, bottom);
+    }
+
+    fn split<F: FnOnce(&mut Frame), S: FnOnce(&mut Frame)>(
+        &mut self,
+        theme: &str,
+        id: &str,
+        initial_frac: f32,
+        vertical: bool,
+        first: F,
+        second: S,
+    ) {
+        let initial_frac = initial_frac.clamp(0.0, 1.0);
+        let frac = self.modify(id, |state| *state.split.get_or_insert(initial_frac));
+
+        // the component along the axis being split
+        let axis = |point: Point| if vertical { point.y } else { point.x };
+        let along = |size: f32| if vertical { Point::new(0.0, size) } else { Point::new(size, 0.0) };
+
+        let mut inner = Rect::default();
+        let mut new_frac = None;
+        self.start(theme).id(id).trigger_layout_inner(&mut inner).children(|ui| {
+            let theme_id = ui.widget(ui.parent_index()).theme_id().to_string();
+            let min_size = ui.custom_float(&theme_id, "min_pane_size", 20.0);
+            let collapse = ui.custom_float(&theme_id, "collapse_distance", 0.0);
+
+            let mut divider_rect = Rect::default();
+            let divider = ui.start("divider")
+            .id(format!("{}_divider", id))
+            .wants_mouse(true)
+            .focusable(false)
+            .trigger_layout(&mut divider_rect);
+
+            let thickness = axis(divider_rect.size);
+            let avail = (axis(inner.size) - thickness).max(0.0);
+            let first_size = split_size(frac, avail, min_size);
+
+            let offset = along(first_size);
+            let result = divider.align(Align::TopLeft).pos(offset.x, offset.y).finish();
+            if result.pressed && avail > 0.0 {
+                let mouse = axis(ui.context_internal().borrow().mouse_pos() - inner.pos) - thickness / 2.0;
+                new_frac = Some(drag_split(mouse, avail, min_size, collapse));
+            }
+
+            // each pane is clipped to itself, so its content can't spill over the other
+            let mut pane = Rect::default();
+            let size = along(first_size);
+            let builder = ui.start("first")
+            .align(Align::TopLeft)
+            .pos(0.0, 0.0)
+            .size(size.x, size.y)
+            .trigger_layout(&mut pane)
+            .clip(pane);
+            if first_size > 0.0 {
+                builder.children(first);
+            } else {
+                builder.finish();
+            }
+
+            let pos = along(first_size + thickness);
+            let size = along(avail - first_size);
+            let builder = ui.start("second")
+            .align(Align::TopLeft)
+            .pos(pos.x, pos.y)
+            .size(size.x, size.y)
+            .trigger_layout(&mut pane)
+            .clip(pane);
+            if first_size < avail {
+                builder.children(second);
+            } else {
+                builder.finish();
+            }
+        });
+
+        if let Some(frac) = new_frac {
+            self.modify(id, |state| state.split = Some(frac));
+        }
+    }
+
+    /// A simple toggle button that can be toggle on or off, based on the passed in `active` state.
+    ///
+    /// See [`button`](#method.button) for a YAML example.
+    pub fn toggle_button<T: Into<String>>(&mut self, theme: &str, label: T, active: bool) -> WidgetState {
+        self.start(theme).text(label).active(active).wants_mouse(true).finish()
+    }
+
+    /**
+    A checkbox, with a box and a `label` to the right of it.  The box is the `check` child, which is
+    [`active`](struct.WidgetBuilder.html#method.active) when `checked` is true, so its foreground image can show
+    the checkmark based on the `Active` [`AnimState`](struct.AnimState.html).  Clicking anywhere on the checkbox,
+    including the label, toggles it.  Returns the new value on the frame the checkbox is clicked, or `None` otherwise.
+    See [`tri_state_checkbox`](#method.tri_state_checkbox) for a checkbox which may also be indeterminate.
+
+    Note that the `check` child does not take the mouse, so its image states include `Disabled`.
+
+    An example YAML theme definition:
+    ```yaml
+    checkbox:
+      wants_mouse: true
+      size: [150, 24]
+      border: { width: 5 }
+      layout: Horizontal
+      layout_spacing: [5, 5]
+      child_align: Left
+      children:
+        check:
+          size: [12, 12]
+          foreground: gui/check
+        indeterminate:
+          size: [12, 12]
+          background: gui/check_normal
+          foreground: gui/check_indeterminate
+          border: { all: 3 }
+        label:
+          from: label
+          text_align: Left
+          size: [-17, 0]
+    ```
+
+    # Example
+    ```
+    fn sound_options(ui: &mut Frame, options: &mut Options) {
+        if let Some(enabled) = ui.checkbox("checkbox", "Enable Sound", options.sound_enabled) {
+            options.sound_enabled = enabled;
+        }
+    }
+    ```
+    */
+    pub fn checkbox<T: Into<String>>(&mut self, theme: &str, label: T, checked: bool) -> Option<bool> {
+        let state = if checked { CheckState::Checked } else { CheckState::Unchecked };
+        self.tri_state_checkbox(theme, label, state).map(|state| state == CheckState::Checked)
+    }
+
+    /**
+    A checkbox which may be [`Checked`](enum.CheckState.html#variant.Checked),
+    [`Unchecked`](enum.CheckState.html#variant.Unchecked), or [`Indeterminate`](enum.CheckState.html#variant.Indeterminate),
+    such as for a parent entry in a tree of settings where only some of the children are checked.  This is the same
+    as a [`checkbox`](#method.checkbox), except that when `Indeterminate`, the `indeterminate` child is shown in place of
+    the `check` child, so the theme may give it a separate foreground image.  Clicking an unchecked or indeterminate
+    checkbox checks it, while clicking a checked checkbox unchecks it.  Returns the new state on the frame the
+    checkbox is clicked, or `None` otherwise.
+
+    # Example
+    ```
+    fn all_sounds(ui: &mut Frame, sounds: &mut [bool]) {
+        let state = if sounds.iter().all(|enabled| *enabled) {
+            CheckState::Checked
+        } else if sounds.iter().any(|enabled| *enabled) {
+            CheckState::Indeterminate
+        } else {
+            CheckState::Unchecked
+        };
+
+        if let Some(state) = ui.tri_state_checkbox("checkbox", "All Sounds", state) {
+            sounds.iter_mut().for_each(|enabled| *enabled = state == CheckState::Checked);
+        }
+    }
+    ```
+    */
+    pub fn tri_state_checkbox<T: Into<String>>(&mut self, theme: &str, label: T, state: CheckState) -> Option<CheckState> {
+        let clicked = self.start(theme)
+        .active(state == CheckState::Checked)
+        .wants_mouse(true)
+        .children(|ui| {
+            match state {
+                CheckState::Indeterminate => { ui.child("indeterminate"); },
+                _ => { ui.start("check").active(state == CheckState::Checked).finish(); },
+            }
+
+            ui.label("label", label);
+        }).clicked;
+
+        if !clicked { return None; }
+
+        match state {
+            CheckState::Checked => Some(CheckState::Unchecked),
+            CheckState::Unchecked | CheckState::Indeterminate => Some(CheckState::Checked),
+        }
+    }
+
+    /**
+    A compact row of connected toggle buttons, one for each of the `labels`, of which exactly one, `selected`, is
+    active.  The first and last segments use the `segment_first` and `segment_last` child themes, allowing them to have
+    distinct end caps, while the others use `segment_mid`.  A lone segment uses `segment_first`.  Returns the index of
+    the clicked segment, or `None` if no segment or the already selected segment was clicked.  The `id` must be unique.
+
+    An example theme definition:
+    ```yaml
+    segmented:
+      layout: Horizontal
+      size_from: [Children, Children]
+      children:
+        segment_first:
+          from: button
+          background: gui/segment_left
+          size: [80, 24]
+        segment_mid:
+          from: button
+          background: gui/segment_mid
+          size: [80, 24]
+        segment_last:
+          from: button
+          background: gui/segment_right
+          size: [80, 24]
+    ```
+
+    # Example
+    ```
+    fn difficulty(ui: &mut Frame, difficulty: &mut usize) {
+        if let Some(index) = ui.segmented("segmented", "difficulty", &["Easy", "Normal", "Hard"], *difficulty) {
+            *difficulty = index;
+        }
+    }
+    ```
+    **/
+    pub fn segmented(&mut self, theme: &str, id: &str, labels: &[&str], selected: usize) -> Option<usize> {
+        let mut result = None;
 
+        self.start(theme).id(id).children(|ui| {
+            for (index, label) in labels.iter().enumerate() {
+                let segment_theme = if index == 0 {
+                    "segment_first"
+                } else if index == labels.len() - 1 {
+                    "segment_last"
+                } else {
+                    "segment_mid"
+                };
+
+                let clicked = ui.start(segment_theme)
+                .id(format!("{}_segment{}", id, index))
+                .text(*label)
+                .active(index == selected)
+                .wants_mouse(true)
+                .finish().clicked;
+
+                if clicked && index != selected {
+                    result = Some(index);
+                }
+            }
         });
-        if open_result.clicked {
-            self.open_modal(&popup_id);
-            self.close_modal_on_click_outside();
-        }
 
         result
     }
 
-    /// A simple toggle button that can be toggle on or off, based on the passed in `active` state.
-    ///
-    /// See [`button`](#method.button) for a YAML example.
-    pub fn toggle_button<T: Into<String>>(&mut self, theme: &str, label: T, active: bool) -> WidgetState {
-        self.start(theme).text(label).active(active).wants_mouse(true).finish()
-    }
-
     /**
     Creates a simple text input field.  The `id` that is passed in must be unique.
     The text input will grab keyboard focus when the user clicks on it, allowing
-    the user to type text.  The return value will be `None` if the text didn't change
+    the user to type text.  Text is inserted and deleted at the caret, which may be moved
+    with the arrow, Home, and End keys, or by clicking within the text.  The caret moves over and
+    deletes whole grapheme clusters, such as a letter with combining accents or an emoji sequence, as
+    a single character.  Text may be selected by holding Shift while moving the caret or by dragging the mouse, and the selection is
+    highlighted with the `selection` child theme.  Ctrl+A selects all text, and Ctrl+C, Ctrl+X, and
+    Ctrl+V copy, cut, and paste using the [`IO`](trait.IO.html) clipboard.  Newlines in pasted text
+    are removed.  Tab and Shift+Tab move the keyboard focus to the next or previous field.  The caret position and selection are stored in the [`PersistentState`](struct.PersistentState.html) for the `id`.  The return value will be `None` if the text didn't change
     this frame, or will contain the current text displayed by the textbox if it did
     change.  Optionally, pass an initial_value which will set the field's text if it
-    is not already set.
+    is not already set.  See [`input_field_with`](#method.input_field_with) to specify
+    additional [`options`](struct.InputFieldOptions.html).
 
     An example YAML theme definition:
     ```yaml
@@ -392,6 +2726,7 @@ impl Frame {
       border: { height: 4, width: 5 }
       background: gui/input_field
       text_align: Left
+      placeholder_color: "#888"
       wants_mouse: true
       size: [150, 24]
       child_align: TopLeft
@@ -400,6 +2735,14 @@ impl Frame {
           size: [2, -2]
           height_from: Parent
           background: gui/caret
+        selection:
+          align: TopLeft
+          background: gui/selection
+        placeholder:
+          font: small
+          text_align: Left
+          align: TopLeft
+          size_from: [Parent, Parent]
     ```
 
     # Example
@@ -412,49 +2755,347 @@ impl Frame {
     ```
     */
     pub fn input_field(&mut self, theme: &str, id: &str, initial_value: Option<String>) -> Option<String> {
+        self.input_field_with(theme, id, initial_value, InputFieldOptions::default()).text_changed
+    }
+
+    /**
+    Creates a text input field, as in [`input_field`](#method.input_field), with the specified
+    [`options`](struct.InputFieldOptions.html).  If a `placeholder` is specified, it is shown
+    using the `placeholder` child theme while the field is empty and does not have keyboard focus.
+    The placeholder text is drawn in the field theme's `placeholder_color`, or in the `placeholder`
+    child's own text color if the field does not define one.
+    Typed or pasted characters rejected by the `filter`, the `numeric` check, or beyond the
+    `max_length` are dropped before they are added to the text.  The returned
+    [`InputFieldResult`](struct.InputFieldResult.html) reports whether the text changed, whether
+    Enter was pressed, and whether the field gained or lost keyboard focus this frame.
+
+    # Example
+    ```
+    fn select_name(ui: &mut Frame, name: &mut String) {
+        let options = InputFieldOptions {
+            placeholder: Some("Enter name...".to_string()),
+            ..Default::default()
+        };
+
+        let result = ui.input_field_with("input_field", "unique_id", None, options);
+        if let Some(text) = result.text_changed {
+            *name = text;
+        }
+
+        if result.submitted || result.lost_focus {
+            println!("Name entered: {}", name);
+        }
+    }
+
+    fn enter_amount(ui: &mut Frame, amount: &mut f32) {
+        let options = InputFieldOptions {
+            max_length: Some(8),
+            numeric: true,
+            ..Default::default()
+        };
+
+        if let Some(text) = ui.input_field_with("input_field", "amount", None, options).text_changed {
+            *amount = text.parse().unwrap_or_default();
+        }
+    }
+    ```
+    */
+    pub fn input_field_with(
+        &mut self,
+        theme: &str,
+        id: &str,
+        initial_value: Option<String>,
+        options: InputFieldOptions,
+    ) -> InputFieldResult {
         let mut text_out = None;
+        let mut submitted = false;
+
+        let mut copied = None;
 
         self.modify(id, |state| {
             if state.text.is_none() {
                 state.text = Some(initial_value.unwrap_or_default());
             }
 
-            let mut text_changed = false;
-            for c in state.characters.drain(..) {
-                if c as u32 == 8 { //backspace
-                    state.text.as_mut().unwrap().pop();
-                    text_changed = true;
-                } else {
-                    state.text.as_mut().unwrap().push(c);
-                    text_changed = true;
+            let mut edit = text_edit::TextEdit::new(state.text.as_mut().unwrap(), state.caret, state.selection);
+
+            // this is a single line field, so strip any newlines, such as from pasted text
+            for c in state.characters.drain(..).filter(|c| *c != '\n' && *c != '\r') {
+                if let Some(filter) = options.filter {
+                    if !filter(c) { continue; }
+                }
+
+                if options.numeric && !edit.accepts_numeric(c) { continue; }
+
+                // ignore further typing once the limit is hit
+                if let Some(max_length) = options.max_length {
+                    if edit.len_without_selection() >= max_length { continue; }
+                }
+
+                edit.insert(c);
+            }
+
+            for event in state.keys.drain(..) {
+                if event.key == KeyCode::Return {
+                    submitted = true;
                 }
+
+                edit.apply_key(event);
             }
 
-            if text_changed {
+            state.caret = edit.caret;
+            state.selection = edit.anchor;
+            copied = edit.copied.take();
+
+            if edit.changed {
                 text_out = state.text.clone();
             }
         });
-        let mut text_pos = Point::default();
+
+        if let Some(text) = copied {
+            self.set_clipboard_text(text);
+        }
+
+        let (caret, selection, empty) = self.modify(id, |state| {
+            (state.caret, state.selection, state.text.as_deref().unwrap_or_default().is_empty())
+        });
+
+        let placeholder_color = {
+            let context = self.context_internal().borrow();
+            context.themes().theme(theme).and_then(|theme| theme.placeholder_color)
+        };
+
+        let mut inner = Rect::default();
+        let mut positions = Vec::new();
+        let mut line_height = 0.0;
 
         let result = self.start(theme)
         .id(id)
-        .trigger_text_layout(&mut text_pos)
+        .focusable(true)
+        .trigger_layout_inner(&mut inner)
+        .trigger_text_positions(&mut positions)
+        .trigger_line_height(&mut line_height)
+        .children(|ui| {
+            if empty && !ui.is_focus_keyboard(id) {
+                if let Some(placeholder) = options.placeholder {
+                    let mut builder = ui.start("placeholder");
+                    if let Some(color) = placeholder_color {
+                        builder = builder.text_color(color);
+                    }
+                    builder.text(placeholder).finish();
+                }
+            }
+
+            ui.text_edit_children(id, &positions, line_height, caret, selection);
+        });
+
+        self.text_edit_mouse(id, &result, &positions, inner);
+
+        // focus may have been taken by a click elsewhere or by another widget since this was last built
+        let focused = self.is_focus_keyboard(id);
+        let was_focused = self.modify(id, |state| std::mem::replace(&mut state.focused, focused));
+
+        InputFieldResult {
+            text_changed: text_out,
+            submitted,
+            gained_focus: focused && !was_focused,
+            lost_focus: !focused && was_focused,
+        }
+    }
+
+    /**
+    Creates a multi-line text area.  The `id` that is passed in must be unique.  This
+    behaves like an [`input_field`](#method.input_field), including caret movement,
+    selection, and the clipboard, but the Enter key inserts a new line and the Up and Down
+    keys move the caret between lines.  Text wraps at the width of the area, and the area
+    is a [`scrollpane`](#method.scrollpane) so long text may be scrolled vertically.  The
+    scrollpane content will use the id `"{id}_content"`.  The text, caret, and selection
+    are stored in the [`PersistentState`](struct.PersistentState.html) for the `id`.
+    The return value will be `None` if the text didn't change this frame, or will contain
+    the current text if it did change.
+
+    An example YAML theme definition:
+    ```yaml
+    text_area:
+      from: scrollpane_vertical
+      height_from: Normal
+      size: [0, 150]
+      background: gui/input_field
+      children:
+        content:
+          children:
+            text:
+              font: small
+              wants_mouse: true
+              text_align: TopLeft
+              border: { height: 4, width: 5 }
+              size_from: [Parent, Parent]
+              child_align: TopLeft
+              children:
+                caret:
+                  font: small
+                  size: [2, 0]
+                  height_from: FontLine
+                  background: gui/caret
+                selection:
+                  align: TopLeft
+                  background: gui/selection
+    ```
+
+    # Example
+    ```
+    fn edit_notes(ui: &mut Frame, notes: &mut String) {
+        if let Some(text) = ui.text_area("text_area", "notes") {
+            *notes = text;
+        }
+    }
+    ```
+    */
+    pub fn text_area(&mut self, theme: &str, id: &str) -> Option<String> {
+        let mut text_out = None;
+        let mut copied = None;
+        let mut caret_moved = false;
+
+        let mut inner = Rect::default();
+        let mut positions = Vec::new();
+        let mut line_height = 0.0;
+        let mut caret = 0;
+
+        let content_id = format!("{}_content", id);
+
+        self.start(theme)
+        .scrollpane(&content_id)
+        .show_horizontal_scrollbar(ShowElement::Never)
         .children(|ui| {
-            if ui.is_focus_keyboard(id) {
-                ui.start("caret").pos(text_pos.x, text_pos.y).finish();
+            let builder = ui.start("text")
+            .id(id)
+            .focusable(true)
+            .trigger_layout_inner(&mut inner)
+            .trigger_line_height(&mut line_height);
+
+            let (mut text, state_caret, anchor, characters, keys) = builder.frame.modify(id, |state| {
+                (
+                    state.text.take().unwrap_or_default(),
+                    state.caret,
+                    state.selection,
+                    std::mem::take(&mut state.characters),
+                    std::mem::take(&mut state.keys),
+                )
+            });
+            caret_moved = !characters.is_empty() || !keys.is_empty();
+
+            let mut edit = text_edit::TextEdit::new(&mut text, state_caret, anchor);
+            for c in characters.into_iter().filter(|c| *c != '\r') {
+                edit.insert(c);
+            }
+
+            for event in keys {
+                match event.key {
+                    KeyCode::Return => edit.insert('\n'),
+                    KeyCode::Up | KeyCode::Down => {
+                        // vertical movement depends on the layout of the text as edited so far
+                        let lines = if event.key == KeyCode::Up { -1.0 } else { 1.0 };
+                        let positions = builder.layout_text_positions(edit.text());
+                        edit.move_vertical(&positions, line_height, lines, event.modifiers.shift);
+                    },
+                    _ => edit.apply_key(event),
+                }
             }
+
+            caret = edit.caret;
+            let selection = edit.anchor;
+            copied = edit.copied.take();
+            if edit.changed {
+                text_out = Some(text.clone());
+            }
+
+            builder.frame.modify(id, |state| {
+                state.text = Some(text);
+                state.caret = caret;
+                state.selection = selection;
+            });
+
+            let mut builder = builder.trigger_text_positions(&mut positions);
+
+            // grow to fit all lines of text, so the scrollpane can scroll through them
+            let text_height = positions.iter().map(|pos| pos.y).fold(0.0, f32::max) + line_height;
+            let border = builder.widget.border().vertical();
+            builder.expand_height(text_height + border);
+
+            let result = builder.children(|ui| {
+                ui.text_edit_children(id, &positions, line_height, caret, selection);
+            });
+
+            ui.text_edit_mouse(id, &result, &positions, inner);
         });
 
-        if result.clicked {
-            self.focus_keyboard(id);
+        if let Some(text) = copied {
+            self.set_clipboard_text(text);
+        }
+
+        // keep the caret in view when it is moved with the keyboard
+        if let (true, Some(caret_pos)) = (caret_moved, positions.get(caret)) {
+            let view_height = inner.size.y;
+            self.modify(&content_id, |state| {
+                let view_top = -state.scroll.y;
+                if caret_pos.y < view_top {
+                    state.scroll.y = -caret_pos.y;
+                } else if caret_pos.y + line_height > view_top + view_height {
+                    state.scroll.y = view_height - caret_pos.y - line_height;
+                }
+            });
         }
 
         text_out
     }
 
+    // draws the selection highlight and caret for a focused text editing widget
+    fn text_edit_children(&mut self, id: &str, positions: &[Point], line_height: f32, caret: usize, selection: Option<usize>) {
+        if !self.is_focus_keyboard(id) { return; }
+
+        // the selection highlight backgrounds are drawn behind the parent text
+        if let Some((start, end)) = text_edit::selection_range(caret, selection) {
+            for (pos, size) in text_edit::selection_rects(positions, start, end, line_height) {
+                self.start("selection").pos(pos.x, pos.y).size(size.x, size.y).finish();
+            }
+        }
+
+        let caret_pos = positions.get(caret).copied().unwrap_or_default();
+        self.start("caret").pos(caret_pos.x, caret_pos.y).finish();
+    }
+
+    // focuses a text editing widget when it is pressed and handles mouse caret placement and selection.
+    // `inner` is the widget's inner area, which `positions` are relative to
+    fn text_edit_mouse(&mut self, id: &str, result: &WidgetState, positions: &[Point], inner: Rect) {
+        if result.pressed {
+            self.focus_keyboard(id);
+
+            let (mouse_pos, just_pressed, shift) = {
+                let context = self.context_internal().borrow();
+                (context.mouse_pos(), context.mouse_just_pressed(0), context.input_modifiers().shift)
+            };
+            // a new press starts a new selection, unless shift is held to extend it.
+            // dragging extends the selection from the point of the press
+            self.modify(id, |state| {
+                let caret = text_edit::hit_test(state.text.as_deref().unwrap_or_default(), positions, mouse_pos - inner.pos);
+                if just_pressed && !shift {
+                    state.selection = Some(caret);
+                } else if state.selection.is_none() {
+                    state.selection = Some(state.caret);
+                }
+                state.caret = caret;
+            });
+        }
+
+        if result.clicked {
+            self.focus_keyboard(id);
+        }
+    }
+
     /**
     Creates a simple progress bar.  The drawing will be clipped based on the size
-    of the widget and the passed in `frac`.
+    of the widget and the passed in `frac`, which is clamped to [0, 1].  The bar fills
+    from left to right; see [`progress_bar_with`](#method.progress_bar_with) for other directions.
 
     An example YAML theme definition:
     ```yaml
@@ -471,20 +3112,116 @@ impl Frame {
     ```
     **/
     pub fn progress_bar(&mut self, theme: &str, frac: f32) {
-        self.start(theme)
-        .children(|ui| {
+        self.progress_bar_with(theme, frac, ProgressBarOptions::default());
+    }
+
+    /**
+    Creates a progress bar, as in [`progress_bar`](#method.progress_bar), with the specified
+    [`options`](struct.ProgressBarOptions.html).  The `bar` is clipped from the side opposite
+    the one it fills from, so a bar filling upward shows its bottom portion.
+
+    If `text` is specified, it is shown centered over the bar in the `text` child.  If an `id` and
+    `animation_millis` are specified, the bar moves smoothly toward `frac` rather than jumping to it.  The
+    shown fraction is stored in the [`PersistentState`](struct.PersistentState.html) for the `id`.  If
+    `lag_bar` is set, the `lag` child is drawn behind the `bar`, covering the gap between the shown fraction
+    and `frac`.  The `bar` then jumps to a lower `frac` right away while the `lag` trails behind, and the
+    `bar` grows into the `lag` for a higher `frac`.
+
+    An example YAML theme definition, see [`progress_bar`](#method.progress_bar) for the remainder of the theme:
+    ```yaml
+    health_bar:
+      from: progress_bar
+      children:
+        lag:
+          background: gui/progress_bar_lag
+          size_from: [Parent, Parent]
+        text:
+          font: small
+          text_align: Center
+          size_from: [Parent, Parent]
+    ```
+
+    # Example
+    ```
+    fn health_bar(ui: &mut Frame, health: f32, max_health: f32) {
+        let options = ProgressBarOptions {
+            direction: FillDirection::BottomToTop,
+            text: Some(format!("{} / {}", health, max_health)),
+            id: Some("player_health".to_string()),
+            animation_millis: 500,
+            lag_bar: true,
+        };
+
+        ui.progress_bar_with("health_bar", health / max_health, options);
+    }
+    ```
+    **/
+    pub fn progress_bar_with(&mut self, theme: &str, frac: f32, options: ProgressBarOptions) {
+        let frac = frac.clamp(0.0, 1.0);
+        let shown = match &options.id {
+            Some(id) if options.animation_millis > 0 => self.animate_progress(id, frac, options.animation_millis),
+            _ => frac,
+        };
+
+        let (bar, lag) = if options.lag_bar {
+            (shown.min(frac), Some(shown.max(frac)))
+        } else {
+            (shown, None)
+        };
+
+        let mut builder = self.start(theme);
+        if let Some(id) = &options.id {
+            builder = builder.id(id);
+        }
+
+        let direction = options.direction;
+        builder.children(|ui| {
+            if let Some(lag) = lag {
+                let mut rect = Rect::default();
+
+                ui.start("lag")
+                .trigger_layout(&mut rect)
+                .clip(fill_rect(rect, lag, direction))
+                .finish();
+            }
+
             let mut rect = Rect::default();
 
             ui.start("bar")
             .trigger_layout(&mut rect)
-            .clip(Rect::new(rect.pos, Point::new(rect.size.x * frac, rect.size.y)))
+            .clip(fill_rect(rect, bar, direction))
             .finish();
+
+            if let Some(text) = options.text {
+                ui.start("text").text(text).finish();
+            }
         });
     }
 
+    // moves the fraction shown by the progress bar with `id` toward `frac`, crossing the whole bar
+    // in `millis`, and returns the new fraction
+    fn animate_progress(&mut self, id: &str, frac: f32, millis: u32) -> f32 {
+        let now = self.cur_time_millis();
+        self.modify(id, |state| {
+            let shown = match state.progress {
+                None => frac,
+                Some(shown) => {
+                    let max_change = now.saturating_sub(state.progress_millis) as f32 / millis as f32;
+                    shown + (frac - shown).max(-max_change).min(max_change)
+                }
+            };
+
+            state.progress = Some(shown);
+            state.progress_millis = now;
+            shown
+        })
+    }
+
     /**
     Creates a simple tooltip with the specified text.  The tooltip is placed based on the
-    position of the mouse.
+    position of the mouse, and is moved as needed to stay within the display.  It is drawn on
+    top of all other widgets.  See also [`WidgetBuilder::tooltip`](struct.WidgetBuilder.html#method.tooltip),
+    which shows a tooltip automatically after the mouse has stayed over a widget.
 
     An example YAML theme definition:
     ```yaml
@@ -495,15 +3232,224 @@ impl Frame {
     ```
     **/
     pub fn tooltip<T: Into<String>>(&mut self, theme: &str, label: T) {
-        let mouse = self.mouse_rect();
+        let label = label.into();
+        self.show_tooltip(theme, Some(&label), None);
+    }
+
+    /**
+    Creates a full screen widget with the specified `theme` and `id`, in its own render group, which blocks
+    the mouse from reaching any widgets beneath it.  With a translucent background, this dims the rest of the
+    UI.  The `children` closure is called to add content on top of the blocker, such as a dialog.  The blocker
+    starts closed, and should usually be opened with [`open_modal`](#method.open_modal) so that it and its
+    children are the only widgets receiving input.  Closing the `id` closes the blocker and its content.
+    See [`confirm_dialog`](#method.confirm_dialog) for an example.
+
+    An example YAML theme definition, with a `gui/blocker` image defined with a translucent `color`:
+    ```yaml
+    modal_blocker:
+      background: gui/blocker
+      wants_mouse: true
+      child_align: Center
+    ```
+    */
+    pub fn modal_blocker<F: FnOnce(&mut Frame)>(&mut self, theme: &str, id: &str, children: F) -> WidgetState {
+        self.context_internal().borrow_mut().init_state(id, false, true);
+        let display_size = self.display_size();
 
         self.start(theme)
+        .id(id)
         .unclip()
         .unparent()
-        .text(label)
-        .screen_pos(mouse.right(), mouse.bot())
+        .size(display_size.x, display_size.y)
+        .screen_pos(0.0, 0.0)
         .new_render_group()
-        .finish();
+        .wants_mouse(true)
+        .focusable(false)
+        .children(children)
+    }
+
+    /**
+    Creates a confirmation dialog with the specified `message` and `yes` and `no` buttons, centered over a
+    [`modal_blocker`](#method.modal_blocker).  The dialog starts closed and is shown once opened with
+    [`open_modal`](#method.open_modal), using the specified `id`.  While open, the `yes` button takes the
+    keyboard focus, so Enter chooses yes, while Escape chooses no.  On the frame a choice is made, the
+    dialog closes itself and returns `Some(true)` for yes and `Some(false)` for no.  Otherwise, returns `None`.
+    The text of the buttons is set in the theme.
+
+    An example YAML theme definition:
+    ```yaml
+    confirm_dialog:
+      from: modal_blocker
+      children:
+        panel:
+          background: gui/window_bg
+          border: { all: 10 }
+          size_from: [Children, Children]
+          layout: Vertical
+          layout_spacing: [10, 10]
+          child_align: TopLeft
+          children:
+            message:
+              from: label
+              size_from: [Normal, FontLine]
+              size: [300, 0]
+            buttons:
+              size_from: [Children, Children]
+              layout: Horizontal
+              layout_spacing: [10, 10]
+              children:
+                yes:
+                  from: button
+                  text: "Yes"
+                no:
+                  from: button
+                  text: "No"
+    ```
+
+    # Example
+    ```
+    fn delete_save(ui: &mut Frame, saves: &mut Vec<String>) {
+        if ui.button("button", "Delete").clicked {
+            ui.open_modal("delete_confirm");
+        }
+
+        if let Some(true) = ui.confirm_dialog("confirm_dialog", "delete_confirm", "Delete the save game?") {
+            saves.pop();
+        }
+    }
+    ```
+    */
+    pub fn confirm_dialog<T: Into<String>>(&mut self, theme: &str, id: &str, message: T) -> Option<bool> {
+        let yes_id = format!("{}_yes", id);
+        let no_id = format!("{}_no", id);
+        let mut choice = None;
+
+        self.modal_blocker(theme, id, |ui| {
+            ui.start("panel").children(|ui| {
+                ui.start("message").text(message).finish();
+                ui.start("buttons").children(|ui| {
+                    if ui.start("yes").id(&yes_id).wants_mouse(true).finish().clicked {
+                        choice = Some(true);
+                    }
+
+                    if ui.start("no").id(&no_id).wants_mouse(true).finish().clicked {
+                        choice = Some(false);
+                    }
+                });
+            });
+        });
+
+        if !self.is_open(id) { return None; }
+
+        if !self.is_focus_keyboard(&yes_id) && !self.is_focus_keyboard(&no_id) {
+            self.focus_keyboard(&yes_id);
+        }
+
+        let escaped = [&yes_id, &no_id].iter().any(|button| {
+            self.keys_pressed(button).any(|event| event.key == KeyCode::Escape)
+        });
+        if escaped && choice.is_none() {
+            self.context_internal().borrow_mut().dismiss_modal(id);
+            choice = Some(false);
+        }
+
+        if choice.is_some() {
+            self.close(id);
+        }
+
+        choice
+    }
+
+    /**
+    Shows the notifications queued with [`push_toast`](#method.push_toast) that have not yet expired, each as
+    a `toast` child of a widget with the specified `theme`.  This should be called once per frame at the top
+    level of the UI, outside of any other widget, after the rest of the UI has been built.  The widget's
+    `align` places the toasts in a corner of the display and its `layout` stacks them, oldest first.  The
+    toasts are drawn above all windows.
+
+    For the last `fade_time` custom float milliseconds, defaulting to 500, each toast is given the `Fading`
+    [`AnimStateKey`](enum.AnimStateKey.html), with its base time set to when it started fading, so timed
+    images can fade it out.  Clicking a toast dismisses it early.  If more toasts are queued than the
+    `max_toasts` custom float, defaulting to 5, the oldest are dropped.
+
+    An example YAML theme definition:
+    ```yaml
+    toasts:
+      align: BotRight
+      pos: [10, 10]
+      size_from: [Children, Children]
+      layout: Vertical
+      layout_spacing: [5, 5]
+      custom_floats:
+        fade_time: 500
+        max_toasts: 5
+      children:
+        toast:
+          from: label
+          background: gui/window_bg
+          wants_mouse: true
+          size: [250, 30]
+          size_from: [Normal, Normal]
+    ```
+
+    # Example
+    ```
+    fn build_ui(ui: &mut Frame, saved: bool) {
+        if saved {
+            ui.push_toast("Game saved", 3000);
+        }
+
+        // build the rest of the UI here
+
+        ui.render_toasts("toasts");
+    }
+    ```
+    */
+    pub fn render_toasts(&mut self, theme: &str) {
+        let builder = self.start(theme);
+        let theme_id = builder.widget.theme_id().to_string();
+        let fade_time = builder.frame.custom_float(&theme_id, "fade_time", 500.0) as u32;
+        let max_toasts = builder.frame.custom_float(&theme_id, "max_toasts", 5.0).max(0.0) as usize;
+
+        let toasts = builder.frame.context_internal().borrow_mut().update_toasts(max_toasts);
+        if toasts.is_empty() { return; }
+
+        let now = builder.frame.cur_time_millis();
+        let first_group = builder.frame.num_render_groups();
+        let mut dismissed = None;
+
+        builder
+        .unclip()
+        .unparent()
+        .new_render_group()
+        .children(|ui| {
+            for toast in &toasts {
+                let id = format!("toast_{}", toast.id);
+                let fade_start = toast.expire_millis - fade_time.min(toast.duration_millis);
+                let fading = now >= fade_start;
+                if fading {
+                    ui.set_base_time_millis(&id, fade_start);
+                }
+
+                let result = ui.start("toast")
+                .id(&id)
+                .text(&toast.text)
+                .wants_mouse(true)
+                .focusable(false)
+                .fading(fading)
+                .finish();
+
+                if result.clicked {
+                    dismissed = Some(toast.id);
+                }
+            }
+        });
+
+        self.set_always_on_top(first_group);
+
+        if let Some(id) = dismissed {
+            self.context_internal().borrow_mut().dismiss_toast(id);
+        }
     }
 
     /**
@@ -545,4 +3491,319 @@ impl Frame {
     pub fn scrollpane<F: FnOnce(&mut Frame)>(&mut self, theme: &str, content_id: &str, children: F) {
         self.start(theme).scrollpane(content_id).children(children);
     }
-}
\ No newline at end of file
+}
+
+/// The state of a [`tri_state_checkbox`](struct.Frame.html#method.tri_state_checkbox).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CheckState {
+    /// The checkbox is not checked.
+    Unchecked,
+
+    /// The checkbox is checked.
+    Checked,
+
+    /// The checkbox is partially checked, such as when only some of its children are checked.
+    Indeterminate,
+}
+
+/// Additional options for an [`input field`](struct.Frame.html#method.input_field_with).
+#[derive(Default)]
+pub struct InputFieldOptions {
+    /// Hint text shown while the field is empty and does not have keyboard focus, such as
+    /// `"Enter name..."`.  The placeholder is only displayed and is never part of the field's text.
+    /// The default value is `None`.
+    pub placeholder: Option<String>,
+
+    /// The maximum number of characters in the field.  Once the limit is reached, further
+    /// characters are ignored.  The default value is `None`, for no limit.
+    pub max_length: Option<usize>,
+
+    /// A filter applied to each character before it is added to the field.  Characters for which
+    /// the filter returns `false` are dropped.  The default value is `None`, accepting all characters.
+    pub filter: Option<fn(char) -> bool>,
+
+    /// Whether to only accept numeric input: digits, at most one `.` decimal separator, and an
+    /// optional leading `-`.  This is applied in addition to any `filter`.  The default value is `false`.
+    pub numeric: bool,
+}
+
+// the part of `rect` covered by a bar filled to `frac` in `direction`.  `frac` is clamped to [0, 1]
+fn fill_rect(rect: Rect, frac: f32, direction: FillDirection) -> Rect {
+    let frac = frac.clamp(0.0, 1.0);
+    let fill = Point::new(rect.size.x * frac, rect.size.y * frac);
+
+    use FillDirection::*;
+    match direction {
+        LeftToRight => Rect::new(rect.pos, Point::new(fill.x, rect.size.y)),
+        RightToLeft => Rect::new(
+            Point::new(rect.pos.x + rect.size.x - fill.x, rect.pos.y),
+            Point::new(fill.x, rect.size.y),
+        ),
+        BottomToTop => Rect::new(
+            Point::new(rect.pos.x, rect.pos.y + rect.size.y - fill.y),
+            Point::new(rect.size.x, fill.y),
+        ),
+        TopToBottom => Rect::new(rect.pos, Point::new(rect.size.x, fill.y)),
+    }
+}
+
+// the size of the first pane of a split with `avail` space between its panes, keeping both panes at least
+// `min_size` unless one is collapsed
+fn split_size(frac: f32, avail: f32, min_size: f32) -> f32 {
+    if frac <= 0.0 || frac >= 1.0 {
+        return frac.clamp(0.0, 1.0) * avail;
+    }
+
+    let max = (avail - min_size).max(min_size);
+    (frac * avail).clamp(min_size, max).min(avail)
+}
+
+// the split fraction with the divider dragged to `pos` along `avail`, collapsing the pane on either
+// side when within `collapse` of its edge
+fn drag_split(pos: f32, avail: f32, min_size: f32, collapse: f32) -> f32 {
+    if collapse > 0.0 && pos < collapse {
+        0.0
+    } else if collapse > 0.0 && pos > avail - collapse {
+        1.0
+    } else {
+        let max = (avail - min_size).max(min_size);
+        pos.clamp(min_size, max).min(avail) / avail
+    }
+}
+
+// splits `values` into one column per pixel across `width`, giving the low and high value of each column.  For
+// `bars`, both are the highest value.  Otherwise they are in the order they occur, so a polyline through them keeps
+// its shape
+fn plot_columns(values: &[f32], width: f32, bars: bool) -> Vec<(f32, f32)> {
+    let count = values.len().min((width as usize).max(1));
+
+    (0..count).map(|column| {
+        let start = column * values.len() / count;
+        let end = ((column + 1) * values.len() / count).max(start + 1);
+        let slice = &values[start..end];
+
+        let (mut low, mut high) = (0, 0);
+        for (i, value) in slice.iter().enumerate() {
+            if *value < slice[low] { low = i; }
+            if *value > slice[high] { high = i; }
+        }
+
+        if bars {
+            (slice[high], slice[high])
+        } else if low < high {
+            (slice[low], slice[high])
+        } else {
+            (slice[high], slice[low])
+        }
+    }).collect()
+}
+
+// the index of the value in a plot of `len` values across `width` nearest to `x`
+fn plot_index(len: usize, width: f32, x: f32, bars: bool) -> Option<usize> {
+    if len == 0 || width <= 0.0 { return None; }
+
+    let index = if bars {
+        (x / width * len as f32).floor()
+    } else {
+        (x / width * (len - 1) as f32).round()
+    };
+
+    Some((index.max(0.0) as usize).min(len - 1))
+}
+
+// the index of the gap between `rows`, which are laid out vertically, closest to the mouse at `mouse_y`
+fn drop_gap(rows: &[Rect], mouse_y: f32) -> usize {
+    rows.iter().take_while(|row| row.pos.y + row.size.y / 2.0 < mouse_y).count()
+}
+
+// builds a single run of a rich label, sized to fit its text
+fn rich_run(ui: &mut Frame, run: &Run) {
+    match run {
+        Run::Icon { id, font } => {
+            let size = rich_icon_size(ui, id, font.as_deref());
+            let builder = ui.start("icon").foreground(id);
+            match size {
+                None => builder.finish(),
+                Some(size) => builder.size(size.x, size.y).finish(),
+            };
+        },
+        Run::Text { text, style } => {
+            let mut builder = ui.start("text").wrap(false);
+            if let Some(font) = &style.font {
+                builder = builder.font(font);
+            }
+            if let Some(color) = style.color {
+                builder = builder.text_color(color);
+            }
+
+            let width = builder.text_width(text);
+            builder.text(text.as_str()).min_width(width).finish();
+        },
+    }
+}
+
+// the size of an icon in a rich label, with the height of a line of the surrounding text and
+// the width keeping the image's aspect ratio.  The text is drawn with the sibling `text` theme,
+// in `font` if specified.  Returns `None` if there is no font to size the icon from
+fn rich_icon_size(ui: &Frame, image: &str, font: Option<&str>) -> Option<Point> {
+    let context = ui.context_internal().borrow();
+    let themes = context.themes();
+
+    let text_theme = themes.theme(&format!("{}/text", ui.widget(ui.parent_index()).theme_id()));
+    let font = match font {
+        Some(id) => themes.find_font(Some(id)),
+        None => text_theme.and_then(|theme| theme.font),
+    }?;
+    let font = match text_theme {
+        None => font,
+        Some(theme) => font.with_spacing(theme.letter_spacing, theme.line_height),
+    };
+
+    let height = font.line_height;
+    let base_size = themes.find_image(Some(image)).map(|handle| themes.image(handle).base_size());
+    let width = match base_size {
+        Some(base) if base.x > 0.0 && base.y > 0.0 => height * base.x / base.y,
+        _ => height,
+    };
+    Some(Point::new(width, height))
+}
+
+// builds a plain text combo box entry, returning whether it was clicked
+fn text_entry<T: Display>(ui: &mut Frame, value: &T, id: String, highlight: bool) -> bool {
+    ui.start("entry").id(id).text(value.to_string()).wants_mouse(true).highlight(highlight).finish().clicked
+}
+
+// the indices of the `values` whose `Display` output contains `filter`, ignoring case
+fn filter_matches<T: Display>(values: &[T], filter: &str) -> Vec<usize> {
+    let filter = filter.to_lowercase();
+    values.iter()
+        .enumerate()
+        .filter(|(_, value)| value.to_string().to_lowercase().contains(&filter))
+        .map(|(index, _)| index)
+        .collect()
+}
+
+// positions a popup placed relative to `anchor` so it stays on the display, opening it on the
+// other side of the anchor if it would extend past the bottom of the display
+fn popup_pos(anchor: Rect, popup: Rect, display_size: Point) -> Point {
+    let mut pos = popup.pos;
+
+    if popup.bot() > display_size.y {
+        let gap = popup.pos.y - anchor.bot();
+        let flipped = anchor.pos.y - gap - popup.size.y;
+
+        // stay below the anchor if there is even less room above it
+        if flipped >= 0.0 || -flipped < popup.bot() - display_size.y {
+            pos.y = flipped;
+        }
+    }
+
+    pos.x = pos.x.min(display_size.x - popup.size.x).max(0.0);
+    pos.y = pos.y.min(display_size.y - popup.size.y).max(0.0);
+    pos
+}
+
+/// Options for a [`labeled slider`](struct.Frame.html#method.labeled_slider).
+#[derive(Debug, Default, Copy, Clone)]
+pub struct LabeledSliderOptions {
+    /// Whether to show the value on the slider button, rather than in the `value` label to the right of
+    /// the slider.  The default value is `false`.
+    pub value_in_button: bool,
+
+    /// Whether to show the `min_label` and `max_label` captions at either end of the slider.  The default
+    /// value is `false`.
+    pub captions: bool,
+
+    /// The step to snap the value to, as in [`horizontal_slider_with`](struct.Frame.html#method.horizontal_slider_with).
+    /// The default value is `None`.
+    pub step: Option<f32>,
+}
+
+/// Options for a [`progress bar`](struct.Frame.html#method.progress_bar_with).
+#[derive(Debug, Default, Clone)]
+pub struct ProgressBarOptions {
+    /// The direction the bar fills in as its fraction increases.  The default value is
+    /// [`LeftToRight`](enum.FillDirection.html#variant.LeftToRight).
+    pub direction: FillDirection,
+
+    /// Text shown centered over the bar, such as `"1450 / 2000"`.  The default value is `None`.
+    pub text: Option<String>,
+
+    /// The `id` of the progress bar, which must be unique.  This is required for animation, as the
+    /// fraction being shown is stored in the bar's [`PersistentState`](struct.PersistentState.html).
+    /// The default value is `None`.
+    pub id: Option<String>,
+
+    /// The time, in milliseconds, for the bar to animate across its whole length.  Smaller changes
+    /// take proportionally less time.  Zero, the default value, shows changes immediately.
+    pub animation_millis: u32,
+
+    /// Whether to draw the `lag` child, which trails behind the bar as it changes.  The default value
+    /// is `false`.
+    pub lag_bar: bool,
+}
+
+/// The direction that a [`progress bar`](struct.Frame.html#method.progress_bar_with) fills in.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FillDirection {
+    /// The bar grows from the left edge toward the right.
+    LeftToRight,
+
+    /// The bar grows from the right edge toward the left.
+    RightToLeft,
+
+    /// The bar grows from the bottom edge upward.
+    BottomToTop,
+
+    /// The bar grows from the top edge downward.
+    TopToBottom,
+}
+
+impl Default for FillDirection {
+    fn default() -> Self { FillDirection::LeftToRight }
+}
+
+/// A column in a [`table`](struct.Frame.html#method.table).
+#[derive(Debug, Clone)]
+pub struct Column {
+    /// The title shown in the column's header.
+    pub title: String,
+
+    /// The initial width of the column, in logical pixels.  The user may resize the column from this width.
+    pub width: f32,
+}
+
+impl Column {
+    /// Creates a new column with the specified `title` and initial `width`.
+    pub fn new<T: Into<String>>(title: T, width: f32) -> Column {
+        Column { title: title.into(), width }
+    }
+}
+
+/// The result of a slider, such as a [`horizontal slider`](struct.Frame.html#method.horizontal_slider_with),
+/// on the current frame.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SliderResult {
+    /// The new value if the user moved the slider this frame, or `None` otherwise.
+    pub value: Option<f32>,
+
+    /// Whether the user let go of the slider this frame, after dragging or pressing on it.
+    pub released: bool,
+}
+
+/// The result of an [`input field`](struct.Frame.html#method.input_field_with) on the current frame.
+#[derive(Debug, Clone)]
+pub struct InputFieldResult {
+    /// The current text of the field if it changed this frame, or `None` otherwise.
+    pub text_changed: Option<String>,
+
+    /// Whether Enter was pressed while the field had keyboard focus this frame.
+    pub submitted: bool,
+
+    /// Whether the field gained keyboard focus this frame.
+    pub gained_focus: bool,
+
+    /// Whether the field lost keyboard focus since it was last built, either from a click
+    /// elsewhere or from another widget taking focus.
+    pub lost_focus: bool,
+}
@@ -0,0 +1,203 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use serde::{Serialize, Deserialize};
+
+use crate::context::ContextInternal;
+use crate::{Context, Frame, InputModifiers, KeyCode, Point};
+
+/// A single input passed to the [`Context`](struct.Context.html), as captured in an
+/// [`InputLog`](struct.InputLog.html).  Positions and sizes are as sent by the
+/// [`IO`](trait.IO.html) backend, in physical pixels.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum InputEvent {
+    /// The input state when recording started.  This is always the first event in a log.
+    Snapshot {
+        /// The display size
+        display_size: Point,
+
+        /// The display scale factor
+        scale_factor: f32,
+
+        /// The mouse position
+        mouse_pos: Point,
+
+        /// Whether each mouse button was held down, where index 0 is the left button
+        mouse_pressed: [bool; 3],
+
+        /// The keyboard modifier keys that were held down
+        modifiers: InputModifiers,
+    },
+
+    /// The mouse was moved to the specified position
+    MousePos(Point),
+
+    /// The mouse button with the specified index was pressed or released
+    MousePressed {
+        /// The mouse button index, where 0 is the left button
+        index: usize,
+
+        /// Whether the button was pressed, rather than released
+        pressed: bool,
+    },
+
+    /// The mouse wheel was moved by the specified amount, in pixels
+    MouseWheel(Point),
+
+    /// The mouse wheel was moved by the specified amount, in lines
+    MouseWheelLines(Point),
+
+    /// The keyboard modifier keys changed
+    Modifiers(InputModifiers),
+
+    /// A character was typed
+    Character(char),
+
+    /// A single key press was sent, without repeat
+    Key(KeyCode),
+
+    /// A key was pressed or released.  Held keys repeat based on `time_millis`, the UI time
+    /// when the event was received.
+    KeyPressed {
+        /// The key
+        key: KeyCode,
+
+        /// Whether the key was pressed, rather than released
+        pressed: bool,
+
+        /// The UI time, in milliseconds, when the key was pressed or released
+        time_millis: u32,
+    },
+
+    /// The display was resized to the specified size
+    DisplaySize(Point),
+
+    /// The display scale factor changed
+    ScaleFactor(f32),
+
+    /// A [`Frame`](struct.Frame.html) was created at the specified UI time, in milliseconds.  Inputs
+    /// are applied to the next frame after them.
+    Frame {
+        /// The UI time, in milliseconds
+        time_millis: u32,
+    },
+}
+
+/**
+A log of all inputs passed to a [`Context`](struct.Context.html), along with the times of each
+[`Frame`](struct.Frame.html), for regression tests and bug reports.  Record a log with
+[`Context::start_recording`](struct.Context.html#method.start_recording) and then replay it later with
+[`replay`](#method.replay), against the same UI code.  See also [`Recorder`](struct.Recorder.html).  This works with any [`IO`](trait.IO.html) backend,
+so you can record using your normal backend and replay with a [`HeadlessIo`](struct.HeadlessIo.html) and
+[`HeadlessRenderer`](struct.HeadlessRenderer.html).  The log may be saved and loaded with any `serde` format.
+
+# Example
+```
+fn replay_bug_report(log: &InputLog, context: &mut Context, renderer: &mut HeadlessRenderer, app: &mut App) {
+    log.replay(context, |mut ui| {
+        build_ui(&mut ui, app);
+        renderer.draw_frame(ui);
+    });
+
+    assert_eq!(app.party.members.len(), 2);
+}
+```
+*/
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct InputLog {
+    events: Vec<InputEvent>,
+}
+
+impl InputLog {
+    /// Returns all events in this log, in the order they were received
+    pub fn events(&self) -> &[InputEvent] { &self.events }
+
+    /// Returns the number of frames in this log
+    pub fn frames(&self) -> usize {
+        self.events.iter().filter(|event| matches!(event, InputEvent::Frame { .. })).count()
+    }
+
+    pub(crate) fn push(&mut self, event: InputEvent) {
+        self.events.push(event);
+    }
+
+    /// Replays this log against the specified `context`.  The context's input state is first set from the
+    /// log's [`Snapshot`](enum.InputEvent.html#variant.Snapshot).  The recorded inputs are then sent to the context
+    /// and, for each recorded frame, a new [`Frame`](struct.Frame.html) is created at the recorded time and
+    /// passed to `build_frame`, which should build the UI and then draw or otherwise finish the frame.
+    /// The context's time is left fixed at the time of the last frame afterwards; use
+    /// [`Context::use_wall_clock_time`](struct.Context.html#method.use_wall_clock_time) to resume normal timing.
+    pub fn replay<F: FnMut(Frame)>(&self, context: &mut Context, mut build_frame: F) {
+        for event in &self.events {
+            match *event {
+                InputEvent::Snapshot { display_size, scale_factor, mouse_pos, mouse_pressed, modifiers } => {
+                    context.restore_snapshot(display_size, scale_factor, mouse_pos, mouse_pressed, modifiers);
+                },
+                InputEvent::MousePos(pos) => context.set_mouse_pos(pos),
+                InputEvent::MousePressed { index, pressed } => context.set_mouse_pressed(pressed, index),
+                InputEvent::MouseWheel(delta) => context.add_mouse_wheel(delta),
+                InputEvent::MouseWheelLines(delta) => context.add_mouse_wheel_lines(delta),
+                InputEvent::Modifiers(modifiers) => context.set_input_modifiers(modifiers),
+                InputEvent::Character(c) => context.push_character(c),
+                InputEvent::Key(key) => context.push_key(key),
+                InputEvent::KeyPressed { key, pressed, time_millis } => {
+                    context.set_time_millis(time_millis);
+                    context.set_key_pressed(key, pressed);
+                },
+                InputEvent::DisplaySize(size) => context.set_display_size(size),
+                InputEvent::ScaleFactor(scale) => context.set_scale_factor(scale),
+                InputEvent::Frame { time_millis } => {
+                    context.set_time_millis(time_millis);
+                    let frame = context.create_frame();
+                    (build_frame)(frame);
+                }
+            }
+        }
+    }
+}
+
+/// Replays the specified `log` against `context`, passing each recorded [`Frame`](struct.Frame.html) to
+/// `build_frame`.  See [`InputLog::replay`](struct.InputLog.html#method.replay).
+pub fn replay<F: FnMut(Frame)>(log: &InputLog, context: &mut Context, build_frame: F) {
+    log.replay(context, build_frame);
+}
+
+/**
+Records the inputs sent to a [`Context`](struct.Context.html) into an [`InputLog`](struct.InputLog.html),
+starting with a [`Snapshot`](enum.InputEvent.html#variant.Snapshot) of the current input state.  The recorder
+does not borrow the context, so the application keeps running normally while recording.  Recording stops when
+[`finish`](#method.finish) is called, or when the recorder is dropped, which discards the log.  This is a
+wrapper around [`Context::start_recording`](struct.Context.html#method.start_recording) and
+[`Context::finish_recording`](struct.Context.html#method.finish_recording).
+
+# Example
+```
+fn record_session(context: &mut Context, app: &mut App) -> InputLog {
+    let recorder = Recorder::start(context);
+    app.run(context);
+    recorder.finish()
+}
+```
+*/
+pub struct Recorder {
+    internal: Rc<RefCell<ContextInternal>>,
+}
+
+impl Recorder {
+    /// Starts recording the inputs sent to `context`.  Any recording already in progress is discarded.
+    pub fn start(context: &mut Context) -> Recorder {
+        context.start_recording();
+        Recorder { internal: Rc::clone(context.internal()) }
+    }
+
+    /// Stops recording and returns the recorded log.
+    pub fn finish(self) -> InputLog {
+        self.internal.borrow_mut().take_recording().unwrap_or_default()
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        self.internal.borrow_mut().take_recording();
+    }
+}
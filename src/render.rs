@@ -1,4 +1,5 @@
 use std::num::NonZeroU16;
+use std::rc::Rc;
 
 use crate::{Color, Rect, Point, Error};
 use crate::font::{FontSource, Font};
@@ -12,6 +13,18 @@ pub trait IO {
 
     /// Returns the current window size in logical pixels.
     fn display_size(&self) -> Point;
+
+    /// Returns the current contents of the clipboard, if any.  The default implementation
+    /// has no clipboard and always returns `None`.
+    fn clipboard_text(&mut self) -> Option<String> { None }
+
+    /// Sets the contents of the clipboard.  The default implementation does nothing.
+    fn set_clipboard_text(&mut self, _text: String) {}
+
+    /// Returns the time, in milliseconds, that the [`Context`](struct.Context.html) should start at when
+    /// it is built, if the IO drives the time itself rather than using the wall clock.  The default
+    /// implementation returns `None`, using the wall clock.
+    fn time_millis(&self) -> Option<u32> { None }
 }
 
 /// A trait to be implemented on the type to be used for rendering the UI.  See [`GliumRenderer`](struct.GliumRenderer.html)
@@ -33,6 +46,19 @@ pub trait Renderer {
         image_data: &[u8],
         dimensions: (u32, u32),
     ) -> Result<TextureData, Error>;
+
+    /// Replaces the contents of part or all of the texture registered with `handle`, keeping the same handle.
+    /// `image_data` is in the same format as for [`register_texture`](#tymethod.register_texture), and covers
+    /// the region of size `dimensions` with its corner at the texel `pos`.  This method is called via
+    /// [`Context::update_texture`](struct.Context.html#method.update_texture), which checks that the region
+    /// is within the texture and that `image_data` is the correct size.
+    fn update_texture(
+        &mut self,
+        handle: TextureHandle,
+        image_data: &[u8],
+        pos: (u32, u32),
+        dimensions: (u32, u32),
+    ) -> Result<(), Error>;
 }
 
 pub(crate) fn view_matrix(display_pos: Point, display_size: Point) -> [[f32; 4]; 4] {
@@ -49,9 +75,74 @@ pub(crate) fn view_matrix(display_pos: Point, display_size: Point) -> [[f32; 4];
     ]
 }
 
+/// The parameters passed to a widget's [`draw callback`](struct.WidgetBuilder.html#method.draw_callback)
+/// when it is invoked by the renderer.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DrawCallbackParams {
+    /// The widget's final position and size on screen, in logical pixels
+    pub rect: Rect,
+
+    /// The area of the screen the widget is allowed to draw in, in logical pixels.  This is usually
+    /// the inner area of the widget's parent.
+    pub clip: Rect,
+
+    /// The display scale factor.  Multiply logical pixels by this to get physical pixels.
+    pub scale: f32,
+}
+
+/// The render target passed to a widget's [`draw callback`](struct.WidgetBuilder.html#method.draw_callback),
+/// which the callback may draw into.  Which variant is passed depends on the renderer invoking the callback.
+/// The renderer does not restrict drawing to [`DrawCallbackParams::clip`](struct.DrawCallbackParams.html#structfield.clip),
+/// so callbacks should set their own scissor or viewport from it where needed.
+#[non_exhaustive]
+pub enum DrawTarget<'a, 'b> {
+    /// No render target is available, such as when the callback is invoked from
+    /// [`DrawCallbackCommand::invoke`](struct.DrawCallbackCommand.html#method.invoke)
+    None,
+
+    /// The Glium frame being drawn by the [`GliumRenderer`](struct.GliumRenderer.html)
+    #[cfg(feature = "glium_backend")]
+    Glium(&'a mut glium::Frame),
+
+    /// The render pass being recorded by the [`WgpuRenderer`](struct.WgpuRenderer.html).  Anything
+    /// set on the pass, such as pipelines and buffers, must live as long as the pass itself.  The
+    /// renderer restores its own state after the callback returns.
+    #[cfg(feature = "wgpu_backend")]
+    Wgpu(&'a mut wgpu::RenderPass<'b>),
+
+    #[doc(hidden)]
+    __Lifetimes(std::marker::PhantomData<(&'a mut (), &'b ())>),
+}
+
+pub(crate) type DrawCallback = Rc<dyn Fn(&DrawCallbackParams, &mut DrawTarget)>;
+
+// a draw callback to be invoked just before the draw group with index `group` is drawn
+#[cfg(any(feature = "glium_backend", feature = "wgpu_backend"))]
+pub(crate) struct PendingCallback {
+    pub group: usize,
+    pub callback: DrawCallback,
+    pub params: DrawCallbackParams,
+}
+
+// invokes all callbacks that are to be drawn before the draw group with index `group`,
+// returning whether any were invoked
+#[cfg(any(feature = "glium_backend", feature = "wgpu_backend"))]
+pub(crate) fn run_callbacks(callbacks: &[PendingCallback], group: usize, target: &mut DrawTarget) -> bool {
+    let mut invoked = false;
+    for pending in callbacks.iter().filter(|pending| pending.group == group) {
+        (pending.callback)(&pending.params, target);
+        invoked = true;
+    }
+    invoked
+}
+
+/// The texture used to draw a group of triangles.  See [`DrawGroup`](struct.DrawGroup.html).
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum DrawMode {
+    /// An RGBA image texture
     Image(TextureHandle),
+
+    /// A single channel font atlas
     Font(FontHandle),
 }
 
@@ -73,6 +164,38 @@ pub trait DrawList {
     fn back_adjust_positions(&mut self, since_index: usize, amount: Point);
 }
 
+/// A DrawList that multiplies a tint into the color of everything drawn, before passing it
+/// on to the underlying list
+pub(crate) struct TintedDrawList<'a, D: DrawList> {
+    draw_list: &'a mut D,
+    tint: Color,
+}
+
+impl<'a, D: DrawList> TintedDrawList<'a, D> {
+    pub fn new(draw_list: &'a mut D, tint: Color) -> TintedDrawList<'a, D> {
+        TintedDrawList { draw_list, tint }
+    }
+}
+
+impl<'a, D: DrawList> DrawList for TintedDrawList<'a, D> {
+    fn push_rect(
+        &mut self,
+        pos: [f32; 2],
+        size: [f32; 2],
+        tex: [TexCoord; 2],
+        color: Color,
+        clip: Rect,
+    ) {
+        self.draw_list.push_rect(pos, size, tex, color * self.tint, clip);
+    }
+
+    fn len(&self) -> usize { self.draw_list.len() }
+
+    fn back_adjust_positions(&mut self, since_index: usize, amount: Point) {
+        self.draw_list.back_adjust_positions(since_index, amount);
+    }
+}
+
 /// An implementation of DrawList that does nothing.  It should be (mostly) optimized
 /// out when used
 pub(crate) struct DummyDrawList {
@@ -102,12 +225,15 @@ impl DrawList for DummyDrawList {
     fn back_adjust_positions(&mut self, _since_index: usize, _amount: Point) {}
 }
 
+/// A texture registered with a [`Renderer`](trait.Renderer.html), as returned from
+/// [`Renderer::register_texture`](trait.Renderer.html#tymethod.register_texture).
 pub struct TextureData {
     handle: TextureHandle,
     size: [u32; 2],
 }
 
 impl TextureData {
+    /// Creates the data for the texture with `handle` and the specified size in texels
     pub fn new(handle: TextureHandle, width: u32, height: u32) -> TextureData {
         TextureData {
             handle,
@@ -115,13 +241,17 @@ impl TextureData {
         }
     }
 
-    pub fn tex_coord(&self, x: u32, y: u32) -> TexCoord {
+    pub(crate) fn tex_coord(&self, x: u32, y: u32) -> TexCoord {
         let x = x as f32 / self.size[0] as f32;
         let y = y as f32 / self.size[1] as f32;
         TexCoord([x, y])
     }
 
+    /// The handle the texture was registered with
     pub fn handle(&self) -> TextureHandle { self.handle }
+
+    /// The width and height of the texture, in texels
+    pub fn dimensions(&self) -> (u32, u32) { (self.size[0], self.size[1]) }
 }
 
 #[derive(Copy, Clone)]
@@ -148,6 +278,8 @@ impl From<TexCoord> for [f32; 2] {
     }
 }
 
+/// Identifies a texture registered with a [`Renderer`](trait.Renderer.html).  See
+/// [`Context::texture_id`](struct.Context.html#method.texture_id).
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct TextureHandle {
     id: NonZeroU16,
@@ -160,8 +292,10 @@ impl Default for TextureHandle {
 }
 
 impl TextureHandle {
+    /// The index of this texture, starting at 0.  Renderers typically store textures in a `Vec` by this index.
     pub fn id(self) -> usize { (self.id.get() - 1).into() }
 
+    /// The handle following this one
     pub fn next(self) -> TextureHandle {
         if self.id.get() == u16::MAX {
             panic!("Cannot allocate more than {} textures", u16::MAX);
@@ -173,6 +307,8 @@ impl TextureHandle {
     }
 }
 
+/// Identifies a font registered with a [`Renderer`](trait.Renderer.html).  See
+/// [`Context::font_id`](struct.Context.html#method.font_id).
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct FontHandle {
     id: NonZeroU16,
@@ -185,8 +321,10 @@ impl Default for FontHandle {
 }
 
 impl FontHandle {
+    /// The index of this font, starting at 0.  Renderers typically store font atlases in a `Vec` by this index.
     pub fn id(self) -> usize { (self.id.get() - 1).into() }
 
+    /// The handle following this one
     pub fn next(self) -> FontHandle {
         if self.id.get() == u16::MAX {
             panic!("Cannot allocate more than {} fonts", u16::MAX);
@@ -11,10 +11,16 @@ use notify::{Watcher, RecommendedWatcher, RecursiveMode, watcher, DebouncedEvent
 use crate::Error;
 use crate::theme::ThemeSet;
 use crate::theme_definition::ThemeDefinition;
-use crate::render::{Renderer, TextureData, TextureHandle};
+use crate::render::{Renderer, TextureData, TextureHandle, FontHandle};
 
 static RELOAD_THEME: AtomicBool = AtomicBool::new(false);
 
+/// The name of the theme set built from the theme registered with
+/// [`ContextBuilder::register_theme`](struct.ContextBuilder.html#method.register_theme)
+pub(crate) const DEFAULT_THEME_SET: &str = "default";
+
+pub(crate) type ThemeSets = Vec<(String, ThemeSet)>;
+
 struct ThemeSource {
     data: Option<ThemeDefinition>,
     files: Option<ThemeSourceFiles>,
@@ -43,6 +49,13 @@ pub(crate) struct ResourceSet {
     images: Vec<(String, ImageSource)>,
     fonts: Vec<(String, FontSource)>,
     theme: ThemeSource,
+    named_themes: Vec<(String, ThemeDefinition)>,
+
+    // additional definitions merged, in order, on top of the default theme
+    theme_overlays: Vec<ThemeDefinition>,
+
+    // the textures registered with the renderer by the last successful build
+    textures: HashMap<String, TextureData>,
 
     watcher: Option<RecommendedWatcher>,
 }
@@ -74,6 +87,9 @@ impl ResourceSet {
                 data: None,
                 files: None,
             },
+            named_themes: Vec::new(),
+            theme_overlays: Vec::new(),
+            textures: HashMap::new(),
             watcher,
         }
     }
@@ -102,6 +118,32 @@ impl ResourceSet {
         self.theme.files = None;
     }
 
+    pub(crate) fn add_theme(&mut self, theme: ThemeDefinition) {
+        self.theme_overlays.push(theme);
+    }
+
+    // the default theme, with all overlays merged on top of it
+    fn default_theme(&self) -> Option<ThemeDefinition> {
+        let mut overlays = self.theme_overlays.iter().cloned();
+        let mut theme = self.theme.data.clone().or_else(|| overlays.next())?;
+        for overlay in overlays {
+            theme.merge(overlay);
+        }
+        Some(theme)
+    }
+
+    pub(crate) fn register_named_theme(&mut self, name: String, theme: ThemeDefinition) {
+        if name == DEFAULT_THEME_SET {
+            self.register_theme(theme);
+            return;
+        }
+
+        match self.named_themes.iter_mut().find(|(id, _)| *id == name) {
+            None => self.named_themes.push((name, theme)),
+            Some((_, data)) => *data = theme,
+        }
+    }
+
     pub(crate) fn register_theme_from_files<E, D, F>(
         &mut self,
         paths: &[&Path],
@@ -150,6 +192,36 @@ impl ResourceSet {
         self.images.push((id, ImageSource { data: Some((data, width, height)), file: None }));
     }
 
+    pub(crate) fn replace_font_from_file(&mut self, id: String, path: &Path) {
+        self.replace_font(id, FontSource { font: None, data: None, file: Some(path.to_owned()) });
+        self.add_path_to_watcher(path);
+    }
+
+    pub(crate) fn replace_font_from_data(&mut self, id: String, data: Vec<u8>) {
+        self.replace_font(id, FontSource { font: None, data: Some(data), file: None });
+    }
+
+    fn replace_font(&mut self, id: String, source: FontSource) {
+        for path in replace_source(&mut self.fonts, id, source).into_iter().filter_map(|old| old.file) {
+            self.remove_path_from_watcher(&path);
+        }
+    }
+
+    pub(crate) fn replace_image_from_file(&mut self, id: String, path: &Path) {
+        self.replace_image(id, ImageSource { data: None, file: Some(path.to_owned()) });
+        self.add_path_to_watcher(path);
+    }
+
+    pub(crate) fn replace_image_from_data(&mut self, id: String, data: Vec<u8>, width: u32, height: u32) {
+        self.replace_image(id, ImageSource { data: Some((data, width, height)), file: None });
+    }
+
+    fn replace_image(&mut self, id: String, source: ImageSource) {
+        for path in replace_source(&mut self.images, id, source).into_iter().filter_map(|old| old.file) {
+            self.remove_path_from_watcher(&path);
+        }
+    }
+
     pub(crate) fn remove_theme_file(&mut self, path: &Path) {
         self.remove_path_from_watcher(path);
         if let Some(theme) = self.theme.files.as_mut() {
@@ -166,10 +238,28 @@ impl ResourceSet {
         }
     }
 
+    /// Replaces the theme with the specified definition and rebuilds all assets.  If there is
+    /// a problem rebuilding, the previous theme is kept and Err is returned.
+    pub(crate) fn rebuild_with_theme<R: Renderer>(
+        &mut self,
+        theme: ThemeDefinition,
+        renderer: &mut R,
+        scale_factor: f32,
+    ) -> Result<ThemeSets, Error> {
+        let old_theme = std::mem::replace(&mut self.theme, ThemeSource { data: Some(theme), files: None });
+
+        let result = self.cache_data().and_then(|_| self.build_assets(renderer, scale_factor));
+        if result.is_err() {
+            self.theme = old_theme;
+        }
+
+        result
+    }
+
     /// Checks for a file watch change and rebuilds the theme if neccessary, clearing the data cache
     /// and reloading all data.  Will return Ok(None) if there was no change, or Err if there was
     /// a problem rebuilding the theme.
-    pub(crate) fn check_live_reload<R: Renderer>(&mut self, renderer: &mut R, scale_factor: f32) -> Result<Option<ThemeSet>, Error> {
+    pub(crate) fn check_live_reload<R: Renderer>(&mut self, renderer: &mut R, scale_factor: f32) -> Result<Option<ThemeSets>, Error> {
         if !RELOAD_THEME.compare_and_swap(true, false, Ordering::AcqRel) {
             return Ok(None);
         }
@@ -184,23 +274,135 @@ impl ResourceSet {
 
     /// Builds all assets and registers them with the renderer.  You must make sure all asset
     /// data is cached with [`cache_data`](#method.cache_assets) prior to calling this.
-    pub(crate) fn build_assets<R: Renderer>(&mut self, renderer: &mut R, scale_factor: f32) -> Result<ThemeSet, Error> {
+    /// Each theme is built into its own `ThemeSet`, all sharing the same textures.  The
+    /// default theme, if any, is first.
+    pub(crate) fn build_assets<R: Renderer>(&mut self, renderer: &mut R, scale_factor: f32) -> Result<ThemeSets, Error> {
         RELOAD_THEME.store(false, Ordering::Release);
 
-        let textures = self.build_images(renderer)?;
+        if self.theme.data.is_none() && self.theme_overlays.is_empty() && self.named_themes.is_empty() {
+            return Err(Error::Theme("Cannot build assets.  No theme specified.".to_string()));
+        }
+
+        let (textures, solid_texture) = self.build_images(|handle, data, dims| renderer.register_texture(handle, data, dims))?;
         let fonts = self.build_fonts()?;
 
-        let theme_def = match &self.theme.data {
-            None => {
-                return Err(Error::Theme("Cannot build assets.  No theme specified.".to_string()));
-            },
-            Some(def) => def,
-        };
-        let themes = ThemeSet::new(theme_def, textures, fonts, renderer, scale_factor)?;
+        let default_theme = self.default_theme();
+        let theme_defs = default_theme.iter()
+            .map(|def| (DEFAULT_THEME_SET, def))
+            .chain(self.named_themes.iter().map(|(name, def)| (name.as_str(), def)));
+
+        let mut font_handle = FontHandle::default();
+        let mut themes = Vec::new();
+        for (name, def) in theme_defs {
+            let set = match ThemeSet::new(def, &textures, &solid_texture, &fonts, renderer, scale_factor, &mut font_handle) {
+                Err(e) if !self.named_themes.is_empty() => return Err(in_theme_set(name, e)),
+                result => result?,
+            };
+            themes.push((name.to_string(), set));
+        }
+
+        check_theme_fonts(&themes)?;
 
+        self.textures = textures;
         Ok(themes)
     }
 
+    /// Replaces the contents of the region of size `dimensions` at `pos` in the texture registered with `id`,
+    /// both with the renderer and in the cached image data, so the new contents are kept when rebuilding.
+    // the ID of the image registered as the texture with `handle`, if any
+    pub(crate) fn texture_id(&self, handle: TextureHandle) -> Option<&str> {
+        self.textures.iter()
+            .find(|(_, texture)| texture.handle() == handle)
+            .map(|(id, _)| id.as_str())
+    }
+
+    pub(crate) fn update_texture<R: Renderer>(
+        &mut self,
+        renderer: &mut R,
+        id: &str,
+        data: &[u8],
+        pos: (u32, u32),
+        dimensions: (u32, u32),
+    ) -> Result<(), Error> {
+        let texture = self.textures.get(id).ok_or_else(||
+            Error::Theme(format!("Unable to locate texture '{}'", id))
+        )?;
+
+        let (width, height) = texture.dimensions();
+        let fits = |start: u32, len: u32, size: u32| matches!(start.checked_add(len), Some(end) if end <= size);
+        if !fits(pos.0, dimensions.0, width) || !fits(pos.1, dimensions.1, height) {
+            return Err(Error::Theme(format!(
+                "Region at {:?} of size {:?} is outside texture '{}' of size {:?}", pos, dimensions, id, (width, height)
+            )));
+        }
+
+        let expected = dimensions.0 as usize * dimensions.1 as usize * 4;
+        if data.len() != expected {
+            return Err(Error::Theme(format!(
+                "Expected {} bytes to update region of size {:?} in texture '{}', but found {}", expected, dimensions, id, data.len()
+            )));
+        }
+
+        if dimensions.0 == 0 || dimensions.1 == 0 { return Ok(()); }
+
+        renderer.update_texture(texture.handle(), data, pos, dimensions)?;
+
+        // a texture replaced since the last build may have a different size, and is left as is
+        let row_len = dimensions.0 as usize * 4;
+        for (_, source) in self.images.iter_mut().filter(|(image_id, _)| image_id == id) {
+            let (cached, cached_width, cached_height) = match source.data.as_mut() {
+                Some(data) => data,
+                None => continue,
+            };
+            if (*cached_width, *cached_height) != (width, height) { continue; }
+
+            for (row, src) in data.chunks_exact(row_len).enumerate() {
+                let start = ((pos.1 as usize + row) * width as usize + pos.0 as usize) * 4;
+                cached[start..start + row_len].copy_from_slice(src);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks all themes for errors in the same way as [`build_assets`](#method.build_assets), but
+    /// without registering anything with a renderer.  Rather than stopping at the first error, every
+    /// error found is returned.  You must make sure all asset data is cached prior to calling this.
+    pub(crate) fn validate_assets(&self) -> Result<(), Error> {
+        if self.theme.data.is_none() && self.theme_overlays.is_empty() && self.named_themes.is_empty() {
+            return Err(Error::Theme("Cannot validate assets.  No theme specified.".to_string()));
+        }
+
+        let (textures, solid_texture) = self.build_images(|handle, _, (width, height)| {
+            Ok(TextureData::new(handle, width, height))
+        })?;
+        let font_ids: Vec<&str> = self.fonts.iter().map(|(id, _)| id.as_str()).collect();
+
+        let default_theme = self.default_theme();
+        let theme_defs = default_theme.iter()
+            .map(|def| (DEFAULT_THEME_SET, def))
+            .chain(self.named_themes.iter().map(|(name, def)| (name.as_str(), def)));
+
+        let mut errors = Vec::new();
+        let mut themes = Vec::new();
+        for (name, def) in theme_defs {
+            let mut set_errors = Vec::new();
+            let set = ThemeSet::validate(def, &textures, &solid_texture, &font_ids, &mut set_errors);
+            if self.named_themes.is_empty() {
+                errors.append(&mut set_errors);
+            } else {
+                errors.extend(set_errors.into_iter().map(|e| in_theme_set(name, e)));
+            }
+            themes.push((name.to_string(), set));
+        }
+
+        if let Err(e) = check_theme_fonts(&themes) {
+            errors.push(e);
+        }
+
+        Error::check_all(errors)
+    }
+
     pub(crate) fn clear_data_cache(&mut self) {
         if self.theme.files.is_some() {
             self.theme.data = None;
@@ -335,21 +537,89 @@ impl ResourceSet {
         Ok(output)
     }
 
-    fn build_images<R: Renderer>(&self, renderer: &mut R) -> Result<HashMap<String, TextureData>, Error> {
+    // registers all textures using `register`, along with a single white texel used to draw solid images
+    fn build_images<F: FnMut(TextureHandle, &[u8], (u32, u32)) -> Result<TextureData, Error>>(
+        &self,
+        mut register: F,
+    ) -> Result<(HashMap<String, TextureData>, TextureData), Error> {
         let mut handle = TextureHandle::default();
 
         let mut output = HashMap::new();
-        for (id, source) in self.images.iter() {
+        for (index, (id, source)) in self.images.iter().enumerate() {
+            // the same texture may be registered more than once, but an ID can't refer to different textures
+            if let Some((_, earlier)) = self.images[..index].iter().find(|(earlier_id, _)| earlier_id == id) {
+                if earlier.data == source.data { continue; }
+
+                return Err(Error::Theme(format!("Texture '{}' was registered more than once with different images", id)));
+            }
+
             let (tex_data, width, height) = source.data.as_ref().unwrap();
             let dims = (*width, *height);
-            let tex_data = renderer.register_texture(handle, &tex_data, dims)?;
+            let tex_data = register(handle, tex_data, dims)?;
             output.insert(id.to_string(), tex_data);
 
             handle = handle.next();
         }
 
-        Ok(output)
+        let solid = register(handle, &[255; 4], (1, 1))?;
+
+        Ok((output, solid))
+    }
+}
+
+// replaces the first source with `id` in place, so the handles of all other sources are unchanged
+// when the theme is rebuilt, and removes any later sources with the same `id`.  If there is no source
+// with `id`, it is added.  Returns the replaced sources
+fn replace_source<T>(sources: &mut Vec<(String, T)>, id: String, source: T) -> Vec<T> {
+    let mut source = Some(source);
+    let mut replaced = Vec::new();
+    let mut index = 0;
+    while index < sources.len() {
+        if sources[index].0 != id {
+            index += 1;
+            continue;
+        }
+
+        match source.take() {
+            Some(source) => {
+                replaced.push(std::mem::replace(&mut sources[index].1, source));
+                index += 1;
+            },
+            None => replaced.push(sources.remove(index).1),
+        }
     }
+
+    if let Some(source) = source {
+        sources.push((id, source));
+    }
+
+    replaced
+}
+
+// names the theme set an error occurred in, for when there is more than one set
+fn in_theme_set(name: &str, error: Error) -> Error {
+    match error {
+        Error::Theme(msg) => Error::Theme(format!("In theme set '{}': {}", name, msg)),
+        Error::Serde(msg) => Error::Serde(format!("In theme set '{}': {}", name, msg)),
+        Error::Multiple(errors) => Error::Multiple(errors.into_iter().map(|e| in_theme_set(name, e)).collect()),
+        error => error,
+    }
+}
+
+// Every font must be defined in every theme set, as the font for a widget may
+// be looked up by id at any time, and theme sets can be switched at runtime.
+fn check_theme_fonts(themes: &[(String, ThemeSet)]) -> Result<(), Error> {
+    for (name, set) in themes {
+        for font_id in set.font_ids() {
+            if let Some((other_name, _)) = themes.iter().find(|(_, other)| other.find_font(Some(font_id)).is_none()) {
+                return Err(Error::Theme(format!(
+                    "Font '{}' is defined in theme set '{}' but not in theme set '{}'", font_id, name, other_name
+                )));
+            }
+        }
+    }
+
+    Ok(())
 }
 
 fn watcher_loop(rx: Receiver<DebouncedEvent>) {
@@ -0,0 +1,221 @@
+use crate::Color;
+
+// The style of a run of rich text.  Unset fields use the widget theme's values
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Style {
+    pub color: Option<Color>,
+    pub font: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Run {
+    Text { text: String, style: Style },
+
+    // an image, sized to the line height of the surrounding text's font
+    Icon { id: String, font: Option<String> },
+}
+
+// Rich text is broken into words, which are kept together on a single line, and
+// explicit line breaks.  Each word includes any whitespace following it
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Item {
+    Word(Vec<Run>),
+    LineBreak,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum TagKind {
+    Color,
+    Font,
+}
+
+enum Token {
+    Char(char),
+    Open(TagKind, String),
+    Close(TagKind),
+    Icon(String),
+}
+
+/// Parses `text` containing inline markup.  The supported tags are `<c=COLOR>...</c>` for a
+/// color span, `<f=FONT_ID>...</f>` for a font span, and `<i=IMAGE_ID>` for an inline icon.
+/// Spans may be nested.  Tags that are unknown, invalid, or unbalanced are kept as literal
+/// text, and a warning is logged.  `is_font` and `is_image` check whether an ID is defined
+/// in the theme.
+pub(crate) fn parse<F, I>(text: &str, is_font: F, is_image: I) -> Vec<Item>
+    where F: Fn(&str) -> bool, I: Fn(&str) -> bool
+{
+    let tokens = tokenize(text, &is_font, &is_image);
+    let tokens = match_tags(text, tokens);
+
+    let mut items = Vec::new();
+    let mut builder = WordBuilder::default();
+    let mut styles = vec![Style::default()];
+
+    for token in tokens {
+        match token {
+            Token::Char('\n') => {
+                builder.finish_word(&mut items);
+                items.push(Item::LineBreak);
+            },
+            Token::Char(c) => {
+                let style = styles.last().cloned().unwrap_or_default();
+                builder.push_char(c, style);
+                if c.is_whitespace() {
+                    builder.finish_word(&mut items);
+                }
+            },
+            Token::Open(kind, value) => {
+                let mut style = styles.last().cloned().unwrap_or_default();
+                match kind {
+                    TagKind::Color => style.color = Color::parse(&value),
+                    TagKind::Font => style.font = Some(value),
+                }
+                styles.push(style);
+            },
+            Token::Close(_) => {
+                styles.pop();
+            },
+            Token::Icon(id) => {
+                let font = styles.last().and_then(|style| style.font.clone());
+                builder.push_icon(id, font);
+            },
+        }
+    }
+
+    builder.finish_word(&mut items);
+    items
+}
+
+// splits the text into characters and tags, with any tag that cannot be parsed
+// kept as literal characters
+fn tokenize<F, I>(text: &str, is_font: &F, is_image: &I) -> Vec<Token>
+    where F: Fn(&str) -> bool, I: Fn(&str) -> bool
+{
+    let mut tokens = Vec::new();
+    let mut index = 0;
+    while let Some(c) = text[index..].chars().next() {
+        if c == '<' {
+            if let Some(end) = text[index..].find('>') {
+                let tag = &text[index + 1..index + end];
+                match parse_tag(tag, is_font, is_image) {
+                    Ok(token) => {
+                        tokens.push(token);
+                        index += end + 1;
+                        continue;
+                    },
+                    Err(msg) => log::warn!("{} in rich text '{}'", msg, text),
+                }
+            }
+        }
+
+        tokens.push(Token::Char(c));
+        index += c.len_utf8();
+    }
+    tokens
+}
+
+fn parse_tag<F, I>(tag: &str, is_font: &F, is_image: &I) -> Result<Token, String>
+    where F: Fn(&str) -> bool, I: Fn(&str) -> bool
+{
+    match tag {
+        "/c" => return Ok(Token::Close(TagKind::Color)),
+        "/f" => return Ok(Token::Close(TagKind::Font)),
+        _ => (),
+    }
+
+    let (name, value) = match tag.find('=') {
+        None => return Err(format!("Unknown tag '<{}>'", tag)),
+        Some(pos) => (&tag[..pos], &tag[pos + 1..]),
+    };
+
+    match name {
+        "c" => match Color::parse(value) {
+            None => Err(format!("Invalid color '{}'", value)),
+            Some(_) => Ok(Token::Open(TagKind::Color, value.to_string())),
+        },
+        "f" => if is_font(value) {
+            Ok(Token::Open(TagKind::Font, value.to_string()))
+        } else {
+            Err(format!("Unknown font '{}'", value))
+        },
+        "i" => if is_image(value) {
+            Ok(Token::Icon(value.to_string()))
+        } else {
+            Err(format!("Unknown image '{}'", value))
+        },
+        _ => Err(format!("Unknown tag '<{}>'", tag)),
+    }
+}
+
+// pairs up opening and closing tags.  Any tags without a match are replaced with their
+// literal text
+fn match_tags(text: &str, mut tokens: Vec<Token>) -> Vec<Token> {
+    let mut open: Vec<usize> = Vec::new();
+    let mut unmatched: Vec<usize> = Vec::new();
+
+    for (index, token) in tokens.iter().enumerate() {
+        match token {
+            Token::Open(..) => open.push(index),
+            Token::Close(kind) => {
+                let matches = match open.last().map(|open_index| &tokens[*open_index]) {
+                    Some(Token::Open(open_kind, _)) => open_kind == kind,
+                    _ => false,
+                };
+
+                if matches {
+                    open.pop();
+                } else {
+                    unmatched.push(index);
+                }
+            },
+            _ => (),
+        }
+    }
+    unmatched.extend(open);
+
+    if unmatched.is_empty() { return tokens; }
+
+    log::warn!("Unbalanced tags in rich text '{}'", text);
+    unmatched.sort_unstable();
+    for index in unmatched.into_iter().rev() {
+        let literal = match &tokens[index] {
+            Token::Open(TagKind::Color, value) => format!("<c={}>", value),
+            Token::Open(TagKind::Font, value) => format!("<f={}>", value),
+            Token::Close(TagKind::Color) => "</c>".to_string(),
+            Token::Close(TagKind::Font) => "</f>".to_string(),
+            _ => continue,
+        };
+        tokens.splice(index..index + 1, literal.chars().map(Token::Char));
+    }
+
+    tokens
+}
+
+#[derive(Default)]
+struct WordBuilder {
+    runs: Vec<Run>,
+}
+
+impl WordBuilder {
+    fn push_char(&mut self, c: char, style: Style) {
+        // whitespace joins the preceding run, as it ends the word
+        if let Some(Run::Text { text, style: cur_style }) = self.runs.last_mut() {
+            if *cur_style == style || c.is_whitespace() {
+                text.push(c);
+                return;
+            }
+        }
+
+        self.runs.push(Run::Text { text: c.to_string(), style });
+    }
+
+    fn push_icon(&mut self, id: String, font: Option<String>) {
+        self.runs.push(Run::Icon { id, font });
+    }
+
+    fn finish_word(&mut self, items: &mut Vec<Item>) {
+        if self.runs.is_empty() { return; }
+
+        items.push(Item::Word(std::mem::take(&mut self.runs)));
+    }
+}
@@ -1,11 +1,25 @@
-use crate::{Frame, widget::WidgetBuilder, Rect, Point};
+use crate::{Frame, widget::{WidgetBuilder, WidgetState}, Rect, Point};
+
+// once released, the velocity of a flicked scroll is multiplied by this amount each millisecond
+const FLICK_DECAY: f32 = 0.995;
+
+// flicked scrolls stop once slower than this, in logical pixels per millisecond
+const FLICK_MIN_SPEED: f32 = 0.02;
 
 /**
 A [`WidgetBuilder`](struct.WidgetBuilder.html) specifically for creating scrollpanes.
 
 Create this using [`WidgetBuilder.scrollpane`](struct.WidgetBuilder.html#method.scrollpane).
 Scrollpanes can have fairly complex behavior, and can include optional horizontal and vertical scrollbars.
-Scrollbars are, by default, only shown when the content size exceeds the pane's inner size.
+Scrollbars are, by default, only shown when the content size exceeds the pane's inner size.  When a scrollbar is
+not shown, the content grows to fill the space the scrollbar would have taken.
+The scrollpane content may also be scrolled with the mouse wheel while the mouse is over the pane.  When scrollpanes
+are nested, the innermost pane under the mouse receives the wheel movement, passing it on to the next pane out once it
+is scrolled as far as it can go in that direction.  Holding Shift while using a vertical
+wheel scrolls horizontally.  The `scroll_amount` custom float sets the distance, in logical pixels, scrolled by each
+click of the arrow buttons or line of mouse wheel movement, defaulting to 10.  Clicking the scrollbar `track` on either
+side of the `scroll` thumb scrolls by one page toward the click, repeating while the mouse button is held.  The track
+is drawn over the thumb, so it should generally not have any images of its own.
 There is also a [`scrollpane method`](struct.Frame.html#method.scrollpane) on `Frame` as a convenience for simple cases.
 
 Once you are finished setting up the scrollpane, you call [`children`](#method.children) to add children to the scrollpane
@@ -20,7 +34,7 @@ fn build_scrollpane(ui: &mut Frame, unique_id: &str) {
     .show_horizontal_scrollbar(ShowElement::Never)
     .children(|ui| {
         // scrollable UI here
-    })
+    });
 }
 ```
 
@@ -32,6 +46,8 @@ An example of a theme definition for a scrollpane:
     width_from: Parent
     height_from: Parent
     border: { all: 5 }
+    custom_floats:
+      scroll_amount: 20
     children:
       content:
         height_from: Parent
@@ -69,6 +85,9 @@ An example of a theme definition for a scrollpane:
         background: gui/small_button
         align: Left
         border: { all: 4 }
+      track:
+        wants_mouse: true
+        align: Left
   scrollbar_vertical:
     size: [20, 10]
     pos: [-5, -5]
@@ -89,6 +108,9 @@ An example of a theme definition for a scrollpane:
         background: gui/small_button
         align: Top
         border: { all: 4 }
+      track:
+        wants_mouse: true
+        align: Top
 ```
 */
 pub struct ScrollpaneBuilder<'a> {
@@ -100,16 +122,25 @@ struct ScrollpaneState {
     content_id: String,
     show_horiz: ShowElement,
     show_vert: ShowElement,
+    scroll_amount: f32,
+    auto_hide: bool,
+    smooth: bool,
 }
 
 impl<'a> ScrollpaneBuilder<'a> {
-    pub(crate) fn new(builder: WidgetBuilder<'a>, content_id: &str) -> ScrollpaneBuilder<'a> {
+    pub(crate) fn new(mut builder: WidgetBuilder<'a>, content_id: &str) -> ScrollpaneBuilder<'a> {
+        let scroll_amount = builder.frame.custom_float(builder.widget.theme_id(), "scroll_amount", 10.0);
+        builder.set_scroll_line_amount(scroll_amount);
+
         ScrollpaneBuilder {
             builder,
             state: ScrollpaneState {
                 content_id: content_id.to_string(),
                 show_horiz: ShowElement::Sometimes,
                 show_vert: ShowElement::Sometimes,
+                scroll_amount,
+                auto_hide: false,
+                smooth: false,
             }
         }
     }
@@ -130,9 +161,34 @@ impl<'a> ScrollpaneBuilder<'a> {
         self
     }
 
+    /// Specify whether the scrollbars in this scrollpane automatically hide.  If `auto_hide` is true,
+    /// the content fills the entire pane, with the scrollbars drawn over it only while the mouse is over the
+    /// pane or the content is being scrolled.  Once the pane is no longer in use, the scrollbars are kept for
+    /// the `scrollbar_hide_delay` custom float in milliseconds, defaulting to 1000, and then given the `Fading`
+    /// [`AnimStateKey`](enum.AnimStateKey.html) for the `scrollbar_fade_time` custom float, defaulting to 500,
+    /// before being hidden.  The scrollbars are still only shown as specified by
+    /// [`show_vertical_scrollbar`](#method.show_vertical_scrollbar) and
+    /// [`show_horizontal_scrollbar`](#method.show_horizontal_scrollbar).  Defaults to false.
+    pub fn auto_hide_scrollbars(mut self, auto_hide: bool) -> ScrollpaneBuilder<'a> {
+        self.state.auto_hide = auto_hide;
+        self
+    }
+
+    /// Specify whether this scrollpane scrolls smoothly.  If `smooth` is true, scrolling with the mouse
+    /// wheel, the arrow buttons, the track, or via [`scroll_to`](struct.Frame.html#method.scroll_to) animates
+    /// the scroll toward its new position over the `smooth_scroll_time` custom float in milliseconds, defaulting
+    /// to 150.  Releasing a scroll thumb while it is moving flicks the content, which keeps scrolling and
+    /// gradually slows down.  Defaults to false, where all scrolling is applied immediately.
+    pub fn smooth_scroll(mut self, smooth: bool) -> ScrollpaneBuilder<'a> {
+        self.state.smooth = smooth;
+        self
+    }
+
     /// Consumes this builder to create a scrollpane.  Calls the specified `children` closure
-    /// to add children to the scrollpane.
-    pub fn children<F: FnOnce(&mut Frame)>(self, children: F) {
+    /// to add children to the scrollpane.  Returns the [`WidgetState`](struct.WidgetState.html) of the
+    /// scrollpane.  Wheel movement in a direction the content is already scrolled as far as it can go is
+    /// passed on to any outer scrollpane, and is not included in the returned state.
+    pub fn children<F: FnOnce(&mut Frame)>(self, children: F) -> WidgetState {
         let mut min_scroll = Point::default();
         let mut max_scroll = Point::default();
         let mut delta = Point::default();
@@ -141,20 +197,92 @@ impl<'a> ScrollpaneBuilder<'a> {
         let content_id = state.content_id;
         let horiz = state.show_horiz;
         let vert = state.show_vert;
+        let scroll_amount = state.scroll_amount;
+        let auto_hide = state.auto_hide;
+        let smooth = state.smooth;
+        let request = self.builder.frame.context_internal().borrow_mut().take_scroll_request(&content_id);
+        let mut request_delta = Point::default();
+
+        // auto hiding scrollbars are shown while the pane is hovered or scrolling, then fade and hide
+        let activity_id = format!("{}_scrollbars", content_id);
+        let mut pane_rect = Rect::default();
+        let builder = self.builder.trigger_layout(&mut pane_rect);
+        let theme_id = builder.widget.theme_id().to_string();
+        let (bars_visible, bars_fading) = if auto_hide {
+            let hide_delay = builder.frame.custom_float(&theme_id, "scrollbar_hide_delay", 1000.0);
+            let fade_time = builder.frame.custom_float(&theme_id, "scrollbar_fade_time", 500.0);
+
+            let mouse_pos = builder.frame.context_internal().borrow().mouse_pos();
+            if pane_rect.is_inside(mouse_pos) {
+                builder.frame.set_base_time_now(&activity_id);
+            }
 
-        let (ui, result) = self.builder.finish_with(
+            let now = builder.frame.cur_time_millis();
+            let idle = now.saturating_sub(builder.frame.base_time_millis(&activity_id)) as f32;
+            (idle < hide_delay + fade_time, idle >= hide_delay)
+        } else {
+            (true, false)
+        };
+        let mut bars_pressed = false;
+        let mut drag = None;
+
+        let (ui, mut result) = builder.finish_with(
             Some(|ui: &mut Frame| {
                 let mut content_bounds = Rect::default();
-        
-                // TODO if horizontal and/or vertical scrollbars aren't present,
-                // change the scrollpane content size to fill up the available space
-        
-                ui.start("content")
+
+                // the children aren't known until they are built, so use their bounds from the
+                // last frame to decide which scrollbars to show
+                let prev_children = ui.context_internal().borrow().state(&content_id).children_bounds;
+
+                let mut content = ui.start("content")
                 .id(&content_id)
-                .trigger_layout(&mut content_bounds)
+                .trigger_layout(&mut content_bounds);
+
+                // the content fills the space of any scrollbar that is not shown.  Each scrollbar
+                // shown may reduce the space for the content enough to require the other.  Auto
+                // hiding scrollbars are drawn over the content instead
+                let base_size = content_bounds.size;
+                let fill_size = content.parent_fill_size();
+                let mut size = fill_size;
+                let mut show_horiz = false;
+                let mut show_vert = false;
+                for _ in 0..2 {
+                    let prev_max = prev_children.pos + prev_children.size;
+                    show_horiz = bars_visible && horiz.show(prev_children.pos.x < 0.0 || prev_max.x > size.x);
+                    show_vert = bars_visible && vert.show(prev_children.pos.y < 0.0 || prev_max.y > size.y);
+                    if auto_hide { break; }
+
+                    size = Point::new(
+                        if show_vert { base_size.x } else { fill_size.x },
+                        if show_horiz { base_size.y } else { fill_size.y },
+                    );
+                }
+
+                content.set_calculated_size(size);
+                content_bounds.size = size;
+
+                let content_pos = content_bounds.pos;
+                content
                 .clip(content_bounds)
-                .children(children);
-        
+                .children(|ui| {
+                    let start = ui.num_widgets();
+                    (children)(ui);
+
+                    if let Some(ScrollRequest::Widget(target_id)) = &request {
+                        let target = (start..ui.num_widgets()).map(|index| ui.widget(index))
+                            .find(|widget| widget.id() == target_id);
+                        if let Some(target) = target {
+                            let target_bounds = Rect::new(target.pos(), target.size());
+                            request_delta = scroll_into_view(content_bounds, target_bounds);
+                        }
+                    }
+
+                    let bounds = ui.children_bounds_since(start).map_or(Rect::default(), |bounds| {
+                        Rect::new(bounds.pos - content_pos, bounds.size)
+                    });
+                    ui.modify(&content_id, |state| state.children_bounds = bounds);
+                });
+
                 let content_min = content_bounds.pos;
                 let content_max = content_bounds.pos + content_bounds.size;
         
@@ -163,26 +291,34 @@ impl<'a> ScrollpaneBuilder<'a> {
                 let pane_max = pane_bounds.pos + pane_bounds.size;
         
                 let mut delta_scroll = Point::default();
+                let mut drag_scroll = Point::default();
+                let mut thumb_pressed = false;
 
                 let enable_horiz = pane_min.x < content_min.x || pane_max.x > content_max.x;
-                // check whether to show horizontal scrollbar
-                if horiz.show(enable_horiz) {
+                if show_horiz {
                     ui.start("scrollbar_horizontal")
+                    .fading(bars_fading)
                     .children(|ui| {
                         let mut right_rect = Rect::default();
                         let result = ui.start("right")
+                        .fading(bars_fading)
+                        .focusable(false)
                         .enabled(pane_max.x > content_max.x)
                         .trigger_layout(&mut right_rect).finish();
+                        bars_pressed |= result.pressed;
                         if result.clicked {
-                            delta_scroll.x -= 10.0;
+                            delta_scroll.x -= scroll_amount;
                         }
         
                         let mut left_rect = Rect::default();
                         let result = ui.start("left")
+                        .fading(bars_fading)
+                        .focusable(false)
                         .enabled(pane_min.x < content_min.x)
                         .trigger_layout(&mut left_rect).finish();
+                        bars_pressed |= result.pressed;
                         if result.clicked {
-                            delta_scroll.x += 10.0;
+                            delta_scroll.x += scroll_amount;
                         }
         
                         // compute size and position for main scroll button
@@ -197,37 +333,72 @@ impl<'a> ScrollpaneBuilder<'a> {
                         let size_x = width_frac * (max_x - min_x);
                         let size_y = left_rect.size.y;
         
+                        let mut thumb_rect = Rect::default();
                         let result = ui.start("scroll")
+                        .fading(bars_fading)
+                        .focusable(false)
                         .size(size_x, size_y)
                         .pos(pos_x, pos_y)
                         .enabled(enable_horiz)
+                        .trigger_layout(&mut thumb_rect)
                         .finish();
         
+                        bars_pressed |= result.pressed;
                         if result.pressed {
-                            delta_scroll.x -= result.moved.x / width_frac;
+                            drag_scroll.x -= result.moved.x / width_frac;
+                            thumb_pressed = true;
+                        }
+
+                        // the track is built last so the buttons and thumb take the mouse first
+                        let track_id = format!("{}_track_horizontal", content_id);
+                        let result = ui.start("track")
+                        .fading(bars_fading)
+                        .id(&track_id)
+                        .wants_mouse(true)
+                        .focusable(false)
+                        .size(max_x - min_x, size_y)
+                        .pos(min_x, pos_y)
+                        .enabled(enable_horiz)
+                        .finish();
+
+                        let mouse_x = ui.context_internal().borrow().mouse_pos().x;
+                        let page = result.pressed && (mouse_x < thumb_rect.pos.x || mouse_x > thumb_rect.pos.x + thumb_rect.size.x);
+                        bars_pressed |= result.pressed;
+                        if ui.repeat_press(&track_id, page) {
+                            if mouse_x < thumb_rect.pos.x {
+                                delta_scroll.x += content_bounds.size.x;
+                            } else {
+                                delta_scroll.x -= content_bounds.size.x;
+                            }
                         }
                     });
                 }
         
                 let enable_vertical = pane_min.y < content_min.y || pane_max.y > content_max.y;
-                // check whether to show vertical scrollbar
-                if vert.show(enable_vertical) {
+                if show_vert {
                     ui.start("scrollbar_vertical")
+                    .fading(bars_fading)
                     .children(|ui| {
                         let mut top_rect = Rect::default();
                         let result = ui.start("up")
+                        .fading(bars_fading)
+                        .focusable(false)
                         .enabled(pane_min.y < content_min.y)
                         .trigger_layout(&mut top_rect).finish();
+                        bars_pressed |= result.pressed;
                         if result.clicked {
-                            delta_scroll.y += 10.0;
+                            delta_scroll.y += scroll_amount;
                         }
         
                         let mut bot_rect = Rect::default();
                         let result = ui.start("down")
+                        .fading(bars_fading)
+                        .focusable(false)
                         .enabled(pane_max.y > content_max.y)
                         .trigger_layout(&mut bot_rect).finish();
+                        bars_pressed |= result.pressed;
                         if result.clicked {
-                            delta_scroll.y -= 10.0;
+                            delta_scroll.y -= scroll_amount;
                         }
         
                         // compute size and position for main scroll button
@@ -242,14 +413,43 @@ impl<'a> ScrollpaneBuilder<'a> {
                         let size_y = height_frac * (max_y - min_y);
                         let size_x = top_rect.size.x;
         
+                        let mut thumb_rect = Rect::default();
                         let result = ui.start("scroll")
+                        .fading(bars_fading)
+                        .focusable(false)
                         .size(size_x, size_y)
                         .pos(pos_x, pos_y)
                         .enabled(enable_vertical)
+                        .trigger_layout(&mut thumb_rect)
                         .finish();
         
+                        bars_pressed |= result.pressed;
                         if result.pressed {
-                            delta_scroll.y -= result.moved.y / height_frac;
+                            drag_scroll.y -= result.moved.y / height_frac;
+                            thumb_pressed = true;
+                        }
+
+                        // the track is built last so the buttons and thumb take the mouse first
+                        let track_id = format!("{}_track_vertical", content_id);
+                        let result = ui.start("track")
+                        .fading(bars_fading)
+                        .id(&track_id)
+                        .wants_mouse(true)
+                        .focusable(false)
+                        .size(size_x, max_y - min_y)
+                        .pos(pos_x, min_y)
+                        .enabled(enable_vertical)
+                        .finish();
+
+                        let mouse_y = ui.context_internal().borrow().mouse_pos().y;
+                        let page = result.pressed && (mouse_y < thumb_rect.pos.y || mouse_y > thumb_rect.pos.y + thumb_rect.size.y);
+                        bars_pressed |= result.pressed;
+                        if ui.repeat_press(&track_id, page) {
+                            if mouse_y < thumb_rect.pos.y {
+                                delta_scroll.y += content_bounds.size.y;
+                            } else {
+                                delta_scroll.y -= content_bounds.size.y;
+                            }
                         }
                     });
                 }
@@ -257,17 +457,193 @@ impl<'a> ScrollpaneBuilder<'a> {
                 min_scroll = content_max - pane_max;
                 max_scroll = content_min - pane_min;
                 delta = delta_scroll;
+                if thumb_pressed {
+                    drag = Some(drag_scroll);
+                }
             })
         );
 
+        // release the wheel along any axis where the content is already at the limit, so the
+        // innermost scrollpane that can scroll in that direction uses it
+        let scroll = ui.scroll(&content_id);
+        let (min, max) = (min_scroll + scroll, max_scroll + scroll);
+        let at_limit = |wheel: f32, scroll: f32, min: f32, max: f32| {
+            (wheel > 0.0 && scroll >= max) || (wheel < 0.0 && scroll <= min)
+        };
+        let wheel = result.scrolled;
+        let released = Point::new(
+            if at_limit(wheel.x, scroll.x, min.x, max.x) { wheel.x } else { 0.0 },
+            if at_limit(wheel.y, scroll.y, min.y, max.y) { wheel.y } else { 0.0 },
+        );
+        if released != Point::default() {
+            ui.release_mouse_wheel(released);
+            result.moved = result.moved - released;
+            result.scrolled = wheel - released;
+        }
+
+        let dragged = drag.unwrap_or_default();
+        if auto_hide && (bars_pressed || delta + dragged != Point::default() || result.moved != Point::default()) {
+            ui.set_base_time_now(&activity_id);
+        }
+
+        let delta = match request {
+            Some(ScrollRequest::Widget(_)) => delta + result.moved + request_delta,
+            _ => delta + result.moved,
+        };
+        let to_end = matches!(request, Some(ScrollRequest::End));
+
+        // smooth scrolling state is left alone while measuring, as the scroll isn't kept
+        let mut smooth_state = if smooth && !ui.context_internal().borrow().is_measuring() {
+            Some(ui.context_internal().borrow_mut().take_smooth_scroll(&content_id))
+        } else {
+            None
+        };
+        let smooth_time = ui.custom_float(&theme_id, "smooth_scroll_time", 150.0);
+        let now = ui.cur_time_millis();
+
         // set the scroll every frame to bound it, in case it was modified externally
         ui.modify(&content_id, |state| {
-            let min = min_scroll + state.scroll;
-            let max = max_scroll + state.scroll;
+            let bounds = (min_scroll + state.scroll, max_scroll + state.scroll);
+
+            match smooth_state.as_mut() {
+                None => {
+                    let mut scroll = state.scroll + delta + dragged;
+                    if to_end {
+                        scroll.y = bounds.0.y;
+                    }
 
-            state.scroll = (state.scroll + delta + result.moved).max(min).min(max);
+                    state.scroll = scroll.max(bounds.0).min(bounds.1);
+                }, Some(smooth_state) => {
+                    let input = ScrollInput { delta, drag, to_end };
+                    state.scroll = smooth_state.update(state.scroll, bounds, input, now, smooth_time);
+                }
+            }
         });
+
+        if let Some(smooth_state) = smooth_state {
+            ui.context_internal().borrow_mut().set_smooth_scroll(content_id, smooth_state);
+        }
+
+        result
+    }
+}
+
+// the scroll changes from a single frame, for smooth scrolling
+struct ScrollInput {
+    // the change to the target scroll
+    delta: Point,
+
+    // the change from dragging a scroll thumb, if one is held
+    drag: Option<Point>,
+
+    // whether to scroll vertically to the end
+    to_end: bool,
+}
+
+// the animation state of a smooth scrolling scrollpane
+#[derive(Default)]
+pub(crate) struct SmoothScroll {
+    start: Point,
+    target: Point,
+    start_millis: u32,
+
+    // the scroll set at the last update, to detect changes made elsewhere
+    current: Point,
+    last_millis: u32,
+
+    // the speed of a flicked scroll, in logical pixels per millisecond
+    velocity: Point,
+}
+
+impl SmoothScroll {
+    // computes the new scroll, within the `bounds` minimum and maximum
+    fn update(&mut self, scroll: Point, bounds: (Point, Point), input: ScrollInput, now: u32, duration: f32) -> Point {
+        let (min, max) = bounds;
+        let elapsed = now.saturating_sub(self.last_millis).min(100) as f32;
+        self.last_millis = now;
+
+        if scroll != self.current {
+            // the scroll was changed elsewhere, so start from it
+            self.start = scroll;
+            self.target = scroll;
+            self.velocity = Point::default();
+        }
+
+        let new_scroll = if let Some(drag) = input.drag {
+            // follow a held thumb exactly, measuring its speed for a flick on release
+            let new_scroll = (scroll + drag).max(min).min(max);
+            if elapsed > 0.0 {
+                self.velocity = (new_scroll - scroll) / elapsed;
+            }
+            self.start = new_scroll;
+            self.target = new_scroll;
+            new_scroll
+        } else if input.delta != Point::default() || input.to_end {
+            self.velocity = Point::default();
+            self.start = scroll;
+            self.start_millis = now;
+            self.target = self.target + input.delta;
+            if input.to_end {
+                self.target.y = min.y;
+            }
+            self.target = self.target.max(min).min(max);
+            self.animate(now, duration)
+        } else if self.velocity != Point::default() {
+            let new_scroll = scroll + self.velocity * elapsed;
+            let clamped = new_scroll.max(min).min(max);
+
+            // stop flicking along an axis at the end of the content, or once slow enough
+            self.velocity = self.velocity * FLICK_DECAY.powf(elapsed);
+            if clamped.x != new_scroll.x || self.velocity.x.abs() < FLICK_MIN_SPEED { self.velocity.x = 0.0; }
+            if clamped.y != new_scroll.y || self.velocity.y.abs() < FLICK_MIN_SPEED { self.velocity.y = 0.0; }
+
+            self.start = clamped;
+            self.target = clamped;
+            clamped
+        } else {
+            // the bounds may have changed since the animation started
+            self.target = self.target.max(min).min(max);
+            self.animate(now, duration)
+        };
+
+        self.current = new_scroll;
+        new_scroll
     }
+
+    // the scroll along the current animation, easing out toward the target
+    fn animate(&self, now: u32, duration: f32) -> Point {
+        let frac = if duration > 0.0 {
+            (now.saturating_sub(self.start_millis) as f32 / duration).min(1.0)
+        } else {
+            1.0
+        };
+
+        let eased = 1.0 - (1.0 - frac) * (1.0 - frac);
+        self.start + (self.target - self.start) * eased
+    }
+}
+
+pub(crate) enum ScrollRequest {
+    End,
+    Widget(String),
+}
+
+// the smallest scroll moving `target` inside `view`, or aligning it with the top left if it is too large
+fn scroll_into_view(view: Rect, target: Rect) -> Point {
+    let axis = |view_min: f32, view_size: f32, target_min: f32, target_size: f32| {
+        if target_min < view_min || target_size > view_size {
+            view_min - target_min
+        } else if target_min + target_size > view_min + view_size {
+            view_min + view_size - target_min - target_size
+        } else {
+            0.0
+        }
+    };
+
+    Point::new(
+        axis(view.pos.x, view.size.x, target.pos.x, target.size.x),
+        axis(view.pos.y, view.size.y, target.pos.y, target.size.y),
+    )
 }
 
 /// An enum to define when to show a particular UI element.
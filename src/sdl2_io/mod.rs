@@ -0,0 +1,231 @@
+use sdl2::event::{Event, WindowEvent};
+use sdl2::keyboard::{Keycode, Mod};
+use sdl2::mouse::{MouseButton, MouseWheelDirection, SystemCursor};
+use sdl2::video::Window;
+
+use crate::point::Point;
+use crate::context::{InputModifiers, Context, KeyCode};
+use crate::frame::CursorIcon;
+use crate::render::IO;
+
+/**
+A Thyme Input/Output adapter for [`SDL2`](https://github.com/Rust-SDL2/rust-sdl2).
+
+This adapter handles events from `sdl2` and sends them to the Thyme [`Context`](struct.Context.html).
+Events should be passed to this handler, assuming [`Context.wants_mouse`](struct.Context.html#method.wants_mouse)
+returns true for the given frame.  The window should be created with high DPI support enabled, so that
+the scale factor can be determined.
+
+# Example
+```
+fn main_loop(mut event_pump: sdl2::EventPump, mut context: thyme::Context, mut io: thyme::Sdl2Io) {
+    'running: loop {
+        for event in event_pump.poll_iter() {
+            match event {
+                Event::Quit { .. } => break 'running,
+                event => io.handle_event(&mut context, &event),
+            }
+        }
+
+        // Renderer specific code here
+
+        let mut ui = context.create_frame();
+        // create UI here
+
+        // draw the frame and finish up rendering here
+    }
+}
+```
+*/
+pub struct Sdl2Io {
+    scale_factor: f32,
+    display_size: Point,
+    modifiers: InputModifiers,
+    clipboard: String,
+    clipboard_get: Option<Box<dyn FnMut() -> Option<String>>>,
+    clipboard_set: Option<Box<dyn FnMut(String)>>,
+}
+
+impl IO for Sdl2Io {
+    fn scale_factor(&self) -> f32 { self.scale_factor }
+
+    fn display_size(&self) -> Point { self.display_size }
+
+    fn clipboard_text(&mut self) -> Option<String> {
+        match self.clipboard_get.as_mut() {
+            Some(get) => get(),
+            None => Some(self.clipboard.clone()),
+        }
+    }
+
+    fn set_clipboard_text(&mut self, text: String) {
+        match self.clipboard_set.as_mut() {
+            Some(set) => set(text),
+            None => self.clipboard = text,
+        }
+    }
+}
+
+impl Sdl2Io {
+    /// Creates a new adapter for the given `window`.  The scale factor is the ratio of the window's
+    /// drawable size, in physical pixels, to its size in logical pixels.
+    pub fn new(window: &Window) -> Sdl2Io {
+        let (logical_width, logical_height) = window.size();
+        let (physical_width, _) = window.drawable_size();
+        let scale_factor = if logical_width == 0 {
+            1.0
+        } else {
+            physical_width as f32 / logical_width as f32
+        };
+
+        Sdl2Io {
+            scale_factor,
+            display_size: Point::new(logical_width as f32, logical_height as f32) * scale_factor,
+            modifiers: InputModifiers::default(),
+            clipboard: String::new(),
+            clipboard_get: None,
+            clipboard_set: None,
+        }
+    }
+
+    /// Connects this adapter to the OS clipboard (or any other clipboard), using the specified
+    /// functions to `get` and `set` the clipboard text.  By default, the adapter uses a
+    /// clipboard internal to the application.
+    pub fn set_clipboard<G, S>(&mut self, get: G, set: S) where
+        G: FnMut() -> Option<String> + 'static, S: FnMut(String) + 'static {
+        self.clipboard_get = Some(Box::new(get));
+        self.clipboard_set = Some(Box::new(set));
+    }
+
+    /// Handles an sdl2 `Event` and passes it to the Thyme [`Context`](struct.Context.html).
+    /// Any text copied by widgets since the last call is also sent to the clipboard.
+    pub fn handle_event(&mut self, context: &mut Context, event: &Event) {
+        if let Some(text) = context.take_clipboard_text() {
+            self.set_clipboard_text(text);
+        }
+
+        match event {
+            Event::Window { win_event: WindowEvent::SizeChanged(width, height), .. } => {
+                let size = Point::new(*width as f32, *height as f32) * self.scale_factor;
+                self.display_size = size;
+                context.set_display_size(size);
+            },
+            Event::MouseButtonDown { mouse_btn, .. } => {
+                if let Some(index) = mouse_index(*mouse_btn) {
+                    context.set_mouse_pressed(true, index);
+                }
+            },
+            Event::MouseButtonUp { mouse_btn, .. } => {
+                if let Some(index) = mouse_index(*mouse_btn) {
+                    context.set_mouse_pressed(false, index);
+                }
+            },
+            Event::MouseWheel { x, y, direction, .. } => {
+                let delta = Point::new(*x as f32, *y as f32);
+                let delta = match direction {
+                    MouseWheelDirection::Flipped => delta * -1.0,
+                    _ => delta,
+                };
+                context.add_mouse_wheel_lines(delta);
+            },
+            Event::MouseMotion { x, y, .. } => {
+                // sdl2 mouse positions are already in logical pixels
+                context.set_mouse_pos(Point::new(*x as f32, *y as f32));
+            },
+            Event::KeyDown { keycode, keymod, .. } => {
+                self.set_modifiers(context, *keymod);
+
+                let key = match keycode {
+                    None => return,
+                    Some(key) => *key,
+                };
+
+                if self.modifiers.ctrl {
+                    match key {
+                        Keycode::A => { context.push_key(KeyCode::SelectAll); return; },
+                        Keycode::C => { context.push_key(KeyCode::Copy); return; },
+                        Keycode::X => { context.push_key(KeyCode::Cut); return; },
+                        Keycode::V => {
+                            for c in self.clipboard_text().unwrap_or_default().chars() {
+                                context.push_character(c);
+                            }
+                            return;
+                        },
+                        _ => (),
+                    }
+                }
+
+                if let Some(key) = key_code(key) {
+                    context.set_key_pressed(key, true);
+                }
+            },
+            Event::KeyUp { keycode, keymod, .. } => {
+                self.set_modifiers(context, *keymod);
+
+                if let Some(key) = keycode.and_then(key_code) {
+                    context.set_key_pressed(key, false);
+                }
+            },
+            // control characters such as backspace are sent as key events instead
+            Event::TextInput { text, .. } => {
+                for c in text.chars().filter(|c| !c.is_control()) {
+                    context.push_character(c);
+                }
+            },
+            _ => (),
+        }
+    }
+
+    fn set_modifiers(&mut self, context: &mut Context, keymod: Mod) {
+        self.modifiers = InputModifiers {
+            shift: keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD),
+            ctrl: keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD),
+            alt: keymod.intersects(Mod::LALTMOD | Mod::RALTMOD),
+        };
+        context.set_input_modifiers(self.modifiers);
+    }
+}
+
+fn mouse_index(button: MouseButton) -> Option<usize> {
+    Some(match button {
+        MouseButton::Left => 0,
+        MouseButton::Right => 1,
+        MouseButton::Middle => 2,
+        MouseButton::X1 => 3,
+        MouseButton::X2 => 4,
+        MouseButton::Unknown => return None,
+    })
+}
+
+fn key_code(key: Keycode) -> Option<KeyCode> {
+    Some(match key {
+        Keycode::Left => KeyCode::Left,
+        Keycode::Right => KeyCode::Right,
+        Keycode::Up => KeyCode::Up,
+        Keycode::Down => KeyCode::Down,
+        Keycode::Home => KeyCode::Home,
+        Keycode::End => KeyCode::End,
+        Keycode::PageUp => KeyCode::PageUp,
+        Keycode::PageDown => KeyCode::PageDown,
+        Keycode::Insert => KeyCode::Insert,
+        Keycode::Delete => KeyCode::Delete,
+        Keycode::Backspace => KeyCode::Backspace,
+        Keycode::Return | Keycode::KpEnter => KeyCode::Return,
+        Keycode::Tab => KeyCode::Tab,
+        Keycode::Escape => KeyCode::Escape,
+        Keycode::Space => KeyCode::Space,
+        _ => return None,
+    })
+}
+
+impl From<CursorIcon> for SystemCursor {
+    fn from(icon: CursorIcon) -> Self {
+        match icon {
+            CursorIcon::Default => SystemCursor::Arrow,
+            CursorIcon::EwResize => SystemCursor::SizeWE,
+            CursorIcon::NsResize => SystemCursor::SizeNS,
+            CursorIcon::NwseResize => SystemCursor::SizeNWSE,
+            CursorIcon::NeswResize => SystemCursor::SizeNESW,
+        }
+    }
+}
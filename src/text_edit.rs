@@ -0,0 +1,379 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{KeyCode, KeyEvent, Point};
+
+// Helper for editing text with a caret and optional selection.  The caret and
+// selection anchor are character indices rather than byte indices, so edits
+// always fall on char boundaries.  Caret movement and deletion step over whole
+// grapheme clusters, so the caret never lands inside a cluster.
+pub(crate) struct TextEdit<'a> {
+    text: &'a mut String,
+    pub caret: usize,
+    pub anchor: Option<usize>,
+    pub changed: bool,
+
+    // text that was copied or cut, to be sent to the clipboard
+    pub copied: Option<String>,
+}
+
+impl<'a> TextEdit<'a> {
+    pub fn new(text: &'a mut String, caret: usize, anchor: Option<usize>) -> TextEdit<'a> {
+        // the text may have been modified externally, so make sure the caret is valid
+        let caret = cluster_start(text, caret);
+        let anchor = anchor.map(|anchor| cluster_start(text, anchor));
+        TextEdit {
+            text,
+            caret,
+            anchor,
+            changed: false,
+            copied: None,
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        self.text
+    }
+
+    // the selected range of characters, if it is not empty
+    pub fn selection(&self) -> Option<(usize, usize)> {
+        selection_range(self.caret, self.anchor)
+    }
+
+    fn selected_text(&self) -> Option<String> {
+        let (start, end) = self.selection()?;
+        let start = byte_index(self.text, start);
+        let end = byte_index(self.text, end);
+        Some(self.text[start..end].to_string())
+    }
+
+    // deletes the selected text, if any.  returns true if anything was deleted
+    fn delete_selection(&mut self) -> bool {
+        let (start, end) = match self.selection() {
+            None => {
+                self.anchor = None;
+                return false;
+            }, Some(range) => range,
+        };
+
+        let start_byte = byte_index(self.text, start);
+        let end_byte = byte_index(self.text, end);
+        self.text.replace_range(start_byte..end_byte, "");
+        self.caret = start;
+        self.anchor = None;
+        self.changed = true;
+        true
+    }
+
+    // the number of characters in the text once the selection is replaced
+    pub fn len_without_selection(&self) -> usize {
+        let selected = self.selection().map_or(0, |(start, end)| end - start);
+        char_count(self.text) - selected
+    }
+
+    // whether inserting `c`, replacing any selection, keeps the text a valid number: digits
+    // with at most one decimal separator and an optional leading minus
+    pub fn accepts_numeric(&self, c: char) -> bool {
+        let (start, end) = self.selection().unwrap_or((self.caret, self.caret));
+        let before = &self.text[..byte_index(self.text, start)];
+        let after = &self.text[byte_index(self.text, end)..];
+
+        // nothing may be inserted ahead of a leading minus
+        let before_minus = start == 0 && after.starts_with('-');
+
+        match c {
+            '0'..='9' => !before_minus,
+            '.' => !before_minus && !before.contains('.') && !after.contains('.'),
+            '-' => start == 0 && !after.starts_with('-'),
+            _ => false,
+        }
+    }
+
+    pub fn insert(&mut self, c: char) {
+        self.delete_selection();
+        self.text.insert(byte_index(self.text, self.caret), c);
+        self.caret += 1;
+        self.changed = true;
+    }
+
+    // removes the characters from `start` to `end`
+    fn remove_range(&mut self, start: usize, end: usize) {
+        let start = byte_index(self.text, start);
+        let end = byte_index(self.text, end);
+        self.text.replace_range(start..end, "");
+        self.changed = true;
+    }
+
+    fn move_caret(&mut self, caret: usize, extend_selection: bool) {
+        if extend_selection {
+            if self.anchor.is_none() {
+                self.anchor = Some(self.caret);
+            }
+        } else {
+            self.anchor = None;
+        }
+
+        self.caret = caret;
+    }
+
+    // moves the caret up or down by `lines`, keeping its horizontal position where possible.
+    // `positions` must be the layout of the current text.  Moving above the first line or below
+    // the last line moves the caret to the start or end of the text
+    pub fn move_vertical(&mut self, positions: &[Point], line_height: f32, lines: f32, extend_selection: bool) {
+        let cur = match positions.get(self.caret) {
+            None => return,
+            Some(pos) => *pos,
+        };
+
+        // target the middle of the destination line
+        let target = Point::new(cur.x, cur.y + (lines + 0.5) * line_height);
+        let first_y = positions.first().map_or(0.0, |pos| pos.y);
+        let last_y = positions.iter().map(|pos| pos.y).fold(first_y, f32::max);
+
+        let caret = if target.y < first_y {
+            0
+        } else if target.y > last_y + line_height {
+            char_count(self.text)
+        } else {
+            hit_test(self.text, positions, target)
+        };
+
+        self.move_caret(caret, extend_selection);
+    }
+
+    pub fn apply_key(&mut self, event: KeyEvent) {
+        let len = char_count(self.text);
+        let shift = event.modifiers.shift;
+
+        match event.key {
+            KeyCode::Left => match self.selection() {
+                Some((start, _)) if !shift => self.move_caret(start, false),
+                _ => self.move_caret(prev_boundary(self.text, self.caret), shift),
+            },
+            KeyCode::Right => match self.selection() {
+                Some((_, end)) if !shift => self.move_caret(end, false),
+                _ => self.move_caret(next_boundary(self.text, self.caret), shift),
+            },
+            KeyCode::Home => self.move_caret(0, shift),
+            KeyCode::End => self.move_caret(len, shift),
+            KeyCode::Backspace => {
+                if self.delete_selection() || self.caret == 0 { return; }
+
+                let start = prev_boundary(self.text, self.caret);
+                self.remove_range(start, self.caret);
+                self.caret = start;
+            },
+            KeyCode::Delete => {
+                if self.delete_selection() || self.caret >= len { return; }
+
+                let end = next_boundary(self.text, self.caret);
+                self.remove_range(self.caret, end);
+            },
+            KeyCode::SelectAll => {
+                self.anchor = Some(0);
+                self.caret = len;
+            },
+            KeyCode::Copy => {
+                if let Some(text) = self.selected_text() {
+                    self.copied = Some(text);
+                }
+            },
+            KeyCode::Cut => {
+                if let Some(text) = self.selected_text() {
+                    self.copied = Some(text);
+                    self.delete_selection();
+                }
+            },
+            _ => (),
+        }
+    }
+}
+
+// the range from the lower to the higher of `caret` and `anchor`, if not empty
+pub(crate) fn selection_range(caret: usize, anchor: Option<usize>) -> Option<(usize, usize)> {
+    let anchor = anchor?;
+    if anchor == caret { return None; }
+
+    Some((caret.min(anchor), caret.max(anchor)))
+}
+
+pub(crate) fn char_count(text: &str) -> usize {
+    text.chars().count()
+}
+
+pub(crate) fn byte_index(text: &str, char_index: usize) -> usize {
+    text.char_indices().nth(char_index).map_or(text.len(), |(index, _)| index)
+}
+
+// the character index of the grapheme cluster boundary before `char_index`, or 0
+pub(crate) fn prev_boundary(text: &str, char_index: usize) -> usize {
+    boundaries(text).into_iter().rev().find(|index| *index < char_index).unwrap_or(0)
+}
+
+// the character index of the grapheme cluster boundary after `char_index`, or the
+// number of characters
+pub(crate) fn next_boundary(text: &str, char_index: usize) -> usize {
+    let boundaries = boundaries(text);
+    let len = *boundaries.last().unwrap();
+    boundaries.into_iter().find(|index| *index > char_index).unwrap_or(len)
+}
+
+// the character index of the start of the grapheme cluster containing `char_index`
+fn cluster_start(text: &str, char_index: usize) -> usize {
+    boundaries(text).into_iter().rev().find(|index| *index <= char_index).unwrap_or(0)
+}
+
+// the character indices of all extended grapheme cluster boundaries in `text`, in order,
+// including 0 and the number of characters
+fn boundaries(text: &str) -> Vec<usize> {
+    let mut boundaries = Vec::new();
+    let mut char_index = 0;
+    let mut prev_byte = 0;
+    for (byte, _) in text.grapheme_indices(true) {
+        char_index += text[prev_byte..byte].chars().count();
+        prev_byte = byte;
+        boundaries.push(char_index);
+    }
+
+    let len = char_index + text[prev_byte..].chars().count();
+    if boundaries.last() != Some(&len) {
+        boundaries.push(len);
+    }
+    boundaries
+}
+
+// Finds the caret position closest to `point`, given the character boundary
+// positions of `text` as computed by `Font::layout_positions`.  Only grapheme
+// cluster boundaries are considered.
+pub(crate) fn hit_test(text: &str, positions: &[Point], point: Point) -> usize {
+    // collect the boundaries on the line containing the point; positions are at the top of
+    // each line, and a position lower than the previous one starts a new line
+    let mut line: Vec<(usize, f32)> = Vec::new();
+    let mut line_y: Option<f32> = None;
+    for index in boundaries(text) {
+        let pos = match positions.get(index) {
+            None => break,
+            Some(pos) => *pos,
+        };
+
+        let new_line = match line_y {
+            None => true,
+            Some(y) => pos.y > y,
+        };
+
+        if new_line {
+            if !line.is_empty() && pos.y > point.y { break; }
+
+            line.clear();
+            line_y = Some(pos.y);
+        }
+        line.push((index, pos.x));
+    }
+
+    // the caret goes before the first cluster whose midpoint is right of the point
+    for pair in line.windows(2) {
+        let (index, x) = pair[0];
+        let (_, next_x) = pair[1];
+        if point.x < (x + next_x) / 2.0 {
+            return index;
+        }
+    }
+
+    line.last().map_or(0, |(index, _)| *index)
+}
+
+// Computes the rectangles, one per line, covering the characters from `start` to `end`
+pub(crate) fn selection_rects(positions: &[Point], start: usize, end: usize, line_height: f32) -> Vec<(Point, Point)> {
+    let mut rects: Vec<(Point, Point)> = Vec::new();
+
+    for pos in positions.iter().take(end + 1).skip(start) {
+        match rects.last_mut() {
+            Some((rect_pos, size)) if rect_pos.y == pos.y => {
+                size.x = pos.x - rect_pos.x;
+            },
+            _ => rects.push((*pos, Point::new(0.0, line_height))),
+        }
+    }
+
+    rects
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InputModifiers;
+
+    // applies `key` to `text` with the caret at `caret`, returning the new text and caret
+    fn apply(text: &str, caret: usize, key: KeyCode) -> (String, usize) {
+        let mut text = text.to_string();
+        let mut edit = TextEdit::new(&mut text, caret, None);
+        edit.apply_key(KeyEvent { key, modifiers: InputModifiers::default() });
+        let caret = edit.caret;
+        (text, caret)
+    }
+
+    // checks the caret movement and deletion around a single cluster, with `len` characters,
+    // between an "a" and a "b"
+    fn check_cluster(cluster: &str, len: usize) {
+        let text = format!("a{}b", cluster);
+        let end = 1 + len;
+
+        assert_eq!(apply(&text, 1, KeyCode::Right), (text.clone(), end));
+        assert_eq!(apply(&text, end, KeyCode::Left), (text.clone(), 1));
+        assert_eq!(apply(&text, end, KeyCode::Backspace), ("ab".to_string(), 1));
+        assert_eq!(apply(&text, 1, KeyCode::Delete), ("ab".to_string(), 1));
+
+        // a caret inside the cluster is moved to its start
+        assert_eq!(apply(&text, 2, KeyCode::Right), (text.clone(), end));
+
+        // stepping back over the "b" stops at the end of the cluster
+        assert_eq!(apply(&text, end + 1, KeyCode::Left), (text, end));
+    }
+
+    #[test]
+    fn combining_mark() {
+        check_cluster("e\u{301}", 2);
+    }
+
+    #[test]
+    fn zwj_family() {
+        check_cluster("\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}", 5);
+    }
+
+    #[test]
+    fn flag_pair() {
+        check_cluster("\u{1F1EF}\u{1F1F5}", 2);
+
+        // adjacent flags are separate clusters
+        let flags = "\u{1F1EF}\u{1F1F5}\u{1F1FA}\u{1F1F8}";
+        assert_eq!(apply(flags, 0, KeyCode::Right), (flags.to_string(), 2));
+        assert_eq!(apply(flags, 4, KeyCode::Backspace), ("\u{1F1EF}\u{1F1F5}".to_string(), 2));
+    }
+
+    #[test]
+    fn crlf() {
+        check_cluster("\r\n", 2);
+    }
+
+    #[test]
+    fn hit_test_midpoints() {
+        // "e\u{301}x\ncd", with the accent drawn on the e and the newline ending the first line
+        let text = "e\u{301}x\ncd";
+        let positions = [
+            Point::new(0.0, 0.0), Point::new(10.0, 0.0), Point::new(10.0, 0.0), Point::new(20.0, 0.0),
+            Point::new(0.0, 20.0), Point::new(10.0, 20.0), Point::new(20.0, 20.0),
+        ];
+
+        assert_eq!(hit_test(text, &positions, Point::new(4.9, 5.0)), 0);
+        assert_eq!(hit_test(text, &positions, Point::new(5.0, 5.0)), 2);
+        assert_eq!(hit_test(text, &positions, Point::new(14.9, 5.0)), 2);
+        assert_eq!(hit_test(text, &positions, Point::new(15.0, 5.0)), 3);
+        assert_eq!(hit_test(text, &positions, Point::new(50.0, 5.0)), 3);
+
+        assert_eq!(hit_test(text, &positions, Point::new(12.0, 25.0)), 5);
+        assert_eq!(hit_test(text, &positions, Point::new(50.0, 25.0)), 6);
+
+        // points above or below the text use the first or last line
+        assert_eq!(hit_test(text, &positions, Point::new(12.0, -10.0)), 2);
+        assert_eq!(hit_test(text, &positions, Point::new(12.0, 100.0)), 5);
+    }
+}
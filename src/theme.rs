@@ -1,17 +1,23 @@
 use std::collections::{HashMap};
 
 use crate::theme_definition::{
-    ThemeDefinition, ImageDefinition, ImageDefinitionKind, WidgetThemeDefinition,
+    ThemeDefinition, ResolvedTheme, ImageDefinition, ImageDefinitionKind, WidgetThemeDefinition, Easing,
 };
-use crate::font::{Font, FontSummary, FontSource};
+use crate::font::{Font, FontAtlasStats, FontSummary, FontSource};
 use crate::image::{Image, ImageHandle};
 use crate::render::{TextureData, Renderer, FontHandle};
-use crate::{Color, Error, Point, Border, Align, Layout, WidthRelative, HeightRelative};
+use crate::widget::Widget;
+use crate::{AnimState, Color, Error, Point, Border, Align, Layout, LineHeight, TextOverflow, WidthRelative, HeightRelative};
 
 pub struct ThemeSet {
+    // the font handles of each theme set are a separate range; this is the first one
+    first_font: usize,
     fonts: Vec<Font>,
     font_handles: HashMap<String, FontSummary>,
 
+    // the display scale the fonts are currently rasterized at
+    font_scale: f32,
+
     images: Vec<Image>,
     image_handles: HashMap<String, ImageHandle>,
 
@@ -22,40 +28,98 @@ pub struct ThemeSet {
 impl ThemeSet {
     pub(crate) fn new<R: Renderer>(
         definition: &ThemeDefinition,
-        textures: HashMap<String, TextureData>,
-        font_sources: HashMap<String, FontSource>,
+        textures: &HashMap<String, TextureData>,
+        solid_texture: &TextureData,
+        font_sources: &HashMap<String, FontSource>,
         renderer: &mut R,
         display_scale: f32,
+        font_handle: &mut FontHandle,
     ) -> Result<ThemeSet, Error> {
-        // TODO need to be able to rebuild fonts when scale factor changes
-        // FontSummary size will stay the same for this
+        let mut errors = Vec::new();
+        let definition = definition.resolve(&mut errors);
+
+        // fonts are rebuilt in place when the scale factor changes; see `set_font_scale`.
+        // FontSummary sizes are logical and so stay the same
         let mut font_handles = HashMap::new();
-        let mut font_handle = FontHandle::default();
+        let first_font = font_handle.id();
         let mut fonts = Vec::new();
-        for (font_id, font) in &definition.fonts {
-            let source = font_sources.get(&font.source).ok_or_else(||
-                Error::Theme(format!("Unable to locate font handle {}", font.source))
-            )?;
+        for (font_id, def) in &definition.fonts {
+            let source = match font_sources.get(&def.source) {
+                None => {
+                    errors.push(missing_font_source(font_id, &def.source));
+                    continue;
+                }, Some(source) => source,
+            };
 
-            let font = renderer.register_font(font_handle, source, font.size, display_scale)?;
-            font_handle = font_handle.next();
+            let font = renderer.register_font(*font_handle, source, def.size, display_scale)?;
+            *font_handle = font_handle.next();
 
             let line_height = font.line_height() / display_scale;
             let handle = font.handle();
-            assert!(handle.id() == fonts.len());
+            assert!(handle.id() == first_font + fonts.len());
             fonts.push(font);
-            font_handles.insert(font_id.to_string(), FontSummary { handle, line_height });
+            let summary = FontSummary::new(handle, line_height).with_spacing(def.letter_spacing, def.line_height);
+            font_handles.insert(font_id.to_string(), summary);
+        }
+
+        let mut set = ThemeSet::build(&definition, first_font, fonts, font_handles, textures, solid_texture, &mut errors);
+        set.font_scale = display_scale;
+        Error::check_all(errors)?;
+        Ok(set)
+    }
+
+    // checks `definition` in the same way as `new`, adding all problems found to `errors`, but
+    // without registering fonts with a renderer.  Fonts in the returned set have no glyphs
+    pub(crate) fn validate(
+        definition: &ThemeDefinition,
+        textures: &HashMap<String, TextureData>,
+        solid_texture: &TextureData,
+        font_ids: &[&str],
+        errors: &mut Vec<Error>,
+    ) -> ThemeSet {
+        let definition = definition.resolve(errors);
+
+        let mut font_handles = HashMap::new();
+        let mut font_handle = FontHandle::default();
+        for (font_id, font) in &definition.fonts {
+            if !font_ids.contains(&font.source.as_str()) {
+                errors.push(missing_font_source(font_id, &font.source));
+                continue;
+            }
+
+            font_handles.insert(font_id.to_string(), FontSummary::new(font_handle, 0.0));
+            font_handle = font_handle.next();
         }
 
+        ThemeSet::build(&definition, 0, Vec::new(), font_handles, textures, solid_texture, errors)
+    }
+
+    // builds the images and widget themes of the set.  Anything that can't be built is left
+    // out, with the reason added to `errors`
+    fn build(
+        definition: &ResolvedTheme,
+        first_font: usize,
+        fonts: Vec<Font>,
+        font_handles: HashMap<String, FontSummary>,
+        textures: &HashMap<String, TextureData>,
+        solid_texture: &TextureData,
+        errors: &mut Vec<Error>,
+    ) -> ThemeSet {
         let mut aliases: Vec<(String, String)> = Vec::new();
 
         let mut images = HashMap::new();
         for (set_id, set) in &definition.image_sets {
             let mut images_in_set = HashMap::new();
 
-            let texture = textures.get(&set.source).ok_or_else(||
-                Error::Theme(format!("Unable to locate texture {}", set.source))
-            )?;
+            let texture = match &set.source {
+                None => solid_texture,
+                Some(source) => match textures.get(source) {
+                    None => {
+                        errors.push(field_error("image set", set_id, "source", format!("Unable to locate texture '{}'", source)));
+                        continue;
+                    }, Some(texture) => texture,
+                },
+            };
 
             let mut collected_images: Vec<(&str, &ImageDefinition)> = Vec::new();
             let mut timed_images: Vec<(&str, &ImageDefinition)> = Vec::new();
@@ -63,38 +127,47 @@ impl ThemeSet {
 
             // first parse all images without dependencies
             for (image_id, image_def) in &set.images {
+                let full_id = format!("{}/{}", set_id, image_id);
+
+                // solid images don't use the set's texture, so they must be in a set without one
+                if !image_def.kind.is_reference() {
+                    if set.source.is_some() && image_def.kind.is_solid() {
+                        errors.push(Error::Theme(format!(
+                            "In image '{}': Solid, gradient, and outline images must be in an image set without a source", full_id
+                        )));
+                        continue;
+                    } else if set.source.is_none() && !image_def.kind.is_solid() {
+                        errors.push(Error::Theme(format!(
+                            "In image '{}': Images must be in an image set with a source texture", full_id
+                        )));
+                        continue;
+                    }
+                }
+
                 match &image_def.kind {
                     ImageDefinitionKind::Animated { .. } => animated_images.push((image_id, image_def)),
                     ImageDefinitionKind::Timed { .. } => timed_images.push((image_id, image_def)),
                     ImageDefinitionKind::Collected { .. } => collected_images.push((image_id, image_def)),
                     ImageDefinitionKind::Alias { from } => {
-                        let to = format!("{}/{}", set_id, image_id);
                         let from = format!("{}/{}", set_id, from);
-                        aliases.push((to, from));
+                        aliases.push((full_id, from));
                     },
                     _ => {
-                        let image = Image::new(&image_id, image_def, texture, &images_in_set, set.scale)?;
-                        images_in_set.insert(image_id.to_string(), image);
+                        match Image::new(&full_id, image_def, texture, &images_in_set, set.scale) {
+                            Ok(image) => { images_in_set.insert(image_id.to_string(), image); },
+                            Err(e) => errors.push(e),
+                        }
                     }
                 }
             }
 
-            // now parse collected images
-            for (id, image_def) in collected_images {
-                let image = Image::new(id, image_def, texture, &images_in_set, set.scale)?;
-                images_in_set.insert(id.to_string(), image);
-            }
-
-            // now parse timed images
-            for (id, image_def) in timed_images {
-                let image = Image::new(id, image_def, texture, &images_in_set, set.scale)?;
-                images_in_set.insert(id.to_string(), image);
-            }
-
-            // now parse animated images
-            for (id, image_def) in animated_images {
-                let image = Image::new(id, image_def, texture, &images_in_set, set.scale)?;
-                images_in_set.insert(id.to_string(), image);
+            // now parse collected images, then timed images, then animated images
+            for (id, image_def) in collected_images.into_iter().chain(timed_images).chain(animated_images) {
+                let full_id = format!("{}/{}", set_id, id);
+                match Image::new(&full_id, image_def, texture, &images_in_set, set.scale) {
+                    Ok(image) => { images_in_set.insert(id.to_string(), image); },
+                    Err(e) => errors.push(e),
+                }
             }
 
             // create the full hashmap with all images
@@ -122,21 +195,23 @@ impl ThemeSet {
 
         // add in aliases
         for (to, from) in aliases {
-            let handle = *image_handles.get(&from).ok_or_else(||
-                Error::Theme(format!("Unable to locate image alias from '{}'", from))
-            )?;
-
-            image_handles.insert(to, handle);
+            match image_handles.get(&from) {
+                None => errors.push(field_error("image", &to, "from", format!("Unable to locate image '{}'", from))),
+                Some(handle) => { image_handles.insert(to, *handle); },
+            }
         }
 
         // build the set of themes
         let mut theme_handles = HashMap::new();
         let mut themes = Vec::new();
 
-        // create the default theme
+        // create the default theme, using the font named "default" if there is one
+        let default_font = font_handles.get("default").copied().or_else(||
+            font_handles.values().min_by_key(|font| font.handle.id()).copied()
+        );
         let default_handle = WidgetThemeHandle { id: 0 };
         let default_id = "default";
-        themes.push(WidgetTheme::create_default(default_id, default_handle));
+        themes.push(WidgetTheme::create_default(default_id, default_handle, default_font));
         theme_handles.insert(default_id.to_string(), default_handle);
 
         let mut handle_index = 1;
@@ -151,7 +226,8 @@ impl ThemeSet {
                 theme, 
                 &image_handles,
                 &font_handles,
-            )?;
+                errors,
+            );
         }
 
         // recursively resolve all "from" theme references
@@ -161,10 +237,17 @@ impl ThemeSet {
         let mut iteration = 0;
         loop {
             if iteration == MAX_ITERATIONS {
-                return Err(
-                    Error::Theme(format!("Unable to resolve all from references after {} iterations.  \
-                        This is most likely caused by a circular reference.", iteration))
+                let mut unresolved: Vec<&str> = themes.iter()
+                    .filter(|theme| theme.from.is_some())
+                    .map(|theme| theme.full_id.as_str())
+                    .collect();
+                unresolved.sort_unstable();
+
+                errors.push(
+                    Error::Theme(format!("Unable to resolve the from references of {:?} after {} iterations.  \
+                        This is most likely caused by a circular reference.", unresolved, iteration))
                 );
+                break;
             }
 
             let to_ids: Vec<WidgetThemeHandle> = theme_handles.values().copied().collect();
@@ -178,9 +261,14 @@ impl ThemeSet {
 
                 found_new = true;
 
-                let from_id = resolve_from(&themes, &theme_handles, from_str, *to_id).ok_or_else(|| {
-                    Error::Theme(format!("Invalid from theme '{}' in '{}'", from_str, themes[to_id.id as usize].id))
-                })?;
+                let from_id = match resolve_from(&themes, &theme_handles, from_str, *to_id) {
+                    None => {
+                        let to = &themes[to_id.id as usize];
+                        errors.push(field_error("widget", &to.full_id, "from", format!("Unable to locate theme '{}'", from_str)));
+                        themes[to_id.id as usize].from.take();
+                        continue;
+                    }, Some(from_id) => from_id,
+                };
 
                 // if the 'from' field has its own 'from', don't resolve
                 // it yet.  we need the nested froms to resolve first
@@ -203,14 +291,16 @@ impl ThemeSet {
             iteration += 1;
         }
 
-        Ok(ThemeSet {
+        ThemeSet {
+            first_font,
             font_handles,
             fonts,
+            font_scale: 1.0,
             image_handles,
             images: images_out,
             theme_handles,
             themes,
-        })
+        }
     }
 
     pub(crate) fn default_theme(&self) -> &WidgetTheme {
@@ -223,7 +313,60 @@ impl ThemeSet {
     }
 
     pub fn font(&self, handle: FontHandle) -> &Font {
-        &self.fonts[handle.id()]
+        &self.fonts[handle.id() - self.first_font]
+    }
+
+    // marks the glyphs of all visible `widgets` as used this frame, adding any that are missing,
+    // and passes each font atlas that changed to `upload`.  Glyphs not used since the previous
+    // call may be evicted once an atlas is full
+    pub(crate) fn prepare_fonts<F: FnMut(FontHandle, &[u8], u32, u32)>(&self, widgets: &[Widget], mut upload: F) {
+        for font in &self.fonts {
+            font.next_generation();
+        }
+
+        for widget in widgets {
+            if !widget.visible() { continue; }
+
+            if let (Some(text), Some(font)) = (widget.text(), widget.font()) {
+                self.font(font.handle).cache_text(text);
+            }
+        }
+
+        for font in &self.fonts {
+            font.upload_atlas(|data, width, height| upload(font.handle(), data, width, height));
+        }
+    }
+
+    // re-rasterizes the fonts in this set for the display `scale`, if it differs from the scale they
+    // were built at.  Font sizes in logical pixels, and so all layout, are unchanged
+    pub(crate) fn set_font_scale(&mut self, scale: f32) {
+        if (self.font_scale - scale).abs() < f32::EPSILON { return; }
+
+        self.font_scale = scale;
+        for font in &mut self.fonts {
+            font.set_scale(scale);
+        }
+    }
+
+    pub(crate) fn font_atlas_stats(&self) -> Vec<(String, FontAtlasStats)> {
+        let mut stats: Vec<_> = self.font_handles.iter()
+            .filter(|(_, summary)| summary.handle.id() >= self.first_font)
+            .filter_map(|(id, summary)| {
+                self.fonts.get(summary.handle.id() - self.first_font).map(|font| (id.to_string(), font.atlas_stats()))
+            }).collect();
+        stats.sort_by(|a, b| a.0.cmp(&b.0));
+        stats
+    }
+
+    // the ID of the font registered with `handle` in this set, if any
+    pub(crate) fn font_id(&self, handle: FontHandle) -> Option<&str> {
+        self.font_handles.iter()
+            .find(|(_, summary)| summary.handle == handle)
+            .map(|(id, _)| id.as_str())
+    }
+
+    pub(crate) fn font_ids(&self) -> impl Iterator<Item=&str> {
+        self.font_handles.keys().map(|id| id.as_str())
     }
 
     pub fn find_font(&self, id: Option<&str>) -> Option<FontSummary> {
@@ -263,6 +406,15 @@ impl ThemeSet {
     }
 }
 
+// an error in `field` of the widget theme, image, or other `item` at `path`
+pub(crate) fn field_error(item: &str, path: &str, field: &str, msg: String) -> Error {
+    Error::Theme(format!("In {} '{}', field '{}': {}", item, path, field, msg))
+}
+
+fn missing_font_source(font_id: &str, source: &str) -> Error {
+    field_error("font", font_id, "source", format!("Unable to locate font source '{}'", source))
+}
+
 fn resolve_from(
     themes: &[WidgetTheme],
     handles: &HashMap<String, WidgetThemeHandle>,
@@ -301,7 +453,11 @@ pub struct WidgetTheme {
 
     pub text: Option<String>,
     pub text_color: Option<Color>,
+    pub state_text_colors: Vec<(AnimState, Color)>,
+    pub placeholder_color: Option<Color>,
+    pub tint: Option<Color>,
     pub font: Option<FontSummary>,
+    pub state_fonts: Vec<(AnimState, FontSummary)>,
     pub background: Option<ImageHandle>,
     pub foreground: Option<ImageHandle>,
 
@@ -309,7 +465,12 @@ pub struct WidgetTheme {
     // we can detect when to override them
     pub wants_mouse: Option<bool>,
     pub wants_scroll: Option<bool>,
+    pub expand: Option<bool>,
     pub text_align: Option<Align>,
+    pub wrap: Option<bool>,
+    pub overflow: Option<TextOverflow>,
+    pub letter_spacing: Option<f32>,
+    pub line_height: Option<LineHeight>,
     pub pos: Option<Point>,
     pub screen_pos: Option<Point>,
     pub size: Option<Point>,
@@ -320,13 +481,25 @@ pub struct WidgetTheme {
     pub child_align: Option<Align>,
     pub layout: Option<Layout>,
     pub layout_spacing: Option<Point>,
+    pub transition_ms: Option<u32>,
+    pub easing: Option<Easing>,
+    pub state_tints: Vec<(AnimState, Color)>,
+    pub state_offsets: Vec<(AnimState, Point)>,
     pub children: Vec<WidgetThemeHandle>,
 
     pub custom_floats: HashMap<String, f32>,
 }
 
 impl WidgetTheme {
-    fn create_default(id: &'static str, handle: WidgetThemeHandle) -> WidgetTheme {
+    // the fallback theme used for widgets whose theme can't be found.  It has no images, so in
+    // debug builds the text is drawn in magenta to make these widgets stand out
+    fn create_default(id: &'static str, handle: WidgetThemeHandle, font: Option<FontSummary>) -> WidgetTheme {
+        let text_color = if cfg!(debug_assertions) {
+            Color { r: 255, g: 0, b: 255 }
+        } else {
+            Color::white()
+        };
+
         WidgetTheme {
             from: None,
             full_id: id.to_string(),
@@ -334,16 +507,25 @@ impl WidgetTheme {
             parent_handle: None,
             handle,
             text: None,
-            text_color: None,
-            font: None,
+            text_color: Some(text_color),
+            state_text_colors: Vec::new(),
+            placeholder_color: None,
+            tint: None,
+            font,
+            state_fonts: Vec::new(),
             background: None,
             foreground: None,
             wants_mouse: None,
             wants_scroll: None,
-            text_align: None,
+            expand: None,
+            text_align: Some(Align::Center),
+            wrap: None,
+            overflow: None,
+            letter_spacing: None,
+            line_height: None,
             pos: None,
             screen_pos: None,
-            size: None,
+            size: Some(Point::new(100.0, 24.0)),
             width_from: None,
             height_from: None,
             border: None,
@@ -351,6 +533,10 @@ impl WidgetTheme {
             child_align: None,
             layout: None,
             layout_spacing: None,
+            transition_ms: None,
+            easing: None,
+            state_tints: Vec::new(),
+            state_offsets: Vec::new(),
             children: Vec::new(),
             custom_floats: HashMap::new(),
         }
@@ -367,13 +553,8 @@ impl WidgetTheme {
         def: &WidgetThemeDefinition,
         images: &HashMap<String, ImageHandle>,
         fonts: &HashMap<String, FontSummary>,
-    ) -> Result<WidgetThemeHandle, Error> {
-        if id.contains('/') {
-            return Err(
-                Error::Theme(format!("'{}' theme name invalid.  the '/' character is not allowed", id))
-            );
-        }
-
+        errors: &mut Vec<Error>,
+    ) -> Option<WidgetThemeHandle> {
         // handle top level as a special case
         let parent_id = if parent_id.is_empty() {
             id.to_string()
@@ -381,30 +562,40 @@ impl WidgetTheme {
             format!("{}/{}", parent_id, id)
         };
 
-        let background = if let Some(bg) = def.background.as_ref() {
-            Some(*images.get(bg).ok_or_else(||
-                Error::Theme(format!("Unable to locate image '{}' as background for widget '{}'", bg, parent_id))
-            )?)
-        } else {
-            None
-        };
+        if id.contains('/') {
+            errors.push(
+                Error::Theme(format!("'{}' theme name invalid.  the '/' character is not allowed", parent_id))
+            );
+            return None;
+        }
 
-        let foreground = if let Some(fg) = def.foreground.as_ref() {
-            Some(*images.get(fg).ok_or_else(||
-                Error::Theme(format!("Unable to locate image '{}' as foreground for widget '{}'", fg, parent_id))
-            )?)
-        } else {
-            None
+        // fields that can't be found are reported and then left unset, so the rest of the theme is still checked
+        let mut find_image = |field: &str, image: &str| {
+            let handle = images.get(image).copied();
+            if handle.is_none() {
+                errors.push(field_error("widget", &parent_id, field, format!("Unable to locate image '{}'", image)));
+            }
+            handle
         };
 
-        let font = if let Some(font) = def.font.as_ref() {
-            let font_handle = fonts.get(font).ok_or_else(||
-                Error::Theme(format!("Unable to locate font '{}' for widget '{}'", font, parent_id))
-            )?;
-            Some(*font_handle)
-        } else {
-            None
+        let background = def.background.as_ref().and_then(|bg| find_image("background", bg));
+        let foreground = def.foreground.as_ref().and_then(|fg| find_image("foreground", fg));
+
+        let (font, state_fonts) = def.font.as_ref().map(|font| font.split()).unwrap_or_default();
+        let mut find_font = |font: &str| {
+            let summary = fonts.get(font).copied();
+            if summary.is_none() {
+                errors.push(field_error("widget", &parent_id, "font", format!("Unable to locate font '{}'", font)));
+            }
+            summary
         };
+        let font = font.and_then(|font| find_font(&font));
+        let state_fonts = state_fonts.into_iter()
+            .filter_map(|(state, font)| Some((state, find_font(&font)?)))
+            .collect();
+
+        let (text_color, state_text_colors) = def.text_color.as_ref()
+            .map(|color| color.split()).unwrap_or_default();
 
         let (width_from, height_from) = if let Some((width_from, height_from)) = def.size_from {
             (Some(width_from), Some(height_from))
@@ -421,13 +612,22 @@ impl WidgetTheme {
             id,
             full_id: parent_id.to_string(),
             text: def.text.clone(),
-            text_color: def.text_color,
+            text_color,
+            state_text_colors,
+            placeholder_color: def.placeholder_color,
+            tint: def.tint,
             font,
+            state_fonts,
             background,
             foreground,
             wants_mouse: def.wants_mouse,
             wants_scroll: def.wants_scroll,
+            expand: def.expand,
             text_align: def.text_align,
+            wrap: def.wrap,
+            overflow: def.overflow,
+            letter_spacing: def.letter_spacing,
+            line_height: def.line_height,
             pos: def.pos,
             screen_pos: def.screen_pos,
             size: def.size,
@@ -438,6 +638,10 @@ impl WidgetTheme {
             border: def.border,
             layout: def.layout,
             layout_spacing: def.layout_spacing,
+            transition_ms: def.transition_ms,
+            easing: def.easing,
+            state_tints: def.state_tints.iter().map(|(state, color)| (*state, *color)).collect(),
+            state_offsets: def.state_offsets.iter().map(|(state, offset)| (*state, *offset)).collect(),
             children: Vec::new(),
             custom_floats: def.custom_floats.clone(),
         };
@@ -455,16 +659,17 @@ impl WidgetTheme {
                 themes,
                 child_def,
                 images,
-                fonts
-            )?;
-            children.push(child);
+                fonts,
+                errors,
+            );
+            children.extend(child);
         }
 
         themes[handle.id as usize].children = children;
 
         handles.insert(parent_id, handle);
 
-        Ok(handle)
+        Some(handle)
     }
 }
 
@@ -486,10 +691,19 @@ fn merge_from(
 
     if to.wants_mouse.is_none() { to.wants_mouse = from.wants_mouse; }
     if to.wants_scroll.is_none() { to.wants_scroll = from.wants_scroll; }
+    if to.expand.is_none() { to.expand = from.expand; }
     if to.font.is_none() { to.font = from.font; }
+    if to.state_fonts.is_empty() { to.state_fonts = from.state_fonts.clone(); }
+    if to.state_text_colors.is_empty() { to.state_text_colors = from.state_text_colors.clone(); }
     if to.background.is_none() { to.background = from.background; }
     if to.foreground.is_none() { to.foreground = from.foreground; }
+    if to.placeholder_color.is_none() { to.placeholder_color = from.placeholder_color; }
+    if to.tint.is_none() { to.tint = from.tint; }
     if to.text_align.is_none() { to.text_align = from.text_align; }
+    if to.wrap.is_none() { to.wrap = from.wrap; }
+    if to.overflow.is_none() { to.overflow = from.overflow; }
+    if to.letter_spacing.is_none() { to.letter_spacing = from.letter_spacing; }
+    if to.line_height.is_none() { to.line_height = from.line_height; }
     if to.pos.is_none() { to.pos = from.pos; }
     if to.screen_pos.is_none() { to.screen_pos = from.screen_pos; }
     if to.size.is_none() { to.size = from.size; }
@@ -501,6 +715,10 @@ fn merge_from(
     if to.layout.is_none() { to.layout = from.layout; }
     if to.layout_spacing.is_none() { to.layout_spacing = from.layout_spacing; }
     if to.text.is_none() { to.text = from.text.clone(); }
+    if to.transition_ms.is_none() { to.transition_ms = from.transition_ms; }
+    if to.easing.is_none() { to.easing = from.easing; }
+    if to.state_tints.is_empty() { to.state_tints = from.state_tints.clone(); }
+    if to.state_offsets.is_empty() { to.state_offsets = from.state_offsets.clone(); }
 
     for (id, value) in from.custom_floats.iter() {
         match to.custom_floats.entry(id.to_string()) {
@@ -1,28 +1,84 @@
 use std::collections::{HashMap, hash_map::Entry};
 use std::fmt;
+use std::ops::Mul;
 
-use serde::{Serialize, Deserialize, Deserializer, Serializer, de::{self, Visitor}};
+use serde::{Serialize, Deserialize, Deserializer, Serializer, de::{self, Visitor, MapAccess, IntoDeserializer}};
+use serde::de::value::MapAccessDeserializer;
 
-use crate::{Border, Point};
+use crate::{Border, Error, Point};
+use crate::theme_value::ThemeValue;
 
-#[derive(Serialize, Deserialize)]
+// fonts, image sets, and widgets are kept as untyped values until the theme is built, so
+// that constants may be substituted into them
+#[derive(Serialize, Deserialize, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct ThemeDefinition {
     #[serde(default)]
-    pub fonts: HashMap<String, FontDefinition>,
+    pub constants: HashMap<String, ThemeValue>,
 
     #[serde(default)]
-    pub image_sets: HashMap<String, ImageSet>,
+    pub fonts: HashMap<String, ThemeValue>,
 
     #[serde(default)]
+    pub image_sets: HashMap<String, ThemeValue>,
+
+    #[serde(default)]
+    pub widgets: HashMap<String, ThemeValue>,
+}
+
+// a theme definition with all constants substituted
+pub struct ResolvedTheme {
+    pub fonts: HashMap<String, FontDefinition>,
+    pub image_sets: HashMap<String, ImageSet>,
     pub widgets: HashMap<String, WidgetThemeDefinition>,
 }
 
 impl ThemeDefinition {
-    /// Merges the specified `other` theme definition into this one
+    /// Substitutes constants into all fonts, image sets, and widgets, and reads them into
+    /// their definitions.  Entries that can't be read are left out, with the reason added to `errors`
+    pub fn resolve(&self, errors: &mut Vec<Error>) -> ResolvedTheme {
+        fn resolve_all<T: for<'de> Deserialize<'de>>(
+            values: &HashMap<String, ThemeValue>,
+            constants: &HashMap<String, ThemeValue>,
+            section: &str,
+            errors: &mut Vec<Error>,
+        ) -> HashMap<String, T> {
+            values.iter().filter_map(|(id, value)| {
+                let path = format!("{}/{}", section, id);
+                let mut value = value.clone();
+                let result = value.substitute(constants, &path).and_then(|_| value.deserialize_into(&path));
+                match result {
+                    Ok(def) => Some((id.to_string(), def)),
+                    Err(e) => {
+                        errors.push(e);
+                        None
+                    }
+                }
+            }).collect()
+        }
+
+        ResolvedTheme {
+            fonts: resolve_all(&self.fonts, &self.constants, "fonts", errors),
+            image_sets: resolve_all(&self.image_sets, &self.constants, "image_sets", errors),
+            widgets: resolve_all(&self.widgets, &self.constants, "widgets", errors),
+        }
+    }
+
+    /// Merges the specified `other` theme definition into this one.  Entries in `other` are
+    /// added, replacing any existing entries with the same ID
     pub fn merge(&mut self, other: ThemeDefinition) {
         use Entry::*;
 
+        for (id, constant) in other.constants {
+            match self.constants.entry(id) {
+                Occupied(mut entry) => {
+                    log::warn!("Overwriting constant '{}'", entry.key());
+                    entry.insert(constant);
+                },
+                Vacant(entry) => { entry.insert(constant); }
+            }
+        }
+
         for (id, font) in other.fonts {
             match self.fonts.entry(id) {
                 Occupied(mut entry) => {
@@ -33,12 +89,18 @@ impl ThemeDefinition {
             }
         }
 
-        for (id, image) in other.image_sets {
+        // image sets from the same source are merged image by image, while a set with a
+        // different source replaces the existing set entirely, such as when reskinning
+        for (id, set) in other.image_sets {
             match self.image_sets.entry(id) {
+                Occupied(mut entry) if entry.get().get("source") == set.get("source") => {
+                    let set_id = entry.key().to_string();
+                    merge_image_set(&set_id, entry.get_mut(), set);
+                },
                 Occupied(mut entry) => {
                     log::warn!("Overwriting image set id '{}'", entry.key());
-                    entry.insert(image);
-                }, Vacant(entry) => { entry.insert(image); }
+                    entry.insert(set);
+                }, Vacant(entry) => { entry.insert(set); }
             }
         }
 
@@ -53,22 +115,65 @@ impl ThemeDefinition {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+// merges the fields and images of `from` into the image set `to`
+fn merge_image_set(set_id: &str, to: &mut ThemeValue, from: ThemeValue) {
+    let (to, from) = match (to.entries_mut(), from) {
+        (Some(to), ThemeValue::Map(from)) => (to, from),
+        (_, from) => {
+            log::warn!("Overwriting image set id '{}'", set_id);
+            *to = from;
+            return;
+        }
+    };
+
+    for (key, value) in from {
+        let existing = match to.iter_mut().find(|(existing, _)| *existing == key) {
+            None => {
+                to.push((key, value));
+                continue;
+            }, Some((_, existing)) => existing,
+        };
+
+        match (key.as_str(), existing.entries_mut(), value) {
+            (Some("images"), Some(images), ThemeValue::Map(new_images)) => {
+                for (image_id, image) in new_images {
+                    match images.iter_mut().find(|(id, _)| *id == image_id) {
+                        Some((_, existing_image)) => {
+                            log::warn!("Overwriting image id '{}/{}'", set_id, image_id.as_str().unwrap_or("?"));
+                            *existing_image = image;
+                        },
+                        None => images.push((image_id, image)),
+                    }
+                }
+            },
+            (_, _, value) => *existing = value,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct WidgetThemeDefinition {
     pub from: Option<String>,
 
     pub text: Option<String>,
-    pub font: Option<String>,
+    pub font: Option<StateValues<String>>,
     pub background: Option<String>,
     pub foreground: Option<String>,
 
     // all fields are options instead of using default so
     // we can detect when to override them
-    pub text_color: Option<Color>,
+    pub text_color: Option<StateValues<Color>>,
+    pub placeholder_color: Option<Color>,
+    pub tint: Option<Color>,
     pub wants_mouse: Option<bool>,
     pub wants_scroll: Option<bool>,
+    pub expand: Option<bool>,
     pub text_align: Option<Align>,
+    pub wrap: Option<bool>,
+    pub overflow: Option<TextOverflow>,
+    pub letter_spacing: Option<f32>,
+    pub line_height: Option<LineHeight>,
     pub pos: Option<Point>,
     pub screen_pos: Option<Point>,
     pub size: Option<Point>,
@@ -80,6 +185,14 @@ pub struct WidgetThemeDefinition {
     pub child_align: Option<Align>,
     pub layout: Option<Layout>,
     pub layout_spacing: Option<Point>,
+    pub transition_ms: Option<u32>,
+    pub easing: Option<Easing>,
+
+    #[serde(default)]
+    pub state_tints: HashMap<AnimState, Color>,
+
+    #[serde(default)]
+    pub state_offsets: HashMap<AnimState, Point>,
 
     #[serde(default)]
     pub custom_floats: HashMap<String, f32>,
@@ -88,10 +201,12 @@ pub struct WidgetThemeDefinition {
     pub children: HashMap<String, WidgetThemeDefinition>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct ImageSet {
-    pub source: String,
+    // image sets without a source may only contain images drawn without a texture
+    #[serde(default)]
+    pub source: Option<String>,
 
     #[serde(default="f32_one")]
     pub scale: f32,
@@ -101,7 +216,7 @@ pub struct ImageSet {
 
 fn f32_one() -> f32 { 1.0 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct ImageDefinition {
     #[serde(default)]
     pub color: Color,
@@ -123,14 +238,14 @@ impl Default for ImageFill {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct CollectedSubImage {
     pub position: [i32; 2],
     pub size: [i32; 2],
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 #[serde(untagged)]
 #[serde(deny_unknown_fields)]
 pub enum ImageDefinitionKind {
@@ -160,7 +275,12 @@ pub enum ImageDefinitionKind {
         fill: ImageFill,
     },
     Timed {
-        frame_time_millis: u32,
+        #[serde(default)]
+        frame_time_millis: Option<u32>,
+
+        #[serde(default)]
+        total_time_millis: Option<u32>,
+
         frames: Vec<String>,
 
         #[serde(default)]
@@ -168,6 +288,64 @@ pub enum ImageDefinitionKind {
     },
     Animated {
         states: HashMap<AnimState, String>,
+    },
+    Outline {
+        outline: Border,
+        outline_color: OutlineColor,
+
+        #[serde(default)]
+        solid: Option<Color>,
+    },
+    Solid {
+        solid: Color,
+    },
+    GradientVertical {
+        gradient_vert: [Color; 2],
+    },
+    GradientHorizontal {
+        gradient_horiz: [Color; 2],
+    },
+}
+
+impl ImageDefinitionKind {
+    // whether this kind of image is drawn using a solid color, rather than from a texture
+    pub fn is_solid(&self) -> bool {
+        matches!(
+            self,
+            ImageDefinitionKind::Outline { .. } | ImageDefinitionKind::Solid { .. } |
+            ImageDefinitionKind::GradientVertical { .. } | ImageDefinitionKind::GradientHorizontal { .. }
+        )
+    }
+
+    // whether this kind of image only references other images
+    pub fn is_reference(&self) -> bool {
+        matches!(
+            self,
+            ImageDefinitionKind::Alias { .. } | ImageDefinitionKind::Collected { .. } |
+            ImageDefinitionKind::Timed { .. } | ImageDefinitionKind::Animated { .. }
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone)]
+#[serde(untagged)]
+pub enum OutlineColor {
+    All(Color),
+    Sides {
+        top: Color,
+        bot: Color,
+        left: Color,
+        right: Color,
+    },
+}
+
+impl OutlineColor {
+    // the top, bottom, left, and right colors
+    pub fn sides(self) -> [Color; 4] {
+        match self {
+            OutlineColor::All(color) => [color; 4],
+            OutlineColor::Sides { top, bot, left, right } => [top, bot, left, right],
+        }
     }
 }
 
@@ -260,6 +438,43 @@ impl AnimState {
 
         self.keys.sort();
     }
+
+    // returns a copy of this `AnimState` with the specified key removed
+    pub(crate) fn without(&self, to_remove: AnimStateKey) -> AnimState {
+        let mut keys = self.keys;
+        for key in keys.iter_mut() {
+            if *key == to_remove {
+                *key = AnimStateKey::Normal;
+            }
+        }
+
+        keys.sort();
+        AnimState { keys }
+    }
+
+    // this state, followed by the states that images not defining it fall back to, with
+    // each combination of the focused, fading, and flash keys removed
+    pub(crate) fn fallbacks(self) -> impl Iterator<Item = AnimState> {
+        const OPTIONAL: [AnimStateKey; 3] = [AnimStateKey::Focused, AnimStateKey::Fading, AnimStateKey::Flash];
+
+        (0..(1 << OPTIONAL.len())).map(move |mask| {
+            let mut state = self;
+            for (index, key) in OPTIONAL.iter().enumerate() {
+                if mask & (1 << index) != 0 {
+                    state = state.without(*key);
+                }
+            }
+            state
+        })
+    }
+
+    // finds the value for this state from a list of per state values, falling back in the same
+    // way as animated images
+    pub(crate) fn find_value<T: Copy>(self, values: &[(AnimState, T)]) -> Option<T> {
+        self.fallbacks().find_map(|candidate| {
+            values.iter().find(|(state, _)| *state == candidate).map(|(_, value)| *value)
+        })
+    }
 }
 
 struct AnimStateVisitor;
@@ -304,6 +519,15 @@ impl<'de> Visitor<'de> for AnimStateVisitor {
                 "Active" => {
                     add_if_not_already_present(&mut keys, key_index, AnimStateKey::Active)?;
                 }
+                "Focused" => {
+                    add_if_not_already_present(&mut keys, key_index, AnimStateKey::Focused)?;
+                }
+                "Fading" => {
+                    add_if_not_already_present(&mut keys, key_index, AnimStateKey::Fading)?;
+                }
+                "Flash" => {
+                    add_if_not_already_present(&mut keys, key_index, AnimStateKey::Flash)?;
+                }
                 _ => {
                     return Err(E::custom(format!("Unable to parse AnimStateKey from {}", key_id)));
                 }
@@ -348,6 +572,9 @@ impl Serialize for AnimState {
                 Pressed => val.push_str("Pressed"),
                 Disabled => val.push_str("Disabled"),
                 Active => val.push_str("Active"),
+                Focused => val.push_str("Focused"),
+                Fading => val.push_str("Fading"),
+                Flash => val.push_str("Flash"),
             }
 
             first = false;
@@ -379,6 +606,18 @@ pub enum AnimStateKey {
 
     /// The widget is activated.
     Active,
+
+    /// The widget has keyboard focus.  Images without a state including `Focused` fall
+    /// back to the same state without it.
+    Focused,
+
+    /// The widget is fading out, such as an auto-hiding scrollbar that is no longer in use.
+    /// Images without a state including `Fading` fall back to the same state without it.
+    Fading,
+
+    /// The widget is briefly highlighted, such as with [`Frame.flash`](struct.Frame.html#method.flash).
+    /// Images without a state including `Flash` fall back to the same state without it.
+    Flash,
 }
 
 /// The Layout direction for a widget's children.
@@ -393,15 +632,113 @@ pub enum Layout {
     /// Layout children vertically, from top to bottom
     Vertical,
 
+    /// Layout children horizontally, from left to right, wrapping to a new row whenever the
+    /// next child would not fit within the parent's inner width.  Each row is as tall as its
+    /// tallest child.
+    HorizontalWrap,
+
     /// Don't layout children in any order.  Children must specify manual alignments to
     /// avoid overlap.
     Free,
+
+    /// Layout children in a grid with the specified number of `columns`, from left to right and
+    /// then top to bottom.  The parent's inner width is divided evenly between the columns, and
+    /// each row is as tall as its tallest child.  The horizontal component of the parent's
+    /// `child_align` determines where children narrower than their cell are placed within it;
+    /// children are placed at the top of their row.  In a theme file, this is specified as
+    /// `layout: { Grid: { columns: 4 } }`.
+    Grid {
+        /// The number of children in each row
+        columns: u32,
+    },
 }
 
 impl Default for Layout {
     fn default() -> Self { Layout::Horizontal }
 }
 
+// a theme value that is either given once, or separately for each `AnimState`, in the
+// same way as the images of an animated image
+#[derive(Serialize, Clone)]
+#[serde(untagged)]
+pub enum StateValues<T> {
+    Single(T),
+    States(HashMap<AnimState, T>),
+}
+
+// single values are always given as strings.  This is deserialized by hand rather than as an
+// untagged enum so that errors in the value itself are reported, rather than a failure to match
+struct StateValuesVisitor<T>(std::marker::PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for StateValuesVisitor<T> {
+    type Value = StateValues<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("A single value, or a map of anim states to values")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        Ok(StateValues::Single(T::deserialize(value.into_deserializer())?))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        Ok(StateValues::States(HashMap::deserialize(MapAccessDeserializer::new(map))?))
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for StateValues<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<StateValues<T>, D::Error> {
+        deserializer.deserialize_any(StateValuesVisitor(std::marker::PhantomData))
+    }
+}
+
+impl<T: Clone> StateValues<T> {
+    // splits into the value for the normal state, if any, and the values for each state
+    pub fn split(&self) -> (Option<T>, Vec<(AnimState, T)>) {
+        match self {
+            StateValues::Single(value) => (Some(value.clone()), Vec::new()),
+            StateValues::States(states) => (
+                states.get(&AnimState::normal()).cloned(),
+                states.iter().map(|(state, value)| (*state, value.clone())).collect(),
+            ),
+        }
+    }
+}
+
+/// The rate of change over the course of an animated transition between two values, such as a
+/// widget's tint changing when it is hovered.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Easing {
+    /// Changes at a constant rate
+    Linear,
+
+    /// Starts slowly and speeds up toward the end
+    EaseIn,
+
+    /// Starts quickly and slows down toward the end
+    EaseOut,
+
+    /// Starts and ends slowly, and is fastest in the middle
+    EaseInOut,
+}
+
+impl Default for Easing {
+    fn default() -> Self { Easing::Linear }
+}
+
+impl Easing {
+    /// Returns the eased progress for the linear progress `frac`, which is clamped to [0, 1].
+    pub fn apply(self, frac: f32) -> f32 {
+        let t = frac.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => t * (2.0 - t),
+            Easing::EaseInOut => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
 /// Widget or text horizontal and vertical alignment.
 ///
 /// `Left`, `Right`, and `Center` variants will center the element
@@ -464,11 +801,56 @@ impl Align {
     }
 }
 
+/// How a widget's text is drawn when it does not fit within the widget's inner area.
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[serde(deny_unknown_fields)]
+pub enum TextOverflow {
+    /// Text is drawn past the edges of the widget, limited only by the widget's [`clip`](struct.WidgetBuilder.html#method.clip)
+    Visible,
+
+    /// Text is clipped to the widget's inner area
+    Clip,
+
+    /// Text is not wrapped, and each line too wide for the widget's inner area is cut short and ended with an
+    /// ellipsis, drawn as three periods.  The text is also clipped to the widget's inner area.  Whether any text
+    /// was cut short is reported in [`WidgetState.truncated`](struct.WidgetState.html#structfield.truncated).
+    Ellipsis,
+}
+
+impl Default for TextOverflow {
+    fn default() -> Self { TextOverflow::Visible }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct FontDefinition {
     pub source: String,
     pub size: f32,
+    pub letter_spacing: Option<f32>,
+    pub line_height: Option<LineHeight>,
+}
+
+/// The height of each line of text, overriding the natural line height of a font.  May be specified
+/// in a font definition or a widget theme.  Any extra space is split evenly above and below the text
+/// on each line.
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub enum LineHeight {
+    /// Each line is the specified height, in logical pixels
+    Absolute(f32),
+
+    /// Each line is the font's natural line height multiplied by the specified amount
+    Multiplier(f32),
+}
+
+impl LineHeight {
+    /// Returns the line height for a font with the natural line height `natural`.
+    pub fn apply(self, natural: f32) -> f32 {
+        match self {
+            LineHeight::Absolute(height) => height,
+            LineHeight::Multiplier(multiplier) => natural * multiplier,
+        }
+    }
 }
 
 /// What to compute the width of a widget relative to.
@@ -479,12 +861,18 @@ pub enum WidthRelative {
     Normal,
 
     /// Width is sized so that the widget's inner width just encompasses all child widgets, plus the `x` field
-    /// of the widget's `size`.  Note that any children of this Widget should not use `Parent` width if this
-    /// is used.
+    /// of the widget's `size`, which acts as padding.  The final width is computed once all children have been
+    /// added, and the widget is moved as needed to keep its alignment.  Note that any children of this Widget
+    /// should not use `Parent` width or be aligned to the right or center if this is used, as they are placed
+    /// before the final width is known.
     Children,
 
     /// Width is equal to the parent widget's inner width plus the `x` field of the widget's `size`.
     Parent,
+
+    /// Width is equal to the parent widget's inner width multiplied by the `x` field of the widget's `size`.
+    /// For example, a `size` of `0.5` gives half the parent's inner width.
+    ParentFraction,
 }
 
 impl Default for WidthRelative {
@@ -501,13 +889,24 @@ pub enum HeightRelative {
     /// Height is equal to the parent widget's inner height plus the `y` field of the widget's `size`.
     Parent,
 
+    /// Height is equal to the parent widget's inner height multiplied by the `y` field of the widget's `size`.
+    /// For example, a `size` of `0.5` gives half the parent's inner height.
+    ParentFraction,
+
     /// Height is sized so that the widget's inner height just encompasses all child widgets, plus the `y` field
-    /// of the widget's `size`.  Note that any children of this Widget should not use `Parent` height if this
-    /// is used.
+    /// of the widget's `size`, which acts as padding.  The final height is computed once all children have been
+    /// added, and the widget is moved as needed to keep its alignment.  Note that any children of this Widget
+    /// should not use `Parent` height or be aligned to the bottom or center if this is used, as they are placed
+    /// before the final height is known.
     Children,
 
     /// Height is equal to the line height of the widget's font plus the `y` field of the widget's `size`.
     FontLine,
+
+    /// Height is equal to the height of the widget's text plus the `y` field of the widget's `size`.  The text
+    /// is laid out at the widget's inner width, so a widget with [`wrap`](struct.WidgetBuilder.html#method.wrap)
+    /// enabled grows to fit all of its wrapped lines.
+    Text,
 }
 
 impl Default for HeightRelative {
@@ -560,12 +959,27 @@ impl Color {
 
     /// The color magenta or purple.  `#FF00FF` or `#F0F` in the theme
     pub fn magenta() -> Self { Color { r: 255, g: 0, b: 255 }}
+
+    // parses a color name or hex code, as in the theme
+    pub(crate) fn parse(value: &str) -> Option<Color> {
+        ColorVisitor.visit_str::<de::value::Error>(value).ok()
+    }
 }
 
 impl Default for Color {
     fn default() -> Self { Color::white() }
 }
 
+impl Mul<Color> for Color {
+    type Output = Color;
+
+    /// Multiplies the components of the two colors, as when tinting one with the other.
+    fn mul(self, other: Color) -> Color {
+        let mul = |a: u8, b: u8| ((a as u16 * b as u16 + 127) / 255) as u8;
+        Color { r: mul(self.r, other.r), g: mul(self.g, other.g), b: mul(self.b, other.b) }
+    }
+}
+
 impl Into<[f32; 3]> for Color {
     fn into(self) -> [f32; 3] {
         [self.r as f32 / 255.0, self.g as f32 / 255.0, self.b as f32 / 255.0]
@@ -591,15 +1005,15 @@ impl<'de> Visitor<'de> for ColorVisitor {
             }
             match count {
                 4 => {
-                    let r = hex_str_to_color_component(&value[1..2])? * 17;
-                    let g = hex_str_to_color_component(&value[2..3])? * 17;
-                    let b = hex_str_to_color_component(&value[3..4])? * 17;
+                    let r = hex_str_to_color_component(value, &value[1..2])? * 17;
+                    let g = hex_str_to_color_component(value, &value[2..3])? * 17;
+                    let b = hex_str_to_color_component(value, &value[3..4])? * 17;
                     Ok(Color { r, g, b })
                 },
                 7 => {
-                    let r = hex_str_to_color_component(&value[1..3])?;
-                    let g = hex_str_to_color_component(&value[3..5])?;
-                    let b = hex_str_to_color_component(&value[5..7])?;
+                    let r = hex_str_to_color_component(value, &value[1..3])?;
+                    let g = hex_str_to_color_component(value, &value[3..5])?;
+                    let b = hex_str_to_color_component(value, &value[5..7])?;
                     Ok(Color { r, g, b })
                 },
                 _ => Err(E::custom(format!("{} is not a valid 3 or 6 character hex code", value)))
@@ -622,9 +1036,9 @@ impl<'de> Visitor<'de> for ColorVisitor {
     }
 }
 
-fn hex_str_to_color_component<E: de::Error>(input: &str) -> Result<u8, E> {
+fn hex_str_to_color_component<E: de::Error>(value: &str, input: &str) -> Result<u8, E> {
     let c = u8::from_str_radix(input, 16).map_err(|_| {
-        E::custom(format!("Unable to parse color component from {}", input))
+        E::custom(format!("Unable to parse color component '{}' of {}", input, value))
     })?;
 
     Ok(c)
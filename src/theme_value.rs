@@ -0,0 +1,338 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde::de::{self, Visitor, SeqAccess, MapAccess, DeserializeSeed, IntoDeserializer};
+use serde::de::value::MapAccessDeserializer;
+use serde::ser::{SerializeSeq, SerializeMap};
+
+use crate::Error;
+
+// references to constants that are themselves references are followed at most this many times
+const MAX_CONSTANT_DEPTH: u32 = 16;
+
+// An untyped value read from a theme file.  Widget, image, and font definitions are kept
+// in this form until the theme is built, so constants defined in one theme source can be
+// used in any other source merged with it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ThemeValue {
+    Unit,
+    Bool(bool),
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+    String(String),
+    Seq(Vec<ThemeValue>),
+    Map(Vec<(ThemeValue, ThemeValue)>),
+}
+
+impl ThemeValue {
+    // the value stored under `key`, if this is a map containing it
+    pub fn get(&self, key: &str) -> Option<&ThemeValue> {
+        match self {
+            ThemeValue::Map(entries) => entries.iter().find(|(k, _)| k.as_str() == Some(key)).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    // the entries of this value, if it is a map
+    pub fn entries_mut(&mut self) -> Option<&mut Vec<(ThemeValue, ThemeValue)>> {
+        match self {
+            ThemeValue::Map(entries) => Some(entries),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            ThemeValue::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    // replaces every string value beginning with `$` with the value of the constant it names.  A
+    // leading `$$` is replaced with a literal `$` instead.  `path` is the location of this value in
+    // the theme, used in the error for an undefined constant
+    pub fn substitute(&mut self, constants: &HashMap<String, ThemeValue>, path: &str) -> Result<(), Error> {
+        self.substitute_depth(constants, path, 0)
+    }
+
+    fn substitute_depth(&mut self, constants: &HashMap<String, ThemeValue>, path: &str, depth: u32) -> Result<(), Error> {
+        match self {
+            ThemeValue::String(value) => {
+                if let Some(escaped) = value.strip_prefix("$$") {
+                    *value = format!("${}", escaped);
+                } else if let Some(name) = value.strip_prefix('$') {
+                    if depth >= MAX_CONSTANT_DEPTH {
+                        return Err(Error::Theme(format!(
+                            "Too many nested constant references at '{}'.  This is most likely caused by a circular reference.", path
+                        )));
+                    }
+
+                    let mut constant = constants.get(name).cloned().ok_or_else(||
+                        Error::Theme(format!("Undefined constant '${}' referenced at '{}'", name, path))
+                    )?;
+                    constant.substitute_depth(constants, path, depth + 1)?;
+                    *self = constant;
+                }
+            },
+            ThemeValue::Seq(values) => {
+                for (index, value) in values.iter_mut().enumerate() {
+                    value.substitute_depth(constants, &format!("{}/{}", path, index), depth)?;
+                }
+            },
+            ThemeValue::Map(entries) => {
+                for (key, value) in entries.iter_mut() {
+                    let path = format!("{}/{}", path, key.as_str().unwrap_or("?"));
+                    value.substitute_depth(constants, &path, depth)?;
+                }
+            },
+            _ => (),
+        }
+
+        Ok(())
+    }
+
+    // deserializes this value into `T`.  Errors name the full path of the value that
+    // could not be read, starting with `path`
+    pub fn deserialize_into<'de, T: Deserialize<'de>>(self, path: &str) -> Result<T, Error> {
+        T::deserialize(self).map_err(|e| {
+            let mut path = path.to_string();
+            for key in e.path.iter().rev() {
+                path.push('/');
+                path.push_str(key);
+            }
+            Error::Serde(format!("In '{}': {}", path, e.msg))
+        })
+    }
+
+    // the name of this value when used as a key in an error path
+    fn key_name(&self) -> String {
+        match self {
+            ThemeValue::Bool(value) => value.to_string(),
+            ThemeValue::Int(value) => value.to_string(),
+            ThemeValue::UInt(value) => value.to_string(),
+            ThemeValue::Float(value) => value.to_string(),
+            ThemeValue::String(value) => value.clone(),
+            _ => "?".to_string(),
+        }
+    }
+}
+
+// An error deserializing a `ThemeValue`, along with the keys leading from the value being
+// deserialized to the one that caused the error, innermost first
+#[derive(Debug)]
+pub struct ValueError {
+    path: Vec<String>,
+    msg: String,
+}
+
+impl ValueError {
+    fn within(mut self, key: String) -> ValueError {
+        self.path.push(key);
+        self
+    }
+}
+
+impl de::Error for ValueError {
+    fn custom<T: fmt::Display>(msg: T) -> ValueError {
+        ValueError { path: Vec::new(), msg: msg.to_string() }
+    }
+}
+
+impl fmt::Display for ValueError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.msg)
+    }
+}
+
+impl std::error::Error for ValueError {}
+
+struct SeqAccessor {
+    values: std::vec::IntoIter<ThemeValue>,
+    index: usize,
+}
+
+impl<'de> SeqAccess<'de> for SeqAccessor {
+    type Error = ValueError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, ValueError> {
+        let value = match self.values.next() {
+            None => return Ok(None),
+            Some(value) => value,
+        };
+
+        let index = self.index;
+        self.index += 1;
+        seed.deserialize(value).map(Some).map_err(|e| e.within(index.to_string()))
+    }
+
+    fn size_hint(&self) -> Option<usize> { Some(self.values.len()) }
+}
+
+struct MapAccessor {
+    entries: std::vec::IntoIter<(ThemeValue, ThemeValue)>,
+    value: Option<(String, ThemeValue)>,
+}
+
+impl<'de> MapAccess<'de> for MapAccessor {
+    type Error = ValueError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, ValueError> {
+        let (key, value) = match self.entries.next() {
+            None => return Ok(None),
+            Some(entry) => entry,
+        };
+
+        let name = key.key_name();
+        let key = seed.deserialize(key).map_err(|e| e.within(name.clone()))?;
+        self.value = Some((name, value));
+        Ok(Some(key))
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, ValueError> {
+        let (name, value) = self.value.take().ok_or_else(||
+            <ValueError as de::Error>::custom("Map value requested before its key")
+        )?;
+        seed.deserialize(value).map_err(|e| e.within(name))
+    }
+
+    fn size_hint(&self) -> Option<usize> { Some(self.entries.len()) }
+}
+
+struct ThemeValueVisitor;
+
+impl<'de> Visitor<'de> for ThemeValueVisitor {
+    type Value = ThemeValue;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("Any valid theme value")
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<ThemeValue, E> { Ok(ThemeValue::Unit) }
+    fn visit_none<E: de::Error>(self) -> Result<ThemeValue, E> { Ok(ThemeValue::Unit) }
+    fn visit_bool<E: de::Error>(self, value: bool) -> Result<ThemeValue, E> { Ok(ThemeValue::Bool(value)) }
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<ThemeValue, E> { Ok(ThemeValue::Int(value)) }
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<ThemeValue, E> { Ok(ThemeValue::UInt(value)) }
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<ThemeValue, E> { Ok(ThemeValue::Float(value)) }
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<ThemeValue, E> { Ok(ThemeValue::String(value.to_string())) }
+    fn visit_string<E: de::Error>(self, value: String) -> Result<ThemeValue, E> { Ok(ThemeValue::String(value)) }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<ThemeValue, D::Error> {
+        ThemeValue::deserialize(deserializer)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<ThemeValue, A::Error> {
+        let mut values = Vec::new();
+        while let Some(value) = seq.next_element()? {
+            values.push(value);
+        }
+        Ok(ThemeValue::Seq(values))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<ThemeValue, A::Error> {
+        let mut entries = Vec::new();
+        while let Some(entry) = map.next_entry()? {
+            entries.push(entry);
+        }
+        Ok(ThemeValue::Map(entries))
+    }
+}
+
+impl<'de> Deserialize<'de> for ThemeValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<ThemeValue, D::Error> {
+        deserializer.deserialize_any(ThemeValueVisitor)
+    }
+}
+
+impl Serialize for ThemeValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            ThemeValue::Unit => serializer.serialize_unit(),
+            ThemeValue::Bool(value) => serializer.serialize_bool(*value),
+            ThemeValue::Int(value) => serializer.serialize_i64(*value),
+            ThemeValue::UInt(value) => serializer.serialize_u64(*value),
+            ThemeValue::Float(value) => serializer.serialize_f64(*value),
+            ThemeValue::String(value) => serializer.serialize_str(value),
+            ThemeValue::Seq(values) => {
+                let mut seq = serializer.serialize_seq(Some(values.len()))?;
+                for value in values {
+                    seq.serialize_element(value)?;
+                }
+                seq.end()
+            },
+            ThemeValue::Map(entries) => {
+                let mut map = serializer.serialize_map(Some(entries.len()))?;
+                for (key, value) in entries {
+                    map.serialize_entry(key, value)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+impl<'de> Deserializer<'de> for ThemeValue {
+    type Error = ValueError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ValueError> {
+        match self {
+            ThemeValue::Unit => visitor.visit_unit(),
+            ThemeValue::Bool(value) => visitor.visit_bool(value),
+            ThemeValue::Int(value) => visitor.visit_i64(value),
+            ThemeValue::UInt(value) => visitor.visit_u64(value),
+            ThemeValue::Float(value) => visitor.visit_f64(value),
+            ThemeValue::String(value) => visitor.visit_string(value),
+            ThemeValue::Seq(values) => {
+                let len = values.len();
+                let mut seq = SeqAccessor { values: values.into_iter(), index: 0 };
+                let result = visitor.visit_seq(&mut seq)?;
+                if seq.values.len() > 0 {
+                    return Err(de::Error::invalid_length(len, &format!("{} elements", seq.index).as_str()));
+                }
+                Ok(result)
+            },
+            ThemeValue::Map(entries) => {
+                let len = entries.len();
+                let mut map = MapAccessor { entries: entries.into_iter(), value: None };
+                let result = visitor.visit_map(&mut map)?;
+                if map.entries.len() > 0 {
+                    return Err(de::Error::invalid_length(len, &format!("{} entries", len - map.entries.len()).as_str()));
+                }
+                Ok(result)
+            }
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ValueError> {
+        match self {
+            ThemeValue::Unit => visitor.visit_none(),
+            value => visitor.visit_some(value),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, ValueError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    // enums are either a unit variant given by name, or a map with a single variant entry
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ValueError> {
+        match self {
+            ThemeValue::String(value) => visitor.visit_enum(value.into_deserializer()),
+            ThemeValue::Map(entries) => {
+                visitor.visit_enum(MapAccessDeserializer::new(MapAccessor { entries: entries.into_iter(), value: None }))
+            },
+            _ => Err(de::Error::custom("Expected an enum variant name or a map with a single variant")),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
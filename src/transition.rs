@@ -0,0 +1,119 @@
+use crate::{AnimState, Color, Easing, Point};
+use crate::theme::WidgetTheme;
+
+// the transition settings from a widget's theme
+#[derive(Clone)]
+pub(crate) struct TransitionDef {
+    millis: u32,
+    easing: Easing,
+    tints: Vec<(AnimState, Color)>,
+    offsets: Vec<(AnimState, Point)>,
+}
+
+impl TransitionDef {
+    // returns `None` if the theme has no state dependent values to transition between
+    pub fn from_theme(theme: &WidgetTheme) -> Option<TransitionDef> {
+        if theme.state_tints.is_empty() && theme.state_offsets.is_empty() { return None; }
+
+        Some(TransitionDef {
+            millis: theme.transition_ms.unwrap_or_default(),
+            easing: theme.easing.unwrap_or_default(),
+            tints: theme.state_tints.clone(),
+            offsets: theme.state_offsets.clone(),
+        })
+    }
+
+    // the values a widget in `state` transitions to
+    pub fn target(&self, state: AnimState) -> TransitionValues {
+        let tint = find_state(&self.tints, state).unwrap_or_default();
+        TransitionValues {
+            tint: tint.into(),
+            offset: find_state(&self.offsets, state).unwrap_or_default(),
+        }
+    }
+}
+
+// finds the value for `state`, falling back in the same way as animated images,
+// and then to the normal state
+fn find_state<T: Copy>(values: &[(AnimState, T)], state: AnimState) -> Option<T> {
+    state.find_value(values).or_else(|| AnimState::normal().find_value(values))
+}
+
+#[derive(Copy, Clone)]
+pub(crate) struct TransitionValues {
+    tint: [f32; 3],
+    offset: Point,
+}
+
+impl TransitionValues {
+    pub fn tint(&self) -> Color {
+        let component = |value: f32| (value * 255.0).round().clamp(0.0, 255.0) as u8;
+        Color { r: component(self.tint[0]), g: component(self.tint[1]), b: component(self.tint[2]) }
+    }
+
+    pub fn offset(&self) -> Point { self.offset }
+
+    fn lerp(self, other: TransitionValues, frac: f32) -> TransitionValues {
+        let mix = |a: f32, b: f32| a + (b - a) * frac;
+        TransitionValues {
+            tint: [
+                mix(self.tint[0], other.tint[0]),
+                mix(self.tint[1], other.tint[1]),
+                mix(self.tint[2], other.tint[2]),
+            ],
+            offset: self.offset + (other.offset - self.offset) * frac,
+        }
+    }
+}
+
+// the animation state of a widget moving between the values of its previous and current `AnimState`
+pub(crate) struct Transition {
+    state: AnimState,
+    from: TransitionValues,
+    to: TransitionValues,
+    start_millis: u32,
+
+    // whether the widget was drawn this frame; transitions for widgets that weren't are
+    // discarded, so the widget snaps to its values when it appears again
+    seen: bool,
+}
+
+impl Transition {
+    // starts at the values for `state` with no animation, as for a widget appearing for the first time
+    pub fn new(def: &TransitionDef, state: AnimState, now: u32) -> Transition {
+        let values = def.target(state);
+        Transition {
+            state,
+            from: values,
+            to: values,
+            start_millis: now,
+            seen: true,
+        }
+    }
+
+    pub fn take_seen(&mut self) -> bool {
+        std::mem::take(&mut self.seen)
+    }
+
+    // computes the current values, starting a new transition from wherever the
+    // current one is if the state has changed
+    pub fn update(&mut self, def: &TransitionDef, state: AnimState, now: u32) -> TransitionValues {
+        self.seen = true;
+
+        if state != self.state {
+            self.from = self.current(def, now);
+            self.to = def.target(state);
+            self.state = state;
+            self.start_millis = now;
+        }
+
+        self.current(def, now)
+    }
+
+    fn current(&self, def: &TransitionDef, now: u32) -> TransitionValues {
+        if def.millis == 0 { return self.to; }
+
+        let frac = now.saturating_sub(self.start_millis) as f32 / def.millis as f32;
+        self.from.lerp(self.to, def.easing.apply(frac))
+    }
+}
@@ -12,10 +12,13 @@ use wgpu::{
     util::{BufferInitDescriptor, DeviceExt},
 };
 
-use crate::render::{DrawMode, view_matrix, TextureData, TexCoord, DrawList};
-use crate::font::FontTextureWriter;
+use crate::render::{
+    DrawMode, view_matrix, run_callbacks, TextureData, TexCoord, DrawList, TintedDrawList,
+    DrawCallbackParams, DrawTarget, PendingCallback,
+};
 use crate::image::ImageDrawParams;
-use crate::{Renderer, Frame, Point, Color, Rect};
+use crate::canvas::CanvasCommand;
+use crate::{Renderer, Frame, Point, Color, Rect, TextOverflow};
 
 /**
 A Thyme [`Renderer`](trait.Renderer.html) for [`wgpu`](https://github.com/gfx-rs/wgpu-rs).
@@ -46,6 +49,7 @@ pub struct WgpuRenderer {
     // per frame data
     draw_list: WgpuDrawList,
     draw_groups: Vec<DrawGroup>,
+    callbacks: Vec<PendingCallback>,
     buffered: Option<BufferedData>,
 }
 
@@ -165,7 +169,7 @@ impl WgpuRenderer {
                 vertex_buffers: &[VertexBufferDescriptor {
                     stride: std::mem::size_of::<Vertex>() as BufferAddress,
                     step_mode: InputStepMode::Vertex,
-                    attributes: &vertex_attr_array![0 => Float2, 1 => Float2, 2 => Float3, 3 => Float2, 4 => Float2],
+                    attributes: &vertex_attr_array![0 => Float2, 1 => Float2, 2 => Float4, 3 => Float2, 4 => Float2],
                 }],
             },
             sample_count: 1,
@@ -194,11 +198,14 @@ impl WgpuRenderer {
             queue,
             draw_list: WgpuDrawList::new(),
             draw_groups: Vec::new(),
+            callbacks: Vec::new(),
             buffered: None,
         }
     }
 
-    /// Draws the current [`Frame`](struct.Frame.html) to the screen
+    /// Draws the current [`Frame`](struct.Frame.html) to the screen.  Widget
+    /// [`draw callbacks`](struct.WidgetBuilder.html#method.draw_callback) are invoked in order as the
+    /// `render_pass` is recorded, and are passed it as [`DrawTarget::Wgpu`](enum.DrawTarget.html#variant.Wgpu).
     pub fn draw_frame<'a>(&'a mut self, frame: Frame, render_pass: &mut RenderPass<'a>) {
         let mouse_cursor = frame.mouse_cursor();
         let (context, widgets, render_groups) = frame.finish_frame();
@@ -206,9 +213,15 @@ impl WgpuRenderer {
 
         let time_millis = context.time_millis();
         let scale = context.scale_factor();
+
+        // add any glyphs new to this frame to the font atlases before drawing
+        context.themes().prepare_fonts(&widgets, |handle, data, width, height| {
+            self.set_font_texture(handle, data, width, height);
+        });
         
         self.update_view_matrix(Point::default(), context.display_size());
         self.draw_groups.clear();
+        self.callbacks.clear();
         self.draw_list.clear();
         self.buffered.take();
 
@@ -218,18 +231,19 @@ impl WgpuRenderer {
 
             // render backgrounds
             for widget in render_group.iter(&widgets) {
-                if !widget.visible() { continue; }
+                if !widget.visible() || widget.opacity() <= 0.0 { continue; }
+                self.draw_list.alpha = widget.opacity();
                 let image_handle = match widget.background() {
                     None => continue,
                     Some(handle) => handle,
                 };
-                let time_millis = time_millis - context.base_time_millis_for(widget.id());
+                let time_millis = time_millis.saturating_sub(context.base_time_millis_for(widget.id()));
                 let image = context.themes().image(image_handle);
     
                 self.buffer_if_changed(&mut draw_mode, DrawMode::Image(image.texture()));
 
                 image.draw(
-                    &mut self.draw_list,
+                    &mut TintedDrawList::new(&mut self.draw_list, widget.tint()),
                     ImageDrawParams {
                         pos: widget.pos().into(),
                         size: widget.size().into(),
@@ -243,20 +257,53 @@ impl WgpuRenderer {
 
             // render foregrounds & text
             for widget in render_group.iter(&widgets) {
-                if !widget.visible() { continue; }
+                if !widget.visible() || widget.opacity() <= 0.0 { continue; }
+                self.draw_list.alpha = widget.opacity();
 
                 let border = widget.border();
                 let fg_pos = widget.pos() + border.tl();
                 let fg_size = widget.inner_size();
     
-                if let Some(image_handle) = widget.foreground() {
-                    let time_millis = time_millis - context.base_time_millis_for(widget.id());
+                if !widget.canvas().is_empty() {
+                    let time_millis = time_millis.saturating_sub(context.base_time_millis_for(widget.id()));
+                    let clip = widget.clip().min(Rect::new(fg_pos, fg_size));
+                    let brush = widget.foreground().map(|handle| context.themes().image(handle));
+
+                    for command in widget.canvas() {
+                        match command {
+                            CanvasCommand::Solid { pos, size, color } => {
+                                let brush = match brush {
+                                    None => continue,
+                                    Some(brush) => brush,
+                                };
+                                self.buffer_if_changed(&mut draw_mode, DrawMode::Image(brush.texture()));
+                                brush.draw_solid(&mut TintedDrawList::new(&mut self.draw_list, widget.tint()), fg_pos + *pos, *size, *color, clip, scale);
+                            },
+                            CanvasCommand::Image { image, pos, size } => {
+                                let image = context.themes().image(*image);
+                                self.buffer_if_changed(&mut draw_mode, DrawMode::Image(image.texture()));
+                                image.draw(
+                                    &mut TintedDrawList::new(&mut self.draw_list, widget.tint()),
+                                    ImageDrawParams {
+                                        pos: (fg_pos + *pos).into(),
+                                        size: (*size).into(),
+                                        anim_state: widget.anim_state(),
+                                        clip,
+                                        time_millis,
+                                        scale,
+                                    }
+                                );
+                            }
+                        }
+                    }
+                } else if let Some(image_handle) = widget.foreground() {
+                    let time_millis = time_millis.saturating_sub(context.base_time_millis_for(widget.id()));
                     let image = context.themes().image(image_handle);
 
                     self.buffer_if_changed(&mut draw_mode, DrawMode::Image(image.texture()));
 
-                    image.draw(
-                        &mut self.draw_list,
+                    image.draw_region(
+                        &mut TintedDrawList::new(&mut self.draw_list, widget.tint()),
                         ImageDrawParams {
                             pos: fg_pos.into(),
                             size: fg_size.into(),
@@ -264,7 +311,9 @@ impl WgpuRenderer {
                             clip: widget.clip(),
                             time_millis,
                             scale,
-                        }
+                        },
+                        widget.image_region(),
+                        widget.image_scaling(),
                     );
                 }
 
@@ -272,18 +321,37 @@ impl WgpuRenderer {
                     if let Some(font_sum) = widget.font() {
                         self.buffer_if_changed(&mut draw_mode, DrawMode::Font(font_sum.handle));
                         let font = context.themes().font(font_sum.handle);
+                        let clip = match widget.overflow() {
+                            TextOverflow::Visible => widget.clip(),
+                            TextOverflow::Clip | TextOverflow::Ellipsis => widget.clip().min(Rect::new(fg_pos, fg_size)),
+                        };
     
                         font.draw(
-                            &mut self.draw_list,
+                            &mut TintedDrawList::new(&mut self.draw_list, widget.tint()),
                             fg_size * scale,
                             (fg_pos * scale).into(),
                             text,
                             widget.text_align(),
+                            widget.wrap(),
+                            font_sum.spacing(scale),
                             widget.text_color(),
-                            widget.clip() * scale,
+                            clip * scale,
                         )
                     }
                 }
+
+                // the callback is drawn on top of everything so far, so end the current group
+                if let Some(callback) = widget.draw_callback() {
+                    if let Some(mode) = draw_mode.take() {
+                        self.buffer(mode);
+                    }
+
+                    self.callbacks.push(PendingCallback {
+                        group: self.draw_groups.len(),
+                        callback: std::rc::Rc::clone(callback),
+                        params: DrawCallbackParams { rect: Rect::new(widget.pos(), widget.size()), clip: widget.clip(), scale },
+                    });
+                }
             }
 
             // draw any not already drawn vertices
@@ -293,6 +361,7 @@ impl WgpuRenderer {
         }
 
         if let Some((mouse_cursor, align, anim_state)) = mouse_cursor {
+            self.draw_list.alpha = 1.0;
             let image = context.themes().image(mouse_cursor);
             let mouse_pos = context.mouse_pos();
             let size = image.base_size();
@@ -312,9 +381,6 @@ impl WgpuRenderer {
             self.buffer(DrawMode::Image(image.texture()));
         }
 
-        // setup view matrix uniform
-        render_pass.set_bind_group(0, &self.view_matrix_bind_group, &[]);
-
         // draw buffers to render pass
         let vertices = self.create_vertex_buffer(&self.draw_list.vertices);
         let indices = self.create_index_buffer(&self.draw_list.indices);
@@ -325,10 +391,20 @@ impl WgpuRenderer {
         });
 
         if let Some(data) = &self.buffered {
-            render_pass.set_vertex_buffer(0, data.vertices.slice(..));
-            render_pass.set_index_buffer(data.indices.slice(..));
+            let view_matrix_bind_group = &self.view_matrix_bind_group;
+            let bind_state = move |render_pass: &mut RenderPass<'a>| {
+                render_pass.set_bind_group(0, view_matrix_bind_group, &[]);
+                render_pass.set_vertex_buffer(0, data.vertices.slice(..));
+                render_pass.set_index_buffer(data.indices.slice(..));
+            };
+            bind_state(render_pass);
     
-            for group in &self.draw_groups {
+            for (index, group) in self.draw_groups.iter().enumerate() {
+                // callbacks may have changed the render pass state
+                if run_callbacks(&self.callbacks, index, &mut DrawTarget::Wgpu(render_pass)) {
+                    bind_state(render_pass);
+                }
+
                 let texture = match &group.mode {
                     DrawMode::Image(handle) => {
                        render_pass.set_pipeline(&self.image_pipe);
@@ -343,6 +419,7 @@ impl WgpuRenderer {
                 render_pass.set_bind_group(1, &texture.bind_group, &[]);
                 render_pass.draw_indexed(group.start..group.end, 0, 0..1);
             }
+            run_callbacks(&self.callbacks, self.draw_groups.len(), &mut DrawTarget::Wgpu(render_pass));
         }
     }
 
@@ -399,6 +476,24 @@ impl WgpuRenderer {
         self.queue.write_buffer(&self.view_matrix_buffer, 0, data);
     }
 
+    // creates or replaces the texture for the font atlas with `handle`
+    fn set_font_texture(&mut self, handle: crate::render::FontHandle, data: &[u8], width: u32, height: u32) {
+        let texture = self.create_texture(
+            data,
+            width,
+            height,
+            wgpu::TextureFormat::R8Unorm,
+            FilterMode::Nearest,
+        );
+
+        assert!(handle.id() <= self.fonts.len());
+        if handle.id() == self.fonts.len() {
+            self.fonts.push(texture);
+        } else {
+            self.fonts[handle.id()] = texture;
+        }
+    }
+
     fn create_texture(
         &self,
         image_data: &[u8],
@@ -406,7 +501,7 @@ impl WgpuRenderer {
         height: u32,
         format: wgpu::TextureFormat,
         filter: FilterMode,
-    ) -> BindGroup {
+    ) -> Texture {
         let texture = self.device.create_texture(&wgpu::TextureDescriptor {
             size: wgpu::Extent3d { width, height, depth: 1, },
             mip_level_count: 1,
@@ -464,7 +559,7 @@ impl WgpuRenderer {
             ],
         });
 
-        bind_group
+        Texture { texture, bind_group }
     }
 }
 
@@ -476,28 +571,10 @@ impl<'a> Renderer for WgpuRenderer {
         size: f32,
         scale: f32,
     ) -> Result<crate::font::Font, crate::Error> {
-        let font = &source.font;
-
-        let writer = FontTextureWriter::new(font, size, scale);
-        let writer_out = writer.write(handle)?;
-
-        let bind_group = self.create_texture(
-            &writer_out.data,
-            writer_out.tex_width,
-            writer_out.tex_height,
-            wgpu::TextureFormat::R8Unorm,
-            FilterMode::Nearest,
-        );
+        let font = crate::font::Font::new(handle, source, size, scale);
+        font.upload_atlas(|data, width, height| self.set_font_texture(handle, data, width, height));
 
-        assert!(handle.id() <= self.fonts.len());
-        if handle.id() == self.fonts.len() {
-            self.fonts.push(Texture { bind_group });
-        } else {
-            self.fonts[handle.id()] = Texture { bind_group };
-        }
-        
-
-        Ok(writer_out.font)
+        Ok(font)
     }
 
     fn register_texture(
@@ -506,7 +583,7 @@ impl<'a> Renderer for WgpuRenderer {
         image_data: &[u8],
         dimensions: (u32, u32),
     ) -> Result<crate::render::TextureData, crate::Error> {
-        let bind_group = self.create_texture(
+        let texture = self.create_texture(
             image_data,
             dimensions.0,
             dimensions.1,
@@ -516,13 +593,44 @@ impl<'a> Renderer for WgpuRenderer {
 
         assert!(handle.id() <= self.textures.len());
         if handle.id() == self.textures.len() {
-            self.textures.push(Texture { bind_group });
+            self.textures.push(texture);
         } else {
-            self.textures[handle.id()] = Texture { bind_group };
+            self.textures[handle.id()] = texture;
         }
 
         Ok(TextureData::new(handle, dimensions.0, dimensions.1))
     }
+
+    fn update_texture(
+        &mut self,
+        handle: crate::render::TextureHandle,
+        image_data: &[u8],
+        pos: (u32, u32),
+        dimensions: (u32, u32),
+    ) -> Result<(), crate::Error> {
+        let texture = match self.textures.get(handle.id()) {
+            None => return Err(crate::Error::Theme(format!("Invalid texture handle {}", handle.id()))),
+            Some(texture) => &texture.texture,
+        };
+
+        let (width, height) = dimensions;
+        self.queue.write_texture(
+            TextureCopyView {
+                texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d { x: pos.0, y: pos.1, z: 0 },
+            },
+            image_data,
+            TextureDataLayout {
+                offset: 0,
+                bytes_per_row: width * 4,
+                rows_per_image: height,
+            },
+            wgpu::Extent3d { width, height, depth: 1, },
+        );
+
+        Ok(())
+    }
 }
 
 struct DrawGroup {
@@ -537,6 +645,7 @@ struct BufferedData {
 }
 
 struct Texture {
+    texture: wgpu::Texture,
     bind_group: BindGroup,
 }
 
@@ -545,18 +654,21 @@ struct Texture {
 struct Vertex {
     position: [f32; 2],
     tex: [f32; 2],
-    color: [f32; 3],
+    color: [f32; 4],
     clip_pos: [f32; 2],
     clip_size: [f32; 2],
 }
 
-// safety - Vertex is exactly 44 bytes with no padding.  all bit patterns are allowed.
+// safety - Vertex is exactly 48 bytes with no padding.  all bit patterns are allowed.
 unsafe impl bytemuck::Pod for Vertex {}
 unsafe impl bytemuck::Zeroable for Vertex {}
 
 struct WgpuDrawList {
     vertices: Vec<Vertex>,
     indices: Vec<u16>,
+
+    // the opacity of the widget currently being drawn
+    alpha: f32,
 }
 
 impl WgpuDrawList {
@@ -564,6 +676,7 @@ impl WgpuDrawList {
         WgpuDrawList {
             vertices: Vec::new(),
             indices: Vec::new(),
+            alpha: 1.0,
         }
     }
 
@@ -591,10 +704,13 @@ impl DrawList for WgpuDrawList {
         color: Color,
         clip: Rect,
     ) {
+        let [r, g, b]: [f32; 3] = color.into();
+        let color = [r, g, b, self.alpha];
+
         let ul = Vertex {
             position: [pos[0], pos[1]],
             tex: tex[0].into(),
-            color: color.into(),
+            color,
             clip_pos: clip.pos.into(),
             clip_size: clip.size.into(),
         };
@@ -602,7 +718,7 @@ impl DrawList for WgpuDrawList {
         let lr = Vertex {
             position: [pos[0] + size[0], pos[1] + size[1]],
             tex: tex[1].into(),
-            color: color.into(),
+            color,
             clip_pos: clip.pos.into(),
             clip_size: clip.size.into(),
         };
@@ -1,16 +1,22 @@
+use std::rc::Rc;
+
 use crate::{
     AnimState, AnimStateKey, Color, Frame, Point, Border, Align, 
-    Layout, WidthRelative, HeightRelative, Rect,
+    Layout, LineHeight, TextOverflow, WidthRelative, HeightRelative, Rect,
 };
-use crate::{frame::{RendGroup}, font::FontSummary, image::ImageHandle};
+use crate::{frame::{RendGroup}, font::FontSummary, image::{ImageHandle, ImageScaling}, context::MouseButton};
 use crate::theme::{WidgetTheme};
 use crate::window::WindowBuilder;
+use crate::canvas::{Canvas, CanvasCommand};
 use crate::scrollpane::ScrollpaneBuilder;
+use crate::transition::TransitionDef;
+use crate::render::{DrawCallback, DrawCallbackParams, DrawTarget};
 
 pub struct Widget {
     // identifier for persistent state
     id: String,
     rend_group: RendGroup,
+    parent_index: usize,
 
     // TODO potentially move these out and store current parent data
     // in the frame for a small perf boost
@@ -22,19 +28,38 @@ pub struct Widget {
     layout: Layout,
     layout_spacing: Point,
 
+    // the number of children and the tallest child so far in the current row of a grid
+    // or wrapping layout
+    row_children: u32,
+    row_height: f32,
+
+    // whether this widget was placed by its parent's layout, whether it should expand to
+    // fill the space remaining in that layout, and whether any of its own children expand
+    in_layout: bool,
+    expand: bool,
+    has_expanding_children: bool,
+
     // stored in the widget for drawing purposes
     clip: Rect,
     text: Option<String>,
     text_color: Color,
+    tint: Color,
     text_align: Align,
+    wrap: bool,
+    overflow: TextOverflow,
     font: Option<FontSummary>,
     background: Option<ImageHandle>,
     foreground: Option<ImageHandle>,
+    image_region: Option<Rect>,
+    image_scaling: ImageScaling,
+    canvas: Vec<CanvasCommand>,
+    draw_callback: Option<DrawCallback>,
     pos: Point,
     size: Point,
     border: Border,
     anim_state: AnimState,
     visible: bool,
+    opacity: f32,
 }
 
 impl Widget {
@@ -43,12 +68,24 @@ impl Widget {
             theme_id: String::new(),
             text: None,
             text_align: Align::default(),
+            wrap: true,
+            overflow: TextOverflow::default(),
             text_color: Color::default(),
+            tint: Color::default(),
             font: None,
             background: None,
             foreground: None,
+            image_region: None,
+            image_scaling: ImageScaling::default(),
+            canvas: Vec::new(),
+            draw_callback: None,
             layout: Layout::default(),
             layout_spacing: Point::default(),
+            row_children: 0,
+            row_height: 0.0,
+            in_layout: false,
+            expand: false,
+            has_expanding_children: false,
             child_align: Align::default(),
             pos: Point::default(),
             scroll: Point::default(),
@@ -57,14 +94,17 @@ impl Widget {
             size,
             id: String::new(),
             rend_group: RendGroup::default(),
+            parent_index: 0,
             anim_state: AnimState::normal(),
             visible: true,
+            opacity: 1.0,
             clip: Rect { pos: Point::default(), size },
         }
     }
 
-    fn create(parent: &Widget, theme: &WidgetTheme, id: String) -> (WidgetData, Widget) {
-        let font = theme.font;
+    fn create(parent: &Widget, parent_index: usize, theme: &WidgetTheme, id: String) -> (WidgetData, Widget) {
+        let spacing = |font: FontSummary| font.with_spacing(theme.letter_spacing, theme.line_height);
+        let font = theme.font.map(spacing);
         let border = theme.border.unwrap_or_default();
         let raw_size = theme.size.unwrap_or_default();
         let width_from = theme.width_from.unwrap_or_default();
@@ -79,7 +119,7 @@ impl Widget {
             parent.scroll
         };
         let mut raw_pos = theme.pos.unwrap_or(cursor_pos) + parent.scroll;
-        let mut pos = pos(parent, raw_pos, size, align);
+        let mut pos = layout_pos(parent, raw_pos, size, align, manual_pos);
         let mut recalc_pos_size = true;
 
         if let Some(screen_pos) = theme.screen_pos {
@@ -94,29 +134,55 @@ impl Widget {
             manual_pos,
             wants_mouse: theme.wants_mouse.unwrap_or_default(),
             wants_scroll: theme.wants_scroll.unwrap_or_default(),
+            expand: theme.expand.unwrap_or_default(),
+            scroll_line_amount: 10.0,
+            focusable: None,
+            handles_tab: false,
             raw_size,
             raw_pos,
+            min_width: 0.0,
             width_from,
             height_from,
             align,
             enabled: true,
             active: false,
+            fading: false,
+            highlight: false,
+            drag_source: None,
+            drop_target: false,
             recalc_pos_size,
             next_render_group: false,
             unparent: false,
+            transition: TransitionDef::from_theme(theme),
+            state_text_colors: theme.state_text_colors.clone(),
+            state_fonts: theme.state_fonts.iter().map(|(state, font)| (*state, spacing(*font))).collect(),
+            letter_spacing: theme.letter_spacing,
+            line_height: theme.line_height,
         };
 
         let widget = Widget {
             layout: theme.layout.unwrap_or_default(),
             layout_spacing: theme.layout_spacing.unwrap_or_default(),
+            row_children: 0,
+            row_height: 0.0,
+            in_layout: false,
+            expand: false,
+            has_expanding_children: false,
             child_align: theme.child_align.unwrap_or_default(),
             theme_id: theme.full_id.to_string(),
             text: theme.text.clone(),
             text_color: theme.text_color.unwrap_or_default(),
+            tint: theme.tint.unwrap_or_default(),
             text_align: theme.text_align.unwrap_or_default(),
+            wrap: theme.wrap.unwrap_or(true),
+            overflow: theme.overflow.unwrap_or_default(),
             font,
             background: theme.background,
             foreground: theme.foreground,
+            image_region: None,
+            image_scaling: ImageScaling::default(),
+            canvas: Vec::new(),
+            draw_callback: None,
             pos,
             scroll: Point::default(),
             cursor: Point::default(),
@@ -124,8 +190,10 @@ impl Widget {
             size,
             id,
             rend_group: RendGroup::default(),
+            parent_index,
             anim_state: AnimState::normal(),
             visible: true,
+            opacity: parent.opacity,
             clip: parent.clip,
         };
 
@@ -134,15 +202,24 @@ impl Widget {
 
     pub fn clip(&self) -> Rect { self.clip }
     pub fn visible(&self) -> bool { self.visible }
+    pub fn opacity(&self) -> f32 { self.opacity }
     pub fn text_color(&self) -> Color { self.text_color }
+    pub fn tint(&self) -> Color { self.tint }
     pub fn text_align(&self) -> Align { self.text_align }
+    pub fn wrap(&self) -> bool { self.wrap && self.overflow != TextOverflow::Ellipsis }
+    pub fn overflow(&self) -> TextOverflow { self.overflow }
     pub fn text(&self) -> Option<&str> { self.text.as_deref() }
     pub fn font(&self) -> Option<FontSummary> { self.font }
     pub fn foreground(&self) -> Option<ImageHandle> { self.foreground }
+    pub fn image_region(&self) -> Option<Rect> { self.image_region }
+    pub fn image_scaling(&self) -> ImageScaling { self.image_scaling }
+    pub(crate) fn canvas(&self) -> &[CanvasCommand] { &self.canvas }
+    pub(crate) fn draw_callback(&self) -> Option<&DrawCallback> { self.draw_callback.as_ref() }
     pub fn background(&self) -> Option<ImageHandle> { self.background }
     pub fn border(&self) -> Border { self.border }
     pub fn id(&self) -> &str { &self.id }
     pub fn theme_id(&self) -> &str { &self.theme_id }
+    pub fn layout(&self) -> Layout { self.layout }
     pub fn anim_state(&self) -> AnimState { self.anim_state }
     pub fn size(&self) -> Point { self.size }
     pub fn pos(&self) -> Point { self.pos }
@@ -159,16 +236,49 @@ impl Widget {
         self.cursor
     }
 
+    // the state used to lay out the next child of this widget
+    pub(crate) fn child_layout(&self) -> ChildLayout {
+        ChildLayout {
+            cursor: self.cursor,
+            row_children: self.row_children,
+            row_height: self.row_height,
+            has_expanding_children: self.has_expanding_children,
+        }
+    }
+
+    pub(crate) fn set_child_layout(&mut self, layout: ChildLayout) {
+        self.cursor = layout.cursor;
+        self.row_children = layout.row_children;
+        self.row_height = layout.row_height;
+        self.has_expanding_children = layout.has_expanding_children;
+    }
+
     pub fn gap(&mut self, gap: f32) {
         match self.layout {
             Layout::Horizontal => self.cursor.x += gap,
             Layout::Vertical => self.cursor.y += gap,
+            Layout::HorizontalWrap | Layout::Grid { .. } => self.cursor.x += gap,
             Layout::Free => (),
         }
     }
 
+    // moves the cursor to the start of the next row if a child with the specified width would
+    // not fit in the remainder of the current row.  returns true if the cursor was moved
+    fn wrap_row(&mut self, width: f32) -> bool {
+        if self.cursor.x <= 0.0 || self.cursor.x + width <= self.inner_size().x {
+            return false;
+        }
+
+        self.cursor.x = 0.0;
+        self.cursor.y += self.row_height + self.layout_spacing.y;
+        self.row_height = 0.0;
+        true
+    }
+
     pub(crate) fn rend_group(&self) -> RendGroup { self.rend_group }
 
+    pub(crate) fn parent_index(&self) -> usize { self.parent_index }
+
     pub(crate) fn set_rend_group(&mut self, group: RendGroup) {
         self.rend_group = group;
     }
@@ -206,25 +316,110 @@ pub struct WidgetState {
     pub pressed: bool,
 
     /// Whether the mouse clicked on this widget on the current frame.  This field will only be `true` once
-    /// per click.
+    /// per click.  Only clicks with the [`Left`](enum.MouseButton.html) button are counted; see
+    /// [`clicked_with`](#method.clicked_with) for the other buttons.
     pub clicked: bool,
 
-    /// How far the mouse has been dragged or scrolled on this widget, in logical pixels.
+    /// How far the mouse has been dragged or scrolled on this widget, in logical pixels.  Drags with
+    /// any mouse button are included; see [`pressed_button`](#structfield.pressed_button).
     pub moved: Point,
+
+    /// The mouse button currently held down on this widget, if any.  When the mouse is dragged,
+    /// this is the button responsible for the drag.  Note that [`pressed`](#structfield.pressed)
+    /// only applies to the [`Left`](enum.MouseButton.html) button.
+    pub pressed_button: Option<MouseButton>,
+
+    /// How long, in milliseconds, the mouse has continuously been over this widget, or zero if
+    /// it is not [`hovered`](#structfield.hovered).  This is reset when the mouse leaves the widget
+    /// or moves onto a different one, and is zero on the first frame the widget is hovered.
+    pub hover_millis: u32,
+
+    /// How far the mouse wheel scrolled this widget on the current frame, in logical pixels.  Only
+    /// widgets that [`want scroll`](struct.WidgetBuilder.html#method.wants_scroll) take the wheel, and
+    /// this movement is also included in [`moved`](#structfield.moved).  For a
+    /// [`scrollpane`](struct.ScrollpaneBuilder.html), this is only the movement the pane used, so it is
+    /// zero when the wheel was passed on to an outer scrollpane.
+    pub scrolled: Point,
+
+    /// Whether a drag and drop is in progress over this widget, if it is a
+    /// [`drop_target`](struct.WidgetBuilder.html#method.drop_target).
+    pub drop_hovered: bool,
+
+    /// The payload of a drag and drop released over this widget on the current frame, if it is a
+    /// [`drop_target`](struct.WidgetBuilder.html#method.drop_target).  Only the innermost drop target
+    /// under the mouse receives the drop.
+    pub dropped: Option<String>,
+
+    /// Whether any of this widget's text was cut short to fit its inner area, for widgets using
+    /// [`TextOverflow::Ellipsis`](enum.TextOverflow.html#variant.Ellipsis), so the full text can be shown
+    /// elsewhere when needed.
+    pub truncated: bool,
+
+    /// Whether the mouse was first pressed on this widget on the current frame, with any button.  This is
+    /// `true` for only one frame per press, and is followed by exactly one frame with [`released`](#structfield.released).
+    pub just_pressed: bool,
+
+    /// Whether the mouse was released on the current frame, after being pressed on this widget.  This is `true`
+    /// for only one frame, even if the widget was dragged and the mouse released outside of it, so it may be
+    /// used to commit a value or finish a move once a drag ends.  Unlike [`clicked`](#structfield.clicked), this
+    /// applies to any mouse button.
+    pub released: bool,
+
+    /// How far the mouse has moved in total, in logical pixels, since it was pressed on this widget, while
+    /// this widget is [`pressed`](#structfield.pressed_button) or on the frame it is [`released`](#structfield.released).
+    /// Otherwise, this is zero.  See also [`moved`](#structfield.moved) for the movement on the current frame.
+    pub drag_total: Point,
+
+    /// The final position and size of this widget, in logical pixels, including any move or resize in its
+    /// [`PersistentState`](struct.PersistentState.html) and any transition offset, as drawn.  One exception is
+    /// when a parent is sized from its children and is not aligned to its top left; the parent and its children
+    /// are then moved after the children are finished.  Empty if the widget is not [`visible`](#structfield.visible).
+    pub bounds: Rect,
+
+    /// The [`bounds`](#structfield.bounds) of this widget, with its [`Border`](struct.Border.html) removed.  This
+    /// is the area children are laid out in.
+    pub inner_bounds: Rect,
+
+    clicked_buttons: [bool; 3],
 }
 
 impl WidgetState {
-    fn hidden() -> WidgetState {
+    /// Returns whether the specified mouse `button` clicked on this widget on the current frame.
+    /// `clicked_with(MouseButton::Left)` is equivalent to [`clicked`](#structfield.clicked).
+    pub fn clicked_with(&self, button: MouseButton) -> bool {
+        self.clicked_buttons[button.index()]
+    }
+
+    pub(crate) fn hidden() -> WidgetState {
         WidgetState {
             visible: false,
             hovered: false,
             pressed: false,
             clicked: false,
             moved: Point::default(),
+            pressed_button: None,
+            hover_millis: 0,
+            scrolled: Point::default(),
+            drop_hovered: false,
+            dropped: None,
+            truncated: false,
+            just_pressed: false,
+            released: false,
+            drag_total: Point::default(),
+            bounds: Rect::default(),
+            inner_bounds: Rect::default(),
+            clicked_buttons: [false; 3],
         }
     }
 
-    fn new(anim_state: AnimState, clicked: bool, moved: Point) -> WidgetState {
+    fn new(
+        anim_state: AnimState,
+        clicked_buttons: [bool; 3],
+        moved: Point,
+        pressed_button: Option<MouseButton>,
+        hover_millis: u32,
+        scrolled: Point,
+    ) -> WidgetState {
         let (hovered, pressed) = if anim_state.contains(AnimStateKey::Pressed) {
             (true, true)
         } else if anim_state.contains(AnimStateKey::Hover) {
@@ -237,8 +432,20 @@ impl WidgetState {
             visible: true,
             hovered,
             pressed,
-            clicked,
+            clicked: clicked_buttons[0],
             moved,
+            pressed_button,
+            hover_millis,
+            scrolled,
+            drop_hovered: false,
+            dropped: None,
+            truncated: false,
+            just_pressed: false,
+            released: false,
+            drag_total: Point::default(),
+            bounds: Rect::default(),
+            inner_bounds: Rect::default(),
+            clicked_buttons,
         }
     }
 }
@@ -255,13 +462,16 @@ fn size(
         WidthRelative::Children => size.x, // this will be added to after children are layed out
         WidthRelative::Normal => size.x,
         WidthRelative::Parent => size.x + parent.size.x - parent.border.horizontal(),
+        WidthRelative::ParentFraction => size.x * (parent.size.x - parent.border.horizontal()),
     };
     let y = match height_from {
         HeightRelative::Children => size.y, // this will be added to after children are layed out
         HeightRelative::Normal => size.y,
         HeightRelative::Parent => size.y + parent.size.y - parent.border.vertical(),
+        HeightRelative::ParentFraction => size.y * (parent.size.y - parent.border.vertical()),
         HeightRelative::FontLine => size.y + font.map_or(0.0,
             |sum| sum.line_height) + border.vertical(),
+        HeightRelative::Text => size.y + border.vertical(), // the text height is added once the text is known
     };
     Point { x, y }
 }
@@ -312,22 +522,164 @@ fn pos(parent: &Widget, pos: Point, self_size: Point, align: Align) -> Point {
     pos - align.adjust_for(self_size).round()
 }
 
+// the position of a widget, taking into account its parent's grid layout if the widget
+// is being placed by its parent
+fn layout_pos(parent: &Widget, pos: Point, self_size: Point, align: Align, manual_pos: bool) -> Point {
+    match parent.layout {
+        Layout::Grid { columns } if !manual_pos => grid_pos(parent, columns, pos, self_size, align),
+        _ => self::pos(parent, pos, self_size, align),
+    }
+}
+
+// positions a widget in the grid cell starting at `cursor`, within the parent's inner area
+fn grid_pos(parent: &Widget, columns: u32, cursor: Point, self_size: Point, align: Align) -> Point {
+    use Align::*;
+    let cell_width = grid_cell_width(parent, columns);
+    let offset = match align {
+        Left | TopLeft | BotLeft => 0.0,
+        Top | Center | Bot => ((cell_width - self_size.x) / 2.0).round(),
+        Right | TopRight | BotRight => cell_width - self_size.x,
+    };
+
+    parent.pos + Point {
+        x: parent.border.left + cursor.x + offset,
+        y: parent.border.top + cursor.y,
+    }
+}
+
+fn grid_cell_width(parent: &Widget, columns: u32) -> f32 {
+    let columns = columns.max(1) as f32;
+    (parent.inner_size().x - parent.layout_spacing.x * (columns - 1.0)) / columns
+}
+
+// moves the widget at `start`, along with its children in the same render group, by `amount`.
+// Clip rects inherited from outside the widget are left unchanged
+fn shift_widgets(frame: &mut Frame, start: usize, amount: Point) {
+    if amount == Point::default() { return; }
+
+    let outer_clip = frame.widget(start).clip;
+    let rend_group = frame.widget(start).rend_group;
+    for index in start..frame.num_widgets() {
+        let widget = frame.widget_mut(index);
+        if widget.rend_group != rend_group { continue; }
+
+        widget.pos = widget.pos + amount;
+        if widget.clip != outer_clip {
+            widget.clip.pos = widget.clip.pos + amount;
+        }
+    }
+}
+
+// cuts short any lines of text too wide for the widget at `index`, if it uses ellipsis overflow.
+// Returns true if any text was truncated
+fn truncate_text(frame: &mut Frame, index: usize) -> bool {
+    let widget = frame.widget(index);
+    if widget.overflow != TextOverflow::Ellipsis { return false; }
+
+    let (font_def, text) = match (widget.font, widget.text.as_ref()) {
+        (Some(font_def), Some(text)) => (font_def, text),
+        _ => return false,
+    };
+
+    let truncated = {
+        let internal = frame.context_internal().borrow();
+        let scale = internal.scale_factor();
+        let width = widget.inner_size().x * scale;
+        internal.themes().font(font_def.handle).truncate(text, width, font_def.spacing(scale))
+    };
+
+    match truncated {
+        None => false,
+        Some(text) => {
+            frame.widget_mut(index).text = Some(text);
+            true
+        }
+    }
+}
+
+// updates the share of leftover space given to each expanding child of the widget at `index`,
+// based on how much space along the layout axis the children used in this frame
+fn update_expand_share(frame: &mut Frame, index: usize, cursor_start: Point) {
+    let parent = frame.widget(index);
+    if !parent.has_expanding_children { return; }
+
+    let axis = match parent.layout {
+        Layout::Horizontal => |point: Point| point.x,
+        Layout::Vertical => |point: Point| point.y,
+        _ => return,
+    };
+
+    let children = (index + 1..frame.num_widgets())
+        .map(|child| frame.widget(child))
+        .filter(|child| child.parent_index == index && child.in_layout);
+
+    let mut num_children = 0;
+    let mut num_expand = 0;
+    let mut used = 0.0;
+    for child in children {
+        num_children += 1;
+        if child.expand { num_expand += 1; }
+        used += axis(child.size);
+    }
+
+    // expanding children may have been removed after measuring
+    if num_expand == 0 { return; }
+
+    used += axis(parent.layout_spacing) * (num_children - 1) as f32;
+    let leftover = axis(parent.inner_size()) - axis(cursor_start) - used;
+
+    let id = parent.id.to_string();
+    let mut internal = frame.context_internal().borrow_mut();
+    let state = internal.state_mut(id);
+    state.expand_share = (state.expand_share + leftover / num_expand as f32).max(0.0);
+}
+
+// a snapshot of a widget's child layout, so that children may be built and then
+// discarded without affecting the layout of the children that follow
+#[derive(Copy, Clone)]
+pub(crate) struct ChildLayout {
+    cursor: Point,
+    row_children: u32,
+    row_height: f32,
+    has_expanding_children: bool,
+}
+
 pub(crate) struct WidgetData {
     manual_pos: bool,
     wants_mouse: bool,
     wants_scroll: bool,
+    expand: bool,
+    scroll_line_amount: f32,
+    focusable: Option<bool>,
+    handles_tab: bool,
 
     raw_pos: Point,
     raw_size: Point,
+    min_width: f32,
     width_from: WidthRelative,
     height_from: HeightRelative,
     align: Align,
 
     enabled: bool,
     active: bool,
+    fading: bool,
+    highlight: bool,
+    drag_source: Option<String>,
+    drop_target: bool,
     recalc_pos_size: bool,
     next_render_group: bool,
     unparent: bool,
+
+    // the theme's state dependent tints and offsets, if it has any
+    transition: Option<TransitionDef>,
+
+    // the theme's text colors and fonts for each anim state, unless overridden by the builder
+    state_text_colors: Vec<(AnimState, Color)>,
+    state_fonts: Vec<(AnimState, FontSummary)>,
+
+    // the theme's overrides of the font's letter spacing and line height
+    letter_spacing: Option<f32>,
+    line_height: Option<LineHeight>,
 }
 
 /// A `WidgetBuilder` is used to customize widgets within your UI tree, following a builder pattern.
@@ -346,6 +698,12 @@ pub struct WidgetBuilder<'a> {
     pub(crate) parent: usize,
     pub(crate) widget: Widget,
     data: WidgetData,    
+    tooltip: Option<Tooltip<'a>>,
+}
+
+enum Tooltip<'a> {
+    Text(String),
+    Content(Box<dyn Fn(&mut Frame) + 'a>),
 }
 
 impl<'a> WidgetBuilder<'a> {
@@ -354,11 +712,14 @@ impl<'a> WidgetBuilder<'a> {
         let (data, widget) = {
             let context = std::rc::Rc::clone(&frame.context_internal());
             let mut context = context.borrow_mut();
+            let mut fallback = false;
             let theme = match context.themes().theme(&theme_id) {
                 None => {
                     match context.themes().theme(base_theme) {
                         None => {
-                            context.log(log::Level::Error, format!("Unable to locate theme either at {} or {}", theme_id, base_theme));
+                            // this is only logged once per missing theme
+                            context.log(log::Level::Warn, format!("Unable to locate theme either at {} or {}", theme_id, base_theme));
+                            fallback = true;
                             context.themes().default_theme()
                         }, Some(theme) => theme,
                     }
@@ -377,7 +738,12 @@ impl<'a> WidgetBuilder<'a> {
             let id = frame.generate_id(id);
             let parent_widget = frame.widget(parent);
 
-            let (data, widget) = Widget::create(parent_widget, theme, id);
+            let (data, mut widget) = Widget::create(parent_widget, parent, theme, id);
+
+            // make widgets with a missing theme easy to spot in debug builds
+            if fallback && cfg!(debug_assertions) {
+                widget.text = Some(base_theme.to_string());
+            }
 
             (data, widget)
         };
@@ -387,6 +753,7 @@ impl<'a> WidgetBuilder<'a> {
             parent,
             widget,
             data,
+            tooltip: None,
         }
     }
 
@@ -404,12 +771,32 @@ impl<'a> WidgetBuilder<'a> {
             );
 
             self.widget.size = size;
+            self.widget.size.x = self.widget.size.x.max(self.data.min_width);
+        }
+
+        if self.data.expand && !self.data.manual_pos {
+            let parent = self.parent();
+            let share = self.frame.context_internal().borrow().state(parent.id()).expand_share;
+            match parent.layout {
+                Layout::Horizontal => self.widget.size.x += share,
+                Layout::Vertical => self.widget.size.y += share,
+                _ => (),
+            }
+        }
+
+        if !self.data.manual_pos && self.parent().layout == Layout::HorizontalWrap {
+            let width = self.widget.size.x + state_resize.x;
+            let parent = self.frame.widget_mut(self.parent);
+            let cursor = parent.cursor;
+            if parent.wrap_row(width) {
+                self.data.raw_pos = self.data.raw_pos + (parent.cursor - cursor);
+            }
         }
 
         {
             let parent = self.frame.widget(self.parent);
             let widget = &self.widget;
-            let pos = pos(parent, self.data.raw_pos, widget.size, self.data.align);
+            let pos = layout_pos(parent, self.data.raw_pos, widget.size, self.data.align, self.data.manual_pos);
             self.widget.pos = pos + state_moved;
         }
 
@@ -422,6 +809,10 @@ impl<'a> WidgetBuilder<'a> {
         self.frame.widget(self.parent)
     }
 
+    pub(crate) fn set_scroll_line_amount(&mut self, amount: f32) {
+        self.data.scroll_line_amount = amount;
+    }
+
     pub(crate) fn set_next_render_group(&mut self, val: bool) {
         self.data.next_render_group = val;
     }
@@ -454,6 +845,63 @@ impl<'a> WidgetBuilder<'a> {
         self
     }
 
+    /// Sets whether this widget may receive keyboard focus by pressing Tab or Shift+Tab.  Focusable
+    /// widgets are visited in the order they are created each frame.  Widgets that are disabled
+    /// or not visible are skipped.  By default, widgets are focusable if they
+    /// [`want the mouse`](#method.wants_mouse).  While a widget that wants the mouse has keyboard
+    /// focus, pressing Enter or Space clicks it, and its anim state includes
+    /// [`Focused`](enum.AnimStateKey.html#variant.Focused).
+    #[must_use]
+    pub fn focusable(mut self, focusable: bool) -> WidgetBuilder<'a> {
+        self.data.focusable = Some(focusable);
+        self
+    }
+
+    /// Sets whether this widget handles the Tab key itself while it has keyboard focus.  If `true`,
+    /// Tab is sent to this widget as a [`KeyEvent`](struct.KeyEvent.html) rather than moving the
+    /// keyboard focus to the next focusable widget.  Defaults to `false`.
+    #[must_use]
+    pub fn handles_tab(mut self, handles_tab: bool) -> WidgetBuilder<'a> {
+        self.data.handles_tab = handles_tab;
+        self
+    }
+
+    /// Adds a tooltip with the specified `text` to this widget.  Once the mouse has stayed over
+    /// the widget for the [`tooltip delay`](struct.Context.html#method.set_tooltip_delay), the
+    /// tooltip is shown next to the mouse cursor, on top of all other widgets, and kept within
+    /// the display.  It is hidden as soon as the mouse leaves the widget.  The tooltip uses the
+    /// [`tooltip theme`](struct.Context.html#method.set_tooltip_theme) set on the `Context`.
+    /// Only widgets that [`want the mouse`](#method.wants_mouse) show tooltips.
+    #[must_use]
+    pub fn tooltip<T: Into<String>>(mut self, text: T) -> WidgetBuilder<'a> {
+        self.tooltip = Some(Tooltip::Text(text.into()));
+        self
+    }
+
+    /// Adds a tooltip to this widget, as with [`tooltip`](#method.tooltip), but with arbitrary
+    /// content.  `content` is called to add children to the tooltip widget each frame the
+    /// tooltip is shown.  It may be called more than once per frame, in order to measure the
+    /// tooltip.
+    #[must_use]
+    pub fn tooltip_with<F: Fn(&mut Frame) + 'a>(mut self, content: F) -> WidgetBuilder<'a> {
+        self.tooltip = Some(Tooltip::Content(Box::new(content)));
+        self
+    }
+
+    /// Sets whether this widget expands to fill the space left over in its parent's layout.  Any inner
+    /// width (for a [`Horizontal`](enum.Layout.html#variant.Horizontal) layout) or height (for a
+    /// [`Vertical`](enum.Layout.html#variant.Vertical) layout) of the parent not used by its children
+    /// is divided evenly among the expanding children, and added to their size.  The leftover space is
+    /// measured once all of the parent's children have been added and is applied starting with the
+    /// next frame, so the parent must have a consistent `id`; see
+    /// [`expand_share`](struct.PersistentState.html#structfield.expand_share).
+    /// This may also be specified in the widget's [`theme`](index.html).
+    #[must_use]
+    pub fn expand(mut self, expand: bool) -> WidgetBuilder<'a> {
+        self.data.expand = expand;
+        self
+    }
+
     /// Sets an `id` for this widget.  This `id` is used internally to associate the widget with its [`PersistentState`](struct.PersistentState.html).
     /// You will need to specify an `id` if you want to make changes to the [`PersistentState`](struct.PersistentState.html).  Otherwise,
     /// Thyme can usually generate a unique internal ID for most elements.
@@ -482,6 +930,28 @@ impl<'a> WidgetBuilder<'a> {
     #[must_use]
     pub fn text_color(mut self, color: Color) -> WidgetBuilder<'a> {
         self.widget.text_color = color;
+        self.data.state_text_colors.clear();
+        self
+    }
+
+    /// Specify the `opacity` of this widget and all of its children, from 0 for fully transparent to 1
+    /// for fully opaque.  Opacities multiply, so a widget with opacity 0.5 inside a parent with opacity
+    /// 0.5 is drawn at 0.25.  Fully transparent widgets still take part in layout, but do not take
+    /// the mouse.  The default opacity is 1.
+    #[must_use]
+    pub fn opacity(mut self, opacity: f32) -> WidgetBuilder<'a> {
+        self.widget.opacity *= opacity.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Specify a [`Color`](struct.Color.html) to tint this widget with.  The tint is multiplied into the
+    /// colors of the widget's background, foreground, and text when drawn, on top of whichever images
+    /// the theme selects for the widget's current [`AnimState`](struct.AnimState.html).  The default
+    /// tint is white, which leaves the widget unchanged.  The tint does not apply to the widget's children.
+    /// This may also be specified in the widget's [`theme`](index.html).
+    #[must_use]
+    pub fn tint(mut self, color: Color) -> WidgetBuilder<'a> {
+        self.widget.tint = color;
         self
     }
 
@@ -494,6 +964,26 @@ impl<'a> WidgetBuilder<'a> {
         self
     }
 
+    /// Specify whether this widget's text is broken onto new lines to fit within the widget's inner width.
+    /// Lines are broken at whitespace where possible, and words too long to fit on a line by themselves are
+    /// broken between characters.  Explicit newlines in the text are always honored.  Defaults to `true`.
+    /// See also [`HeightRelative::Text`](enum.HeightRelative.html#variant.Text).
+    /// This may also be specified in the widget's [`theme`](index.html).
+    #[must_use]
+    pub fn wrap(mut self, wrap: bool) -> WidgetBuilder<'a> {
+        self.widget.wrap = wrap;
+        self
+    }
+
+    /// Specify how this widget's text is drawn when it does not fit within the widget's inner area.
+    /// See [`TextOverflow`](enum.TextOverflow.html).  Defaults to [`Visible`](enum.TextOverflow.html#variant.Visible).
+    /// This may also be specified in the widget's [`theme`](index.html).
+    #[must_use]
+    pub fn overflow(mut self, overflow: TextOverflow) -> WidgetBuilder<'a> {
+        self.widget.overflow = overflow;
+        self
+    }
+
     /// Specify `text` to display for this widget.  The widget must have a [`font`](#method.font)
     /// specified to render text.
     /// This may also be specified in the widget's [`theme`](index.html).
@@ -508,9 +998,11 @@ impl<'a> WidgetBuilder<'a> {
     /// This may also be specified in the widget's [`theme`](index.html).
     #[must_use]
     pub fn font(mut self, font: &str) -> WidgetBuilder<'a> {
-        let font = self.frame.context().find_font(font);
+        let font = self.frame.context().find_font(font)
+            .map(|font| font.with_spacing(self.data.letter_spacing, self.data.line_height));
 
         self.widget.font = font;
+        self.data.state_fonts.clear();
         self.data.recalc_pos_size = true;
         self
     }
@@ -527,6 +1019,24 @@ impl<'a> WidgetBuilder<'a> {
         self
     }
 
+    /// Specify the region of this widget's foreground image to draw, in texels relative to the top left
+    /// corner of the image within its texture.  This allows showing part of an image, such as one entry
+    /// of a texture atlas, computed at runtime.  The region is scaled to the widget's inner area according
+    /// to [`image_scaling`](#method.image_scaling).  Only simple images support regions.
+    #[must_use]
+    pub fn image_region(mut self, region: Rect) -> WidgetBuilder<'a> {
+        self.widget.image_region = Some(region);
+        self
+    }
+
+    /// Specify how this widget's foreground image is scaled to the widget's inner area.  See
+    /// [`ImageScaling`](enum.ImageScaling.html).  Only simple images support scaling modes.
+    #[must_use]
+    pub fn image_scaling(mut self, scaling: ImageScaling) -> WidgetBuilder<'a> {
+        self.widget.image_scaling = scaling;
+        self
+    }
+
     /// Specify a background image for this widget.  The image ID, `bg` must be registered in the theme's
     /// image definitions.  The ID consists of "{image_set_id}/{image_id}".
     /// Background images are drawn below text and any children.
@@ -570,6 +1080,22 @@ impl<'a> WidgetBuilder<'a> {
         self.layout(Layout::Vertical)
     }
 
+    /// Specifies that the children of this widget should be laid out horizontally, wrapping onto
+    /// additional rows as needed.  See [`Layout`](enum.Layout.html).
+    /// This may also be specified in the widget's [`theme`](index.html).
+    #[must_use]
+    pub fn layout_horizontal_wrap(self) -> WidgetBuilder<'a> {
+        self.layout(Layout::HorizontalWrap)
+    }
+
+    /// Specifies that the children of this widget should be laid out in a grid with the specified number
+    /// of `columns`.  See [`Layout`](enum.Layout.html).
+    /// This may also be specified in the widget's [`theme`](index.html).
+    #[must_use]
+    pub fn layout_grid(self, columns: u32) -> WidgetBuilder<'a> {
+        self.layout(Layout::Grid { columns })
+    }
+
     /// Specifies the `layout` for children of this widget.  See [`Layout`](enum.Layout.html).
     /// This may also be specified in the widget's [`theme`](index.html).
     #[must_use]
@@ -694,7 +1220,7 @@ impl<'a> WidgetBuilder<'a> {
     /// allow the widget to render outside of its parent's area.  See [`clip`](#method.clip).
     #[must_use]
     pub fn unclip(mut self) -> WidgetBuilder<'a> {
-        let display_size = self.frame.context_internal().borrow().display_size();
+        let display_size = self.frame.context_internal().borrow().logical_display_size();
         self.widget.clip = Rect::new(Point::default(), display_size);
         self
     }
@@ -717,6 +1243,53 @@ impl<'a> WidgetBuilder<'a> {
         self
     }
 
+    /// Makes this widget the source for a drag and drop.  Dragging the mouse on the widget starts
+    /// a drag with the specified `payload_id`, which is typically used to identify the item being
+    /// dragged.  While the drag is in progress, the payload is available from
+    /// [`Frame::drag_payload`](struct.Frame.html#method.drag_payload), and may be shown with
+    /// [`Frame::drag_preview`](struct.Frame.html#method.drag_preview).  This also sets the widget
+    /// to [`want the mouse`](#method.wants_mouse).
+    #[must_use]
+    pub fn drag_source(mut self, payload_id: &str) -> WidgetBuilder<'a> {
+        self.data.drag_source = Some(payload_id.to_string());
+        self.data.wants_mouse = true;
+        self
+    }
+
+    /// Makes this widget a target for a drag and drop.  The widget's [`WidgetState`](struct.WidgetState.html)
+    /// reports whether a drag is over the widget, and the payload when it is dropped on the widget.
+    /// The widget does not need to [`want the mouse`](#method.wants_mouse).
+    #[must_use]
+    pub fn drop_target(mut self) -> WidgetBuilder<'a> {
+        self.data.drop_target = true;
+        self
+    }
+
+    // keeps this widget at least `width` wide, whatever its theme size, such as to reserve space
+    // for text that changes
+    #[must_use]
+    pub(crate) fn min_width(mut self, width: f32) -> WidgetBuilder<'a> {
+        self.data.min_width = width;
+        self.data.recalc_pos_size = true;
+        self
+    }
+
+    // shows this widget as hovered even when the mouse is elsewhere, such as for the entry
+    // selected with the keyboard in a list
+    #[must_use]
+    pub(crate) fn highlight(mut self, highlight: bool) -> WidgetBuilder<'a> {
+        self.data.highlight = highlight;
+        self
+    }
+
+    /// Sets whether the widget's [`AnimState`](struct.AnimState.html) will
+    /// include the `fading` [`AnimStateKey`](enum.AnimStateKey.html).
+    #[must_use]
+    pub fn fading(mut self, fading: bool) -> WidgetBuilder<'a> {
+        self.data.fading = fading;
+        self
+    }
+
     /// Sets whether this widget will be `visible`.  If the widget is not
     /// visible, it will not be shown and any child closures (such as passed in
     /// [`children`](#method.children)) will not be run.
@@ -781,6 +1354,48 @@ impl<'a> WidgetBuilder<'a> {
         self
     }
 
+    /// Draws custom graphics on this widget using a [`Canvas`](struct.Canvas.html).  This triggers the widget's
+    /// layout, so you should use any `WidgetBuilder` methods affecting position and size before calling it.
+    /// The `draw` closure is called immediately with a canvas covering the widget's inner area.  The canvas
+    /// is drawn in place of the widget's foreground image, which is instead used as the brush for lines,
+    /// rectangles, and circles.
+    #[must_use]
+    pub fn canvas<F: FnOnce(&mut Canvas)>(self, draw: F) -> WidgetBuilder<'a> {
+        let mut inner = Rect::default();
+        let mut builder = self.trigger_layout_inner(&mut inner);
+
+        let commands = {
+            let mut canvas = Canvas::new(builder.frame.context(), inner.size, builder.widget.text_color);
+            draw(&mut canvas);
+            canvas.into_commands()
+        };
+
+        let needs_brush = commands.iter().any(|command| matches!(command, CanvasCommand::Solid { .. }));
+        if needs_brush && builder.widget.foreground.is_none() {
+            builder.frame.context_internal().borrow_mut().log(
+                log::Level::Warn,
+                format!("Canvas '{}' has no foreground image to use as a brush", builder.widget.id)
+            );
+        }
+
+        builder.widget.canvas = commands;
+        builder
+    }
+
+    /// Registers a callback that the renderer invokes while drawing this widget, so the application can
+    /// draw its own content, such as a live 3D view, as part of the UI.  The renderer first draws
+    /// everything below the widget, including its own background, foreground, and text, then calls
+    /// `draw` with the widget's final screen rect and clip rect and the renderer's [`DrawTarget`](enum.DrawTarget.html),
+    /// and then draws the rest of the UI on top.  All UI drawn so far has been submitted to the target by the
+    /// time `draw` is called, so the application may draw directly into it.
+    /// Note that the backgrounds of this widget's children are part of the same render group and so are
+    /// drawn before the callback.
+    #[must_use]
+    pub fn draw_callback<F: Fn(&DrawCallbackParams, &mut DrawTarget) + 'static>(mut self, draw: F) -> WidgetBuilder<'a> {
+        self.widget.draw_callback = Some(Rc::new(draw));
+        self
+    }
+
     /// Causes this widget to layout its current text.  The final position of the text
     /// cursor is written into `pos`.  If this widget does not have a font or has no text,
     /// nothing is written into `pos`.
@@ -827,7 +1442,8 @@ impl<'a> WidgetBuilder<'a> {
 
             let mut scaled_cursor = *cursor * scale;
 
-            font.layout(fg_size * scale, fg_pos * scale, text, align, &mut scaled_cursor);
+            let spacing = font_def.spacing(scale);
+            font.layout(fg_size * scale, fg_pos * scale, text, align, widget.wrap(), spacing, &mut scaled_cursor);
 
             *cursor = scaled_cursor / scale;
         }
@@ -835,6 +1451,133 @@ impl<'a> WidgetBuilder<'a> {
         self
     }
 
+    /// Causes this widget to layout its current text.  The position of each character boundary in
+    /// the text is written into `positions`, relative to the widget's inner area.  There is one
+    /// more position than the number of characters in the text; the last position is after the
+    /// final character.  If this widget does not have a font or has no text, nothing is written
+    /// into `positions`.
+    #[must_use]
+    pub fn trigger_text_positions(mut self, positions: &mut Vec<Point>) -> WidgetBuilder<'a> {
+        let (text, state_moved, state_resize) = {
+            let internal = self.frame.context_internal().borrow();
+            let state = internal.state(&self.widget.id);
+            (
+                state.text.as_ref().map(|t| t.to_string()),
+                state.moved,
+                state.resize,
+            )
+        };
+
+        if self.data.recalc_pos_size {
+            self.recalculate_pos_size(state_moved, state_resize);
+        }
+
+        if let Some(text) = text {
+            self.widget.text = Some(text);
+        }
+
+        if let Some(text) = self.widget.text.clone() {
+            *positions = self.layout_text_positions(&text);
+        }
+
+        self
+    }
+
+    // lays out `text` in this widget's inner area using its font and text alignment.  Returns
+    // an empty vec if this widget has no font.  The size must already be calculated
+    pub(crate) fn layout_text_positions(&self, text: &str) -> Vec<Point> {
+        let font_def = match self.widget.font {
+            None => return Vec::new(),
+            Some(def) => def,
+        };
+
+        let size = self.widget.inner_size();
+        let align = self.widget.text_align();
+
+        let internal = self.frame.context_internal().borrow();
+        let scale = internal.scale_factor();
+        let font = internal.themes().font(font_def.handle);
+
+        font.layout_positions(size * scale, Point::default(), text, align, self.widget.wrap(), font_def.spacing(scale))
+            .into_iter()
+            .map(|pos| pos / scale)
+            .collect()
+    }
+
+    // the width of `text` on a single line in this widget's font, plus the horizontal border.
+    // Returns just the border if this widget has no font
+    pub(crate) fn text_width(&self, text: &str) -> f32 {
+        let border = self.widget.border.horizontal();
+        let font_def = match self.widget.font {
+            None => return border,
+            Some(def) => def,
+        };
+
+        let internal = self.frame.context_internal().borrow();
+        let scale = internal.scale_factor();
+        internal.themes().font(font_def.handle).text_width(text, font_def.spacing(scale)) / scale + border
+    }
+
+    // the height of this widget's text, laid out at its inner width.  Returns zero if this
+    // widget has no font or text
+    fn text_height(&self) -> f32 {
+        let (font_def, text) = match (self.widget.font, self.widget.text.as_ref()) {
+            (Some(font_def), Some(text)) => (font_def, text),
+            _ => return 0.0,
+        };
+
+        let internal = self.frame.context_internal().borrow();
+        let scale = internal.scale_factor();
+        let width = self.widget.inner_size().x * scale;
+        let spacing = font_def.spacing(scale);
+        internal.themes().font(font_def.handle).text_height(width, text, self.widget.wrap(), spacing) / scale
+    }
+
+    // adds the height of this widget's text to its height, once its width is known.  Widgets not
+    // aligned by their top left corner are moved to account for the final height
+    fn fit_text_height(&mut self, state_moved: Point, state_resize: Point) {
+        let height = self.text_height();
+        if height == 0.0 { return; }
+
+        self.widget.size.y += height;
+
+        if self.data.align != Align::TopLeft {
+            let size = self.widget.size - state_resize;
+            let parent = self.frame.widget(self.parent);
+            let pos = layout_pos(parent, self.data.raw_pos, size, self.data.align, self.data.manual_pos);
+            self.widget.pos = pos + state_moved;
+        }
+    }
+
+    // grows this widget's height to at least `height`, once its size has been calculated
+    pub(crate) fn expand_height(&mut self, height: f32) {
+        self.widget.size.y = self.widget.size.y.max(height);
+    }
+
+    // the size needed for this widget to reach the parent's inner right and bottom edges, but
+    // never less than the current size.  The position and size must already be calculated
+    pub(crate) fn parent_fill_size(&self) -> Point {
+        let parent = self.parent();
+        let inner_max = parent.pos + parent.size - parent.border.br();
+        (inner_max - self.widget.pos).max(self.widget.size)
+    }
+
+    // overrides this widget's size, once its size has been calculated
+    pub(crate) fn set_calculated_size(&mut self, size: Point) {
+        self.widget.size = size;
+    }
+
+    /// Writes the line height of this widget's font, in logical pixels, into `line_height`.  If
+    /// this widget does not have a font, nothing is written.
+    #[must_use]
+    pub fn trigger_line_height(self, line_height: &mut f32) -> WidgetBuilder<'a> {
+        if let Some(font) = self.widget.font {
+            *line_height = font.line_height;
+        }
+
+        self
+    }
+
     /// Turns this builder into a WindowBuilder.  You should use all `WidgetBuilder` methods
     /// you need before calling this method.  The window must still be completed with one of the
     /// [`WindowBuilder`](struct.WindowBuilder.html) methods.  You must pass a unique `id` for each window
@@ -905,6 +1648,10 @@ impl<'a> WidgetBuilder<'a> {
             self.recalculate_pos_size(state.moved, state.resize);
         }
 
+        if self.data.height_from == HeightRelative::Text {
+            self.fit_text_height(state.moved, state.resize);
+        }
+
         let self_pos = self.widget.pos;
         let self_size = self.widget.size;
         let mut self_bounds = Rect::new(self_pos, self_size);
@@ -912,12 +1659,6 @@ impl<'a> WidgetBuilder<'a> {
 
         // set modal tree value only if a match is found
         if in_modal_tree {
-            {
-                let mut internal = self.frame.context_internal().borrow_mut();
-                internal.mut_modal(|modal| {
-                    modal.bounds = self_bounds;
-                });
-            }
             self.frame.in_modal_tree = true;
         }
 
@@ -927,42 +1668,73 @@ impl<'a> WidgetBuilder<'a> {
             self.frame.next_render_group(self_bounds, self.widget.id.to_string());
         }
 
+        if self.data.focusable.unwrap_or(self.data.wants_mouse) && self.data.enabled {
+            self.frame.push_focusable(&self.widget.id, self.data.handles_tab);
+        }
+
+        self.widget.in_layout = !self.data.manual_pos;
+        self.widget.expand = self.data.expand && !self.data.manual_pos;
+        if self.widget.expand {
+            self.frame.widget_mut(self.parent).has_expanding_children = true;
+        }
+
         let widget_index = self.frame.num_widgets();
         self.frame.push_widget(self.widget);
 
         // if there is a child function
         if let Some(f) = f {
-            // push the max_child pos and parent index
-            self.frame.set_max_child_bounds(self_bounds);
+            let border = self.frame.widget(widget_index).border();
+            let inner_pos = self_pos + Point::new(border.left, border.top);
+            let from_children = self.data.width_from == WidthRelative::Children ||
+                self.data.height_from == HeightRelative::Children;
+
+            // push the max_child pos and parent index.  When sizing from the children, only the
+            // children themselves are measured, starting from the inner top left corner
+            if from_children {
+                self.frame.set_max_child_bounds(Rect::new(inner_pos, Point::default()));
+            } else {
+                self.frame.set_max_child_bounds(self_bounds);
+            }
             let old_parent_index = self.frame.parent_index();
             self.frame.set_parent_index(widget_index);
 
             // build all children
+            let cursor_start = self.frame.widget(widget_index).cursor;
             (f)(self.frame);
+            update_expand_share(self.frame, widget_index, cursor_start);
 
             self.frame.set_parent_index(old_parent_index);
             let this_children_max_bounds = self.frame.max_child_bounds();
             self.frame.set_parent_max_child_bounds(this_children_max_bounds);
 
-            // adjust widget size if needed for Child relative size
+            // adjust widget size if needed for Child relative size.  The size from the
+            // theme is added on top of the children and border
+            let children_size = this_children_max_bounds.pos + this_children_max_bounds.size - inner_pos;
             if self.data.height_from == HeightRelative::Children {
-                let border = self.frame.widget(widget_index).border().bot;
-                self_bounds.size.y += this_children_max_bounds.size.y + border;
-
-                self.frame.widget_mut(widget_index).size.y += this_children_max_bounds.size.y + border;
-
-                if self.data.next_render_group {
-                    // if we just created the render group, rebound it
-                    self.frame.rebound_cur_render_group(self_bounds);
-                }
+                let amount = children_size.y.max(0.0) + border.vertical();
+                self_bounds.size.y += amount;
+                self.frame.widget_mut(widget_index).size.y += amount;
             }
 
             if self.data.width_from == WidthRelative::Children {
-                let border = self.frame.widget(widget_index).border().right;
-                self_bounds.size.x += this_children_max_bounds.size.x + border;
+                let amount = children_size.x.max(0.0) + border.horizontal();
+                self_bounds.size.x += amount;
+                self.frame.widget_mut(widget_index).size.x += amount;
+            }
+
+            if from_children {
+                // widgets not aligned by their top left corner were positioned based on their size
+                // before the children were added, so move them to account for the final size
+                if self.data.align != Align::TopLeft {
+                    let size = self.frame.widget(widget_index).size - state.resize;
+                    let parent = self.frame.widget(self.parent);
+                    let pos = layout_pos(parent, self.data.raw_pos, size, self.data.align, self.data.manual_pos);
+                    let amount = pos + state.moved - self_pos;
+
+                    shift_widgets(self.frame, widget_index, amount);
+                    self_bounds.pos = self_bounds.pos + amount;
+                }
 
-                self.frame.widget_mut(widget_index).size.x += this_children_max_bounds.size.x + border;
-                
                 if self.data.next_render_group {
                     // if we just created the render group, rebound it
                     self.frame.rebound_cur_render_group(self_bounds);
@@ -970,23 +1742,66 @@ impl<'a> WidgetBuilder<'a> {
             }
         }
 
+        // set the modal bounds once the size is final, including any children
+        if in_modal_tree {
+            let mut internal = self.frame.context_internal().borrow_mut();
+            internal.mut_modal(|modal| {
+                modal.bounds = self_bounds;
+            });
+        }
+
         if !self.data.unparent {
             self.frame.set_max_child_bounds(old_max_child_bounds.max(self_bounds));
         } else {
             self.frame.set_max_child_bounds(old_max_child_bounds);
         }
 
-        let (clicked, mut anim_state, mut dragged) = if self.data.enabled && self.data.wants_mouse {
+        // fully transparent widgets can't be seen, so they shouldn't take the mouse from those beneath them
+        let transparent = self.frame.widget(widget_index).opacity <= 0.0;
+        let (mut clicked, mut anim_state, mut dragged, pressed_button, just_pressed, mut drag_total) = if self.data.enabled && self.data.wants_mouse && !transparent {
             let mouse_state = self.frame.check_mouse_state(widget_index);
-            (mouse_state.clicked, mouse_state.anim, mouse_state.dragged)
+            (mouse_state.clicked, mouse_state.anim, mouse_state.dragged, mouse_state.pressed_button,
+                mouse_state.just_pressed, mouse_state.drag_total)
+        } else {
+            ([false; 3], AnimState::disabled(), Point::default(), None, false, Point::default())
+        };
+
+        // the release is reported wherever the mouse is, as the widget held the press
+        let released_drag = {
+            let id = self.frame.widget(widget_index).id();
+            self.frame.context_internal().borrow().mouse_released_drag(id)
+        };
+        if let Some(total) = released_drag {
+            drag_total = total;
+        }
+
+        if let Some(payload) = self.data.drag_source.take() {
+            if pressed_button == Some(MouseButton::Left) && dragged != Point::default() {
+                let id = self.frame.widget(widget_index).id().to_string();
+                self.frame.start_drag(payload, &id);
+            }
+        }
+
+        let (drop_hovered, dropped) = if self.data.drop_target {
+            self.frame.check_drop(widget_index)
         } else {
-            (false, AnimState::disabled(), Point::default())
+            (false, None)
         };
 
+        if self.frame.is_focus_keyboard(self.frame.widget(widget_index).id()) {
+            anim_state.add(AnimStateKey::Focused);
+
+            if self.data.enabled && self.data.wants_mouse && self.frame.take_activation_keys(widget_index) {
+                clicked[0] = true;
+            }
+        }
+
+        let mut scrolled = Point::default();
         if self.data.wants_scroll {
-            if let Some(wheel) = self.frame.check_mouse_wheel(widget_index) {
+            if let Some(wheel) = self.frame.check_mouse_wheel(widget_index, self.data.scroll_line_amount) {
                 dragged.x += wheel.x;
                 dragged.y += wheel.y;
+                scrolled = wheel;
             }
         }
 
@@ -1003,9 +1818,82 @@ impl<'a> WidgetBuilder<'a> {
             anim_state.add(AnimStateKey::Active);
         }
 
-        self.frame.widget_mut(widget_index).anim_state = anim_state;
+        if self.data.fading {
+            anim_state.add(AnimStateKey::Fading);
+        }
+
+        if self.data.highlight && !anim_state.contains(AnimStateKey::Hover) && !anim_state.contains(AnimStateKey::Pressed) {
+            anim_state.add(AnimStateKey::Hover);
+        }
 
-        let state = WidgetState::new(anim_state, clicked, dragged);
+        let animation_key = {
+            let id = self.frame.widget(widget_index).id();
+            self.frame.context_internal().borrow_mut().animation_key(id)
+        };
+        if let Some(key) = animation_key {
+            anim_state.add(key);
+        }
+
+        let widget = self.frame.widget_mut(widget_index);
+        widget.anim_state = anim_state;
+        if let Some(color) = anim_state.find_value(&self.data.state_text_colors) {
+            widget.text_color = color;
+        }
+        if let Some(font) = anim_state.find_value(&self.data.state_fonts) {
+            widget.font = Some(font);
+        }
+        let truncated = truncate_text(self.frame, widget_index);
+
+        if let Some(transition) = self.data.transition.take() {
+            let values = {
+                let id = self.frame.widget(widget_index).id();
+                let mut internal = self.frame.context_internal().borrow_mut();
+                internal.update_transition(id, &transition, anim_state)
+            };
+
+            let widget = self.frame.widget_mut(widget_index);
+            widget.tint = widget.tint * values.tint();
+            shift_widgets(self.frame, widget_index, values.offset());
+        }
+
+        let hovered = anim_state.contains(AnimStateKey::Hover) || anim_state.contains(AnimStateKey::Pressed);
+        let hover_millis = if hovered {
+            let internal = self.frame.context_internal().borrow();
+            internal.hover_millis(self.frame.widget(widget_index).id())
+        } else {
+            0
+        };
+
+        if let Some(tooltip) = self.tooltip.take() {
+            let (theme, delay) = {
+                let internal = self.frame.context_internal().borrow();
+                (internal.tooltip_theme().to_string(), internal.tooltip_delay())
+            };
+
+            if hovered && pressed_button.is_none() && hover_millis >= delay {
+                match &tooltip {
+                    Tooltip::Text(text) => self.frame.show_tooltip(&theme, Some(text), None),
+                    Tooltip::Content(content) => self.frame.show_tooltip(&theme, None, Some(content.as_ref())),
+                }
+            }
+        }
+
+        let mut state = WidgetState::new(anim_state, clicked, dragged, pressed_button, hover_millis, scrolled);
+        state.drop_hovered = drop_hovered;
+        state.dropped = dropped;
+        state.truncated = truncated;
+        state.just_pressed = just_pressed;
+        state.released = released_drag.is_some();
+        state.drag_total = drag_total;
+        {
+            let widget = self.frame.widget(widget_index);
+            let border = widget.border();
+            state.bounds = Rect::new(widget.pos(), widget.size());
+            state.inner_bounds = Rect::new(
+                widget.pos() + border.tl(),
+                Point::new(widget.size().x - border.horizontal(), widget.size().y - border.vertical()),
+            );
+        }
         let size = self.frame.widget(widget_index).size;
         if !self.data.manual_pos {
             use Align::*;
@@ -1026,6 +1914,24 @@ impl<'a> WidgetBuilder<'a> {
             match parent.layout {
                 Horizontal => parent.cursor.x += x + parent.layout_spacing.x,
                 Vertical => parent.cursor.y += y + parent.layout_spacing.y,
+                HorizontalWrap => {
+                    parent.cursor.x += size.x + parent.layout_spacing.x;
+                    parent.row_height = parent.row_height.max(size.y);
+                },
+                Grid { columns } => {
+                    parent.row_height = parent.row_height.max(size.y);
+                    parent.row_children += 1;
+
+                    if parent.row_children >= columns {
+                        parent.cursor.x = 0.0;
+                        parent.cursor.y += parent.row_height + parent.layout_spacing.y;
+                        parent.row_children = 0;
+                        parent.row_height = 0.0;
+                    } else {
+                        let cell_width = grid_cell_width(parent, columns);
+                        parent.cursor.x += cell_width + parent.layout_spacing.x;
+                    }
+                },
                 Free => (),
             }
         }
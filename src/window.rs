@@ -1,4 +1,4 @@
-use crate::{Frame, widget::WidgetBuilder, WidgetState, Point};
+use crate::{CursorIcon, Frame, widget::WidgetBuilder, WidgetState, PersistentState, Point, Rect};
 
 /**
 A [`WidgetBuilder`](struct.WidgetBuilder.html) specifically for creating windows.
@@ -55,6 +55,14 @@ An example of a theme definition for a window:
             text: "Main Window"
             font: medium
             width_from: Parent
+          collapse:
+            wants_mouse: true
+            background: gui/small_button
+            foreground: gui/arrow_down
+            size: [20, 20]
+            border: { all: 4 }
+            align: TopRight
+            pos: [24, 0]
           close:
             wants_mouse: true
             background: gui/small_button
@@ -89,6 +97,7 @@ impl<'a> WindowBuilder<'a> {
     #[must_use]
     pub fn cancel_render_group(mut self) -> WindowBuilder<'a> {
         self.builder.set_next_render_group(false);
+        self.state.new_render_group = false;
         self
     }
 
@@ -116,7 +125,9 @@ impl<'a> WindowBuilder<'a> {
 
     /// Specifies whether the user should be able to move the created window
     /// by dragging the mouse.  Note that if the [`titlebar`](#method.with_titlebar) is not shown, there
-    /// will be no way to move the window regardless of this setting.
+    /// will be no way to move the window regardless of this setting.  A moveable window is kept with its
+    /// top edge on the display, and at least the `visible_margin` custom float, defaulting to 30 logical
+    /// pixels, of it inside each side of the display.  This is also applied if the display shrinks.
     #[must_use]
     pub fn moveable(mut self, moveable: bool) -> WindowBuilder<'a> {
         self.state.moveable = moveable;
@@ -124,33 +135,100 @@ impl<'a> WindowBuilder<'a> {
     }
 
     /// Specifies whether the user should be able to resize the created window.
-    /// If false, the resize handle will not be shown.
+    /// If false, the resize handle will not be shown.  The window may not be resized smaller than its
+    /// `min_width` and `min_height` custom floats, which default to the size of its border.
+    ///
+    /// If the window's `edge_margin` custom float is set, the window may also be resized by dragging within
+    /// that many logical pixels of any of its edges, or twice that at its corners.  These areas use the `edge`
+    /// child theme, which is usually invisible, and set the [`cursor icon`](struct.Frame.html#method.set_cursor_icon)
+    /// while hovered.
     #[must_use]
     pub fn resizable(mut self, resizable: bool) -> WindowBuilder<'a> {
         self.state.resizable = resizable;
         self
     }
 
+    /// Specifies whether the created window is kept above all other windows, such as for notifications.
+    /// Windows that are always on top are ordered among themselves as usual when pressed.  Has no effect
+    /// if the window does not use a [`new render group`](#method.cancel_render_group).  Defaults to false.
+    #[must_use]
+    pub fn always_on_top(mut self, always_on_top: bool) -> WindowBuilder<'a> {
+        self.state.always_on_top = always_on_top;
+        self
+    }
+
+    /// Specifies whether the user should be able to collapse the created window down to its titlebar,
+    /// using the `collapse` button in the titlebar or by double clicking the titlebar within the
+    /// `double_click_time` custom float, defaulting to 500 milliseconds.  A collapsed window shrinks to
+    /// the height of its top border, and its children are not created.  The collapsed state is stored as the
+    /// window's [`expanded`](struct.PersistentState.html#structfield.expanded) state, so it may also be set
+    /// with [`Frame::set_expanded`](struct.Frame.html#method.set_expanded).  Any resizing is kept while the
+    /// window is collapsed.  Defaults to false.
+    #[must_use]
+    pub fn collapsible(mut self, collapsible: bool) -> WindowBuilder<'a> {
+        self.state.collapsible = collapsible;
+        self
+    }
+
     /// Consumes the builder and adds a widget to the current frame.  The
     /// returned data includes information about the animation state and
     /// mouse interactions of the created element.
     /// The provided closure is called to enable adding children to this window.
     pub fn children<F: FnOnce(&mut Frame)>(self, children: F) -> WidgetState {
-        let builder = self.builder;
+        // the window itself and its drag areas are not keyboard focus targets
+        let mut builder = self.builder.focusable(false);
         let state = self.state;
         let id = builder.widget.id().to_string();
 
-        builder.children(|ui| {
-            (children)(ui);
+        let mut rect = Rect::default();
+        builder = builder.trigger_layout(&mut rect);
+        let limits = WindowLimits::new(&builder, state.moveable, state.resizable);
+
+        // a collapsed window keeps only its top border, which holds the titlebar
+        let collapsed = state.collapsible && !builder.frame.is_expanded(&id);
+        if collapsed {
+            let height = builder.widget.border().top;
+            builder.set_calculated_size(Point::new(rect.size.x, height));
+        }
+
+        let double_click_time = builder.frame.custom_float(builder.widget.theme_id(), "double_click_time", 500.0);
+        let edge_margin = builder.frame.custom_float(builder.widget.theme_id(), "edge_margin", 0.0);
+        let click_id = format!("{}_titlebar_click", id);
+
+        let first_group = builder.frame.num_render_groups();
+        let (ui, result) = builder.finish_with(Some(|ui: &mut Frame| {
+            // the edges are built first, so they take the mouse ahead of the content and titlebar
+            let mut drag_edges = Edges::default();
+            let mut drag_resize = Point::default();
+            if state.resizable && !collapsed && edge_margin > 0.0 {
+                if let Some((edges, moved)) = resize_edges(ui, &id, rect, edge_margin) {
+                    drag_edges = edges;
+                    drag_resize = edges.grow(moved);
+                }
+            }
+
+            if !collapsed {
+                (children)(ui);
+            }
+
+            let mut toggle_collapse = false;
 
             let drag_move = if state.with_titlebar {
                 let result = ui.start("titlebar")
+                .focusable(false)
                 .children(|ui| {
                     if let Some(title) = state.title.as_ref() {
                         ui.start("title").text(title).finish();
                     } else {
                         ui.start("title").finish();
                     }
+
+                    if state.collapsible {
+                        toggle_collapse |= ui.start("collapse")
+                        .wants_mouse(true)
+                        .active(collapsed)
+                        .finish().clicked;
+                    }
                     
                     if state.with_close_button {
                         let clicked = ui.button("close", "").clicked;
@@ -161,6 +239,17 @@ impl<'a> WindowBuilder<'a> {
                     }
                 });
 
+                if state.collapsible && result.clicked {
+                    let now = ui.cur_time_millis();
+                    let last_click = ui.base_time_millis(&click_id);
+                    if last_click > 0 && (now.saturating_sub(last_click) as f32) < double_click_time {
+                        toggle_collapse = true;
+                        ui.set_base_time_millis(&click_id, 0);
+                    } else {
+                        ui.set_base_time_now(&click_id);
+                    }
+                }
+
                 if state.moveable && result.pressed {
                     result.moved
                 } else {
@@ -170,29 +259,192 @@ impl<'a> WindowBuilder<'a> {
                 Point::default()
             };
 
-            if drag_move != Point::default() {
-                ui.modify(&id, |state| {
-                    state.moved = state.moved + drag_move;
-                });
+            if toggle_collapse {
+                ui.set_expanded(&id, collapsed);
             }
 
-            if state.resizable {
-                let result = ui.button("handle", "");
+            if state.resizable && !collapsed {
+                let result = ui.start("handle").wants_mouse(true).focusable(false).finish();
                 if result.pressed {
-                    ui.modify(&id, |state| {
-                        state.resize = state.resize + result.moved;
-                    });
+                    drag_resize = result.moved;
                 }
             }
-        })
+
+            // the limits are applied every frame, so a window is brought back if the display shrinks
+            let display_size = ui.display_size();
+            ui.modify(&id, |state| {
+                let base = Rect::new(rect.pos - state.moved, rect.size - state.resize);
+                let resize = state.resize;
+                state.resize = state.resize + drag_resize;
+                limits.clamp_size(state, base);
+
+                // growing from the left or top edge also moves the window, so the opposite edge stays put
+                let grown = state.resize - resize;
+                state.moved = state.moved + drag_move + drag_edges.offset(grown);
+                limits.clamp_pos(state, base, display_size);
+            });
+        }));
+
+        if state.always_on_top && state.new_render_group && ui.num_render_groups() > first_group {
+            ui.set_always_on_top(first_group);
+        }
+
+        result
+    }
+}
+
+// the minimum size and display bounds a window is kept within
+struct WindowLimits {
+    moveable: bool,
+    resizable: bool,
+    min_size: Point,
+    visible_margin: f32,
+}
+
+impl WindowLimits {
+    fn new(builder: &WidgetBuilder, moveable: bool, resizable: bool) -> WindowLimits {
+        let theme_id = builder.widget.theme_id();
+        let border = builder.widget.border();
+        let min_width = builder.frame.custom_float(theme_id, "min_width", border.horizontal());
+        let min_height = builder.frame.custom_float(theme_id, "min_height", border.vertical());
+
+        WindowLimits {
+            moveable,
+            resizable,
+            min_size: Point::new(min_width, min_height),
+            visible_margin: builder.frame.custom_float(theme_id, "visible_margin", 30.0),
+        }
+    }
+
+    // clamps the `resize` of the window `state`.  `base` is the window's position and size without any
+    // moving or resizing
+    fn clamp_size(&self, state: &mut PersistentState, base: Rect) {
+        if self.resizable {
+            state.resize = state.resize.max(self.min_size - base.size);
+        }
+    }
+
+    // clamps the `moved` of the window `state`, as in `clamp_size`
+    fn clamp_pos(&self, state: &mut PersistentState, base: Rect, display_size: Point) {
+        if self.moveable {
+            let size = base.size + state.resize;
+            let pos = base.pos + state.moved;
+            let margin = self.visible_margin;
+
+            let min = Point::new(margin - size.x, 0.0);
+            let max = Point::new(display_size.x - margin, display_size.y - margin).max(min);
+            state.moved = pos.max(min).min(max) - base.pos;
+        }
+    }
+}
+
+// the edges of a window being dragged to resize it
+#[derive(Copy, Clone, Default)]
+struct Edges {
+    left: bool,
+    right: bool,
+    top: bool,
+    bot: bool,
+}
+
+impl Edges {
+    const fn new(left: bool, right: bool, top: bool, bot: bool) -> Edges {
+        Edges { left, right, top, bot }
+    }
+
+    // the corners come first, so they take the mouse where they overlap the sides
+    const ALL: [Edges; 8] = [
+        Edges::new(true, false, true, false),
+        Edges::new(false, true, true, false),
+        Edges::new(true, false, false, true),
+        Edges::new(false, true, false, true),
+        Edges::new(true, false, false, false),
+        Edges::new(false, true, false, false),
+        Edges::new(false, false, true, false),
+        Edges::new(false, false, false, true),
+    ];
+
+    // the area within `rect` for dragging these edges
+    fn zone(self, rect: Rect, margin: f32) -> Rect {
+        let corner = 2.0 * margin;
+        let depth = if (self.left || self.right) && (self.top || self.bot) { corner } else { margin };
+        let span = |min: f32, size: f32, low: bool, high: bool| {
+            if low {
+                (min, depth)
+            } else if high {
+                (min + size - depth, depth)
+            } else {
+                (min + corner, size - 2.0 * corner)
+            }
+        };
+
+        let (x, width) = span(rect.pos.x, rect.size.x, self.left, self.right);
+        let (y, height) = span(rect.pos.y, rect.size.y, self.top, self.bot);
+        Rect::new(Point::new(x, y), Point::new(width, height))
+    }
+
+    fn cursor_icon(self) -> CursorIcon {
+        match (self.left || self.right, self.top || self.bot) {
+            (true, false) => CursorIcon::EwResize,
+            (false, true) => CursorIcon::NsResize,
+            _ if (self.left && self.top) || (self.right && self.bot) => CursorIcon::NwseResize,
+            _ => CursorIcon::NeswResize,
+        }
+    }
+
+    // the change in size from the mouse being dragged by `moved`
+    fn grow(self, moved: Point) -> Point {
+        let axis = |low: bool, high: bool, moved: f32| {
+            if low { -moved } else if high { moved } else { 0.0 }
+        };
+
+        Point::new(axis(self.left, self.right, moved.x), axis(self.top, self.bot, moved.y))
+    }
+
+    // the change in position when the window has `grown` from these edges
+    fn offset(self, grown: Point) -> Point {
+        Point::new(
+            if self.left { -grown.x } else { 0.0 },
+            if self.top { -grown.y } else { 0.0 },
+        )
     }
 }
 
+// builds the areas for resizing the window with `id` from its edges, returning the edges being dragged,
+// if any, and the amount the mouse moved
+fn resize_edges(ui: &mut Frame, id: &str, rect: Rect, margin: f32) -> Option<(Edges, Point)> {
+    let mut drag = None;
+
+    for (index, edges) in Edges::ALL.iter().enumerate() {
+        let zone = edges.zone(rect, margin);
+        let result = ui.start("edge")
+        .id(format!("{}_edge{}", id, index))
+        .wants_mouse(true)
+        .focusable(false)
+        .size(zone.size.x, zone.size.y)
+        .screen_pos(zone.pos.x, zone.pos.y)
+        .finish();
+
+        if result.hovered || result.pressed {
+            ui.set_cursor_icon(edges.cursor_icon());
+        }
+
+        if result.pressed {
+            drag = Some((*edges, result.moved));
+        }
+    }
+
+    drag
+}
+
 struct WindowState {
     with_titlebar: bool,
     with_close_button: bool,
     moveable: bool,
     resizable: bool,
+    collapsible: bool,
+    always_on_top: bool,
+    new_render_group: bool,
     title: Option<String>,
 }
 
@@ -203,6 +455,9 @@ impl Default for WindowState {
             with_close_button: true,
             moveable: true,
             resizable: true,
+            collapsible: false,
+            always_on_top: false,
+            new_render_group: true,
             title: None,
         }
     }
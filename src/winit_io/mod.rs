@@ -1,8 +1,9 @@
-use winit::event::{Event, WindowEvent, MouseButton, MouseScrollDelta, ElementState};
+use winit::event::{Event, WindowEvent, MouseButton, MouseScrollDelta, ElementState, VirtualKeyCode};
 use winit::event_loop::EventLoop;
 
 use crate::point::Point;
-use crate::context::{InputModifiers, Context};
+use crate::context::{InputModifiers, Context, KeyCode};
+use crate::frame::CursorIcon;
 use crate::render::IO;
 
 /**
@@ -35,12 +36,30 @@ fn main_loop(event_loop: winit::EventLoop<()>, thyme: thyme::Context) {
 pub struct WinitIo {
     scale_factor: f32,
     display_size: Point,
+    modifiers: InputModifiers,
+    clipboard: String,
+    clipboard_get: Option<Box<dyn FnMut() -> Option<String>>>,
+    clipboard_set: Option<Box<dyn FnMut(String)>>,
 }
 
 impl IO for WinitIo {
     fn scale_factor(&self) -> f32 { self.scale_factor }
 
     fn display_size(&self) -> Point { self.display_size }
+
+    fn clipboard_text(&mut self) -> Option<String> {
+        match self.clipboard_get.as_mut() {
+            Some(get) => get(),
+            None => Some(self.clipboard.clone()),
+        }
+    }
+
+    fn set_clipboard_text(&mut self, text: String) {
+        match self.clipboard_set.as_mut() {
+            Some(set) => set(text),
+            None => self.clipboard = text,
+        }
+    }
 }
 
 impl WinitIo {
@@ -52,11 +71,29 @@ impl WinitIo {
         WinitIo {
             scale_factor,
             display_size: logical_display_size * scale_factor,
+            modifiers: InputModifiers::default(),
+            clipboard: String::new(),
+            clipboard_get: None,
+            clipboard_set: None,
         }
     }
 
+    /// Connects this adapter to the OS clipboard (or any other clipboard), using the specified
+    /// functions to `get` and `set` the clipboard text.  By default, the adapter uses a
+    /// clipboard internal to the application.
+    pub fn set_clipboard<G, S>(&mut self, get: G, set: S) where
+        G: FnMut() -> Option<String> + 'static, S: FnMut(String) + 'static {
+        self.clipboard_get = Some(Box::new(get));
+        self.clipboard_set = Some(Box::new(set));
+    }
+
     /// Handles a winit `Event` and passes it to the Thyme [`Context`](struct.Context.html).
+    /// Any text copied by widgets since the last call is also sent to the clipboard.
     pub fn handle_event<T>(&mut self, context: &mut Context, event: &Event<T>) {
+        if let Some(text) = context.take_clipboard_text() {
+            self.set_clipboard_text(text);
+        }
+
         let event = match event {
             Event::WindowEvent { event, .. } => event,
             _ => return,
@@ -71,16 +108,23 @@ impl WinitIo {
                 context.set_display_size(size);
             },
             ModifiersChanged(m) => {
-                context.set_input_modifiers(InputModifiers {
+                self.modifiers = InputModifiers {
                     shift: m.shift(),
                     ctrl: m.ctrl(),
                     alt: m.alt(),
-                });
+                };
+                context.set_input_modifiers(self.modifiers);
             },
-            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+            WindowEvent::ScaleFactorChanged { scale_factor, new_inner_size } => {
                 let scale = *scale_factor as f32;
                 self.scale_factor = scale;
                 context.set_scale_factor(scale);
+
+                // keep the logical display size consistent with the new scale
+                let (x, y): (u32, u32) = (**new_inner_size).into();
+                let size: Point = (x as f32, y as f32).into();
+                self.display_size = size;
+                context.set_display_size(size);
             },
             MouseInput { state, button, .. } => {
                 let pressed = match state {
@@ -100,8 +144,7 @@ impl WinitIo {
             MouseWheel { delta, .. } => {
                 match delta {
                     MouseScrollDelta::LineDelta(x, y) => {
-                        // TODO configure line delta
-                        context.add_mouse_wheel(Point::new(*x * 10.0, *y * 10.0));
+                        context.add_mouse_wheel_lines(Point::new(*x, *y));
                     }, MouseScrollDelta::PixelDelta(pos) => {
                         let x = pos.x as f32;
                         let y = pos.y as f32;
@@ -112,10 +155,77 @@ impl WinitIo {
             CursorMoved { position, .. } => {
                 context.set_mouse_pos((position.x as f32 / self.scale_factor, position.y as f32 / self.scale_factor).into());
             },
-            ReceivedCharacter(c) => {
+            KeyboardInput { input, .. } => {
+                let key = match input.virtual_keycode {
+                    None => return,
+                    Some(key) => key,
+                };
+
+                if input.state == ElementState::Released {
+                    if let Some(key) = key_code(key) {
+                        context.set_key_pressed(key, false);
+                    }
+                    return;
+                }
+
+                if self.modifiers.ctrl {
+                    match key {
+                        VirtualKeyCode::A => { context.push_key(KeyCode::SelectAll); return; },
+                        VirtualKeyCode::C => { context.push_key(KeyCode::Copy); return; },
+                        VirtualKeyCode::X => { context.push_key(KeyCode::Cut); return; },
+                        VirtualKeyCode::V => {
+                            for c in self.clipboard_text().unwrap_or_default().chars() {
+                                context.push_character(c);
+                            }
+                            return;
+                        },
+                        _ => (),
+                    }
+                }
+
+                if let Some(key) = key_code(key) {
+                    context.set_key_pressed(key, true);
+                }
+            },
+            // control characters such as backspace are sent as key events instead
+            ReceivedCharacter(c) if !c.is_control() => {
                 context.push_character(*c);
             }
             _ => (),
         }
     }
-}
\ No newline at end of file
+}
+
+fn key_code(key: VirtualKeyCode) -> Option<KeyCode> {
+    use VirtualKeyCode::*;
+    Some(match key {
+        Left => KeyCode::Left,
+        Right => KeyCode::Right,
+        Up => KeyCode::Up,
+        Down => KeyCode::Down,
+        Home => KeyCode::Home,
+        End => KeyCode::End,
+        PageUp => KeyCode::PageUp,
+        PageDown => KeyCode::PageDown,
+        Insert => KeyCode::Insert,
+        Delete => KeyCode::Delete,
+        Back => KeyCode::Backspace,
+        Return | NumpadEnter => KeyCode::Return,
+        Tab => KeyCode::Tab,
+        Escape => KeyCode::Escape,
+        Space => KeyCode::Space,
+        _ => return None,
+    })
+}
+
+impl From<CursorIcon> for winit::window::CursorIcon {
+    fn from(icon: CursorIcon) -> Self {
+        match icon {
+            CursorIcon::Default => winit::window::CursorIcon::Default,
+            CursorIcon::EwResize => winit::window::CursorIcon::EwResize,
+            CursorIcon::NsResize => winit::window::CursorIcon::NsResize,
+            CursorIcon::NwseResize => winit::window::CursorIcon::NwseResize,
+            CursorIcon::NeswResize => winit::window::CursorIcon::NeswResize,
+        }
+    }
+}
@@ -0,0 +1,61 @@
+#![allow(dead_code)]
+
+use thyme::{Context, Frame, HeadlessIo, HeadlessRenderer, Point};
+
+/// A context with the headless backend, loaded with the base theme used by the examples
+pub struct Harness {
+    pub context: Context,
+    pub renderer: HeadlessRenderer,
+    pub io: HeadlessIo,
+}
+
+impl Harness {
+    pub fn new() -> Harness {
+        let font_src = include_bytes!("../../examples/data/fonts/Roboto-Medium.ttf");
+        let image_src = include_bytes!("../../examples/data/images/gui-pixel.png");
+        let image = image::load_from_memory(image_src).unwrap().to_rgba8();
+        let theme_src = include_str!("../../examples/data/theme-base.yml");
+        let theme: serde_yaml::Value = serde_yaml::from_str(theme_src).unwrap();
+
+        let mut io = HeadlessIo::new(Point::new(1280.0, 720.0));
+        let mut renderer = HeadlessRenderer::new();
+        let mut context_builder = thyme::ContextBuilder::new(thyme::BuildOptions { enable_live_reload: false });
+
+        let image_dims = image.dimensions();
+        context_builder.register_theme(theme).unwrap();
+        context_builder.register_texture("pixel", image.into_raw(), image_dims);
+        context_builder.register_font("roboto", font_src.to_vec());
+        let context = context_builder.build(&mut renderer, &mut io).unwrap();
+
+        Harness { context, renderer, io }
+    }
+
+    /// Builds and draws a single frame with `build_ui`
+    pub fn frame<F: FnOnce(&mut Frame)>(&mut self, build_ui: F) {
+        let mut ui = self.context.create_frame();
+        (build_ui)(&mut ui);
+        self.renderer.draw_frame(ui);
+    }
+
+    /// The center of the widget with `id`, as drawn in the last frame
+    pub fn center_of(&self, id: &str) -> Point {
+        let rect = self.renderer.quad(id).unwrap_or_else(|| panic!("Widget '{}' was not drawn", id)).rect;
+        rect.pos + rect.size * 0.5
+    }
+
+    /// Moves the mouse over the widget with `id`, building a frame so the widget is hovered
+    pub fn hover<F: FnMut(&mut Frame)>(&mut self, id: &str, build_ui: F) {
+        let pos = self.center_of(id);
+        self.io.move_mouse(&mut self.context, pos);
+        self.frame(build_ui);
+    }
+
+    /// Hovers and then clicks the widget with `id`, building a frame after each step.  The click is
+    /// seen by the UI in the second of these frames
+    pub fn click<F: FnMut(&mut Frame)>(&mut self, id: &str, mut build_ui: F) {
+        self.hover(id, &mut build_ui);
+        let pos = self.center_of(id);
+        self.io.click(&mut self.context, pos);
+        self.frame(build_ui);
+    }
+}
@@ -0,0 +1,155 @@
+mod common;
+
+use common::Harness;
+use thyme::{Frame, HeightRelative, KeyCode, Point, Rect, WidthRelative};
+
+#[derive(Default)]
+struct Ui {
+    clicked: bool,
+    popup_open: bool,
+    scroll: f32,
+    text: Option<String>,
+}
+
+fn build_ui(ui: &mut Frame, state: &mut Ui) {
+    ui.window("window", |ui| {
+        state.clicked = ui.button("button", "Open").clicked;
+        if state.clicked {
+            state.popup_open = true;
+        }
+
+        if let Some(text) = ui.input_field("input_field", "field", None) {
+            state.text = Some(text);
+        }
+
+        ui.start("scrollpane")
+        .id("pane")
+        .size_from(WidthRelative::Normal, HeightRelative::Normal)
+        .size(200.0, 100.0)
+        .scrollpane("pane_content")
+        .children(|ui| {
+            for i in 0..20 {
+                ui.label("label", format!("Row #{}", i));
+            }
+        });
+
+        state.scroll = ui.scroll("pane_content").y;
+    });
+
+    if state.popup_open {
+        ui.start("window")
+        .screen_pos(500.0, 100.0)
+        .window("popup")
+        .children(|ui| {
+            ui.label("label", "Popup");
+        });
+    }
+}
+
+#[test]
+fn button_click_opens_window() {
+    let mut h = Harness::new();
+    let mut state = Ui::default();
+
+    h.frame(|ui| build_ui(ui, &mut state));
+    assert_eq!(h.renderer.quad("window").unwrap().rect, Rect::new(Point::default(), Point::new(300.0, 400.0)));
+    assert!(h.renderer.quad("popup").is_none());
+
+    h.hover("window/button", |ui| build_ui(ui, &mut state));
+    assert!(!state.clicked);
+
+    h.click("window/button", |ui| build_ui(ui, &mut state));
+    assert!(state.clicked);
+
+    h.frame(|ui| build_ui(ui, &mut state));
+    assert!(!state.clicked);
+    assert_eq!(h.renderer.quad("popup").unwrap().rect, Rect::new(Point::new(500.0, 100.0), Point::new(300.0, 400.0)));
+}
+
+#[test]
+fn scrollpane_scrolls_content() {
+    let mut h = Harness::new();
+    let mut state = Ui::default();
+
+    h.frame(|ui| build_ui(ui, &mut state));
+    h.hover("pane", |ui| build_ui(ui, &mut state));
+    let row_start = h.renderer.quad("pane_content/label").unwrap().rect.pos;
+
+    h.io.scroll(&mut h.context, Point::new(0.0, -2.0));
+    h.frame(|ui| build_ui(ui, &mut state));
+
+    // smooth scrolling only advances with the time, moving the content up
+    h.io.advance_time(&mut h.context, 500);
+    h.frame(|ui| build_ui(ui, &mut state));
+    h.frame(|ui| build_ui(ui, &mut state));
+    assert!(state.scroll < 0.0);
+
+    let row = h.renderer.quad("pane_content/label").unwrap();
+    assert_eq!(row.rect.pos.x, row_start.x);
+    assert!(row.rect.pos.y < row_start.y);
+    assert_eq!(row.clip, h.renderer.quad("pane_content").unwrap().clip);
+}
+
+#[test]
+fn input_field_takes_typed_text() {
+    let mut h = Harness::new();
+    let mut state = Ui::default();
+
+    h.frame(|ui| build_ui(ui, &mut state));
+    h.click("field", |ui| build_ui(ui, &mut state));
+
+    h.io.type_text(&mut h.context, "Thyme");
+    h.frame(|ui| build_ui(ui, &mut state));
+    assert_eq!(state.text.as_deref(), Some("Thyme"));
+
+    h.io.press_key(&mut h.context, KeyCode::Backspace);
+    h.io.release_key(&mut h.context, KeyCode::Backspace);
+    h.frame(|ui| build_ui(ui, &mut state));
+    assert_eq!(state.text.as_deref(), Some("Thym"));
+
+    h.frame(|ui| build_ui(ui, &mut state));
+    assert_eq!(h.renderer.quad("field").unwrap().text.as_deref(), Some("Thym"));
+}
+
+#[test]
+fn held_key_repeats_as_time_advances() {
+    let mut h = Harness::new();
+    let mut state = Ui::default();
+    h.context.set_key_repeat(500, 50);
+
+    h.frame(|ui| build_ui(ui, &mut state));
+    h.click("field", |ui| build_ui(ui, &mut state));
+    h.io.type_text(&mut h.context, "Thyme rocks");
+    h.frame(|ui| build_ui(ui, &mut state));
+
+    // the key is sent once when pressed, and not repeated until the delay has passed
+    h.io.press_key(&mut h.context, KeyCode::Backspace);
+    h.frame(|ui| build_ui(ui, &mut state));
+    assert_eq!(state.text.as_deref(), Some("Thyme rock"));
+
+    h.io.advance_time(&mut h.context, 499);
+    h.frame(|ui| build_ui(ui, &mut state));
+    assert_eq!(state.text.as_deref(), Some("Thyme rock"));
+
+    h.io.advance_time(&mut h.context, 1);
+    h.frame(|ui| build_ui(ui, &mut state));
+    assert_eq!(state.text.as_deref(), Some("Thyme roc"));
+
+    h.io.advance_time(&mut h.context, 50);
+    h.frame(|ui| build_ui(ui, &mut state));
+    assert_eq!(state.text.as_deref(), Some("Thyme ro"));
+
+    // a long frame only sends a limited number of repeats
+    h.io.advance_time(&mut h.context, 1000);
+    h.frame(|ui| build_ui(ui, &mut state));
+    assert_eq!(state.text.as_deref(), Some("Thyme "));
+
+    // with the time paused, the key is not repeated
+    h.frame(|ui| build_ui(ui, &mut state));
+    assert_eq!(state.text.as_deref(), Some("Thyme "));
+
+    h.io.release_key(&mut h.context, KeyCode::Backspace);
+    h.io.advance_time(&mut h.context, 1000);
+    h.frame(|ui| build_ui(ui, &mut state));
+    assert_eq!(state.text.as_deref(), Some("Thyme "));
+}
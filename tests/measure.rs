@@ -0,0 +1,55 @@
+mod common;
+
+use common::Harness;
+use thyme::{CursorIcon, Frame, HeadlessQuad};
+
+fn build_grid(ui: &mut Frame, measure: bool) {
+    ui.start("button")
+    .id("grid")
+    .size(600.0, 300.0)
+    .layout_grid(3)
+    .children(|ui| {
+        ui.button("button", "A");
+
+        if measure {
+            let size = ui.measure(|ui| {
+                ui.start("button").size(150.0, 80.0).expand(true).finish();
+                ui.set_cursor_icon(CursorIcon::EwResize);
+            });
+            assert!(size.y >= 80.0);
+        }
+
+        ui.button("button", "B");
+        ui.button("button", "C");
+        ui.button("button", "D");
+    });
+}
+
+fn draw(measure: bool) -> (Vec<HeadlessQuad>, CursorIcon) {
+    let mut h = Harness::new();
+    let mut icon = CursorIcon::Default;
+    for _ in 0..2 {
+        h.frame(|ui| {
+            build_grid(ui, measure);
+            icon = ui.cursor_icon();
+        });
+    }
+
+    (h.renderer.quads().to_vec(), icon)
+}
+
+#[test]
+fn measure_leaves_layout_unchanged() {
+    let (quads, icon) = draw(false);
+    let (measured_quads, measured_icon) = draw(true);
+
+    assert_eq!(quads.iter().filter(|quad| quad.text.is_some()).count(), 4);
+    assert_eq!(measured_quads, quads);
+    assert_eq!(measured_icon, icon);
+
+    // the fourth button starts a new row below the first three
+    let a = quads.iter().find(|quad| quad.text.as_deref() == Some("A")).unwrap().rect;
+    let d = quads.iter().find(|quad| quad.text.as_deref() == Some("D")).unwrap().rect;
+    assert_eq!(d.pos.x, a.pos.x);
+    assert!(d.pos.y > a.pos.y);
+}
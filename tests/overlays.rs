@@ -0,0 +1,121 @@
+mod common;
+
+use common::Harness;
+use thyme::{Frame, MouseButton, Point};
+
+#[derive(Default)]
+struct Ui {
+    // closes the window after its content has been built, as an application might on some event
+    close_window: bool,
+    payload: Option<String>,
+}
+
+fn build_ui(ui: &mut Frame, state: &mut Ui) {
+    ui.window("window", |ui| {
+        ui.combo_box("combo_box", "combo", &"One", &["One", "Two", "Three"]);
+        ui.start("button").id("tip_source").text("Hover").tooltip("Tip").finish();
+        ui.start("button").id("drag_source").text("Drag").drag_source("item").finish();
+
+        if state.close_window {
+            ui.close("window");
+        }
+    });
+
+    state.payload = ui.drag_payload();
+    ui.drag_preview("tooltip", |ui, payload| {
+        ui.label("label", payload);
+    });
+}
+
+fn drawn_with_text(h: &Harness, text: &str) -> bool {
+    h.renderer.quads().iter().any(|quad| quad.text.as_deref() == Some(text))
+}
+
+#[test]
+fn combo_popup_closes_with_window() {
+    let mut h = Harness::new();
+    let mut state = Ui::default();
+
+    h.frame(|ui| build_ui(ui, &mut state));
+    h.click("combo", |ui| build_ui(ui, &mut state));
+    h.frame(|ui| build_ui(ui, &mut state));
+    assert!(h.renderer.quad("combo_popup").is_some());
+
+    // the first click outside the popup only dismisses it, the second closes the window
+    h.click("window/titlebar/close", |ui| build_ui(ui, &mut state));
+    assert!(h.renderer.quad("window").is_some());
+    h.click("window/titlebar/close", |ui| build_ui(ui, &mut state));
+    h.frame(|ui| build_ui(ui, &mut state));
+    assert!(h.renderer.quad("window").is_none());
+    assert!(h.renderer.quad("combo_popup").is_none());
+
+    // reopening the window leaves the popup closed
+    h.frame(|ui| { ui.open("window"); build_ui(ui, &mut state) });
+    h.frame(|ui| build_ui(ui, &mut state));
+    assert!(h.renderer.quad("window").is_some());
+    assert!(h.renderer.quad("combo_popup").is_none());
+}
+
+#[test]
+fn combo_popup_hidden_when_window_closes_while_open() {
+    let mut h = Harness::new();
+    let mut state = Ui::default();
+
+    h.frame(|ui| build_ui(ui, &mut state));
+    h.click("combo", |ui| build_ui(ui, &mut state));
+    h.frame(|ui| build_ui(ui, &mut state));
+    assert!(h.renderer.quad("combo_popup").is_some());
+
+    // the popup is built before the window closes on this frame, but must not be drawn
+    state.close_window = true;
+    h.frame(|ui| build_ui(ui, &mut state));
+    assert!(h.renderer.quad("combo_popup").is_none());
+
+    state.close_window = false;
+    h.frame(|ui| build_ui(ui, &mut state));
+    assert!(h.renderer.quad("window").is_none());
+    assert!(h.renderer.quad("combo_popup").is_none());
+}
+
+#[test]
+fn tooltip_hidden_when_window_closes() {
+    let mut h = Harness::new();
+    let mut state = Ui::default();
+
+    h.frame(|ui| build_ui(ui, &mut state));
+    h.hover("tip_source", |ui| build_ui(ui, &mut state));
+    h.io.advance_time(&mut h.context, 5000);
+    h.frame(|ui| build_ui(ui, &mut state));
+    assert!(drawn_with_text(&h, "Tip"));
+
+    state.close_window = true;
+    h.frame(|ui| build_ui(ui, &mut state));
+    assert!(!drawn_with_text(&h, "Tip"));
+}
+
+#[test]
+fn drag_preview_hidden_when_window_closes() {
+    let mut h = Harness::new();
+    let mut state = Ui::default();
+
+    h.frame(|ui| build_ui(ui, &mut state));
+    h.hover("drag_source", |ui| build_ui(ui, &mut state));
+    let pos = h.center_of("drag_source");
+    h.io.press_mouse(&mut h.context, MouseButton::Left);
+    h.frame(|ui| build_ui(ui, &mut state));
+    h.io.move_mouse(&mut h.context, pos + Point::new(40.0, 40.0));
+    h.frame(|ui| build_ui(ui, &mut state));
+    h.frame(|ui| build_ui(ui, &mut state));
+    assert_eq!(state.payload.as_deref(), Some("item"));
+    assert!(drawn_with_text(&h, "item"));
+
+    state.close_window = true;
+    h.frame(|ui| build_ui(ui, &mut state));
+    assert!(!drawn_with_text(&h, "item"));
+
+    // the drag is cancelled along with its source
+    state.close_window = false;
+    h.frame(|ui| build_ui(ui, &mut state));
+    assert_eq!(state.payload, None);
+    assert!(!drawn_with_text(&h, "item"));
+}
@@ -0,0 +1,74 @@
+mod common;
+
+use common::Harness;
+use thyme::{Frame, InputEvent, InputLog, MouseButton, Recorder};
+
+#[derive(Default, Debug, PartialEq)]
+struct Ui {
+    clicks: u32,
+    text: Option<String>,
+}
+
+fn build_ui(ui: &mut Frame, state: &mut Ui) {
+    ui.window("window", |ui| {
+        if ui.button("button", "Click").clicked {
+            state.clicks += 1;
+        }
+
+        if let Some(text) = ui.input_field("input_field", "field", None) {
+            state.text = Some(text);
+        }
+    });
+}
+
+#[test]
+fn replay_matches_recording() {
+    let mut h = Harness::new();
+    let mut state = Ui::default();
+    h.frame(|ui| build_ui(ui, &mut state));
+
+    // the mouse is already over the button when recording starts, so only the snapshot has its position
+    h.hover("window/button", |ui| build_ui(ui, &mut state));
+    let recorder = Recorder::start(&mut h.context);
+    h.frame(|ui| build_ui(ui, &mut state));
+
+    h.io.press_mouse(&mut h.context, MouseButton::Left);
+    h.io.release_mouse(&mut h.context, MouseButton::Left);
+    h.frame(|ui| build_ui(ui, &mut state));
+
+    h.click("field", |ui| build_ui(ui, &mut state));
+    h.io.type_text(&mut h.context, "Thyme");
+    h.frame(|ui| build_ui(ui, &mut state));
+    h.frame(|ui| build_ui(ui, &mut state));
+
+    let log = recorder.finish();
+    assert!(!h.context.is_recording());
+    assert!(matches!(log.events()[0], InputEvent::Snapshot { .. }));
+    assert_eq!(state, Ui { clicks: 1, text: Some("Thyme".to_string()) });
+
+    // the log survives serialization
+    let log: InputLog = serde_yaml::from_str(&serde_yaml::to_string(&log).unwrap()).unwrap();
+
+    let mut replayed = Harness::new();
+    let mut replayed_state = Ui::default();
+    replayed.frame(|ui| build_ui(ui, &mut replayed_state));
+
+    let renderer = &mut replayed.renderer;
+    thyme::replay(&log, &mut replayed.context, |mut ui| {
+        build_ui(&mut ui, &mut replayed_state);
+        renderer.draw_frame(ui);
+    });
+
+    assert_eq!(replayed_state, state);
+    assert_eq!(replayed.renderer.quads(), h.renderer.quads());
+}
+
+#[test]
+fn dropped_recorder_stops_recording() {
+    let mut h = Harness::new();
+    let recorder = Recorder::start(&mut h.context);
+    assert!(h.context.is_recording());
+
+    drop(recorder);
+    assert!(!h.context.is_recording());
+}